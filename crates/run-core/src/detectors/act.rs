@@ -0,0 +1,195 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{
+    is_tool_installed, CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem,
+};
+use crate::manifest;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Directory holding GitHub Actions workflows, relative to the project
+pub const WORKFLOWS_DIR: &str = ".github/workflows";
+
+/// Whether `task` names a workflow file (`ci.yml`) rather than a job
+pub fn is_workflow(task: &str) -> bool {
+    task.ends_with(".yml") || task.ends_with(".yaml")
+}
+
+/// Command line for `task`: `act -W <workflow file>` for a workflow,
+/// `act -j <job>` for a job
+pub fn command(task: &str, extra_args: &[String]) -> Vec<String> {
+    let mut cmd = if is_workflow(task) {
+        vec![
+            "act".to_string(),
+            "-W".to_string(),
+            format!("{}/{}", WORKFLOWS_DIR, task),
+        ]
+    } else {
+        vec!["act".to_string(), "-j".to_string(), task.to_string()]
+    };
+    cmd.extend(extra_args.iter().cloned());
+    cmd
+}
+
+/// Workflow files in `dir`'s `.github/workflows`, sorted by name
+fn workflow_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir.join(WORKFLOWS_DIR))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|e| e.path())
+                .filter(|p| p.is_file() && is_workflow(&p.to_string_lossy()))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+/// Job ids declared under `jobs:` in a workflow file
+fn workflow_jobs(file: &Path) -> Vec<String> {
+    manifest::yaml(file)
+        .and_then(|workflow| {
+            let jobs = workflow.get("jobs")?.as_mapping()?;
+            Some(
+                jobs.keys()
+                    .filter_map(|k| k.as_str().map(str::to_string))
+                    .collect(),
+            )
+        })
+        .unwrap_or_default()
+}
+
+/// Validator for workflow file names and the job ids they declare
+pub struct ActValidator;
+
+impl CommandValidator for ActValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let files = workflow_files(working_dir);
+        if files.is_empty() {
+            return CommandSupport::Unknown;
+        }
+
+        let mut names: HashSet<String> = HashSet::new();
+        for file in &files {
+            if let Some(name) = file.file_name() {
+                names.insert(name.to_string_lossy().into_owned());
+            }
+            names.extend(workflow_jobs(file));
+        }
+
+        if names.contains(command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        workflow_files(working_dir)
+            .iter()
+            .flat_map(|file| {
+                let name = file.file_name().map(|n| n.to_string_lossy().into_owned());
+                name.into_iter().chain(workflow_jobs(file))
+            })
+            .collect()
+    }
+}
+
+/// Detect GitHub Actions workflows runnable locally with act
+/// Priority: 30
+///
+/// Only detected when `act` is installed: without it the workflows are
+/// nothing `run` can execute, and would shadow "not installed" hints for the
+/// project's real tools.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has(".github") && !workflow_files(scan.path()).is_empty() && is_tool_installed("act") {
+        let validator: Arc<dyn CommandValidator> = Arc::new(ActValidator);
+        runners.push(DetectedRunner::with_validator(
+            "act",
+            WORKFLOWS_DIR,
+            Ecosystem::GitHubActions,
+            30,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        let workflows = dir.path().join(WORKFLOWS_DIR);
+        fs::create_dir_all(&workflows).unwrap();
+        fs::write(
+            workflows.join("ci.yml"),
+            "name: CI\non: push\njobs:\n  lint:\n    runs-on: ubuntu-latest\n  unit-tests:\n    runs-on: ubuntu-latest\n",
+        )
+        .unwrap();
+        fs::write(
+            workflows.join("release.yaml"),
+            "on: workflow_dispatch\njobs:\n  publish:\n    runs-on: ubuntu-latest\n",
+        )
+        .unwrap();
+        fs::write(workflows.join("README.md"), "").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_act_validator() {
+        let dir = project();
+        let validator = ActValidator;
+
+        for name in ["lint", "unit-tests", "publish", "ci.yml", "release.yaml"] {
+            assert_eq!(
+                validator.supports_command(dir.path(), name),
+                CommandSupport::Supported,
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            validator.supports_command(dir.path(), "deploy"),
+            CommandSupport::NotSupported
+        );
+        assert_eq!(
+            validator.supports_command(tempdir().unwrap().path(), "lint"),
+            CommandSupport::Unknown
+        );
+    }
+
+    #[test]
+    fn test_command() {
+        assert_eq!(command("lint", &[]), vec!["act", "-j", "lint"]);
+        assert_eq!(
+            command("ci.yml", &["--dryrun".to_string()]),
+            vec!["act", "-W", ".github/workflows/ci.yml", "--dryrun"]
+        );
+    }
+
+    #[test]
+    fn test_no_workflows() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".github")).unwrap();
+
+        assert!(detect(&DirScan::new(dir.path())).is_empty());
+    }
+}
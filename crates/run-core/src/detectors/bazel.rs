@@ -0,0 +1,162 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::path::Path;
+use std::sync::Arc;
+
+/// Bazel's built-in commands (`bazel help`)
+pub const BAZEL_BUILTINS: &[&str] = &[
+    "analyze-profile",
+    "aquery",
+    "build",
+    "canonicalize-flags",
+    "clean",
+    "config",
+    "coverage",
+    "cquery",
+    "dump",
+    "fetch",
+    "help",
+    "info",
+    "license",
+    "mobile-install",
+    "mod",
+    "print_action",
+    "query",
+    "run",
+    "shutdown",
+    "sync",
+    "test",
+    "vendor",
+    "version",
+];
+
+/// Files marking a Bazel workspace root, in order of preference
+const WORKSPACE_FILES: &[&str] = &["MODULE.bazel", "WORKSPACE.bazel", "WORKSPACE"];
+
+/// Whether `task` is a target label (`//pkg:target`, `:target`, `@repo//pkg`)
+pub fn is_label(task: &str) -> bool {
+    task.starts_with("//") || task.starts_with(':') || task.starts_with('@')
+}
+
+/// Validator for Bazel: built-in commands and target labels (which run with
+/// `bazel run`)
+pub struct BazelValidator;
+
+impl CommandValidator for BazelValidator {
+    fn supports_command(&self, _working_dir: &Path, command: &str) -> CommandSupport {
+        if BAZEL_BUILTINS.contains(&command) || is_label(command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+}
+
+/// Detect Bazel workspaces
+/// Priority: 24
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if let Some(&file) = WORKSPACE_FILES.iter().find(|f| scan.has(f)) {
+        let validator: Arc<dyn CommandValidator> = Arc::new(BazelValidator);
+        runners.push(DetectedRunner::with_validator(
+            "bazel",
+            file,
+            Ecosystem::Bazel,
+            24,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_bazel() {
+        for file in ["WORKSPACE", "WORKSPACE.bazel", "MODULE.bazel"] {
+            let dir = tempdir().unwrap();
+            File::create(dir.path().join(file)).unwrap();
+
+            let runners = detect(&DirScan::new(dir.path()));
+            assert_eq!(runners.len(), 1);
+            assert_eq!(runners[0].name, "bazel");
+            assert_eq!(runners[0].detected_file, file);
+        }
+
+        // Bzlmod wins when both are present
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("WORKSPACE")).unwrap();
+        File::create(dir.path().join("MODULE.bazel")).unwrap();
+        assert_eq!(
+            detect(&DirScan::new(dir.path()))[0].detected_file,
+            "MODULE.bazel"
+        );
+    }
+
+    #[test]
+    fn test_no_bazel() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("BUILD.bazel")).unwrap();
+
+        assert!(detect(&DirScan::new(dir.path())).is_empty());
+    }
+
+    #[test]
+    fn test_bazel_validator() {
+        let dir = tempdir().unwrap();
+        let validator = BazelValidator;
+
+        for command in [
+            "build",
+            "test",
+            "query",
+            "//app:server",
+            ":lint",
+            "@repo//x",
+        ] {
+            assert_eq!(
+                validator.supports_command(dir.path(), command),
+                CommandSupport::Supported,
+                "{}",
+                command
+            );
+        }
+        assert_eq!(
+            validator.supports_command(dir.path(), "deploy"),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_build_command() {
+        let runner = DetectedRunner::new("bazel", "MODULE.bazel", Ecosystem::Bazel, 24);
+        assert_eq!(
+            runner.build_command("build", &["//...".to_string()]),
+            vec!["bazel", "build", "//..."]
+        );
+        assert_eq!(
+            runner.build_command("//app:server", &["--port=80".to_string()]),
+            vec!["bazel", "run", "//app:server", "--", "--port=80"]
+        );
+        assert_eq!(
+            runner.build_command(":lint", &[]),
+            vec!["bazel", "run", ":lint"]
+        );
+    }
+}
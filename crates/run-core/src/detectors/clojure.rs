@@ -0,0 +1,258 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Leiningen's built-in tasks (`lein help`)
+const LEIN_BUILTINS: &[&str] = &[
+    "change",
+    "check",
+    "classpath",
+    "clean",
+    "compile",
+    "deploy",
+    "deps",
+    "do",
+    "help",
+    "install",
+    "jar",
+    "javac",
+    "new",
+    "plugin",
+    "pom",
+    "release",
+    "repl",
+    "retest",
+    "run",
+    "search",
+    "show-profiles",
+    "test",
+    "trampoline",
+    "uberjar",
+    "update-in",
+    "upgrade",
+    "vcs",
+    "version",
+    "with-profile",
+];
+
+/// Validator for Leiningen: built-in tasks and `:aliases` from project.clj
+///
+/// Plugins add tasks that can't be listed without running lein, so projects
+/// with `:plugins` answer `Unknown` for anything else.
+pub struct LeinValidator;
+
+impl CommandValidator for LeinValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        if LEIN_BUILTINS.contains(&command) {
+            return CommandSupport::Supported;
+        }
+
+        let content = match fs::read_to_string(working_dir.join("project.clj")) {
+            Ok(c) => c,
+            Err(_) => return CommandSupport::Unknown,
+        };
+
+        if extract_aliases(&content).contains(command) {
+            CommandSupport::Supported
+        } else if content.contains(":plugins") {
+            CommandSupport::Unknown
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        fs::read_to_string(working_dir.join("project.clj"))
+            .map(|content| extract_aliases(&content).into_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Keys of the `:aliases` map in a project.clj
+fn extract_aliases(content: &str) -> HashSet<String> {
+    let mut aliases = HashSet::new();
+    let Some(start) = content.find(":aliases") else {
+        return aliases;
+    };
+    let rest = &content[start + ":aliases".len()..];
+    let Some(open) = rest.find('{') else {
+        return aliases;
+    };
+
+    // Walk the forms at the top level of the map: key, value, key, value...
+    let mut chars = rest[open + 1..].chars().peekable();
+    let mut depth = 0;
+    let mut is_key = true;
+    // `^{...}` metadata attaches to the next form instead of being one
+    let mut metadata = false;
+    while let Some(c) = chars.next() {
+        match c {
+            ';' => {
+                // Comment to end of line
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                let mut literal = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        c => literal.push(c),
+                    }
+                }
+                if depth == 0 {
+                    if is_key {
+                        aliases.insert(literal);
+                    }
+                    end_form(&mut is_key, &mut metadata);
+                }
+            }
+            '[' | '(' | '{' => depth += 1,
+            ']' | ')' | '}' => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+                if depth == 0 {
+                    end_form(&mut is_key, &mut metadata);
+                }
+            }
+            '^' if depth == 0 => metadata = true,
+            c if depth == 0 && !c.is_whitespace() && c != ',' => {
+                // A bare symbol or keyword; skip the rest of it
+                while chars
+                    .peek()
+                    .is_some_and(|c| !c.is_whitespace() && !"[](){}\",".contains(*c))
+                {
+                    chars.next();
+                }
+                end_form(&mut is_key, &mut metadata);
+            }
+            _ => {}
+        }
+    }
+
+    aliases
+}
+
+/// A form at the top of the map ended: the next one is a value after a key
+/// and vice versa, unless this form was only metadata
+fn end_form(is_key: &mut bool, metadata: &mut bool) {
+    if *metadata {
+        *metadata = false;
+    } else {
+        *is_key = !*is_key;
+    }
+}
+
+/// Detect Leiningen projects
+/// Priority: 27
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has("project.clj") {
+        let validator: Arc<dyn CommandValidator> = Arc::new(LeinValidator);
+        runners.push(DetectedRunner::with_validator(
+            "lein",
+            "project.clj",
+            Ecosystem::Clojure,
+            27,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    const PROJECT: &str = r#"
+(defproject demo "0.1.0"
+  :dependencies [[org.clojure/clojure "1.11.1"]]
+  ; project aliases
+  :aliases {"lint" ["run" "-m" "clj-kondo.main" "--lint" "src"]
+            "ci" ["do" ["clean"] ["test"]]
+            "fmt" ^{:doc "Format"} ["cljfmt" "fix"]}
+  :profiles {:dev {:dependencies []}})
+"#;
+
+    #[test]
+    fn test_detect_lein() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("project.clj")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "lein");
+        assert_eq!(runners[0].build_command("test", &[]), vec!["lein", "test"]);
+
+        let empty = tempdir().unwrap();
+        assert!(detect(&DirScan::new(empty.path())).is_empty());
+    }
+
+    #[test]
+    fn test_extract_aliases() {
+        let aliases = extract_aliases(PROJECT);
+        assert_eq!(aliases.len(), 3);
+        assert!(aliases.contains("lint"));
+        assert!(aliases.contains("ci"));
+        assert!(aliases.contains("fmt"));
+        // Values are not aliases
+        assert!(!aliases.contains("run"));
+        assert!(!aliases.contains("cljfmt"));
+    }
+
+    #[test]
+    fn test_lein_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("project.clj"), PROJECT).unwrap();
+
+        let validator = LeinValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "uberjar"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "lint"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "deploy-prod"),
+            CommandSupport::NotSupported
+        );
+
+        // Plugins may provide any task
+        fs::write(
+            dir.path().join("project.clj"),
+            "(defproject demo \"0.1.0\" :plugins [[lein-ancient \"1.0.0\"]])",
+        )
+        .unwrap();
+        assert_eq!(
+            validator.supports_command(dir.path(), "ancient"),
+            CommandSupport::Unknown
+        );
+    }
+}
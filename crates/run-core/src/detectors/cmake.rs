@@ -0,0 +1,359 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+const PRESETS_FILE: &str = "CMakePresets.json";
+
+/// Build directory used when the project has no presets
+const BUILD_DIR: &str = "build";
+
+/// Non-hidden preset names from `CMakePresets.json`, by kind
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Presets {
+    pub configure: Vec<String>,
+    pub build: Vec<String>,
+    pub test: Vec<String>,
+}
+
+impl Presets {
+    /// Parse `CMakePresets.json` in `dir`
+    pub fn load(dir: &Path) -> Option<Self> {
+        let json = manifest::json(&dir.join(PRESETS_FILE))?;
+        let names = |key: &str| -> Vec<String> {
+            json.get(key)
+                .and_then(Value::as_array)
+                .map(|presets| {
+                    presets
+                        .iter()
+                        .filter(|p| !p.get("hidden").and_then(Value::as_bool).unwrap_or(false))
+                        .filter_map(|p| p.get("name")?.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        Some(Self {
+            configure: names("configurePresets"),
+            build: names("buildPresets"),
+            test: names("testPresets"),
+        })
+    }
+
+    /// Preset `run` uses: `default` if defined, else the first configure
+    /// preset that also has a build preset of the same name, else the first
+    /// configure preset
+    pub fn default_preset(&self) -> Option<&str> {
+        let configure = |name: &&String| self.configure.contains(name);
+        self.configure
+            .iter()
+            .find(|name| *name == "default")
+            .or_else(|| self.build.iter().find(configure))
+            .or_else(|| self.configure.first())
+            .map(String::as_str)
+    }
+
+    fn has(&self, kind: &str) -> bool {
+        match kind {
+            "configure" => !self.configure.is_empty(),
+            "build" => !self.build.is_empty(),
+            "test" => !self.test.is_empty(),
+            _ => true,
+        }
+    }
+}
+
+/// Command line for `task`; `preset` is the runner's target
+///
+/// `configure`, `build` and `test` use the preset (`cmake --preset`,
+/// `cmake --build --preset`, `ctest --preset`) unless the arguments pick one.
+/// Without presets they use a `build/` directory. `install` installs from
+/// it, and any other task builds the CMake target of that name.
+pub fn command(preset: Option<&str>, task: &str, extra_args: &[String]) -> Vec<String> {
+    let picks_preset = extra_args
+        .iter()
+        .any(|arg| arg == "--preset" || arg.starts_with("--preset="));
+    // A preset in the arguments replaces both ours and the build directory
+    let select = |fallback: &'static [&'static str]| -> Vec<&str> {
+        match preset {
+            _ if picks_preset => vec![],
+            Some(preset) => vec!["--preset", preset],
+            None => fallback.to_vec(),
+        }
+    };
+
+    let mut cmd = match task {
+        "configure" => [vec!["cmake"], select(&["-S", ".", "-B", BUILD_DIR])].concat(),
+        "build" => [vec!["cmake", "--build"], select(&[BUILD_DIR])].concat(),
+        "test" => [vec!["ctest"], select(&["--test-dir", BUILD_DIR])].concat(),
+        "install" => vec!["cmake", "--install", BUILD_DIR],
+        target => [
+            vec!["cmake", "--build"],
+            select(&[BUILD_DIR]),
+            vec!["--target", target],
+        ]
+        .concat(),
+    };
+    cmd.extend(extra_args.iter().map(String::as_str));
+    cmd.into_iter().map(str::to_string).collect()
+}
+
+/// Validator for CMake projects
+///
+/// `configure`, `build` and `test` need a preset of that kind when the project
+/// uses presets; other tasks must be targets declared with
+/// `add_custom_target`, `add_executable` or `add_library` in `CMakeLists.txt`.
+pub struct CMakeValidator;
+
+impl CommandValidator for CMakeValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        if matches!(command, "configure" | "build" | "test" | "install") {
+            return match Presets::load(working_dir) {
+                Some(presets) if !presets.has(command) => CommandSupport::NotSupported,
+                _ => CommandSupport::Supported,
+            };
+        }
+
+        match fs::read_to_string(working_dir.join("CMakeLists.txt")) {
+            Ok(content) if extract_targets(&content).iter().any(|t| t == command) => {
+                CommandSupport::Supported
+            }
+            Ok(_) => CommandSupport::NotSupported,
+            Err(_) => CommandSupport::Unknown,
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        let presets = Presets::load(working_dir);
+        let mut commands: Vec<String> = ["configure", "build", "test", "install"]
+            .iter()
+            .filter(|kind| presets.as_ref().is_none_or(|p| p.has(kind)))
+            .map(|kind| kind.to_string())
+            .collect();
+        if let Ok(content) = fs::read_to_string(working_dir.join("CMakeLists.txt")) {
+            commands.extend(extract_targets(&content));
+        }
+        commands
+    }
+}
+
+/// Target names declared in a CMakeLists.txt
+fn extract_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_start();
+        let lower = line.to_ascii_lowercase();
+        for command in ["add_custom_target", "add_executable", "add_library"] {
+            let Some(rest) = lower.strip_prefix(command) else {
+                continue;
+            };
+            let Some(args) = rest.trim_start().strip_prefix('(') else {
+                continue;
+            };
+            // Names keep their case; only the command is case-insensitive
+            let offset = line.len() - args.len();
+            if let Some(name) = line[offset..].split_whitespace().next() {
+                let name = name.trim_end_matches(')');
+                if !name.is_empty() && !name.starts_with('$') {
+                    targets.push(name.to_string());
+                }
+            }
+        }
+    }
+    targets
+}
+
+/// Whether a CMakeLists.txt starts a project (calls `project()`), unlike the
+/// lists of subdirectories it pulls in with `add_subdirectory`
+fn declares_project(content: &str) -> bool {
+    content.lines().any(|line| {
+        let lower = line.trim_start().to_ascii_lowercase();
+        lower
+            .strip_prefix("project")
+            .is_some_and(|rest| rest.trim_start().starts_with('('))
+    })
+}
+
+/// Detect CMake projects
+/// Priority: 25
+///
+/// Only a project's root counts: a directory with `CMakePresets.json`, or
+/// whose CMakeLists.txt calls `project()`. A subdirectory's list is skipped,
+/// so the upward search goes on to the root.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    let file = if scan.has(PRESETS_FILE) {
+        PRESETS_FILE
+    } else if scan.has("CMakeLists.txt")
+        && fs::read_to_string(scan.path().join("CMakeLists.txt"))
+            .is_ok_and(|content| declares_project(&content))
+    {
+        "CMakeLists.txt"
+    } else {
+        return runners;
+    };
+
+    let validator: Arc<dyn CommandValidator> = Arc::new(CMakeValidator);
+    let mut runner = DetectedRunner::with_validator("cmake", file, Ecosystem::CMake, 25, validator);
+    if let Some(preset) = Presets::load(scan.path())
+        .as_ref()
+        .and_then(Presets::default_preset)
+    {
+        runner = runner.with_target(preset);
+    }
+    runners.push(runner);
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const PRESETS: &str = r#"{
+        "version": 6,
+        "configurePresets": [
+            {"name": "base", "hidden": true},
+            {"name": "debug", "inherits": "base"},
+            {"name": "release", "inherits": "base"}
+        ],
+        "buildPresets": [{"name": "release", "configurePreset": "release"}],
+        "testPresets": []
+    }"#;
+
+    #[test]
+    fn test_detect_presets() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("CMakeLists.txt"), "").unwrap();
+        fs::write(dir.path().join(PRESETS_FILE), PRESETS).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "cmake");
+        assert_eq!(runners[0].detected_file, PRESETS_FILE);
+        assert_eq!(runners[0].target.as_deref(), Some("release"));
+        assert_eq!(
+            runners[0].build_command("build", &[]),
+            vec!["cmake", "--build", "--preset", "release"]
+        );
+        assert_eq!(
+            runners[0].build_command("configure", &[]),
+            vec!["cmake", "--preset", "release"]
+        );
+    }
+
+    #[test]
+    fn test_detect_without_presets() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("CMakeLists.txt"), "project(demo)\n").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners[0].detected_file, "CMakeLists.txt");
+        assert_eq!(runners[0].target, None);
+        assert_eq!(
+            runners[0].build_command("build", &[]),
+            vec!["cmake", "--build", "build"]
+        );
+        assert_eq!(
+            runners[0].build_command("test", &[]),
+            vec!["ctest", "--test-dir", "build"]
+        );
+        assert_eq!(
+            runners[0].build_command("docs", &[]),
+            vec!["cmake", "--build", "build", "--target", "docs"]
+        );
+
+        let empty = tempdir().unwrap();
+        assert!(detect(&DirScan::new(empty.path())).is_empty());
+    }
+
+    #[test]
+    fn test_detect_nested_lists() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("CMakeLists.txt"),
+            "cmake_minimum_required(VERSION 3.20)\nPROJECT (demo C)\nadd_subdirectory(src)\n",
+        )
+        .unwrap();
+        let src = dir.path().join("src");
+        fs::create_dir(&src).unwrap();
+        fs::write(src.join("CMakeLists.txt"), "add_executable(demo main.c)\n").unwrap();
+
+        // The subdirectory's list is not a project of its own
+        assert!(detect(&DirScan::new(&src)).is_empty());
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].detected_file, "CMakeLists.txt");
+
+        // Presets alone mark a root
+        fs::write(src.join(PRESETS_FILE), PRESETS).unwrap();
+        assert_eq!(detect(&DirScan::new(&src)).len(), 1);
+    }
+
+    #[test]
+    fn test_explicit_preset_argument() {
+        let args = ["--preset".to_string(), "debug".to_string()];
+        assert_eq!(
+            command(Some("release"), "build", &args),
+            vec!["cmake", "--build", "--preset", "debug"]
+        );
+    }
+
+    #[test]
+    fn test_default_preset() {
+        let presets = Presets {
+            configure: vec!["ninja".into(), "default".into()],
+            ..Default::default()
+        };
+        assert_eq!(presets.default_preset(), Some("default"));
+        assert_eq!(Presets::default().default_preset(), None);
+    }
+
+    #[test]
+    fn test_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("CMakeLists.txt"),
+            "project(demo)\nadd_executable(demo main.c)\nADD_CUSTOM_TARGET(Docs ALL)\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join(PRESETS_FILE), PRESETS).unwrap();
+
+        let validator = CMakeValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "build"),
+            CommandSupport::Supported
+        );
+        // No test presets defined
+        assert_eq!(
+            validator.supports_command(dir.path(), "test"),
+            CommandSupport::NotSupported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "Docs"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "demo"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "lint"),
+            CommandSupport::NotSupported
+        );
+    }
+}
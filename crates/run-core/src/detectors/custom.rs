@@ -0,0 +1,1253 @@
+use crate::detectors::{
+    CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem, TaskDefinition,
+};
+use crate::error::RunError;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A `[commands]` entry in run.toml: a command line, or a table with the
+/// command and settings for the task
+///
+/// A table without `cmd` only configures the task; a detected runner still
+/// runs it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum CommandEntry {
+    Line(String),
+    Task(Box<TaskSettings>),
+}
+
+/// The table form of a [`CommandEntry`]
+///
+/// Unknown keys are rejected, so that a table of commands reads as a
+/// namespace instead (see [`deserialize_commands`]).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct TaskSettings {
+    /// Command line to run instead of a detected runner's task
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<CommandLine>,
+    /// Detected runner to hand the task to (`use = "cargo"`), bypassing
+    /// runner selection
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub runner: Option<String>,
+    /// With `use`, the runner's task to run, with leading arguments
+    /// (`task = "nextest run"`); the task's own name by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Longest the task may run (`90s`, `5m`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<String>,
+    /// Times to re-run the task when it fails or times out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Dotenv file loaded for the task, relative to the project
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_file: Option<String>,
+    /// Tasks to run first, each once, dependencies before dependents
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// A script to run instead of `cmd`, from a temporary file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<Script>,
+    /// Run the command line through a shell (see [`Shell`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shell: Option<Shell>,
+    /// Tasks (with arguments) to run right before this one
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pre: Vec<String>,
+    /// Tasks (with arguments) to run after this one, even when it failed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<String>,
+    /// Leave the task out of `run list` and search; it still runs by name
+    /// and as a dependency or hook
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub private: bool,
+    /// Run the task only where this holds; elsewhere it is skipped and
+    /// left out of `run list`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub condition: Option<Condition>,
+    /// Files the task reads, as globs relative to the project (`src/**`);
+    /// while they are unchanged since its last successful run, the task is
+    /// skipped as cached
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<String>,
+    /// Files the task produces (`dist/**`); it runs again when any of them
+    /// is gone, whatever its inputs
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub outputs: Vec<String>,
+    /// Values to run the task with (`matrix.target = ["x86_64", "aarch64"]`):
+    /// once for each combination, each value as `{{matrix.<name>}}`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub matrix: BTreeMap<String, Vec<String>>,
+    /// Ask before running the task (see [`Confirm`])
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub confirm: Option<Confirm>,
+    /// Tasks of the same group (`group = "db"`) never run at the same time
+    /// under `--parallel`; each waits for the one before it to finish
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+}
+
+/// A task's `confirm`: `true` to ask whether to run it, or the question to
+/// ask (`"This wipes the prod database, continue?"`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Confirm {
+    Enabled(bool),
+    Question(String),
+}
+
+impl Confirm {
+    /// What to ask before running `task`, if anything
+    pub fn question(&self, task: &str) -> Option<String> {
+        match self {
+            Confirm::Enabled(false) => None,
+            Confirm::Enabled(true) => Some(format!("Run '{}'?", task)),
+            Confirm::Question(question) => Some(question.clone()),
+        }
+    }
+}
+
+/// When a task applies: every condition given must hold
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct Condition {
+    /// A file or directory, relative to the project, that must exist
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_exists: Option<String>,
+    /// An environment variable that must be set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env_set: Option<String>,
+    /// The operating system (`linux`, `macos`, `windows`, ...) or `unix`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+}
+
+impl Condition {
+    /// Why the condition doesn't hold in `dir`, if it doesn't; `is_set`
+    /// tells whether an environment variable is set
+    pub fn unmet(&self, dir: &Path, is_set: impl Fn(&str) -> bool) -> Option<String> {
+        if let Some(file) = &self.file_exists {
+            if !dir.join(file).exists() {
+                return Some(format!("{} does not exist", file));
+            }
+        }
+        if let Some(var) = &self.env_set {
+            if !is_set(var) {
+                return Some(format!("{} is not set", var));
+            }
+        }
+        if let Some(os) = &self.os {
+            let os = os.to_lowercase();
+            if os != std::env::consts::OS && !(os == "unix" && cfg!(unix)) {
+                return Some(format!("it only runs on {}", os));
+            }
+        }
+        None
+    }
+}
+
+/// The `cmd` of a task table: one command line, or one per platform
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum CommandLine {
+    Any(String),
+    Platforms(PlatformCommands),
+}
+
+/// `cmd.windows`, `cmd.macos`, `cmd.linux` and `cmd.unix`: on macOS and
+/// Linux the specific variant wins over `unix`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlatformCommands {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub windows: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macos: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub linux: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unix: Option<String>,
+}
+
+impl CommandLine {
+    /// The command line for the platform `run` runs on; `None` when no
+    /// variant covers it
+    pub fn current(&self) -> Option<&str> {
+        match self {
+            CommandLine::Any(line) => Some(line),
+            CommandLine::Platforms(platforms) => platforms.current(),
+        }
+    }
+}
+
+impl PlatformCommands {
+    /// The variant for the platform `run` runs on
+    pub fn current(&self) -> Option<&str> {
+        let specific = if cfg!(windows) {
+            &self.windows
+        } else if cfg!(target_os = "macos") {
+            &self.macos
+        } else if cfg!(target_os = "linux") {
+            &self.linux
+        } else {
+            &None
+        };
+        let unix = if cfg!(unix) { &self.unix } else { &None };
+        specific.as_deref().or(unix.as_deref())
+    }
+}
+
+impl From<&str> for CommandLine {
+    fn from(line: &str) -> Self {
+        CommandLine::Any(line.to_string())
+    }
+}
+
+/// A task's `script`: a multi-line string, or one line per element
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Script {
+    Text(String),
+    Lines(Vec<String>),
+}
+
+impl Script {
+    /// The script's text, ending with a newline
+    pub fn text(&self) -> String {
+        let mut text = match self {
+            Script::Text(text) => text.clone(),
+            Script::Lines(lines) => lines.join("\n"),
+        };
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        text
+    }
+}
+
+impl CommandEntry {
+    /// The command line for this platform (a script's text), empty when the
+    /// entry only holds settings or has no variant for the platform
+    pub fn command(&self) -> Cow<'_, str> {
+        match self {
+            CommandEntry::Line(line) => Cow::Borrowed(line),
+            CommandEntry::Task(task) => match &task.script {
+                Some(script) => Cow::Owned(script.text()),
+                None => Cow::Borrowed(
+                    task.cmd
+                        .as_ref()
+                        .and_then(CommandLine::current)
+                        .unwrap_or(""),
+                ),
+            },
+        }
+    }
+
+    /// Settings of the task (all unset for a plain command line)
+    pub fn settings(&self) -> TaskSettings {
+        match self {
+            CommandEntry::Line(_) => TaskSettings::default(),
+            CommandEntry::Task(task) => (**task).clone(),
+        }
+    }
+}
+
+impl From<&str> for CommandEntry {
+    fn from(line: &str) -> Self {
+        CommandEntry::Line(line.to_string())
+    }
+}
+
+/// A `[commands]` value: an entry, or a namespace of entries
+#[derive(Deserialize, JsonSchema)]
+#[serde(untagged)]
+enum CommandNode {
+    Entry(CommandEntry),
+    Namespace(HashMap<String, CommandNode>),
+}
+
+/// JSON Schema of a `[commands]` table, whose tables of commands are
+/// namespaces (see [`deserialize_commands`])
+pub fn commands_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    generator.subschema_for::<Option<HashMap<String, CommandNode>>>()
+}
+
+/// Add `nodes` to `commands`, naming the entries of a namespace
+/// `<namespace>:<task>`
+fn flatten(
+    prefix: &str,
+    nodes: HashMap<String, CommandNode>,
+    commands: &mut HashMap<String, CommandEntry>,
+) {
+    for (name, node) in nodes {
+        let name = format!("{}{}", prefix, name);
+        match node {
+            CommandNode::Entry(entry) => {
+                commands.insert(name, entry);
+            }
+            CommandNode::Namespace(nodes) => flatten(&format!("{}:", name), nodes, commands),
+        }
+    }
+}
+
+/// Deserialize a `[commands]` table, where a table of commands is a
+/// namespace: `[commands.db]` with `migrate = "..."` declares `db:migrate`
+pub fn deserialize_commands<'de, D>(
+    deserializer: D,
+) -> Result<Option<HashMap<String, CommandEntry>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let nodes = Option::<HashMap<String, CommandNode>>::deserialize(deserializer)?;
+    Ok(nodes.map(|nodes| {
+        let mut commands = HashMap::new();
+        flatten("", nodes, &mut commands);
+        commands
+    }))
+}
+
+/// The names a project's run config can have, in order of precedence:
+/// only the first one present in a directory is read
+pub const CONFIG_FILES: &[&str] = &["run.toml", "run.yaml", "run.yml", "run.json"];
+
+/// The run config of `dir`: the first of [`CONFIG_FILES`] there
+pub fn config_file(dir: &Path) -> Option<PathBuf> {
+    CONFIG_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Parse the config file at `path`, as YAML or JSON by its extension and
+/// as TOML otherwise; errors name the file
+pub fn parse<T: DeserializeOwned>(path: &Path, content: &str) -> Result<T, RunError> {
+    let parsed = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml") => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+        Some("json") => serde_json::from_str(content).map_err(|e| e.to_string()),
+        _ => toml::from_str(content).map_err(|e| e.to_string()),
+    };
+    parsed.map_err(|e| RunError::ConfigError(format!("{}: {}", path.display(), e)))
+}
+
+#[derive(Deserialize)]
+struct RunConfig {
+    #[serde(default, deserialize_with = "deserialize_commands")]
+    commands: Option<HashMap<String, CommandEntry>>,
+}
+
+#[derive(Deserialize)]
+struct Includes {
+    #[serde(default)]
+    include: Vec<String>,
+}
+
+/// A config file and the files it pulls in with `include = [...]` (paths
+/// relative to the including file), with their contents, in increasing
+/// precedence: included files first, in order, then the file itself.
+/// A file including itself, directly or not, is a
+/// [`RunError::ConfigError`] naming the cycle.
+pub fn config_files(path: &Path) -> Result<Vec<(PathBuf, String)>, RunError> {
+    let mut files = Vec::new();
+    visit_includes(path, &mut Vec::new(), &mut files)?;
+    Ok(files)
+}
+
+/// Depth-first step of [`config_files`]; `stack` holds the files being
+/// read, so meeting one of them again closes a cycle
+fn visit_includes(
+    path: &Path,
+    stack: &mut Vec<PathBuf>,
+    files: &mut Vec<(PathBuf, String)>,
+) -> Result<(), RunError> {
+    let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    if let Some(start) = stack.iter().position(|p| *p == canonical) {
+        let cycle: Vec<String> = stack[start..]
+            .iter()
+            .chain([&canonical])
+            .map(|p| p.display().to_string())
+            .collect();
+        return Err(RunError::ConfigError(format!(
+            "Include cycle: {}",
+            cycle.join(" -> ")
+        )));
+    }
+
+    let content = fs::read_to_string(path)?;
+    let includes: Includes = parse(path, &content)?;
+    let base = path.parent().unwrap_or(Path::new("."));
+
+    stack.push(canonical);
+    for include in includes.include {
+        let included = base.join(&include);
+        if !included.is_file() {
+            return Err(RunError::ConfigError(format!(
+                "{} includes {}, which does not exist",
+                path.display(),
+                include
+            )));
+        }
+        visit_includes(&included, stack, files)?;
+    }
+    stack.pop();
+
+    files.push((path.to_path_buf(), content));
+    Ok(())
+}
+
+/// The `[commands]` of the config file at `path` alone, if it parses
+fn file_entries(path: &Path, content: &str) -> HashMap<String, CommandEntry> {
+    parse::<RunConfig>(path, content)
+        .ok()
+        .and_then(|config| config.commands)
+        .unwrap_or_default()
+}
+
+/// The `[commands]` of `dir`'s run config and the files it includes, if
+/// they all parse
+pub fn entries(dir: &Path) -> HashMap<String, CommandEntry> {
+    let mut entries = HashMap::new();
+    let Some(path) = config_file(dir) else {
+        return entries;
+    };
+    for (path, content) in config_files(&path).unwrap_or_default() {
+        entries.extend(file_entries(&path, &content));
+    }
+    entries
+}
+
+/// Entries that run a command, as command lines
+pub fn command_lines(entries: &HashMap<String, CommandEntry>) -> HashMap<String, String> {
+    entries
+        .iter()
+        .filter(|(_, entry)| !entry.command().trim().is_empty())
+        .map(|(task, entry)| (task.clone(), entry.command().to_string()))
+        .collect()
+}
+
+/// Entries to leave out of listings: marked `private = true`, or with a
+/// `condition` that doesn't hold in `dir`
+pub fn hidden_tasks(entries: &HashMap<String, CommandEntry>, dir: &Path) -> Vec<String> {
+    let mut tasks: Vec<String> = entries
+        .iter()
+        .filter(|(_, entry)| match entry {
+            CommandEntry::Task(task) => {
+                task.private
+                    || task.condition.as_ref().is_some_and(|condition| {
+                        condition
+                            .unmet(dir, |var| std::env::var_os(var).is_some())
+                            .is_some()
+                    })
+            }
+            CommandEntry::Line(_) => false,
+        })
+        .map(|(task, _)| task.clone())
+        .collect();
+    tasks.sort();
+    tasks
+}
+
+/// Where `dir`'s run config declares the command of `task` (see
+/// [`file_definition`])
+pub fn definition(dir: &Path, task: &str) -> Option<TaskDefinition> {
+    file_definition(&config_file(dir)?, task)
+}
+
+/// Where the config file at `path`, or the file it includes that wins,
+/// declares the command of `task`: its `task = ...` line under
+/// `[commands]`, or its `[commands.task]` header
+pub fn file_definition(path: &Path, task: &str) -> Option<TaskDefinition> {
+    let files = config_files(path).ok()?;
+    let (path, content, entry) = files.into_iter().rev().find_map(|(path, content)| {
+        let entry = file_entries(&path, &content).remove(task)?;
+        Some((path, content, entry))
+    })?;
+    let line = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("yaml" | "yml" | "json") => nested_key_line(&content, task)?,
+        _ => toml_key_line(&content, task)?,
+    };
+
+    Some(TaskDefinition {
+        file: path,
+        line: line + 1,
+        body: entry.command().to_string(),
+    })
+}
+
+/// The index of the line of TOML `content` declaring `task` under
+/// `[commands]`: its `task = ...` line, or its `[commands.task]` header
+fn toml_key_line(content: &str, task: &str) -> Option<usize> {
+    // The task name prefix of keys in the current table, inside `[commands]`
+    let mut namespace: Option<String> = None;
+    content.lines().position(|line| {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix('[') {
+            let path = task_path(header.trim_end_matches(']'));
+            namespace = match path.strip_prefix("commands") {
+                Some("") => Some(String::new()),
+                Some(rest) => rest.strip_prefix(':').map(|name| format!("{}:", name)),
+                None => None,
+            };
+            return namespace.as_deref() == Some(&format!("{}:", task));
+        }
+        namespace.as_ref().is_some_and(|namespace| {
+            line.split_once('=')
+                .is_some_and(|(key, _)| format!("{}{}", namespace, task_path(key)) == task)
+        })
+    })
+}
+
+/// The index of the line of YAML or JSON `content` declaring `task`: the
+/// first `commands` key, then each part of the task's name in turn
+fn nested_key_line(content: &str, task: &str) -> Option<usize> {
+    let mut keys = std::iter::once("commands").chain(task.split(':'));
+    let mut key = keys.next();
+    for (index, line) in content.lines().enumerate() {
+        let mut rest = line;
+        while let Some(end) = key.and_then(|key| key_end(rest, key)) {
+            rest = &rest[end..];
+            key = keys.next();
+            if key.is_none() {
+                return Some(index);
+            }
+        }
+    }
+    None
+}
+
+/// Where the first `key:`, `"key":` or `'key':` in `text` ends
+fn key_end(text: &str, key: &str) -> Option<usize> {
+    let plain = text.trim_start();
+    if plain
+        .strip_prefix(key)
+        .is_some_and(|after| after.starts_with(':'))
+    {
+        return Some(text.len() - plain.len() + key.len() + 1);
+    }
+    ['"', '\''].iter().find_map(|quote| {
+        let quoted = format!("{}{}{}", quote, key, quote);
+        let after = text[text.find(&quoted)? + quoted.len()..].trim_start();
+        after.starts_with(':').then(|| text.len() - after.len() + 1)
+    })
+}
+
+/// The words of a custom command line, split like a shell would
+pub fn words(line: &str) -> Vec<String> {
+    // An unbalanced quote falls back to plain whitespace splitting
+    shell_words::split(line)
+        .unwrap_or_else(|_| line.split_whitespace().map(|s| s.to_string()).collect())
+}
+
+/// A `{args}` or `{N}` placeholder in a custom command
+enum Placeholder {
+    /// Every argument
+    Args,
+    /// The Nth argument, from 1
+    Position(usize),
+}
+
+/// `word` with each placeholder replaced by what `value` returns for it;
+/// other braces (`${HOME}`, `find -exec {} \;`) are left alone
+fn expand_word(word: &str, mut value: impl FnMut(Placeholder) -> String) -> String {
+    let mut expanded = String::new();
+    let mut rest = word;
+    while let Some(open) = rest.find('{') {
+        expanded.push_str(&rest[..open]);
+        let after = &rest[open + 1..];
+        let placeholder = after.find('}').and_then(|close| {
+            let placeholder = match &after[..close] {
+                "args" => Placeholder::Args,
+                n => Placeholder::Position(n.parse().ok().filter(|&n| n > 0)?),
+            };
+            Some((placeholder, close))
+        });
+        match placeholder {
+            Some((placeholder, close)) => {
+                expanded.push_str(&value(placeholder));
+                rest = &after[close + 1..];
+            }
+            None => {
+                expanded.push('{');
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// The words of the custom command `line` given `args`.
+///
+/// Without placeholders the arguments are appended. Otherwise `{1}`, `{2}`...
+/// take the arguments in position and `{args}` all of them, and the
+/// arguments go nowhere else: missing or unused ones are an error. A word
+/// that is just a placeholder becomes the argument(s) as they are; inside a
+/// word with spaces (a script for `sh -c`) values are shell-quoted.
+pub fn interpolate(line: &str, args: &[String]) -> Result<Vec<String>, String> {
+    let words = words(line);
+    if !check_arity(&words, args)? {
+        return Ok(words.into_iter().chain(args.iter().cloned()).collect());
+    }
+
+    let mut expanded = Vec::new();
+    for word in words {
+        if word == "{args}" {
+            expanded.extend(args.iter().cloned());
+            continue;
+        }
+        let quote = word.contains(char::is_whitespace);
+        expanded.push(expand_word(&word, |placeholder| {
+            substitute(placeholder, args, quote)
+        }));
+    }
+    Ok(expanded)
+}
+
+/// The custom command `line` as a script for a shell, given `args`: like
+/// [`interpolate`], but the whole line is one script, so every value is
+/// shell-quoted and arguments without placeholders are appended quoted
+pub fn script(line: &str, args: &[String]) -> Result<String, String> {
+    if !check_arity(&[line], args)? {
+        return Ok(std::iter::once(line.to_string())
+            .chain(args.iter().map(|arg| shell_words::quote(arg).into_owned()))
+            .collect::<Vec<_>>()
+            .join(" "));
+    }
+    Ok(expand_word(line, |placeholder| {
+        substitute(placeholder, args, true)
+    }))
+}
+
+/// `text` with each `{{name}}` of `vars` replaced by its value. Other
+/// `{{...}}`, such as Go templates (`{{.Names}}`, `{{end}}`), are kept.
+pub fn expand_vars(text: &str, vars: &HashMap<String, String>) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let value = after
+            .find("}}")
+            .and_then(|end| Some((vars.get(after[..end].trim())?, end)));
+        match value {
+            Some((value, end)) => {
+                expanded.push_str(value);
+                rest = &after[end + 2..];
+            }
+            None => {
+                expanded.push_str("{{");
+                rest = after;
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Whether `words` have placeholders, failing when `args` are missing for
+/// them or left over
+fn check_arity(words: &[impl AsRef<str>], args: &[String]) -> Result<bool, String> {
+    // What the placeholders ask for: the highest position, and whether
+    // `{args}` takes everything
+    let (mut found, mut required, mut all) = (false, 0, false);
+    for word in words {
+        expand_word(word.as_ref(), |placeholder| {
+            found = true;
+            match placeholder {
+                Placeholder::Args => all = true,
+                Placeholder::Position(n) => required = required.max(n),
+            }
+            String::new()
+        });
+    }
+    if !found {
+        return Ok(false);
+    }
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    if args.len() < required {
+        return Err(format!(
+            "needs {} argument{}, got {}",
+            required,
+            plural(required),
+            args.len()
+        ));
+    }
+    if !all && args.len() > required {
+        return Err(format!(
+            "takes {} argument{}, got {}",
+            required,
+            plural(required),
+            args.len()
+        ));
+    }
+    Ok(true)
+}
+
+/// The value of `placeholder`, shell-quoted when it lands in a script
+fn substitute(placeholder: Placeholder, args: &[String], quote: bool) -> String {
+    match placeholder {
+        Placeholder::Args if quote => shell_words::join(args),
+        Placeholder::Args => args.join(" "),
+        Placeholder::Position(n) if quote => shell_words::quote(&args[n - 1]).into_owned(),
+        Placeholder::Position(n) => args[n - 1].clone(),
+    }
+}
+
+/// How a task's command line is run (`shell` in run.toml): split into words
+/// and executed directly (`false`), or handed as a script to the platform's
+/// shell (`true`) or a named one (`"bash"`, `"pwsh"`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Shell {
+    Enabled(bool),
+    Program(String),
+}
+
+impl Shell {
+    /// How a script file runs: the words before its path (`sh`, `cmd /C`,
+    /// `pwsh -File`) and the extension the shell needs. Scripts always go
+    /// through a shell, the platform's when `shell` is `false`.
+    pub fn script_invocation(&self) -> (Vec<String>, &'static str) {
+        let shell = match self {
+            Shell::Enabled(false) => Shell::Enabled(true),
+            shell => shell.clone(),
+        };
+        let mut words = shell.invocation().unwrap_or_default();
+        let extension = match words.get(1).map(String::as_str) {
+            Some("/C") => "cmd",
+            Some("-Command") => {
+                words[1] = "-File".to_string();
+                "ps1"
+            }
+            _ => {
+                words.truncate(1);
+                "sh"
+            }
+        };
+        (words, extension)
+    }
+
+    /// The shell and the words before the script (`sh -c`, `cmd /C`,
+    /// `pwsh -Command`), or `None` for direct execution
+    pub fn invocation(&self) -> Option<Vec<String>> {
+        let program = match self {
+            Shell::Enabled(false) => return None,
+            Shell::Enabled(true) if cfg!(windows) => "cmd",
+            Shell::Enabled(true) => "sh",
+            Shell::Program(program) => program.as_str(),
+        };
+        // Either separator, so Windows paths are understood everywhere
+        let name = program
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or(program)
+            .to_ascii_lowercase();
+        let name = name.strip_suffix(".exe").unwrap_or(&name);
+        let flag = match name {
+            "cmd" => "/C",
+            "pwsh" | "powershell" => "-Command",
+            _ => "-c",
+        };
+        Some(vec![program.to_string(), flag.to_string()])
+    }
+}
+
+/// The task name a dotted TOML key stands for: its parts without their
+/// quotes, joined with `:` (`db."seed-all"` is `db:seed-all`)
+fn task_path(key: &str) -> String {
+    let mut parts = Vec::new();
+    let mut part = String::new();
+    let mut quote = None;
+    for c in key.chars() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '.') => parts.push(std::mem::take(&mut part)),
+            _ => part.push(c),
+        }
+    }
+    parts.push(part);
+    parts
+        .iter()
+        .map(|part| part.trim())
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+pub struct CustomValidator {
+    commands: HashMap<String, String>,
+}
+
+impl CustomValidator {
+    pub fn new(commands: HashMap<String, String>) -> Self {
+        Self { commands }
+    }
+}
+
+impl CommandValidator for CustomValidator {
+    fn supports_command(&self, _working_dir: &Path, command: &str) -> CommandSupport {
+        if self.commands.contains_key(command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, _working_dir: &Path) -> Vec<String> {
+        self.commands.keys().cloned().collect()
+    }
+}
+
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let Some(file) = CONFIG_FILES.iter().find(|name| scan.has(name)) else {
+        return vec![];
+    };
+
+    // Entries without a command (settings only) are left out
+    let entries = entries(scan.path());
+    let valid_commands = command_lines(&entries);
+    if valid_commands.is_empty() {
+        return vec![];
+    }
+
+    // Return a single runner for the custom commands
+    // Priority 0 means it overrides everything else
+    vec![DetectedRunner::with_custom_commands(
+        "custom",
+        file,
+        Ecosystem::Custom,
+        0,
+        Arc::new(CustomValidator {
+            commands: valid_commands.clone(),
+        }),
+        valid_commands,
+    )
+    .with_private(hidden_tasks(&entries, scan.path()))]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_command_entries() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            r#"[commands]
+hello = "echo hello"
+e2e = { cmd = "playwright test", timeout = "10m", retries = 2 }
+test = { timeout = "90s" }
+empty = ""
+
+[commands.build]
+cmd = "cargo build"
+depends_on = ["codegen"]
+"#,
+        )
+        .unwrap();
+
+        let entries = entries(dir.path());
+        assert_eq!(entries["hello"], CommandEntry::from("echo hello"));
+        assert_eq!(entries["e2e"].settings().timeout.as_deref(), Some("10m"));
+        assert_eq!(entries["e2e"].settings().retries, Some(2));
+        assert_eq!(entries["test"].command(), "");
+        assert_eq!(entries["build"].settings().depends_on, vec!["codegen"]);
+        assert!(entries["hello"].settings().depends_on.is_empty());
+
+        // Only entries with a command make tasks of the custom runner
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        let mut tasks = runners[0].list_commands(dir.path());
+        tasks.sort();
+        assert_eq!(tasks, vec!["build", "e2e", "hello"]);
+        assert_eq!(
+            runners[0].build_command("e2e", &[]),
+            vec!["playwright", "test"]
+        );
+    }
+
+    #[test]
+    fn test_platform_commands() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            r#"[commands.clean]
+cmd.windows = "rmdir /s /q dist"
+cmd.unix = "rm -rf dist"
+
+[commands.open]
+cmd.macos = "open index.html"
+cmd.linux = "xdg-open index.html"
+cmd.unix = "sensible-browser index.html"
+
+[commands.installer]
+cmd.windows = "makensis setup.nsi"
+"#,
+        )
+        .unwrap();
+
+        let entries = entries(dir.path());
+        let (clean, open, installer) = if cfg!(windows) {
+            ("rmdir /s /q dist", "", "makensis setup.nsi")
+        } else if cfg!(target_os = "macos") {
+            ("rm -rf dist", "open index.html", "")
+        } else if cfg!(target_os = "linux") {
+            ("rm -rf dist", "xdg-open index.html", "")
+        } else {
+            ("rm -rf dist", "sensible-browser index.html", "")
+        };
+        assert_eq!(entries["clean"].command(), clean);
+        assert_eq!(entries["open"].command(), open);
+        assert_eq!(entries["installer"].command(), installer);
+
+        // A task without a variant for this platform is not a custom command
+        let tasks = detect(&DirScan::new(dir.path()))[0].list_commands(dir.path());
+        assert_eq!(tasks.contains(&"installer".to_string()), cfg!(windows));
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        // No placeholders: arguments are appended
+        assert_eq!(
+            interpolate("cargo test", &args(&["--release"])).unwrap(),
+            vec!["cargo", "test", "--release"]
+        );
+        assert_eq!(
+            interpolate(
+                "scripts/deploy.sh {1} --region={2}",
+                &args(&["prod", "eu west"])
+            )
+            .unwrap(),
+            vec!["scripts/deploy.sh", "prod", "--region=eu west"]
+        );
+        assert_eq!(
+            interpolate("pytest -x {args} tests", &args(&["-k", "slow and db"])).unwrap(),
+            vec!["pytest", "-x", "-k", "slow and db", "tests"]
+        );
+        // Inside a script, values are quoted for the shell
+        assert_eq!(
+            interpolate("sh -c 'echo {1} && ls {args}'", &args(&["a b", "c"])).unwrap(),
+            vec!["sh", "-c", "echo 'a b' && ls 'a b' c"]
+        );
+        // Braces that aren't placeholders are kept
+        assert_eq!(
+            interpolate("find . -exec echo {} ${HOME} {0} ;", &[]).unwrap(),
+            vec!["find", ".", "-exec", "echo", "{}", "${HOME}", "{0}", ";"]
+        );
+
+        assert_eq!(
+            interpolate("deploy.sh {1} --region {2}", &args(&["prod"])).unwrap_err(),
+            "needs 2 arguments, got 1"
+        );
+        assert_eq!(
+            interpolate("deploy.sh {1}", &args(&["prod", "extra"])).unwrap_err(),
+            "takes 1 argument, got 2"
+        );
+    }
+
+    #[test]
+    fn test_script() {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            script("cargo build && cargo test", &args(&["--", "it's"])).unwrap(),
+            "cargo build && cargo test -- 'it'\\''s'"
+        );
+        assert_eq!(
+            script("ls {1} | grep {2}", &args(&["my dir", "x"])).unwrap(),
+            "ls 'my dir' | grep x"
+        );
+        assert!(script("ls {1}", &[]).is_err());
+    }
+
+    #[test]
+    fn test_expand_vars() {
+        let vars: HashMap<String, String> = [
+            ("registry".to_string(), "ghcr.io/acme".to_string()),
+            ("tag".to_string(), "v1 beta".to_string()),
+        ]
+        .into();
+
+        assert_eq!(
+            expand_vars("docker push {{registry}}/app:{{ tag }}", &vars),
+            "docker push ghcr.io/acme/app:v1 beta"
+        );
+        assert_eq!(
+            expand_vars(
+                "docker ps --format '{{.Names}}' {{missing}} {{registry",
+                &vars
+            ),
+            "docker ps --format '{{.Names}}' {{missing}} {{registry"
+        );
+        assert_eq!(expand_vars("{{{{registry}}}}", &vars), "{{ghcr.io/acme}}");
+    }
+
+    #[test]
+    fn test_shell_invocation() {
+        assert_eq!(Shell::Enabled(false).invocation(), None);
+        assert_eq!(
+            Shell::Program("bash".to_string()).invocation().unwrap(),
+            vec!["bash", "-c"]
+        );
+        assert_eq!(
+            Shell::Program("pwsh".to_string()).invocation().unwrap(),
+            vec!["pwsh", "-Command"]
+        );
+        assert_eq!(
+            Shell::Program("C:\\Windows\\System32\\cmd.exe".to_string())
+                .invocation()
+                .unwrap()[1],
+            "/C"
+        );
+        let default = if cfg!(windows) { "cmd" } else { "sh" };
+        assert_eq!(Shell::Enabled(true).invocation().unwrap()[0], default);
+
+        let shell: TaskSettings = toml::from_str("shell = false").unwrap();
+        assert_eq!(shell.shell, Some(Shell::Enabled(false)));
+    }
+
+    #[test]
+    fn test_script_entries() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            r#"[commands]
+ci = { script = ["set -e", "cargo build", "cargo test"] }
+
+[commands.release]
+script = """
+set -e
+git tag v1
+"""
+"#,
+        )
+        .unwrap();
+
+        let entries = entries(dir.path());
+        assert_eq!(entries["ci"].command(), "set -e\ncargo build\ncargo test\n");
+        assert_eq!(entries["release"].command(), "set -e\ngit tag v1\n");
+        let tasks = detect(&DirScan::new(dir.path()))[0].list_commands(dir.path());
+        assert_eq!(tasks.len(), 2);
+
+        assert_eq!(
+            Shell::Enabled(false).script_invocation().1,
+            if cfg!(windows) { "cmd" } else { "sh" }
+        );
+        assert_eq!(
+            Shell::Program("bash".to_string()).script_invocation(),
+            (vec!["bash".to_string()], "sh")
+        );
+        assert_eq!(
+            Shell::Program("pwsh".to_string()).script_invocation(),
+            (vec!["pwsh".to_string(), "-File".to_string()], "ps1")
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("docker-compose.yml"), "").unwrap();
+        let unmet = |condition: &str| {
+            toml::from_str::<Condition>(condition)
+                .unwrap()
+                .unmet(dir.path(), |var| var == "CI")
+        };
+
+        assert_eq!(unmet(""), None);
+        assert_eq!(unmet("file_exists = \"docker-compose.yml\""), None);
+        assert_eq!(
+            unmet("file_exists = \"compose.yaml\""),
+            Some("compose.yaml does not exist".to_string())
+        );
+        assert_eq!(unmet("env_set = \"CI\""), None);
+        assert_eq!(
+            unmet("env_set = \"DEPLOY_TOKEN\""),
+            Some("DEPLOY_TOKEN is not set".to_string())
+        );
+        assert_eq!(unmet(&format!("os = \"{}\"", std::env::consts::OS)), None);
+        assert_eq!(
+            unmet("os = \"plan9\""),
+            Some("it only runs on plan9".to_string())
+        );
+        assert_eq!(unmet("os = \"unix\"").is_none(), cfg!(unix));
+    }
+
+    #[test]
+    fn test_confirm() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            "[commands]\n\
+             deploy = { cmd = \"./deploy.sh\", confirm = true }\n\
+             wipe = { cmd = \"./wipe.sh\", confirm = \"Wipe prod?\" }\n\
+             test = { cmd = \"cargo test\", confirm = false }\n",
+        )
+        .unwrap();
+
+        let question = |task: &str| {
+            entries(dir.path())[task]
+                .settings()
+                .confirm
+                .and_then(|confirm| confirm.question(task))
+        };
+        assert_eq!(question("deploy").as_deref(), Some("Run 'deploy'?"));
+        assert_eq!(question("wipe").as_deref(), Some("Wipe prod?"));
+        assert_eq!(question("test"), None);
+    }
+
+    #[test]
+    fn test_namespaces() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            r#"[commands]
+build = "make"
+docs.serve = "mkdocs serve"
+
+[commands.db]
+migrate = "sqlx migrate run"
+seed = { cmd = "./seed.sh", depends_on = ["db:migrate"] }
+
+[commands.db.reset]
+cmd = "./reset.sh"
+"#,
+        )
+        .unwrap();
+
+        let tasks = entries(dir.path());
+        let mut names: Vec<&String> = tasks.keys().collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["build", "db:migrate", "db:reset", "db:seed", "docs:serve"]
+        );
+        assert_eq!(tasks["db:seed"].settings().depends_on, vec!["db:migrate"]);
+        assert_eq!(tasks["db:reset"].command(), "./reset.sh");
+
+        assert_eq!(definition(dir.path(), "docs:serve").unwrap().line, 3);
+        assert_eq!(definition(dir.path(), "db:migrate").unwrap().line, 6);
+        assert_eq!(definition(dir.path(), "db:reset").unwrap().line, 9);
+    }
+
+    #[test]
+    fn test_private_tasks() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            r#"[commands]
+build = "make"
+_codegen = "buf generate"
+setup = { cmd = "./setup.sh", private = true }
+"#,
+        )
+        .unwrap();
+
+        let runner = &detect(&DirScan::new(dir.path()))[0];
+        assert_eq!(runner.private, vec!["setup"]);
+        assert!(runner.is_private("_codegen") && runner.is_private("setup"));
+        assert_eq!(runner.list_commands(dir.path()), vec!["build"]);
+        assert_eq!(
+            runner.supports_command("setup", dir.path()),
+            CommandSupport::Supported
+        );
+    }
+
+    #[test]
+    fn test_included_entries() {
+        let dir = tempdir().unwrap();
+        let shared = dir.path().join("shared");
+        fs::create_dir(&shared).unwrap();
+        fs::write(
+            shared.join("tasks.toml"),
+            "[commands]\nlint = \"eslint .\"\nbuild = \"make\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            "include = [\"shared/tasks.toml\"]\n\n[commands]\nbuild = \"cargo build\"\n",
+        )
+        .unwrap();
+
+        let tasks = entries(dir.path());
+        assert_eq!(tasks["lint"].command(), "eslint .");
+        assert_eq!(tasks["build"].command(), "cargo build");
+        let lint = definition(dir.path(), "lint").unwrap();
+        assert_eq!((lint.file, lint.line), (shared.join("tasks.toml"), 2));
+        let build = definition(dir.path(), "build").unwrap();
+        assert_eq!((build.file, build.line), (dir.path().join("run.toml"), 4));
+
+        fs::write(shared.join("tasks.toml"), "include = [\"../run.toml\"]\n").unwrap();
+        let error = config_files(&dir.path().join("run.toml")).unwrap_err();
+        assert!(error.to_string().contains("Include cycle"));
+        assert!(entries(dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_yaml_and_json_configs() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.yaml"),
+            "include: [shared.json]\ncommands:\n  build: cargo build\n  db:\n    migrate:\n      cmd: sqlx migrate run\n      private: true\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("shared.json"),
+            r#"{ "commands": { "lint": "eslint .", "build": "make" } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(config_file(dir.path()), Some(dir.path().join("run.yaml")));
+        let tasks = entries(dir.path());
+        assert_eq!(tasks["build"].command(), "cargo build");
+        assert_eq!(tasks["lint"].command(), "eslint .");
+        assert!(tasks["db:migrate"].settings().private);
+        assert_eq!(definition(dir.path(), "build").unwrap().line, 3);
+        assert_eq!(definition(dir.path(), "db:migrate").unwrap().line, 5);
+        let lint = definition(dir.path(), "lint").unwrap();
+        assert_eq!((lint.file, lint.line), (dir.path().join("shared.json"), 1));
+
+        // run.toml comes first
+        fs::write(
+            dir.path().join("run.toml"),
+            "[commands]\ntest = \"cargo test\"\n",
+        )
+        .unwrap();
+        assert_eq!(config_file(dir.path()), Some(dir.path().join("run.toml")));
+        assert!(!entries(dir.path()).contains_key("build"));
+    }
+
+    #[test]
+    fn test_command_definition() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            r#"hello = "not a command"
+
+[commands]
+hello = "echo hello"
+"e2e:ci" = { cmd = "playwright test" }
+
+[commands.deploy]
+cmd = "./deploy.sh"
+"#,
+        )
+        .unwrap();
+
+        let hello = definition(dir.path(), "hello").unwrap();
+        assert_eq!((hello.line, hello.body.as_str()), (4, "echo hello"));
+        assert_eq!(definition(dir.path(), "e2e:ci").unwrap().line, 5);
+        let deploy = definition(dir.path(), "deploy").unwrap();
+        assert_eq!((deploy.line, deploy.body.as_str()), (7, "./deploy.sh"));
+        assert!(definition(dir.path(), "missing").is_none());
+    }
+}
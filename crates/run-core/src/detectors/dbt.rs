@@ -0,0 +1,104 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::path::Path;
+use std::sync::Arc;
+
+/// dbt subcommands
+pub const DBT_COMMANDS: &[&str] = &[
+    "build",
+    "clean",
+    "clone",
+    "compile",
+    "debug",
+    "deps",
+    "docs",
+    "init",
+    "list",
+    "ls",
+    "parse",
+    "retry",
+    "run",
+    "run-operation",
+    "seed",
+    "show",
+    "snapshot",
+    "source",
+    "test",
+];
+
+/// Validator for dbt subcommands
+pub struct DbtValidator;
+
+impl CommandValidator for DbtValidator {
+    fn supports_command(&self, _working_dir: &Path, command: &str) -> CommandSupport {
+        if DBT_COMMANDS.contains(&command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, _working_dir: &Path) -> Vec<String> {
+        DBT_COMMANDS.iter().map(|c| c.to_string()).collect()
+    }
+}
+
+/// Detect dbt projects
+/// Priority: 36
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has("dbt_project.yml") {
+        let validator: Arc<dyn CommandValidator> = Arc::new(DbtValidator);
+        runners.push(DetectedRunner::with_validator(
+            "dbt",
+            "dbt_project.yml",
+            Ecosystem::Dbt,
+            36,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_dbt() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("dbt_project.yml"), "name: demo\n").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "dbt");
+        assert_eq!(
+            runners[0].build_command("run", &["--select".to_string(), "orders".to_string()]),
+            vec!["dbt", "run", "--select", "orders"]
+        );
+
+        let validator = DbtValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "seed"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "lint"),
+            CommandSupport::NotSupported
+        );
+    }
+}
@@ -9,8 +9,8 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
-use std::fs;
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -52,27 +52,29 @@ impl CommandValidator for DenoValidator {
 
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        ["deno.json", "deno.jsonc"]
+            .iter()
+            .filter_map(|file| manifest::jsonc(&working_dir.join(file)))
+            .filter_map(|json| {
+                let tasks = json.get("tasks")?.as_object()?;
+                Some(tasks.keys().cloned().collect::<Vec<_>>())
+            })
+            .flatten()
+            .collect()
+    }
 }
 
 fn check_deno_task(dir: &Path, command: &str) -> bool {
     let check_file = |path: &Path| -> bool {
-        if let Ok(content) = fs::read_to_string(path) {
-            // Try parsing as standard JSON first
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Some(tasks) = json.get("tasks").and_then(|t| t.as_object()) {
-                    return tasks.contains_key(command);
-                }
-            } else {
-                // Try removing comments for JSONC
-                let stripped = strip_jsonc_comments(&content);
-                if let Ok(json) = serde_json::from_str::<serde_json::Value>(&stripped) {
-                    if let Some(tasks) = json.get("tasks").and_then(|t| t.as_object()) {
-                        return tasks.contains_key(command);
-                    }
-                }
-            }
-        }
-        false
+        manifest::jsonc(path)
+            .and_then(|json| {
+                json.get("tasks")
+                    .and_then(|t| t.as_object())
+                    .map(|tasks| tasks.contains_key(command))
+            })
+            .unwrap_or(false)
     };
 
     let deno_json = dir.join("deno.json");
@@ -88,76 +90,14 @@ fn check_deno_task(dir: &Path, command: &str) -> bool {
     false
 }
 
-/// Simple JSONC comment stripper
-fn strip_jsonc_comments(json: &str) -> String {
-    let mut result = String::with_capacity(json.len());
-    let mut chars = json.chars().peekable();
-    let mut in_string = false;
-    let mut escape = false;
-
-    while let Some(c) = chars.next() {
-        if in_string {
-            result.push(c);
-            if escape {
-                escape = false;
-            } else if c == '\\' {
-                escape = true;
-            } else if c == '"' {
-                in_string = false;
-            }
-        } else {
-            match c {
-                '"' => {
-                    result.push(c);
-                    in_string = true;
-                }
-                '/' => {
-                    if let Some(&next) = chars.peek() {
-                        if next == '/' {
-                            // Line comment
-                            chars.next(); // Consume second slash
-                            for c in chars.by_ref() {
-                                if c == '\n' {
-                                    result.push(c); // Keep newline
-                                    break;
-                                }
-                            }
-                        } else if next == '*' {
-                            // Block comment
-                            chars.next(); // Consume asterisk
-                            while let Some(c) = chars.next() {
-                                if c == '*' {
-                                    if let Some(&next) = chars.peek() {
-                                        if next == '/' {
-                                            chars.next(); // Consume slash
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        } else {
-                            result.push(c);
-                        }
-                    } else {
-                        result.push(c);
-                    }
-                }
-                _ => result.push(c),
-            }
-        }
-    }
-    result
-}
-
 /// Detect Deno projects
 /// Priority: 22 (after generic/Make, but practically Deno is detected via config files so it's specific)
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
     let validator: Arc<dyn CommandValidator> = Arc::new(DenoValidator);
 
     // Check for deno.json
-    let deno_json = dir.join("deno.json");
-    if deno_json.exists() {
+    if scan.has("deno.json") {
         runners.push(DetectedRunner::with_validator(
             "deno",
             "deno.json",
@@ -169,8 +109,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for deno.jsonc
-    let deno_jsonc = dir.join("deno.jsonc");
-    if deno_jsonc.exists() {
+    if scan.has("deno.jsonc") {
         runners.push(DetectedRunner::with_validator(
             "deno",
             "deno.jsonc",
@@ -182,8 +121,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Usually lock files (deno.lock) exist too, but deno.json is primary for tasks
-    let deno_lock = dir.join("deno.lock");
-    if deno_lock.exists() {
+    if scan.has("deno.lock") {
         runners.push(DetectedRunner::with_validator(
             "deno",
             "deno.lock",
@@ -199,6 +137,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::manifest::strip_jsonc_comments;
     use std::fs::File;
     use std::io::Write;
     use tempfile::tempdir;
@@ -208,7 +147,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("deno.json")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "deno");
         assert_eq!(runners[0].detected_file, "deno.json");
@@ -219,7 +158,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("deno.jsonc")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "deno");
         assert_eq!(runners[0].detected_file, "deno.jsonc");
@@ -230,7 +169,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("deno.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "deno");
         assert_eq!(runners[0].detected_file, "deno.lock");
@@ -0,0 +1,125 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Validator for scripts in devbox.json (`shell.scripts`)
+pub struct DevboxValidator;
+
+impl CommandValidator for DevboxValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let json = match manifest::jsonc(&working_dir.join("devbox.json")) {
+            Some(v) => v,
+            None => return CommandSupport::Unknown,
+        };
+
+        let has_script = json
+            .get("shell")
+            .and_then(|shell| shell.get("scripts"))
+            .and_then(|scripts| scripts.as_object())
+            .is_some_and(|scripts| scripts.contains_key(command));
+        if has_script {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        manifest::jsonc(&working_dir.join("devbox.json"))
+            .and_then(|json| {
+                let scripts = json.get("shell")?.get("scripts")?.as_object()?;
+                Some(scripts.keys().cloned().collect())
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Detect devbox projects
+/// Priority: 31
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has("devbox.json") {
+        let validator: Arc<dyn CommandValidator> = Arc::new(DevboxValidator);
+        runners.push(DetectedRunner::with_validator(
+            "devbox",
+            "devbox.json",
+            Ecosystem::Devbox,
+            31,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_devbox() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("devbox.json"), "{}").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "devbox");
+        assert_eq!(
+            runners[0].build_command("test", &["-v".to_string()]),
+            vec!["devbox", "run", "test", "-v"]
+        );
+
+        let empty = tempdir().unwrap();
+        assert!(detect(&DirScan::new(empty.path())).is_empty());
+    }
+
+    #[test]
+    fn test_devbox_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("devbox.json"),
+            r#"{
+  // comments are allowed
+  "packages": ["go@1.22"],
+  "shell": {
+    "init_hook": ["echo ready"],
+    "scripts": {"test": "go test ./...", "serve": ["go run ."]}
+  }
+}"#,
+        )
+        .unwrap();
+
+        let validator = DevboxValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "test"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "serve"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "init_hook"),
+            CommandSupport::NotSupported
+        );
+        assert_eq!(
+            validator.supports_command(tempdir().unwrap().path(), "test"),
+            CommandSupport::Unknown
+        );
+    }
+}
@@ -0,0 +1,267 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+pub const BAKE_FILE: &str = "docker-bake.hcl";
+pub const DOCKERFILE: &str = "Dockerfile";
+
+/// Command line for `task`, built from the bake file or the Dockerfile
+///
+/// With a bake file `build` bakes the default group and any other task bakes
+/// the target of that name. With a Dockerfile `build` builds the context and
+/// other tasks build the stage of that name (`--target`).
+pub fn command(detected_file: &str, task: &str, extra_args: &[String]) -> Vec<String> {
+    let mut cmd: Vec<String> = if detected_file == BAKE_FILE {
+        let mut cmd = vec![
+            "docker".to_string(),
+            "buildx".to_string(),
+            "bake".to_string(),
+        ];
+        if task != "build" {
+            cmd.push(task.to_string());
+        }
+        cmd
+    } else if task == "build" {
+        vec!["docker".to_string(), "build".to_string()]
+    } else {
+        vec![
+            "docker".to_string(),
+            "build".to_string(),
+            "--target".to_string(),
+            task.to_string(),
+        ]
+    };
+    cmd.extend(extra_args.iter().cloned());
+    if detected_file != BAKE_FILE {
+        cmd.push(".".to_string());
+    }
+    cmd
+}
+
+/// Validator for Docker: bake targets and groups, or Dockerfile stages
+///
+/// `build` runs whatever the file builds by default, so it is only claimed
+/// when a target, group or stage is named `build`; otherwise it is `Unknown`,
+/// leaving it to a runner that does declare `build` while still running
+/// `docker build .` when no other runner does.
+pub struct DockerValidator {
+    file: &'static str,
+}
+
+impl CommandValidator for DockerValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let content = match fs::read_to_string(working_dir.join(self.file)) {
+            Ok(c) => c,
+            Err(_) => return CommandSupport::Unknown,
+        };
+        let names = if self.file == BAKE_FILE {
+            extract_bake_targets(&content)
+        } else {
+            extract_stages(&content)
+        };
+
+        if names.contains(command) {
+            CommandSupport::Supported
+        } else if command == "build" {
+            CommandSupport::Unknown
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        let mut commands = vec!["build".to_string()];
+        if let Ok(content) = fs::read_to_string(working_dir.join(self.file)) {
+            if self.file == BAKE_FILE {
+                commands.extend(extract_bake_targets(&content));
+            } else {
+                commands.extend(extract_stages(&content));
+            }
+        }
+        commands
+    }
+}
+
+/// `target "name" {` and `group "name" {` blocks in a bake file
+fn extract_bake_targets(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim_start();
+            let rest = line
+                .strip_prefix("target")
+                .or_else(|| line.strip_prefix("group"))?;
+            let name = rest.trim_start().strip_prefix('"')?;
+            let (name, after) = name.split_once('"')?;
+            after
+                .trim_start()
+                .starts_with('{')
+                .then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// Stage names (`FROM <image> AS <name>`) in a Dockerfile
+fn extract_stages(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            if !words.first()?.eq_ignore_ascii_case("FROM") {
+                return None;
+            }
+            let as_index = words.iter().position(|w| w.eq_ignore_ascii_case("AS"))?;
+            words.get(as_index + 1).map(|name| name.to_string())
+        })
+        .collect()
+}
+
+/// Detect Docker builds (a bake file wins over a plain Dockerfile)
+/// Priority: 28
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if let Some(&file) = [BAKE_FILE, DOCKERFILE].iter().find(|f| scan.has(f)) {
+        let validator: Arc<dyn CommandValidator> = Arc::new(DockerValidator { file });
+        runners.push(DetectedRunner::with_validator(
+            "docker",
+            file,
+            Ecosystem::Container,
+            28,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_docker() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(DOCKERFILE), "FROM alpine\n").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "docker");
+        assert_eq!(runners[0].detected_file, DOCKERFILE);
+
+        fs::write(dir.path().join(BAKE_FILE), "").unwrap();
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].detected_file, BAKE_FILE);
+
+        let empty = tempdir().unwrap();
+        assert!(detect(&DirScan::new(empty.path())).is_empty());
+    }
+
+    #[test]
+    fn test_command() {
+        let args = ["-t".to_string(), "app".to_string()];
+        assert_eq!(
+            command(DOCKERFILE, "build", &args),
+            vec!["docker", "build", "-t", "app", "."]
+        );
+        assert_eq!(
+            command(DOCKERFILE, "test", &[]),
+            vec!["docker", "build", "--target", "test", "."]
+        );
+        assert_eq!(
+            command(BAKE_FILE, "build", &[]),
+            vec!["docker", "buildx", "bake"]
+        );
+        assert_eq!(
+            command(BAKE_FILE, "release", &["--push".to_string()]),
+            vec!["docker", "buildx", "bake", "release", "--push"]
+        );
+    }
+
+    #[test]
+    fn test_bake_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(BAKE_FILE),
+            r#"
+group "default" {
+  targets = ["app"]
+}
+
+target "app" {
+  dockerfile = "Dockerfile"
+}
+
+target "release" {
+  inherits = ["app"]
+}
+"#,
+        )
+        .unwrap();
+
+        let validator = DockerValidator { file: BAKE_FILE };
+        for name in ["default", "app", "release"] {
+            assert_eq!(
+                validator.supports_command(dir.path(), name),
+                CommandSupport::Supported,
+                "{}",
+                name
+            );
+        }
+        assert_eq!(
+            validator.supports_command(dir.path(), "dockerfile"),
+            CommandSupport::NotSupported
+        );
+        // No target named `build`: another runner's `build` comes first
+        assert_eq!(
+            validator.supports_command(dir.path(), "build"),
+            CommandSupport::Unknown
+        );
+    }
+
+    #[test]
+    fn test_dockerfile_build() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(DOCKERFILE), "FROM alpine\n").unwrap();
+        let validator = DockerValidator { file: DOCKERFILE };
+        assert_eq!(
+            validator.supports_command(dir.path(), "build"),
+            CommandSupport::Unknown
+        );
+
+        fs::write(
+            dir.path().join(DOCKERFILE),
+            "FROM rust:1 AS build\nFROM alpine\n",
+        )
+        .unwrap();
+        assert_eq!(
+            validator.supports_command(dir.path(), "build"),
+            CommandSupport::Supported
+        );
+    }
+
+    #[test]
+    fn test_dockerfile_stages() {
+        let stages = extract_stages(
+            "FROM rust:1 AS builder\nRUN cargo build\nfrom alpine as runtime\nFROM scratch\n",
+        );
+        assert_eq!(stages.len(), 2);
+        assert!(stages.contains("builder"));
+        assert!(stages.contains("runtime"));
+    }
+}
@@ -9,7 +9,7 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
 use std::path::Path;
 use std::sync::Arc;
 
@@ -55,28 +55,22 @@ impl CommandValidator for DotNetValidator {
 
 /// Detect .NET projects
 /// Priority: 17
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
     let validator: Arc<dyn CommandValidator> = Arc::new(DotNetValidator);
 
     // Check for .csproj or .sln files
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        for entry in entries.flatten() {
-            let path = entry.path();
-            if let Some(ext) = path.extension() {
-                if ext == "csproj" || ext == "sln" {
-                    let file_name = path.file_name().unwrap().to_string_lossy().to_string();
-                    runners.push(DetectedRunner::with_validator(
-                        "dotnet",
-                        &file_name,
-                        Ecosystem::DotNet,
-                        17,
-                        Arc::clone(&validator),
-                    ));
-                    break; // Only detect once
-                }
-            }
-        }
+    let project = scan
+        .names()
+        .find(|name| name.ends_with(".csproj") || name.ends_with(".sln"));
+    if let Some(file_name) = project {
+        runners.push(DetectedRunner::with_validator(
+            "dotnet",
+            file_name,
+            Ecosystem::DotNet,
+            17,
+            Arc::clone(&validator),
+        ));
     }
 
     runners
@@ -93,7 +87,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("MyApp.csproj")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "dotnet");
         assert_eq!(runners[0].detected_file, "MyApp.csproj");
@@ -104,7 +98,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("MySolution.sln")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "dotnet");
         assert_eq!(runners[0].detected_file, "MySolution.sln");
@@ -114,7 +108,7 @@ mod tests {
     fn test_no_dotnet() {
         let dir = tempdir().unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 }
@@ -0,0 +1,151 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Validator for Earthly targets declared in the Earthfile
+pub struct EarthlyValidator;
+
+impl CommandValidator for EarthlyValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let content = match fs::read_to_string(working_dir.join("Earthfile")) {
+            Ok(c) => c,
+            Err(_) => return CommandSupport::Unknown,
+        };
+
+        let command = command.strip_prefix('+').unwrap_or(command);
+        if extract_earthly_targets(&content).contains(command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        fs::read_to_string(working_dir.join("Earthfile"))
+            .map(|content| extract_earthly_targets(&content).into_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Target names from Earthfile content
+///
+/// Targets are unindented `name:` lines; commands (`VERSION`, `FROM`, ...)
+/// are upper case and never end with a colon.
+fn extract_earthly_targets(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with(char::is_whitespace))
+        .filter_map(|line| line.trim_end().strip_suffix(':'))
+        .filter(|name| {
+            !name.is_empty()
+                && !name.starts_with('#')
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Detect Earthly builds
+/// Priority: 29
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has("Earthfile") {
+        let validator: Arc<dyn CommandValidator> = Arc::new(EarthlyValidator);
+        runners.push(DetectedRunner::with_validator(
+            "earthly",
+            "Earthfile",
+            Ecosystem::Earthly,
+            29,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const EARTHFILE: &str = r#"VERSION 0.8
+FROM golang:1.22
+WORKDIR /src
+
+deps:
+    COPY go.mod go.sum ./
+    RUN go mod download
+
+build:
+    FROM +deps
+    RUN go build -o app
+    SAVE ARTIFACT app
+
+# Runs the unit tests
+unit-test:
+    FROM +deps
+    RUN go test ./...
+"#;
+
+    #[test]
+    fn test_detect_earthly() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Earthfile"), EARTHFILE).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "earthly");
+        assert_eq!(
+            runners[0].build_command("build", &["--push".to_string()]),
+            vec!["earthly", "+build", "--push"]
+        );
+
+        let empty = tempdir().unwrap();
+        assert!(detect(&DirScan::new(empty.path())).is_empty());
+    }
+
+    #[test]
+    fn test_extract_earthly_targets() {
+        let targets = extract_earthly_targets(EARTHFILE);
+        assert_eq!(targets.len(), 3);
+        assert!(targets.contains("deps"));
+        assert!(targets.contains("build"));
+        assert!(targets.contains("unit-test"));
+    }
+
+    #[test]
+    fn test_earthly_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Earthfile"), EARTHFILE).unwrap();
+
+        let validator = EarthlyValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "unit-test"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "+build"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "deploy"),
+            CommandSupport::NotSupported
+        );
+    }
+}
@@ -9,7 +9,7 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
 use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
@@ -82,6 +82,12 @@ impl CommandValidator for MixValidator {
         // Return Unknown to allow fallback behavior
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        fs::read_to_string(working_dir.join("mix.exs"))
+            .map(|content| extract_mix_aliases(&content).into_iter().collect())
+            .unwrap_or_default()
+    }
 }
 
 /// Extract aliases from mix.exs file content
@@ -164,13 +170,11 @@ fn extract_mix_aliases(content: &str) -> HashSet<String> {
 
 /// Detect Elixir projects (Mix)
 /// Priority: 18
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
-    let mix_exs = dir.join("mix.exs");
-
     // mix.exs is sufficient for detection (mix.lock is optional)
-    if mix_exs.exists() {
+    if scan.has("mix.exs") {
         let validator: Arc<dyn CommandValidator> = Arc::new(MixValidator);
         runners.push(DetectedRunner::with_validator(
             "mix",
@@ -197,7 +201,7 @@ mod tests {
         File::create(dir.path().join("mix.exs")).unwrap();
         File::create(dir.path().join("mix.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "mix");
     }
@@ -207,7 +211,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("mix.exs")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "mix");
     }
@@ -216,7 +220,7 @@ mod tests {
     fn test_no_mix() {
         let dir = tempdir().unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 
@@ -360,7 +364,7 @@ end
         )
         .unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "mix");
 
@@ -9,7 +9,8 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
@@ -55,6 +56,24 @@ impl CommandValidator for TaskfileValidator {
 
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        ["Taskfile.yml", "Taskfile.yaml"]
+            .iter()
+            .map(|file| working_dir.join(file))
+            .find(|path| path.exists())
+            .and_then(|path| manifest::yaml(&path))
+            .and_then(|yaml| {
+                let tasks = yaml.get("tasks")?.as_mapping()?;
+                Some(
+                    tasks
+                        .keys()
+                        .filter_map(|k| k.as_str().map(str::to_string))
+                        .collect(),
+                )
+            })
+            .unwrap_or_default()
+    }
 }
 
 /// Validator for Go modules (built-in go commands)
@@ -79,15 +98,13 @@ impl CommandValidator for GoValidator {
 
 /// Detect Go task runners and Go modules
 /// Priority: Taskfile (11) > Go Modules (12)
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
     // Check for Taskfile (priority 11)
-    let taskfile_yml = dir.join("Taskfile.yml");
-    let taskfile_yaml = dir.join("Taskfile.yaml");
     let taskfile_validator: Arc<dyn CommandValidator> = Arc::new(TaskfileValidator);
 
-    if taskfile_yml.exists() {
+    if scan.has("Taskfile.yml") {
         runners.push(DetectedRunner::with_validator(
             "task",
             "Taskfile.yml",
@@ -95,7 +112,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
             11,
             Arc::clone(&taskfile_validator),
         ));
-    } else if taskfile_yaml.exists() {
+    } else if scan.has("Taskfile.yaml") {
         runners.push(DetectedRunner::with_validator(
             "task",
             "Taskfile.yaml",
@@ -107,8 +124,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
 
     // Check for Go Modules (priority 12)
     // go.mod is sufficient for detection (go.sum is optional)
-    let go_mod = dir.join("go.mod");
-    if go_mod.exists() {
+    if scan.has("go.mod") {
         let go_validator: Arc<dyn CommandValidator> = Arc::new(GoValidator);
         runners.push(DetectedRunner::with_validator(
             "go",
@@ -134,7 +150,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Taskfile.yml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "task");
         assert_eq!(runners[0].detected_file, "Taskfile.yml");
@@ -145,7 +161,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Taskfile.yaml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "task");
         assert_eq!(runners[0].detected_file, "Taskfile.yaml");
@@ -157,7 +173,7 @@ mod tests {
         File::create(dir.path().join("go.mod")).unwrap();
         File::create(dir.path().join("go.sum")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "go");
     }
@@ -167,7 +183,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("go.mod")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "go");
     }
@@ -178,7 +194,7 @@ mod tests {
         File::create(dir.path().join("Taskfile.yml")).unwrap();
         File::create(dir.path().join("go.mod")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 2);
         assert!(runners.iter().any(|r| r.name == "task"));
         assert!(runners.iter().any(|r| r.name == "go"));
@@ -310,7 +326,7 @@ tasks:
         )
         .unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "task");
 
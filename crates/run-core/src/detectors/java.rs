@@ -9,11 +9,29 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
 
+/// Gradle wrapper script, preferred over a global `gradle` when present
+#[cfg(not(windows))]
+pub const GRADLE_WRAPPER: &str = "gradlew";
+#[cfg(windows)]
+pub const GRADLE_WRAPPER: &str = "gradlew.bat";
+
+/// Maven wrapper script, preferred over a global `mvn` when present
+#[cfg(not(windows))]
+pub const MAVEN_WRAPPER: &str = "mvnw";
+#[cfg(windows)]
+pub const MAVEN_WRAPPER: &str = "mvnw.cmd";
+
+/// Program that runs a wrapper script from the project root. Windows resolves
+/// `./gradlew` to `gradlew.bat` through `PATHEXT` (see `tools::spawn_command`).
+pub fn wrapper_program(wrapper: &str) -> String {
+    format!("./{}", wrapper.split('.').next().unwrap_or(wrapper))
+}
+
 pub struct JavaValidator;
 
 impl CommandValidator for JavaValidator {
@@ -74,29 +92,57 @@ impl CommandValidator for JavaValidator {
 
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        ["build.gradle", "build.gradle.kts"]
+            .iter()
+            .find_map(|file| fs::read_to_string(working_dir.join(file)).ok())
+            .map(|content| extract_gradle_tasks(&content))
+            .unwrap_or_default()
+    }
+}
+
+/// Tasks declared as `task name`, `task("name")` or `task('name')`
+fn extract_gradle_tasks(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("task"))
+        .filter_map(|rest| {
+            let rest = match rest.strip_prefix('(') {
+                Some(call) => call.strip_prefix(['"', '\''])?,
+                None => rest.strip_prefix(' ')?.trim_start(),
+            };
+            let name: String = rest
+                .chars()
+                .take_while(|c| c.is_alphanumeric() || *c == '_' || *c == '-')
+                .collect();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect()
 }
 
 /// Detect Java/JVM build tools
 /// Priority: Gradle (15) > Maven (16)
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+///
+/// When the project ships a wrapper script next to its build file, the
+/// wrapper is recorded as the detected file so builds use the pinned version.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
     let validator: Arc<dyn CommandValidator> = Arc::new(JavaValidator);
 
     // Check for Gradle (priority 15)
-    let build_gradle = dir.join("build.gradle");
-    let build_gradle_kts = dir.join("build.gradle.kts");
-    if build_gradle.exists() {
-        runners.push(DetectedRunner::with_validator(
-            "gradle",
-            "build.gradle",
-            Ecosystem::Java,
-            15,
-            Arc::clone(&validator),
-        ));
-    } else if build_gradle_kts.exists() {
+    if let Some(&file) = ["build.gradle", "build.gradle.kts"]
+        .iter()
+        .find(|f| scan.has(f))
+    {
+        let file = if scan.has(GRADLE_WRAPPER) {
+            GRADLE_WRAPPER
+        } else {
+            file
+        };
         runners.push(DetectedRunner::with_validator(
             "gradle",
-            "build.gradle.kts",
+            file,
             Ecosystem::Java,
             15,
             Arc::clone(&validator),
@@ -105,11 +151,15 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
 
     // Check for Maven (priority 16)
     // Note: Maven uses the same validator but will return Unknown for most commands
-    let pom_xml = dir.join("pom.xml");
-    if pom_xml.exists() {
+    if scan.has("pom.xml") {
+        let file = if scan.has(MAVEN_WRAPPER) {
+            MAVEN_WRAPPER
+        } else {
+            "pom.xml"
+        };
         runners.push(DetectedRunner::with_validator(
             "maven",
-            "pom.xml",
+            file,
             Ecosystem::Java,
             16,
             Arc::clone(&validator),
@@ -130,7 +180,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("build.gradle")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "gradle");
         assert_eq!(runners[0].detected_file, "build.gradle");
@@ -141,7 +191,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("build.gradle.kts")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "gradle");
         assert_eq!(runners[0].detected_file, "build.gradle.kts");
@@ -152,7 +202,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("pom.xml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "maven");
     }
@@ -163,9 +213,55 @@ mod tests {
         File::create(dir.path().join("build.gradle")).unwrap();
         File::create(dir.path().join("pom.xml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 2);
         assert!(runners.iter().any(|r| r.name == "gradle"));
         assert!(runners.iter().any(|r| r.name == "maven"));
     }
+
+    #[test]
+    fn test_detect_wrappers() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("build.gradle.kts")).unwrap();
+        File::create(dir.path().join("pom.xml")).unwrap();
+        File::create(dir.path().join(GRADLE_WRAPPER)).unwrap();
+        File::create(dir.path().join(MAVEN_WRAPPER)).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners[0].detected_file, GRADLE_WRAPPER);
+        assert_eq!(runners[1].detected_file, MAVEN_WRAPPER);
+        assert!(runners.iter().all(|r| r.is_installed()));
+        assert_eq!(
+            runners[0].build_command("build", &[]),
+            vec!["./gradlew", "build"]
+        );
+        assert_eq!(
+            runners[1].build_command("verify", &["-q".to_string()]),
+            vec!["./mvnw", "verify", "-q"]
+        );
+    }
+
+    #[test]
+    fn test_wrapper_program() {
+        assert_eq!(wrapper_program("gradlew"), "./gradlew");
+        assert_eq!(wrapper_program("gradlew.bat"), "./gradlew");
+        assert_eq!(wrapper_program("mvnw.cmd"), "./mvnw");
+    }
+
+    #[test]
+    fn test_extract_gradle_tasks() {
+        let build = r#"
+task hello {
+    doLast { println 'hi' }
+}
+task("integrationTest") { }
+task('lint-all')
+tasks.register("docs")
+taskGraph.whenReady { }
+"#;
+        assert_eq!(
+            extract_gradle_tasks(build),
+            vec!["hello", "integrationTest", "lint-all"]
+        );
+    }
 }
@@ -9,7 +9,7 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem, TaskDefinition};
 use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
@@ -50,94 +50,135 @@ impl CommandValidator for JustValidator {
 
         CommandSupport::NotSupported
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        ["justfile", "Justfile", ".justfile"]
+            .iter()
+            .map(|name| working_dir.join(name))
+            .find(|path| path.exists())
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| extract_just_recipes(&content).into_iter().collect())
+            .unwrap_or_default()
+    }
+
+    fn definition(&self, working_dir: &Path, command: &str) -> Option<TaskDefinition> {
+        let path = ["justfile", "Justfile", ".justfile"]
+            .iter()
+            .map(|name| working_dir.join(name))
+            .find(|path| path.exists())?;
+        let content = fs::read_to_string(&path).ok()?;
+        let (line, body) = recipe_definition(&content, command)?;
+        Some(TaskDefinition {
+            file: path,
+            line: line + 1,
+            body,
+        })
+    }
 }
 
 /// Extract recipe names from justfile content
 fn extract_just_recipes(content: &str) -> HashSet<String> {
-    let mut recipes = HashSet::new();
-
-    for line in content.lines() {
-        let trimmed = line.trim();
+    content
+        .lines()
+        .filter_map(recipe_name)
+        .map(str::to_string)
+        .collect()
+}
 
-        // Skip empty lines, comments, and lines starting with whitespace (recipe body)
-        if trimmed.is_empty() || trimmed.starts_with('#') || line.starts_with(char::is_whitespace) {
-            continue;
-        }
+/// Name of the recipe a justfile line declares, if it is a recipe header
+fn recipe_name(line: &str) -> Option<&str> {
+    let trimmed = line.trim();
 
-        // Skip variable assignments (contain :=)
-        if trimmed.contains(":=") {
-            continue;
-        }
+    // Skip empty lines, comments, and lines starting with whitespace (recipe body)
+    if trimmed.is_empty() || trimmed.starts_with('#') || line.starts_with(char::is_whitespace) {
+        return None;
+    }
 
-        // Skip set/alias/export directives
-        if trimmed.starts_with("set ")
-            || trimmed.starts_with("alias ")
-            || trimmed.starts_with("export ")
-            || trimmed.starts_with("import ")
-            || trimmed.starts_with("mod ")
-        {
-            continue;
-        }
+    // Skip variable assignments (contain :=)
+    if trimmed.contains(":=") {
+        return None;
+    }
 
-        // Recipe pattern: name args? ':'
-        // Examples:
-        //   build:
-        //   test *args:
-        //   deploy target='prod':
-        //   @hidden:
-        if let Some(colon_pos) = trimmed.find(':') {
-            let before_colon = &trimmed[..colon_pos];
-
-            // Handle @ prefix for quiet recipes
-            let name_part = before_colon.trim_start_matches('@');
-
-            // Extract just the recipe name (before any parameters)
-            let recipe_name = name_part
-                .split_whitespace()
-                .next()
-                .unwrap_or("")
-                .trim_start_matches('['); // Handle attributes like [private]
-
-            // Skip if it looks like a dependency reference or attribute
-            if !recipe_name.is_empty()
-                && !recipe_name.starts_with('[')
-                && !recipe_name.contains('=')
-            {
-                recipes.insert(recipe_name.to_string());
-            }
-        }
+    // Skip set/alias/export directives
+    if trimmed.starts_with("set ")
+        || trimmed.starts_with("alias ")
+        || trimmed.starts_with("export ")
+        || trimmed.starts_with("import ")
+        || trimmed.starts_with("mod ")
+    {
+        return None;
     }
 
-    recipes
+    // Recipe pattern: name args? ':'
+    // Examples:
+    //   build:
+    //   test *args:
+    //   deploy target='prod':
+    //   @hidden:
+    let colon_pos = trimmed.find(':')?;
+    let before_colon = &trimmed[..colon_pos];
+
+    // Handle @ prefix for quiet recipes
+    let name_part = before_colon.trim_start_matches('@');
+
+    // Extract just the recipe name (before any parameters)
+    let recipe_name = name_part
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .trim_start_matches('['); // Handle attributes like [private]
+
+    // Skip if it looks like a dependency reference or attribute
+    (!recipe_name.is_empty() && !recipe_name.starts_with('[') && !recipe_name.contains('='))
+        .then_some(recipe_name)
+}
+
+/// The header line of `recipe` (from 0) and its body, dedented
+fn recipe_definition(content: &str, recipe: &str) -> Option<(usize, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let header = lines
+        .iter()
+        .position(|line| recipe_name(line) == Some(recipe))?;
+    Some((header, indented_body(&lines[header + 1..])))
+}
+
+/// The indented lines at the start of `lines` (blank ones included), without
+/// the first line's indentation
+fn indented_body(lines: &[&str]) -> String {
+    let body: Vec<&str> = lines
+        .iter()
+        .take_while(|line| line.trim().is_empty() || line.starts_with(char::is_whitespace))
+        .copied()
+        .collect();
+    let indent = body
+        .iter()
+        .find(|line| !line.trim().is_empty())
+        .map_or(0, |line| line.len() - line.trim_start().len());
+    body.iter()
+        .map(|line| line.get(indent..).unwrap_or(line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
 }
 
 /// Detect Just command runner
 /// Priority: 10 (between PHP and Go, as it's a generic task runner)
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
     let justfile_priority = ["justfile", "Justfile", ".justfile"];
 
-    // Use read_dir to get exact filenames (case-sensitive on all platforms)
-    if let Ok(entries) = std::fs::read_dir(dir) {
-        let files: HashSet<String> = entries
-            .flatten()
-            .filter_map(|e| e.file_name().into_string().ok())
-            .collect();
-
-        for &target in &justfile_priority {
-            if files.contains(target) {
-                let validator: Arc<dyn CommandValidator> = Arc::new(JustValidator);
-                runners.push(DetectedRunner::with_validator(
-                    "just",
-                    target,
-                    Ecosystem::Generic,
-                    10, // Priority 10 - between PHP (10) and Go (11)
-                    validator,
-                ));
-                break; // Only detect one justfile
-            }
-        }
+    // DirScan compares exact filenames (case-sensitive on all platforms)
+    if let Some(&target) = justfile_priority.iter().find(|f| scan.has(f)) {
+        let validator: Arc<dyn CommandValidator> = Arc::new(JustValidator);
+        runners.push(DetectedRunner::with_validator(
+            "just",
+            target,
+            Ecosystem::Generic,
+            10, // Priority 10 - between PHP (10) and Go (11)
+            validator,
+        ));
     }
 
     runners
@@ -155,7 +196,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("justfile")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "just");
         assert_eq!(runners[0].detected_file, "justfile");
@@ -166,7 +207,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Justfile")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "just");
         assert_eq!(runners[0].detected_file, "Justfile");
@@ -177,7 +218,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join(".justfile")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "just");
         assert_eq!(runners[0].detected_file, ".justfile");
@@ -187,7 +228,7 @@ mod tests {
     fn test_no_justfile() {
         let dir = tempdir().unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 
@@ -360,7 +401,7 @@ test:
         )
         .unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "just");
 
@@ -407,4 +448,18 @@ deploy target='prod':
         assert!(!recipes.contains("version"));
         assert!(!recipes.contains("set"));
     }
+
+    #[test]
+    fn test_recipe_definition() {
+        let content = "set shell := [\"bash\", \"-c\"]\n\n# Build it\nbuild target='debug':\n    cargo build\n    @echo done\n\ntest: build\n  cargo test\n";
+        assert_eq!(
+            recipe_definition(content, "build"),
+            Some((3, "cargo build\n@echo done".to_string()))
+        );
+        assert_eq!(
+            recipe_definition(content, "test"),
+            Some((7, "cargo test".to_string()))
+        );
+        assert_eq!(recipe_definition(content, "shell"), None);
+    }
 }
@@ -0,0 +1,318 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem, TaskDefinition};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+
+pub struct MakeValidator;
+
+impl CommandValidator for MakeValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let makefile_paths = ["Makefile", "makefile", "GNUmakefile"];
+
+        for makefile_name in makefile_paths {
+            let file = match File::open(working_dir.join(makefile_name)) {
+                Ok(f) => f,
+                Err(_) => continue,
+            };
+
+            return match has_make_target(BufReader::new(file), command) {
+                Ok(true) => CommandSupport::Supported,
+                Ok(false) => CommandSupport::NotSupported,
+                Err(_) => CommandSupport::Unknown,
+            };
+        }
+
+        CommandSupport::Unknown
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        ["Makefile", "makefile", "GNUmakefile"]
+            .iter()
+            .find_map(|name| File::open(working_dir.join(name)).ok())
+            .and_then(|file| make_targets(BufReader::new(file)).ok())
+            .unwrap_or_default()
+    }
+
+    fn definition(&self, working_dir: &Path, command: &str) -> Option<TaskDefinition> {
+        let path = ["Makefile", "makefile", "GNUmakefile"]
+            .iter()
+            .map(|name| working_dir.join(name))
+            .find(|path| path.is_file())?;
+        let content = std::fs::read_to_string(&path).ok()?;
+        let (line, body) = rule_definition(&content, command)?;
+        Some(TaskDefinition {
+            file: path,
+            line: line + 1,
+            body,
+        })
+    }
+}
+
+/// Check whether a Makefile declares `target`.
+///
+/// The input is read one line at a time and scanning stops at the first
+/// match, so multi-megabyte generated Makefiles are never held in memory.
+/// Lines that are not valid UTF-8 are skipped.
+pub fn has_make_target<R: BufRead>(reader: R, target: &str) -> io::Result<bool> {
+    for_each_target(reader, |t| t == target)
+}
+
+/// Every target a Makefile declares, in order, without duplicates
+pub fn make_targets<R: BufRead>(reader: R) -> io::Result<Vec<String>> {
+    let mut targets: Vec<String> = Vec::new();
+    for_each_target(reader, |t| {
+        if !targets.iter().any(|known| known == t) {
+            targets.push(t.to_string());
+        }
+        false
+    })?;
+    Ok(targets)
+}
+
+/// Call `visit` with each declared target until it returns `true`, which is
+/// returned; `false` once the input is exhausted
+fn for_each_target<R: BufRead>(
+    mut reader: R,
+    mut visit: impl FnMut(&str) -> bool,
+) -> io::Result<bool> {
+    let mut buf = Vec::new();
+    let mut continued = false;
+
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(false);
+        }
+
+        let Ok(line) = std::str::from_utf8(&buf) else {
+            continued = false;
+            continue;
+        };
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        // Continuation lines belong to the previous rule or assignment
+        let was_continued = continued;
+        continued = line.ends_with('\\');
+        if was_continued || line.starts_with('\t') {
+            continue;
+        }
+
+        if rule_targets(line).any(&mut visit) {
+            return Ok(true);
+        }
+    }
+}
+
+/// The first rule line declaring `target` (from 0) and its recipe
+fn rule_definition(content: &str, target: &str) -> Option<(usize, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut continued = false;
+    let rule = lines.iter().position(|line| {
+        let was_continued = continued;
+        continued = line.ends_with('\\');
+        !was_continued && !line.starts_with('\t') && rule_targets(line).any(|t| t == target)
+    })?;
+    let recipe: Vec<&str> = lines[rule + 1..]
+        .iter()
+        .take_while(|line| line.starts_with('\t'))
+        .map(|line| &line[1..])
+        .collect();
+    Some((rule, recipe.join("\n")))
+}
+
+/// Target names declared by a rule line (empty for anything else)
+fn rule_targets(line: &str) -> impl Iterator<Item = &str> {
+    let trimmed = line.trim();
+    let targets = match trimmed.find(':') {
+        // `VAR := value` and `VAR ::= value` are assignments, not rules
+        Some(pos) if !trimmed[pos..].trim_start_matches(':').starts_with('=') => {
+            let part = &trimmed[..pos];
+            if trimmed.starts_with('#') || part.contains(['$', '%', '=']) {
+                ""
+            } else {
+                part
+            }
+        }
+        _ => "",
+    };
+
+    targets.split_whitespace().filter(|t| !t.starts_with('.'))
+}
+
+/// Detect Makefile projects
+/// Priority: 21 (last, as it's the most generic)
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+    let validator: Arc<dyn CommandValidator> = Arc::new(MakeValidator);
+
+    // DirScan compares exact filenames (case-sensitive on all platforms)
+    if let Some(name) = ["Makefile", "makefile"].into_iter().find(|f| scan.has(f)) {
+        runners.push(DetectedRunner::with_validator(
+            "make",
+            name,
+            Ecosystem::Generic,
+            21,
+            Arc::clone(&validator),
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_makefile() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("Makefile")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "make");
+        assert_eq!(runners[0].detected_file, "Makefile");
+    }
+
+    #[test]
+    fn test_detect_makefile_lowercase() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("makefile")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "make");
+        assert_eq!(runners[0].detected_file, "makefile");
+    }
+
+    #[test]
+    fn test_no_makefile() {
+        let dir = tempdir().unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert!(runners.is_empty());
+    }
+
+    #[test]
+    fn test_has_make_target() {
+        let makefile = "\
+CC := gcc
+FLAGS ::= -O2
+OBJS = a.o \\
+  fake: b.o
+# commented: target
+.PHONY: build
+%.o: %.c
+build test: deps
+\techo not-a-target: here
+";
+        let has = |t: &str| has_make_target(makefile.as_bytes(), t).unwrap();
+        assert!(has("build"));
+        assert!(has("test"));
+        assert!(!has("CC"));
+        assert!(!has("FLAGS"));
+        assert!(!has("fake"));
+        assert!(!has("commented"));
+        assert!(!has(".PHONY"));
+        assert!(!has("echo"));
+        assert!(!has("not-a-target"));
+
+        assert_eq!(
+            make_targets(makefile.as_bytes()).unwrap(),
+            vec!["build", "test"]
+        );
+    }
+
+    #[test]
+    fn test_has_make_target_skips_invalid_utf8() {
+        let mut makefile = b"gen:\n\t\xff\xfe binary: junk\n".to_vec();
+        makefile.extend_from_slice(b"\xff bad: line\nlast:\n");
+        assert!(has_make_target(&makefile[..], "last").unwrap());
+        assert!(!has_make_target(&makefile[..], "bad").unwrap());
+    }
+
+    #[test]
+    fn test_large_generated_makefile() {
+        use std::io::Write;
+
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("Makefile");
+        let mut file = io::BufWriter::new(File::create(&path).unwrap());
+        // Roughly what CMake emits for a big C project: several megabytes
+        for i in 0..100_000 {
+            writeln!(
+                file,
+                "obj/file{i}.o: src/file{i}.c include/common.h\n\t$(CC) -c $< -o $@"
+            )
+            .unwrap();
+        }
+        writeln!(file, "install: all").unwrap();
+        drop(file);
+
+        assert!(std::fs::metadata(&path).unwrap().len() > 4 * 1024 * 1024);
+        assert_eq!(
+            MakeValidator.supports_command(dir.path(), "install"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            MakeValidator.supports_command(dir.path(), "obj/file10.o"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            MakeValidator.supports_command(dir.path(), "missing"),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_detected_runner_has_working_validator() {
+        use super::CommandSupport;
+        use std::io::Write;
+
+        let dir = tempdir().unwrap();
+        let mut file = File::create(dir.path().join("Makefile")).unwrap();
+        writeln!(file, "build:\n\techo building\n\ntest:\n\techo testing").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "make");
+
+        // Verify the detected runner has a working validator (not UnknownValidator)
+        assert_eq!(
+            runners[0].supports_command("build", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runners[0].supports_command("test", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runners[0].supports_command("nonexistent", dir.path()),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_rule_definition() {
+        let content = "CC := gcc\n\nall: build\n\nbuild test: deps\n\t$(CC) -o app main.c\n\t./check.sh\n\nclean:\n\trm -f app\n";
+        assert_eq!(
+            rule_definition(content, "test"),
+            Some((4, "$(CC) -o app main.c\n./check.sh".to_string()))
+        );
+        assert_eq!(rule_definition(content, "all"), Some((2, String::new())));
+        assert_eq!(rule_definition(content, "CC"), None);
+    }
+}
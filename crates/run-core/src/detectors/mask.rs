@@ -0,0 +1,177 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Validator for commands documented in maskfile.md
+pub struct MaskValidator;
+
+impl CommandValidator for MaskValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let content = match fs::read_to_string(working_dir.join("maskfile.md")) {
+            Ok(c) => c,
+            Err(_) => return CommandSupport::Unknown,
+        };
+
+        if extract_mask_commands(&content).contains(command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        fs::read_to_string(working_dir.join("maskfile.md"))
+            .map(|content| extract_mask_commands(&content).into_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Top-level command names from maskfile.md content
+///
+/// Every heading below the `#` title is a command; subcommands repeat their
+/// parent (`### db migrate`) and named arguments follow in parentheses
+/// (`## test (file)`), so the first word is the command `run` is given.
+/// Headings inside fenced code blocks are shell comments, not commands.
+fn extract_mask_commands(content: &str) -> HashSet<String> {
+    let mut commands = HashSet::new();
+    let mut in_code = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            continue;
+        }
+
+        let Some(heading) = line.strip_prefix("##") else {
+            continue;
+        };
+        let text = heading.trim_start_matches('#');
+        if !text.starts_with(' ') {
+            continue;
+        }
+        if let Some(name) = text.split_whitespace().next() {
+            let name = name.trim_matches('`');
+            if !name.is_empty() && !name.starts_with('(') {
+                commands.insert(name.to_string());
+            }
+        }
+    }
+
+    commands
+}
+
+/// Detect mask (maskfile.md) task files
+/// Priority: 33
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has("maskfile.md") {
+        let validator: Arc<dyn CommandValidator> = Arc::new(MaskValidator);
+        runners.push(DetectedRunner::with_validator(
+            "mask",
+            "maskfile.md",
+            Ecosystem::Mask,
+            33,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const MASKFILE: &str = r#"# Tasks for this project
+
+Some prose describing the project.
+
+## build
+
+> Builds the project
+
+```bash
+# not a heading
+cargo build
+```
+
+## test (file)
+
+```bash
+cargo test $file
+```
+
+## db
+
+### db migrate
+
+```sh
+sqlx migrate run
+```
+
+## `serve`
+"#;
+
+    #[test]
+    fn test_detect_mask() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("maskfile.md"), MASKFILE).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "mask");
+        assert_eq!(
+            runners[0].build_command("db", &["migrate".to_string()]),
+            vec!["mask", "db", "migrate"]
+        );
+
+        let empty = tempdir().unwrap();
+        assert!(detect(&DirScan::new(empty.path())).is_empty());
+    }
+
+    #[test]
+    fn test_extract_mask_commands() {
+        let commands = extract_mask_commands(MASKFILE);
+        assert_eq!(commands.len(), 4);
+        for name in ["build", "test", "db", "serve"] {
+            assert!(commands.contains(name), "{}", name);
+        }
+        assert!(!commands.contains("Tasks"));
+        assert!(!commands.contains("not"));
+    }
+
+    #[test]
+    fn test_mask_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("maskfile.md"), MASKFILE).unwrap();
+
+        let validator = MaskValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "build"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "deploy"),
+            CommandSupport::NotSupported
+        );
+    }
+}
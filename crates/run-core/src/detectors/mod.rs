@@ -0,0 +1,1433 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+pub mod act;
+pub mod bazel;
+pub mod clojure;
+pub mod cmake;
+pub mod custom;
+pub mod dbt;
+pub mod deno;
+pub mod devbox;
+pub mod docker;
+pub mod dotnet;
+pub mod earthly;
+pub mod elixir;
+pub mod go;
+pub mod java;
+pub mod just;
+pub mod make;
+pub mod mask;
+pub mod monorepo;
+pub mod nix;
+pub mod node;
+pub mod php;
+pub mod pixi;
+pub mod python;
+pub mod ruby;
+pub mod rust;
+pub mod scala;
+pub mod snakemake;
+pub mod swift;
+pub mod vscode;
+pub mod xcode;
+pub mod zig;
+
+use crate::error::RunError;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+// Built-in commands that should be run directly without "run"
+const NPM_BUILTINS: &[&str] = &[
+    "install",
+    "i",
+    "test",
+    "t",
+    "start",
+    "stop",
+    "restart",
+    "publish",
+    "version",
+    "uninstall",
+    "remove",
+    "rm",
+    "update",
+    "up",
+    "outdated",
+    "audit",
+    "pack",
+    "login",
+    "logout",
+    "init",
+    "link",
+    "unlink",
+    "list",
+    "ls",
+    "ll",
+    "search",
+    "view",
+    "help",
+    "cache",
+    "doctor",
+    "exec",
+    "set",
+    "get",
+    "config",
+    "prefix",
+    "bin",
+    "root",
+];
+
+const YARN_BUILTINS: &[&str] = &[
+    "add",
+    "audit",
+    "autoclean",
+    "bin",
+    "cache",
+    "config",
+    "create",
+    "dedupe",
+    "dlx",
+    "exec",
+    "generate-lock-entry",
+    "global",
+    "help",
+    "import",
+    "info",
+    "init",
+    "install",
+    "licenses",
+    "link",
+    "list",
+    "lockfile",
+    "login",
+    "logout",
+    "node",
+    "outdated",
+    "owner",
+    "pack",
+    "policies",
+    "publish",
+    "remove",
+    "run",
+    "set",
+    "stage",
+    "tag",
+    "team",
+    "test",
+    "unlink",
+    "unplug",
+    "upgrade",
+    "upgrade-interactive",
+    "version",
+    "versions",
+    "why",
+    "workspace",
+    "workspaces",
+];
+
+const PNPM_BUILTINS: &[&str] = &[
+    "add",
+    "audit",
+    "bin",
+    "config",
+    "create",
+    "dedupe",
+    "deploy",
+    "dlx",
+    "doctor",
+    "env",
+    "exec",
+    "fetch",
+    "help",
+    "import",
+    "init",
+    "install",
+    "install-test",
+    "link",
+    "list",
+    "licenses",
+    "ls",
+    "outdated",
+    "pack",
+    "patch",
+    "patch-commit",
+    "patch-remove",
+    "prune",
+    "publish",
+    "rebuild",
+    "remove",
+    "rm",
+    "root",
+    "run",
+    "server",
+    "setup",
+    "start",
+    "store",
+    "test",
+    "t",
+    "test-recursive",
+    "uninstall",
+    "unlink",
+    "unmount",
+    "update",
+    "up",
+    "why",
+];
+
+const BUN_BUILTINS: &[&str] = &[
+    "add", "bun", "create", "discord", "help", "init", "install", "link", "pm", "remove", "rm",
+    "run", "test", "update", "upgrade", "x",
+];
+
+const PIP_BUILTINS: &[&str] = &[
+    "install",
+    "download",
+    "uninstall",
+    "freeze",
+    "inspect",
+    "list",
+    "show",
+    "check",
+    "config",
+    "search",
+    "cache",
+    "index",
+    "wheel",
+    "hash",
+    "completion",
+    "debug",
+    "help",
+];
+
+/// Indicates if a command is supported by a runner
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandSupport {
+    /// The command is explicitly supported (e.g., found in package.json scripts)
+    Supported,
+    /// The command is definitely not supported (e.g., not found in package.json scripts)
+    NotSupported,
+    /// It's unknown if the command is supported (e.g., no manifest parsing implemented)
+    Unknown,
+}
+
+/// Trait for validating commands against a specific detector.
+/// Implementations must be Send + Sync to allow sharing across threads.
+pub trait CommandValidator: Send + Sync {
+    /// Check if the detected runner supports the given command
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport;
+
+    /// Tasks the project declares for this runner (scripts, targets,
+    /// recipes...), for `run list`. Validators that can only check a given
+    /// name list nothing.
+    fn list_commands(&self, _working_dir: &Path) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Where the project declares `command` and what it runs, for
+    /// `run explain`. Validators that don't read a task file answer `None`.
+    fn definition(&self, _working_dir: &Path, _command: &str) -> Option<TaskDefinition> {
+        None
+    }
+}
+
+/// A task as the project declares it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TaskDefinition {
+    /// File the task is declared in
+    pub file: PathBuf,
+    /// Line of the declaration, counting from 1
+    pub line: usize,
+    /// What the task runs, as written (script, recipe or rule body)
+    pub body: String,
+}
+
+/// Default validator that returns Unknown for all commands.
+/// Used for runners that don't have specific validation logic yet.
+pub struct UnknownValidator;
+
+impl CommandValidator for UnknownValidator {
+    fn supports_command(&self, _working_dir: &Path, _command: &str) -> CommandSupport {
+        CommandSupport::Unknown
+    }
+}
+
+/// Represents a detected runner with its command and configuration
+///
+/// Validators are not serialized: a deserialized runner answers `Unknown` for
+/// every command except its custom ones.
+#[derive(Serialize, Deserialize)]
+pub struct DetectedRunner {
+    /// Name of the runner (e.g., "pnpm", "cargo", "poetry")
+    pub name: String,
+    /// The file that triggered detection
+    pub detected_file: String,
+    /// The ecosystem this runner belongs to
+    pub ecosystem: Ecosystem,
+    /// Priority (lower = higher priority)
+    pub priority: u8,
+    /// Validator for checking command support
+    #[serde(skip, default = "unknown_validator")]
+    validator: Arc<dyn CommandValidator>,
+    /// Custom commands defined by the user (if any)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_commands: Option<HashMap<String, String>>,
+    /// Project tasks are scoped to, for orchestrators that address tasks as
+    /// `<project>:<task>` (e.g. moon), the CMake preset or the Xcode scheme
+    /// tasks use
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Custom commands left out of listings (`private = true`, or a
+    /// `condition` that doesn't hold); they still resolve when asked for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub private: Vec<String>,
+}
+
+fn unknown_validator() -> Arc<dyn CommandValidator> {
+    Arc::new(UnknownValidator)
+}
+
+impl std::fmt::Debug for DetectedRunner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DetectedRunner")
+            .field("name", &self.name)
+            .field("detected_file", &self.detected_file)
+            .field("ecosystem", &self.ecosystem)
+            .field("priority", &self.priority)
+            .field("validator", &"<dyn CommandValidator>")
+            .field("custom_commands", &self.custom_commands)
+            .field("target", &self.target)
+            .field("private", &self.private)
+            .finish()
+    }
+}
+
+impl Clone for DetectedRunner {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            detected_file: self.detected_file.clone(),
+            ecosystem: self.ecosystem,
+            priority: self.priority,
+            validator: Arc::clone(&self.validator),
+            custom_commands: self.custom_commands.clone(),
+            target: self.target.clone(),
+            private: self.private.clone(),
+        }
+    }
+}
+
+impl PartialEq for DetectedRunner {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.detected_file == other.detected_file
+            && self.ecosystem == other.ecosystem
+            && self.priority == other.priority
+            && self.custom_commands == other.custom_commands
+            && self.target == other.target
+            && self.private == other.private
+    }
+}
+
+impl DetectedRunner {
+    /// Create a new DetectedRunner with an UnknownValidator.
+    /// Use `with_validator` for runners with specific validation logic.
+    pub fn new(name: &str, detected_file: &str, ecosystem: Ecosystem, priority: u8) -> Self {
+        Self::with_validator(
+            name,
+            detected_file,
+            ecosystem,
+            priority,
+            Arc::new(UnknownValidator),
+        )
+    }
+
+    /// Create a new DetectedRunner with a specific validator.
+    pub fn with_validator(
+        name: &str,
+        detected_file: &str,
+        ecosystem: Ecosystem,
+        priority: u8,
+        validator: Arc<dyn CommandValidator>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            detected_file: detected_file.to_string(),
+            ecosystem,
+            priority,
+            validator,
+            custom_commands: None,
+            target: None,
+            private: Vec::new(),
+        }
+    }
+
+    /// Create a new DetectedRunner with custom commands
+    pub fn with_custom_commands(
+        name: &str,
+        detected_file: &str,
+        ecosystem: Ecosystem,
+        priority: u8,
+        validator: Arc<dyn CommandValidator>,
+        custom_commands: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            detected_file: detected_file.to_string(),
+            ecosystem,
+            priority,
+            validator,
+            custom_commands: Some(custom_commands),
+            target: None,
+            private: Vec::new(),
+        }
+    }
+
+    /// Leave `tasks` out of listings (see [`DetectedRunner::private`])
+    pub fn with_private(mut self, tasks: Vec<String>) -> Self {
+        self.private = tasks;
+        self
+    }
+
+    /// Whether `task` is a private custom command: marked `private = true`,
+    /// or named with a leading `_`
+    pub fn is_private(&self, task: &str) -> bool {
+        self.private.iter().any(|t| t == task)
+            || (task.starts_with('_') && self.has_custom_command(task))
+    }
+
+    /// Scope tasks to `target` (see [`DetectedRunner::target`])
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    /// Consult `validator` before the current one, falling back on `Unknown`.
+    /// See [`crate::registry`] for the precedence rules.
+    pub fn layer_validator(&mut self, validator: Arc<dyn CommandValidator>) {
+        let base = Arc::clone(&self.validator);
+        self.validator = Arc::new(crate::registry::LayeredValidator::new(validator, base));
+    }
+
+    /// Whether `task` is one of this runner's custom commands, which run their
+    /// own program instead of the runner's tool
+    pub fn has_custom_command(&self, task: &str) -> bool {
+        self.custom_commands
+            .as_ref()
+            .is_some_and(|commands| commands.contains_key(task))
+    }
+
+    /// The command line of the custom command `task`, as written
+    pub fn custom_command(&self, task: &str) -> Option<&str> {
+        self.custom_commands
+            .as_ref()
+            .and_then(|commands| commands.get(task))
+            .map(String::as_str)
+    }
+
+    /// Whether the program this runner invokes is available: a project
+    /// wrapper script (`./gradlew`, `./mvnw`) ships with the project,
+    /// anything else has to be on `PATH`
+    pub fn is_installed(&self) -> bool {
+        self.detected_file == java::GRADLE_WRAPPER
+            || self.detected_file == java::MAVEN_WRAPPER
+            || is_tool_installed(&self.name)
+    }
+
+    /// Check if this runner supports the given command.
+    pub fn supports_command(&self, command: &str, working_dir: &Path) -> CommandSupport {
+        // First check if this is a custom command
+        if let Some(commands) = &self.custom_commands {
+            if commands.contains_key(command) {
+                return CommandSupport::Supported;
+            }
+        }
+        self.validator.supports_command(working_dir, command)
+    }
+
+    /// Custom commands and the tasks the validator lists, sorted and
+    /// deduplicated, without private ones
+    pub fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        let mut commands = self.validator.list_commands(working_dir);
+        if let Some(custom) = &self.custom_commands {
+            commands.extend(custom.keys().cloned());
+        }
+        commands.retain(|task| !self.is_private(task));
+        commands.sort();
+        commands.dedup();
+        commands
+    }
+
+    /// Where `task` is declared, for `run explain`: the run.toml entry of a
+    /// custom command, otherwise whatever the validator finds
+    pub fn definition(&self, task: &str, working_dir: &Path) -> Option<TaskDefinition> {
+        if self.has_custom_command(task) {
+            return custom::definition(working_dir, task);
+        }
+        self.validator.definition(working_dir, task)
+    }
+
+    /// The tool itself followed by `args`, untouched, for `run exec`; `None`
+    /// for custom commands and editor tasks, which have no tool of their own
+    pub fn tool_command(&self, args: &[String]) -> Option<Vec<String>> {
+        let mut cmd = match self.name.as_str() {
+            _ if matches!(self.ecosystem, Ecosystem::Custom | Ecosystem::Editor) => return None,
+            "pip" => vec!["python".to_string(), "-m".to_string(), "pip".to_string()],
+            "bundler" => vec!["bundle".to_string()],
+            "gradle" if self.detected_file == java::GRADLE_WRAPPER => {
+                vec![java::wrapper_program(java::GRADLE_WRAPPER)]
+            }
+            "maven" if self.detected_file == java::MAVEN_WRAPPER => {
+                vec![java::wrapper_program(java::MAVEN_WRAPPER)]
+            }
+            "maven" => vec!["mvn".to_string()],
+            _ => vec![self.name.clone()],
+        };
+        cmd.extend(args.iter().cloned());
+        Some(cmd)
+    }
+
+    /// The command that updates the tool itself to its latest release (`run
+    /// upgrade-tool`); `None` for tools that come from the system's package
+    /// manager or an installer `run` doesn't know about
+    pub fn upgrade_command(&self) -> Option<Vec<String>> {
+        let cmd: &[&str] = match self.name.as_str() {
+            _ if matches!(self.ecosystem, Ecosystem::Custom | Ecosystem::Editor) => return None,
+            "npm" => &["npm", "install", "-g", "npm@latest"],
+            "pnpm" => &["corepack", "prepare", "pnpm@latest", "--activate"],
+            "yarn" => &["corepack", "prepare", "yarn@stable", "--activate"],
+            "bun" => &["bun", "upgrade"],
+            "deno" => &["deno", "upgrade"],
+            "uv" => &["uv", "self", "update"],
+            "pip" => &["python", "-m", "pip", "install", "-U", "pip"],
+            "poetry" | "pipenv" | "snakemake" => &["pip", "install", "-U", self.name.as_str()],
+            "dbt" => &["pip", "install", "-U", "dbt-core"],
+            "cargo" => &["rustup", "update"],
+            "composer" => &["composer", "self-update"],
+            "bundler" | "rake" => &["gem", "update", self.name.as_str()],
+            "gradle" if self.detected_file == java::GRADLE_WRAPPER => {
+                return Some(vec![
+                    java::wrapper_program(java::GRADLE_WRAPPER),
+                    "wrapper".to_string(),
+                    "--gradle-version".to_string(),
+                    "latest".to_string(),
+                ]);
+            }
+            "maven" if self.detected_file == java::MAVEN_WRAPPER => {
+                return Some(vec![
+                    java::wrapper_program(java::MAVEN_WRAPPER),
+                    "wrapper:wrapper".to_string(),
+                ]);
+            }
+            "lein" => &["lein", "upgrade"],
+            "moon" => &["moon", "upgrade"],
+            "pixi" => &["pixi", "self-update"],
+            "devbox" => &["devbox", "version", "update"],
+            _ => return None,
+        };
+        Some(cmd.iter().map(|s| s.to_string()).collect())
+    }
+
+    /// [`Self::build_command`], failing when the arguments don't fit the
+    /// `{1}`, `{2}`, `{args}` placeholders of a custom command
+    pub fn try_build_command(
+        &self,
+        task: &str,
+        extra_args: &[String],
+    ) -> Result<Vec<String>, RunError> {
+        match self.custom_commands.as_ref().and_then(|c| c.get(task)) {
+            Some(line) => custom::interpolate(line, extra_args)
+                .map_err(|e| RunError::InvalidArgument(format!("Task '{}' {}", task, e))),
+            None => Ok(self.build_command(task, extra_args)),
+        }
+    }
+
+    /// Build the command to execute
+    pub fn build_command(&self, task: &str, extra_args: &[String]) -> Vec<String> {
+        // First check if this is a custom command; arguments that don't fit
+        // its placeholders leave them as written
+        if let Some(commands) = &self.custom_commands {
+            if let Some(cmd_str) = commands.get(task) {
+                return custom::interpolate(cmd_str, extra_args)
+                    .unwrap_or_else(|_| custom::words(cmd_str));
+            }
+        }
+
+        let mut cmd = match self.name.as_str() {
+            // Node.js ecosystem
+            "bun" => {
+                if BUN_BUILTINS.contains(&task) {
+                    vec!["bun".to_string(), task.to_string()]
+                } else {
+                    vec!["bun".to_string(), "run".to_string(), task.to_string()]
+                }
+            }
+            "pnpm" => {
+                if PNPM_BUILTINS.contains(&task) {
+                    vec!["pnpm".to_string(), task.to_string()]
+                } else {
+                    vec!["pnpm".to_string(), "run".to_string(), task.to_string()]
+                }
+            }
+            "yarn" => {
+                if YARN_BUILTINS.contains(&task) {
+                    vec!["yarn".to_string(), task.to_string()]
+                } else {
+                    vec!["yarn".to_string(), "run".to_string(), task.to_string()]
+                }
+            }
+            "npm" => {
+                if NPM_BUILTINS.contains(&task) {
+                    vec!["npm".to_string(), task.to_string()]
+                } else {
+                    vec!["npm".to_string(), "run".to_string(), task.to_string()]
+                }
+            }
+
+            // Python ecosystem
+            "uv" => vec!["uv".to_string(), "run".to_string(), task.to_string()],
+            "poetry" => vec!["poetry".to_string(), "run".to_string(), task.to_string()],
+            "pipenv" => vec!["pipenv".to_string(), "run".to_string(), task.to_string()],
+            "pip" => {
+                if PIP_BUILTINS.contains(&task) {
+                    vec![
+                        "python".to_string(),
+                        "-m".to_string(),
+                        "pip".to_string(),
+                        task.to_string(),
+                    ]
+                } else {
+                    vec!["python".to_string(), "-m".to_string(), task.to_string()]
+                }
+            }
+
+            // Rust ecosystem
+            "cargo" => {
+                if self.detected_file == rust::XTASK_FILE
+                    && task != "xtask"
+                    && !rust::CARGO_BUILTIN.contains(&task)
+                {
+                    vec![
+                        "cargo".to_string(),
+                        "run".to_string(),
+                        "-p".to_string(),
+                        "xtask".to_string(),
+                        "--".to_string(),
+                        task.to_string(),
+                    ]
+                } else {
+                    vec!["cargo".to_string(), task.to_string()]
+                }
+            }
+
+            // Deno ecosystem
+            "deno" => {
+                if deno::DENO_BUILTIN.contains(&task) {
+                    vec!["deno".to_string(), task.to_string()]
+                } else if task.contains('/') || task.ends_with(".ts") || task.ends_with(".js") {
+                    vec!["deno".to_string(), "run".to_string(), task.to_string()]
+                } else {
+                    vec!["deno".to_string(), "task".to_string(), task.to_string()]
+                }
+            }
+
+            // PHP ecosystem
+            "composer" => vec!["composer".to_string(), "run".to_string(), task.to_string()],
+
+            // Go ecosystem
+            "task" => vec!["task".to_string(), task.to_string()],
+            "go" => {
+                // Check if task looks like a path (contains / or ends with .go)
+                if task.contains('/') || task.ends_with(".go") {
+                    vec!["go".to_string(), "run".to_string(), task.to_string()]
+                } else {
+                    vec!["go".to_string(), task.to_string()]
+                }
+            }
+
+            // Ruby ecosystem
+            "bundler" => vec!["bundle".to_string(), "exec".to_string(), task.to_string()],
+            "rake" => vec!["rake".to_string(), task.to_string()],
+
+            // Java ecosystem
+            "gradle" if self.detected_file == java::GRADLE_WRAPPER => {
+                vec![
+                    java::wrapper_program(java::GRADLE_WRAPPER),
+                    task.to_string(),
+                ]
+            }
+            "gradle" => vec!["gradle".to_string(), task.to_string()],
+            "maven" if self.detected_file == java::MAVEN_WRAPPER => {
+                vec![java::wrapper_program(java::MAVEN_WRAPPER), task.to_string()]
+            }
+            "maven" => vec!["mvn".to_string(), task.to_string()],
+
+            // .NET ecosystem
+            "dotnet" => vec!["dotnet".to_string(), task.to_string()],
+
+            // Elixir ecosystem
+            "mix" => vec!["mix".to_string(), task.to_string()],
+
+            // Swift ecosystem
+            "swift" => vec!["swift".to_string(), "run".to_string(), task.to_string()],
+
+            // Zig ecosystem
+            "zig" => vec!["zig".to_string(), "build".to_string(), task.to_string()],
+
+            // Just command runner
+            "just" => vec!["just".to_string(), task.to_string()],
+
+            // Monorepo orchestration tools
+            "nx" => vec!["nx".to_string(), task.to_string()],
+            "turbo" => vec!["turbo".to_string(), "run".to_string(), task.to_string()],
+            "lerna" => vec!["lerna".to_string(), "run".to_string(), task.to_string()],
+            "rush" => vec!["rush".to_string(), task.to_string()],
+            "rushx" => vec!["rushx".to_string(), task.to_string()],
+            "moon" => vec![
+                "moon".to_string(),
+                "run".to_string(),
+                format!("{}:{}", self.target.as_deref().unwrap_or(""), task),
+            ],
+
+            // Nix flakes
+            "nix" => {
+                if task == "check" {
+                    vec!["nix".to_string(), "flake".to_string(), "check".to_string()]
+                } else if nix::NIX_BUILTINS.contains(&task) {
+                    vec!["nix".to_string(), task.to_string()]
+                } else {
+                    // Arguments after `--` go to the app, not to nix
+                    let mut cmd = vec!["nix".to_string(), "run".to_string(), format!(".#{}", task)];
+                    if !extra_args.is_empty() {
+                        cmd.push("--".to_string());
+                    }
+                    cmd
+                }
+            }
+
+            // Bazel: built-in commands, or `bazel run <label>`
+            "bazel" => {
+                if bazel::BAZEL_BUILTINS.contains(&task) {
+                    vec!["bazel".to_string(), task.to_string()]
+                } else {
+                    let mut cmd = vec!["bazel".to_string(), "run".to_string(), task.to_string()];
+                    if !extra_args.is_empty() {
+                        cmd.push("--".to_string());
+                    }
+                    cmd
+                }
+            }
+
+            // Scala ecosystem: Mill takes `<module>.<task>` as is
+            "mill" => vec!["mill".to_string(), task.to_string()],
+
+            // sbt: the task and its arguments make a single sbt command
+            "sbt" => return scala::sbt_command(task, extra_args),
+
+            // Clojure ecosystem
+            "lein" => vec!["lein".to_string(), task.to_string()],
+
+            // CMake: presets or a build/ directory (see `cmake::command`)
+            "cmake" => return cmake::command(self.target.as_deref(), task, extra_args),
+
+            // Docker: `docker build` or `docker buildx bake`
+            "docker" => return docker::command(&self.detected_file, task, extra_args),
+
+            // Earthly targets are addressed as `+<target>`
+            "earthly" => vec![
+                "earthly".to_string(),
+                format!("+{}", task.trim_start_matches('+')),
+            ],
+
+            // GitHub Actions run locally: `act -j <job>` or `act -W <workflow>`
+            "act" => return act::command(task, extra_args),
+
+            // devbox scripts
+            "devbox" => vec!["devbox".to_string(), "run".to_string(), task.to_string()],
+
+            // pixi tasks
+            "pixi" => vec!["pixi".to_string(), "run".to_string(), task.to_string()],
+
+            // mask (maskfile.md)
+            "mask" => vec!["mask".to_string(), task.to_string()],
+
+            // Tuist, or xcodebuild on a workspace/project (see `xcode::command`)
+            "tuist" => vec!["tuist".to_string(), task.to_string()],
+            "xcodebuild" => {
+                return xcode::command(
+                    &self.detected_file,
+                    self.target.as_deref(),
+                    task,
+                    extra_args,
+                )
+            }
+
+            // Data pipelines: snakemake targets and dbt subcommands
+            "snakemake" => vec!["snakemake".to_string(), task.to_string()],
+            "dbt" => vec!["dbt".to_string(), task.to_string()],
+
+            // Generic
+            "make" => vec!["make".to_string(), task.to_string()],
+
+            // Fallback
+            _ => vec![self.name.clone(), task.to_string()],
+        };
+
+        cmd.extend(extra_args.iter().cloned());
+        cmd
+    }
+}
+
+/// Ecosystem categories
+///
+/// Serialized with the same human-readable names returned by `as_str`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Ecosystem {
+    #[serde(rename = "Node.js")]
+    NodeJs,
+    Python,
+    Rust,
+    Deno,
+    #[serde(rename = "PHP")]
+    Php,
+    Go,
+    Ruby,
+    Java,
+    #[serde(rename = ".NET")]
+    DotNet,
+    Elixir,
+    Swift,
+    Zig,
+    Scala,
+    Clojure,
+    Nix,
+    Bazel,
+    CMake,
+    Container,
+    Earthly,
+    #[serde(rename = "GitHub Actions")]
+    GitHubActions,
+    Devbox,
+    Pixi,
+    Mask,
+    Xcode,
+    Snakemake,
+    #[serde(rename = "dbt")]
+    Dbt,
+    Generic,
+    /// Editor-defined tasks (VS Code `tasks.json`)
+    Editor,
+    Custom,
+}
+
+impl Ecosystem {
+    /// Human-readable ecosystem name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Ecosystem::NodeJs => "Node.js",
+            Ecosystem::Python => "Python",
+            Ecosystem::Rust => "Rust",
+            Ecosystem::Deno => "Deno",
+            Ecosystem::Php => "PHP",
+            Ecosystem::Go => "Go",
+            Ecosystem::Ruby => "Ruby",
+            Ecosystem::Java => "Java",
+            Ecosystem::DotNet => ".NET",
+            Ecosystem::Elixir => "Elixir",
+            Ecosystem::Swift => "Swift",
+            Ecosystem::Zig => "Zig",
+            Ecosystem::Scala => "Scala",
+            Ecosystem::Clojure => "Clojure",
+            Ecosystem::Nix => "Nix",
+            Ecosystem::Bazel => "Bazel",
+            Ecosystem::CMake => "CMake",
+            Ecosystem::Container => "Container",
+            Ecosystem::Earthly => "Earthly",
+            Ecosystem::GitHubActions => "GitHub Actions",
+            Ecosystem::Devbox => "Devbox",
+            Ecosystem::Pixi => "Pixi",
+            Ecosystem::Mask => "Mask",
+            Ecosystem::Xcode => "Xcode",
+            Ecosystem::Snakemake => "Snakemake",
+            Ecosystem::Dbt => "dbt",
+            Ecosystem::Generic => "Generic",
+            Ecosystem::Editor => "Editor",
+            Ecosystem::Custom => "Custom",
+        }
+    }
+}
+
+/// Names of the entries in a directory, read once and shared by every detector
+/// so detection costs a single `read_dir` instead of an `exists()` per file.
+#[derive(Debug, Clone, Default)]
+pub struct DirScan {
+    path: PathBuf,
+    names: BTreeSet<String>,
+}
+
+impl DirScan {
+    /// Read the entries of `dir`; an unreadable directory scans as empty
+    pub fn new(dir: &Path) -> Self {
+        let names = std::fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    // Dangling (or looping) symlinks can't be read as triggers
+                    .filter(|e| !e.file_type().is_ok_and(|t| t.is_symlink()) || e.path().exists())
+                    .filter_map(|e| e.file_name().into_string().ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            path: dir.to_path_buf(),
+            names,
+        }
+    }
+
+    /// The scanned directory
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether an entry with exactly this name exists (case-sensitive)
+    pub fn has(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+
+    /// Entry names in sorted order
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+/// A detector: the runners it finds in a directory listing
+type Detector = fn(&DirScan) -> Vec<DetectedRunner>;
+
+/// The detectors, in priority order, each with its name
+const DETECTORS: &[(&str, Detector)] = &[
+    ("custom", custom::detect),       // Custom commands (0) - highest priority
+    ("monorepo", monorepo::detect),   // Monorepo tools (0) - highest priority
+    ("node", node::detect),           // Node.js (1-4)
+    ("python", python::detect),       // Python (5-8)
+    ("rust", rust::detect),           // Rust (9)
+    ("php", php::detect),             // PHP (10)
+    ("just", just::detect),           // Just (10)
+    ("deno", deno::detect),           // Deno (22)
+    ("go", go::detect),               // Go (11-12)
+    ("ruby", ruby::detect),           // Ruby (13-14)
+    ("java", java::detect),           // Java (15-16)
+    ("dotnet", dotnet::detect),       // .NET (17)
+    ("elixir", elixir::detect),       // Elixir (18)
+    ("swift", swift::detect),         // Swift (19)
+    ("zig", zig::detect),             // Zig (20)
+    ("make", make::detect),           // Make (21)
+    ("nix", nix::detect),             // Nix flakes (22)
+    ("vscode", vscode::detect),       // VS Code tasks (23)
+    ("bazel", bazel::detect),         // Bazel (24)
+    ("cmake", cmake::detect),         // CMake (25)
+    ("scala", scala::detect),         // Mill, sbt (26-27)
+    ("clojure", clojure::detect),     // Leiningen (27)
+    ("docker", docker::detect),       // Docker (28)
+    ("earthly", earthly::detect),     // Earthly (29)
+    ("act", act::detect),             // GitHub Actions via act (30)
+    ("devbox", devbox::detect),       // devbox scripts (31)
+    ("pixi", pixi::detect),           // pixi tasks (32)
+    ("mask", mask::detect),           // mask (33)
+    ("xcode", xcode::detect),         // Tuist / Xcode (34)
+    ("snakemake", snakemake::detect), // Snakemake (35)
+    ("dbt", dbt::detect),             // dbt (36)
+];
+
+/// Detect all runners in the given directory
+pub fn detect_all(dir: &Path, ignore_list: &[String]) -> Vec<DetectedRunner> {
+    detect_all_with(dir, ignore_list, |_, _, _| {})
+}
+
+/// [`detect_all`], telling `on_detector` what each detector found: its name,
+/// the runners kept and those dropped by `ignore_list`
+pub fn detect_all_with(
+    dir: &Path,
+    ignore_list: &[String],
+    mut on_detector: impl FnMut(&str, &[DetectedRunner], &[DetectedRunner]),
+) -> Vec<DetectedRunner> {
+    // Read the directory once and share the listing with every detector
+    let scan = DirScan::new(dir);
+    let mut runners = Vec::new();
+
+    for (name, detect) in DETECTORS {
+        let (ignored, kept): (Vec<DetectedRunner>, Vec<DetectedRunner>) =
+            detect(&scan).into_iter().partition(|runner| {
+                ignore_list
+                    .iter()
+                    .any(|i| i.eq_ignore_ascii_case(&runner.name))
+            });
+        on_detector(name, &kept, &ignored);
+        runners.extend(kept);
+    }
+
+    // Sort by priority
+    runners.sort_by_key(|r| r.priority);
+    runners
+}
+
+/// Check if a tool is installed on the system
+pub fn is_tool_installed(tool: &str) -> bool {
+    crate::tools::find_tool(tool).is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_build_command_npm() {
+        let runner = DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4);
+        // "test" is a built-in command, so it should run directly
+        let cmd = runner.build_command("test", &[]);
+        assert_eq!(cmd, vec!["npm", "test"]);
+    }
+
+    #[test]
+    fn test_build_command_npm_run() {
+        let runner = DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4);
+        // "custom-script" is NOT a built-in command, so it should use "run"
+        let cmd = runner.build_command("custom-script", &[]);
+        assert_eq!(cmd, vec!["npm", "run", "custom-script"]);
+    }
+
+    #[test]
+    fn test_tool_command() {
+        let args = vec![
+            "--prefix".to_string(),
+            "web".to_string(),
+            "install".to_string(),
+        ];
+        let npm = DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4);
+        assert_eq!(
+            npm.tool_command(&args).unwrap(),
+            vec!["npm", "--prefix", "web", "install"]
+        );
+
+        let pip = DetectedRunner::new("pip", "requirements.txt", Ecosystem::Python, 8);
+        assert_eq!(pip.tool_command(&[]).unwrap(), vec!["python", "-m", "pip"]);
+        let maven = DetectedRunner::new("maven", java::MAVEN_WRAPPER, Ecosystem::Java, 16);
+        assert_eq!(maven.tool_command(&[]).unwrap(), vec!["./mvnw"]);
+
+        let custom = DetectedRunner::new("custom", "run.toml", Ecosystem::Custom, 0);
+        assert!(custom.tool_command(&args).is_none());
+    }
+
+    #[test]
+    fn test_upgrade_command() {
+        let pnpm = DetectedRunner::new("pnpm", "pnpm-lock.yaml", Ecosystem::NodeJs, 2);
+        assert_eq!(
+            pnpm.upgrade_command().unwrap(),
+            vec!["corepack", "prepare", "pnpm@latest", "--activate"]
+        );
+        let cargo = DetectedRunner::new("cargo", "Cargo.toml", Ecosystem::Rust, 9);
+        assert_eq!(cargo.upgrade_command().unwrap(), vec!["rustup", "update"]);
+        let poetry = DetectedRunner::new("poetry", "poetry.lock", Ecosystem::Python, 6);
+        assert_eq!(
+            poetry.upgrade_command().unwrap(),
+            vec!["pip", "install", "-U", "poetry"]
+        );
+
+        // Only the wrapper can upgrade itself; a system mvn can't
+        let wrapper = DetectedRunner::new("maven", java::MAVEN_WRAPPER, Ecosystem::Java, 16);
+        assert_eq!(
+            wrapper.upgrade_command().unwrap(),
+            vec!["./mvnw", "wrapper:wrapper"]
+        );
+        let maven = DetectedRunner::new("maven", "pom.xml", Ecosystem::Java, 16);
+        assert!(maven.upgrade_command().is_none());
+
+        let custom = DetectedRunner::new("custom", "run.toml", Ecosystem::Custom, 0);
+        assert!(custom.upgrade_command().is_none());
+    }
+
+    #[test]
+    fn test_build_command_with_args() {
+        let runner = DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4);
+        let cmd = runner.build_command("test", &["--coverage".to_string()]);
+        assert_eq!(cmd, vec!["npm", "test", "--coverage"]);
+    }
+
+    #[test]
+    fn test_build_command_yarn() {
+        let runner = DetectedRunner::new("yarn", "yarn.lock", Ecosystem::NodeJs, 3);
+        // Built-in
+        let cmd = runner.build_command("install", &[]);
+        assert_eq!(cmd, vec!["yarn", "install"]);
+
+        // Custom script
+        let cmd = runner.build_command("my-script", &[]);
+        assert_eq!(cmd, vec!["yarn", "run", "my-script"]);
+    }
+
+    #[test]
+    fn test_build_command_pnpm() {
+        let runner = DetectedRunner::new("pnpm", "pnpm-lock.yaml", Ecosystem::NodeJs, 2);
+        // Built-in
+        let cmd = runner.build_command("add", &["react".to_string()]);
+        assert_eq!(cmd, vec!["pnpm", "add", "react"]);
+
+        // Custom script
+        let cmd = runner.build_command("dev", &[]);
+        assert_eq!(cmd, vec!["pnpm", "run", "dev"]);
+    }
+
+    #[test]
+    fn test_build_command_bun() {
+        let runner = DetectedRunner::new("bun", "bun.lockb", Ecosystem::NodeJs, 1);
+        // Built-in
+        let cmd = runner.build_command("test", &[]);
+        assert_eq!(cmd, vec!["bun", "test"]);
+
+        // Custom script
+        let cmd = runner.build_command("foo", &[]);
+        assert_eq!(cmd, vec!["bun", "run", "foo"]);
+    }
+
+    #[test]
+    fn test_build_command_pip() {
+        let runner = DetectedRunner::new("pip", "requirements.txt", Ecosystem::Python, 8);
+        // Built-in pip command
+        let cmd = runner.build_command("install", &["requests".to_string()]);
+        assert_eq!(cmd, vec!["python", "-m", "pip", "install", "requests"]);
+
+        // Run module
+        let cmd = runner.build_command("pytest", &[]);
+        assert_eq!(cmd, vec!["python", "-m", "pytest"]);
+    }
+
+    #[test]
+    fn test_build_command_cargo() {
+        let runner = DetectedRunner::new("cargo", "Cargo.toml", Ecosystem::Rust, 9);
+        let cmd = runner.build_command("build", &["--release".to_string()]);
+        assert_eq!(cmd, vec!["cargo", "build", "--release"]);
+    }
+
+    #[test]
+    fn test_build_command_go_path() {
+        let runner = DetectedRunner::new("go", "go.mod", Ecosystem::Go, 12);
+        let cmd = runner.build_command("./cmd/main.go", &[]);
+        assert_eq!(cmd, vec!["go", "run", "./cmd/main.go"]);
+    }
+
+    #[test]
+    fn test_build_command_go_task() {
+        let runner = DetectedRunner::new("go", "go.mod", Ecosystem::Go, 12);
+        let cmd = runner.build_command("build", &[]);
+        assert_eq!(cmd, vec!["go", "build"]);
+    }
+
+    // Validator integration tests (moved from validators.rs)
+
+    #[test]
+    fn test_node_script_supported() {
+        let dir = tempdir().unwrap();
+        let mut file = File::create(dir.path().join("package.json")).unwrap();
+        writeln!(file, r#"{{"scripts": {{"test": "jest", "build": "tsc"}}}}"#).unwrap();
+
+        let runner = DetectedRunner::with_validator(
+            "npm",
+            "package.json",
+            Ecosystem::NodeJs,
+            4,
+            Arc::new(node::NodeValidator),
+        );
+        assert_eq!(
+            runner.supports_command("test", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("build", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("nonexistent", dir.path()),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_cargo_builtin() {
+        let dir = tempdir().unwrap();
+        let runner = DetectedRunner::with_validator(
+            "cargo",
+            "Cargo.toml",
+            Ecosystem::Rust,
+            9,
+            Arc::new(rust::RustValidator),
+        );
+
+        assert_eq!(
+            runner.supports_command("build", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("test", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("clippy", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("precommit", dir.path()),
+            CommandSupport::Unknown
+        );
+    }
+
+    #[test]
+    fn test_make_target_supported() {
+        let dir = tempdir().unwrap();
+        let mut file = File::create(dir.path().join("Makefile")).unwrap();
+        writeln!(
+            file,
+            r#"
+.PHONY: build test precommit
+
+build:
+	cargo build
+
+test:
+	cargo test
+
+precommit: build test
+	@echo "Done"
+"#
+        )
+        .unwrap();
+
+        let runner = DetectedRunner::with_validator(
+            "make",
+            "Makefile",
+            Ecosystem::Generic,
+            21,
+            Arc::new(make::MakeValidator),
+        );
+        assert_eq!(
+            runner.supports_command("build", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("precommit", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("nonexistent", dir.path()),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_composer_script() {
+        let dir = tempdir().unwrap();
+        let mut file = File::create(dir.path().join("composer.json")).unwrap();
+        writeln!(file, r#"{{"scripts": {{"test": "phpunit"}}}}"#).unwrap();
+
+        let runner = DetectedRunner::with_validator(
+            "composer",
+            "composer.json",
+            Ecosystem::Php,
+            10,
+            Arc::new(php::PhpValidator),
+        );
+        assert_eq!(
+            runner.supports_command("test", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("nonexistent", dir.path()),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_gradle_task() {
+        let dir = tempdir().unwrap();
+        let mut file = File::create(dir.path().join("build.gradle")).unwrap();
+        writeln!(file, r#"task customTask {{}}"#).unwrap();
+
+        let runner = DetectedRunner::with_validator(
+            "gradle",
+            "build.gradle",
+            Ecosystem::Java,
+            15,
+            Arc::new(java::JavaValidator),
+        );
+        assert_eq!(
+            runner.supports_command("customTask", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("build", dir.path()),
+            CommandSupport::Supported
+        );
+    }
+
+    #[test]
+    fn test_dotnet_builtin() {
+        let dir = tempdir().unwrap();
+        let runner = DetectedRunner::with_validator(
+            "dotnet",
+            "test.csproj",
+            Ecosystem::DotNet,
+            17,
+            Arc::new(dotnet::DotNetValidator),
+        );
+
+        assert_eq!(
+            runner.supports_command("build", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("test", dir.path()),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runner.supports_command("nonexistent", dir.path()),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_unknown_validator_returns_unknown() {
+        let dir = tempdir().unwrap();
+        let runner = DetectedRunner::new("unknown", "file", Ecosystem::Generic, 100);
+        assert_eq!(
+            runner.supports_command("anything", dir.path()),
+            CommandSupport::Unknown
+        );
+    }
+
+    #[test]
+    fn test_build_custom_command() {
+        let mut commands = HashMap::new();
+        commands.insert("hello".to_string(), "echo 'hello world'".to_string());
+
+        let runner = DetectedRunner::with_custom_commands(
+            "custom",
+            "run.toml",
+            Ecosystem::Custom,
+            0,
+            Arc::new(UnknownValidator),
+            commands,
+        );
+
+        let cmd = runner.build_command("hello", &[]);
+        assert_eq!(cmd, vec!["echo", "hello world"]);
+    }
+
+    #[test]
+    fn test_ecosystem_serializes_as_display_name() {
+        for ecosystem in [
+            Ecosystem::NodeJs,
+            Ecosystem::Php,
+            Ecosystem::DotNet,
+            Ecosystem::Rust,
+        ] {
+            assert_eq!(serde_json::to_value(ecosystem).unwrap(), ecosystem.as_str());
+        }
+    }
+
+    #[test]
+    fn test_detected_runner_serialization() {
+        let runner = DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4);
+        assert_eq!(
+            serde_json::to_value(&runner).unwrap(),
+            serde_json::json!({
+                "name": "npm",
+                "detected_file": "package.json",
+                "ecosystem": "Node.js",
+                "priority": 4,
+            })
+        );
+        assert_eq!(
+            serde_json::to_value(CommandSupport::NotSupported).unwrap(),
+            "not_supported"
+        );
+    }
+
+    #[test]
+    fn test_detected_runner_deserialization() {
+        let mut commands = HashMap::new();
+        commands.insert("hello".to_string(), "echo hello".to_string());
+        let runner = DetectedRunner::with_custom_commands(
+            "custom",
+            "run.toml",
+            Ecosystem::Custom,
+            0,
+            Arc::new(UnknownValidator),
+            commands,
+        );
+
+        let json = serde_json::to_string(&runner).unwrap();
+        let parsed: DetectedRunner = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, runner);
+        assert_eq!(
+            parsed.supports_command("hello", Path::new(".")),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            parsed.supports_command("other", Path::new(".")),
+            CommandSupport::Unknown
+        );
+    }
+
+    #[test]
+    fn test_dir_scan() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("Makefile")).unwrap();
+        File::create(dir.path().join("app.csproj")).unwrap();
+
+        let scan = DirScan::new(dir.path());
+        assert_eq!(scan.path(), dir.path());
+        assert!(scan.has("Makefile"));
+        assert!(!scan.has("makefile"));
+        assert_eq!(
+            scan.names().collect::<Vec<_>>(),
+            vec!["Makefile", "app.csproj"]
+        );
+
+        let missing = DirScan::new(&dir.path().join("missing"));
+        assert_eq!(missing.names().count(), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dir_scan_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        let shared = tempdir().unwrap();
+        File::create(shared.path().join("package.json")).unwrap();
+        // A linked trigger counts, a dangling or looping one doesn't
+        symlink(
+            shared.path().join("package.json"),
+            dir.path().join("package.json"),
+        )
+        .unwrap();
+        symlink(dir.path().join("gone"), dir.path().join("Makefile")).unwrap();
+        symlink("go.mod", dir.path().join("go.mod")).unwrap();
+
+        let scan = DirScan::new(dir.path());
+        assert_eq!(scan.names().collect::<Vec<_>>(), vec!["package.json"]);
+    }
+}
@@ -0,0 +1,469 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::node::NodeValidator;
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Validates `rush <command>` against Rush's built-ins and the custom commands
+/// in `common/config/rush/command-line.json`
+pub struct RushValidator;
+
+impl CommandValidator for RushValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        const BUILTINS: &[&str] = &[
+            "add",
+            "build",
+            "change",
+            "check",
+            "deploy",
+            "init",
+            "install",
+            "link",
+            "list",
+            "publish",
+            "purge",
+            "rebuild",
+            "remove",
+            "scan",
+            "setup",
+            "unlink",
+            "update",
+            "upgrade-interactive",
+            "version",
+        ];
+
+        if BUILTINS.contains(&command) {
+            return CommandSupport::Supported;
+        }
+
+        let command_line = working_dir
+            .join("common")
+            .join("config")
+            .join("rush")
+            .join("command-line.json");
+        let Some(config) = manifest::jsonc(&command_line) else {
+            return CommandSupport::NotSupported;
+        };
+        let declared = config
+            .get("commands")
+            .and_then(|c| c.as_array())
+            .is_some_and(|commands| {
+                commands
+                    .iter()
+                    .any(|c| c.get("name").and_then(|n| n.as_str()) == Some(command))
+            });
+
+        if declared {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        let command_line = working_dir
+            .join("common")
+            .join("config")
+            .join("rush")
+            .join("command-line.json");
+        manifest::jsonc(&command_line)
+            .and_then(|config| {
+                let commands = config.get("commands")?.as_array()?;
+                Some(
+                    commands
+                        .iter()
+                        .filter_map(|c| c.get("name")?.as_str().map(str::to_string))
+                        .collect(),
+                )
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Whether `dir` is a project inside a Rush monorepo (an ancestor has `rush.json`)
+fn in_rush_repo(dir: &Path) -> bool {
+    dir.ancestors()
+        .skip(1)
+        .any(|ancestor| ancestor.join("rush.json").is_file())
+}
+
+/// Validates against tasks declared in `moon.yml` and the workspace's
+/// inherited tasks (`.moon/tasks.yml`, `.moon/tasks/*.yml`)
+pub struct MoonValidator;
+
+impl CommandValidator for MoonValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let project = manifest::yaml(&working_dir.join("moon.yml"));
+        if project
+            .as_deref()
+            .is_some_and(|p| declares_task(p, command))
+        {
+            return CommandSupport::Supported;
+        }
+
+        let workspace = working_dir
+            .ancestors()
+            .find(|dir| dir.join(".moon").join("workspace.yml").is_file());
+        if let Some(workspace) = workspace {
+            let moon_dir = workspace.join(".moon");
+            let mut files = vec![moon_dir.join("tasks.yml")];
+            if let Ok(entries) = fs::read_dir(moon_dir.join("tasks")) {
+                files.extend(
+                    entries
+                        .flatten()
+                        .map(|e| e.path())
+                        .filter(|p| p.extension().is_some_and(|ext| ext == "yml")),
+                );
+            }
+            if files
+                .iter()
+                .filter_map(|file| manifest::yaml(file))
+                .any(|tasks| declares_task(&tasks, command))
+            {
+                return CommandSupport::Supported;
+            }
+        }
+
+        // A project's tasks are all known; from the workspace root `:task`
+        // fans out to projects we don't enumerate
+        if project.is_some() {
+            CommandSupport::NotSupported
+        } else {
+            CommandSupport::Unknown
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        manifest::yaml(&working_dir.join("moon.yml"))
+            .and_then(|project| {
+                let tasks = project.get("tasks")?.as_mapping()?;
+                Some(
+                    tasks
+                        .keys()
+                        .filter_map(|k| k.as_str().map(str::to_string))
+                        .collect(),
+                )
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn declares_task(config: &serde_yaml::Value, task: &str) -> bool {
+    config
+        .get("tasks")
+        .and_then(|t| t.as_mapping())
+        .is_some_and(|tasks| tasks.keys().any(|key| key.as_str() == Some(task)))
+}
+
+/// Project id from `moon.yml`'s `id`, defaulting to the directory name
+fn moon_project_id(scan: &DirScan) -> String {
+    manifest::yaml(&scan.path().join("moon.yml"))
+        .and_then(|config| config.get("id")?.as_str().map(str::to_string))
+        .or_else(|| {
+            scan.path()
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default()
+}
+
+/// Detect monorepo orchestration tools (Nx, Turborepo, Lerna, Rush, moon)
+/// Priority: 0 (highest - these tools orchestrate other package managers)
+///
+/// Rush runs bulk commands (`rush build`, `rush test`) from the repo root and
+/// package scripts with `rushx <script>` from inside a project.
+///
+/// moon runs `moon run <project>:<task>` inside a project (`moon.yml`) and
+/// `moon run :<task>` across all projects from the workspace root.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    // Check for Nx (priority 0)
+    if scan.has("nx.json") {
+        runners.push(DetectedRunner::new("nx", "nx.json", Ecosystem::NodeJs, 0));
+    }
+
+    // Check for Turborepo (priority 0)
+    if scan.has("turbo.json") {
+        runners.push(DetectedRunner::new(
+            "turbo",
+            "turbo.json",
+            Ecosystem::NodeJs,
+            0,
+        ));
+    }
+
+    // Check for Lerna (priority 0)
+    if scan.has("lerna.json") {
+        runners.push(DetectedRunner::new(
+            "lerna",
+            "lerna.json",
+            Ecosystem::NodeJs,
+            0,
+        ));
+    }
+
+    // Check for Rush (priority 0): the repo root, or a project inside it
+    if scan.has("rush.json") {
+        runners.push(DetectedRunner::with_validator(
+            "rush",
+            "rush.json",
+            Ecosystem::NodeJs,
+            0,
+            Arc::new(RushValidator),
+        ));
+    } else if scan.has("package.json") && in_rush_repo(scan.path()) {
+        runners.push(DetectedRunner::with_validator(
+            "rushx",
+            "package.json",
+            Ecosystem::NodeJs,
+            0,
+            Arc::new(NodeValidator),
+        ));
+    }
+
+    // Check for moon (priority 0): a project, or the workspace root
+    if scan.has("moon.yml") {
+        runners.push(
+            DetectedRunner::with_validator(
+                "moon",
+                "moon.yml",
+                Ecosystem::NodeJs,
+                0,
+                Arc::new(MoonValidator),
+            )
+            .with_target(moon_project_id(scan)),
+        );
+    } else if scan.has(".moon") && scan.path().join(".moon").join("workspace.yml").is_file() {
+        runners.push(DetectedRunner::with_validator(
+            "moon",
+            ".moon/workspace.yml",
+            Ecosystem::NodeJs,
+            0,
+            Arc::new(MoonValidator),
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_nx() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("nx.json")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "nx");
+        assert_eq!(runners[0].detected_file, "nx.json");
+        assert_eq!(runners[0].priority, 0);
+    }
+
+    #[test]
+    fn test_detect_turbo() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("turbo.json")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "turbo");
+        assert_eq!(runners[0].detected_file, "turbo.json");
+        assert_eq!(runners[0].priority, 0);
+    }
+
+    #[test]
+    fn test_detect_lerna() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("lerna.json")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "lerna");
+        assert_eq!(runners[0].detected_file, "lerna.json");
+        assert_eq!(runners[0].priority, 0);
+    }
+
+    #[test]
+    fn test_detect_multiple_monorepo_tools() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("nx.json")).unwrap();
+        File::create(dir.path().join("turbo.json")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 2);
+        let names: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
+        assert!(names.contains(&"nx"));
+        assert!(names.contains(&"turbo"));
+    }
+
+    #[test]
+    fn test_detect_rush() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("rush.json")).unwrap();
+        let config = dir.path().join("common").join("config").join("rush");
+        fs::create_dir_all(&config).unwrap();
+        fs::write(
+            config.join("command-line.json"),
+            r#"{
+                // Custom bulk commands
+                "commands": [{"name": "test", "commandKind": "bulk"}]
+            }"#,
+        )
+        .unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "rush");
+        assert_eq!(runners[0].build_command("test", &[]), vec!["rush", "test"]);
+        for (task, support) in [
+            ("build", CommandSupport::Supported),
+            ("test", CommandSupport::Supported),
+            ("storybook", CommandSupport::NotSupported),
+        ] {
+            assert_eq!(runners[0].supports_command(task, dir.path()), support);
+        }
+    }
+
+    #[test]
+    fn test_detect_rush_project() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("rush.json")).unwrap();
+        let project = dir.path().join("apps").join("web");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            project.join("package.json"),
+            r#"{"scripts": {"storybook": "start-storybook"}}"#,
+        )
+        .unwrap();
+
+        let runners = detect(&DirScan::new(&project));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "rushx");
+        assert_eq!(
+            runners[0].build_command("storybook", &[]),
+            vec!["rushx", "storybook"]
+        );
+        assert_eq!(
+            runners[0].supports_command("storybook", &project),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runners[0].supports_command("deploy", &project),
+            CommandSupport::NotSupported
+        );
+
+        // Outside a Rush repo a package.json is left to the Node.js detector
+        let plain = tempdir().unwrap();
+        File::create(plain.path().join("package.json")).unwrap();
+        assert!(detect(&DirScan::new(plain.path())).is_empty());
+    }
+
+    #[test]
+    fn test_detect_moon_project() {
+        let dir = tempdir().unwrap();
+        let project = dir.path().join("web");
+        fs::create_dir_all(dir.path().join(".moon")).unwrap();
+        fs::create_dir(&project).unwrap();
+        fs::write(
+            dir.path().join(".moon").join("workspace.yml"),
+            "projects:\n  - 'web'\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".moon").join("tasks.yml"),
+            "tasks:\n  lint:\n    command: eslint\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join("moon.yml"),
+            "id: frontend\ntasks:\n  build:\n    command: vite build\n",
+        )
+        .unwrap();
+
+        let runners = detect(&DirScan::new(&project));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "moon");
+        assert_eq!(runners[0].target.as_deref(), Some("frontend"));
+        assert_eq!(
+            runners[0].build_command("build", &[]),
+            vec!["moon", "run", "frontend:build"]
+        );
+
+        assert_eq!(
+            runners[0].supports_command("build", &project),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runners[0].supports_command("lint", &project),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            runners[0].supports_command("deploy", &project),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_detect_moon_workspace_root() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join(".moon")).unwrap();
+        File::create(dir.path().join(".moon").join("workspace.yml")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].detected_file, ".moon/workspace.yml");
+        assert_eq!(runners[0].target, None);
+        assert_eq!(
+            runners[0].build_command("test", &["--".to_string()]),
+            vec!["moon", "run", ":test", "--"]
+        );
+        assert_eq!(
+            runners[0].supports_command("test", dir.path()),
+            CommandSupport::Unknown
+        );
+
+        // A project id defaults to the directory name
+        let project = dir.path().join("api");
+        fs::create_dir(&project).unwrap();
+        fs::write(project.join("moon.yml"), "tasks: {}\n").unwrap();
+        let runners = detect(&DirScan::new(&project));
+        assert_eq!(runners[0].target.as_deref(), Some("api"));
+    }
+
+    #[test]
+    fn test_detect_no_monorepo() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("package.json")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert!(runners.is_empty());
+    }
+
+    #[test]
+    fn test_priority_is_highest() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("turbo.json")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners[0].priority, 0);
+        // Priority 0 is higher than Bun (1), PNPM (2), etc.
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{
+    is_tool_installed, CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem,
+};
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, OnceLock};
+
+/// nix subcommands run directly (`nix build`, `nix develop`, ...); any other
+/// task runs a flake app or package with `nix run .#<task>`
+pub const NIX_BUILTINS: &[&str] = &["build", "develop", "shell", "run", "fmt", "flake"];
+
+/// Validator for flake outputs
+///
+/// Evaluating a flake is slow, so `nix flake show --json` runs at most once
+/// per detected runner.
+#[derive(Default)]
+pub struct NixValidator {
+    outputs: OnceLock<Option<HashSet<String>>>,
+}
+
+impl CommandValidator for NixValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        if NIX_BUILTINS.contains(&command) || command == "check" {
+            return CommandSupport::Supported;
+        }
+
+        match self.outputs.get_or_init(|| flake_outputs(working_dir)) {
+            Some(outputs) if outputs.contains(command) => CommandSupport::Supported,
+            Some(_) => CommandSupport::NotSupported,
+            None => CommandSupport::Unknown,
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        self.outputs
+            .get_or_init(|| flake_outputs(working_dir))
+            .iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+/// App and package names of the flake in `dir`, or `None` when nix is not
+/// installed or the flake can't be evaluated
+fn flake_outputs(dir: &Path) -> Option<HashSet<String>> {
+    if !is_tool_installed("nix") {
+        return None;
+    }
+    let output = Command::new("nix")
+        .args(["flake", "show", "--json", "--no-write-lock-file"])
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+    Some(parse_flake_outputs(&json))
+}
+
+/// Names under `apps.<system>` and `packages.<system>` in `nix flake show
+/// --json` output
+fn parse_flake_outputs(json: &serde_json::Value) -> HashSet<String> {
+    ["apps", "packages"]
+        .iter()
+        .filter_map(|kind| json.get(kind)?.as_object())
+        .flat_map(|systems| systems.values())
+        .filter_map(|outputs| outputs.as_object())
+        .flat_map(|outputs| outputs.keys().cloned())
+        .collect()
+}
+
+/// Detect Nix flakes
+/// Priority: 22 (after make, so a Makefile's targets win on shared names)
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has("flake.nix") {
+        let validator: Arc<dyn CommandValidator> = Arc::new(NixValidator::default());
+        runners.push(DetectedRunner::with_validator(
+            "nix",
+            "flake.nix",
+            Ecosystem::Nix,
+            22,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_flake() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("flake.nix")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "nix");
+        assert_eq!(runners[0].ecosystem, Ecosystem::Nix);
+
+        // A bare shell.nix has no tasks, only a dev shell
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("shell.nix")).unwrap();
+        assert!(detect(&DirScan::new(dir.path())).is_empty());
+    }
+
+    #[test]
+    fn test_build_command() {
+        let runner = DetectedRunner::new("nix", "flake.nix", Ecosystem::Nix, 22);
+        assert_eq!(runner.build_command("build", &[]), vec!["nix", "build"]);
+        assert_eq!(
+            runner.build_command("check", &[]),
+            vec!["nix", "flake", "check"]
+        );
+        assert_eq!(
+            runner.build_command("serve", &[]),
+            vec!["nix", "run", ".#serve"]
+        );
+        assert_eq!(
+            runner.build_command("serve", &["--port".to_string(), "80".to_string()]),
+            vec!["nix", "run", ".#serve", "--", "--port", "80"]
+        );
+    }
+
+    #[test]
+    fn test_parse_flake_outputs() {
+        let json = serde_json::json!({
+            "apps": {"x86_64-linux": {"serve": {"type": "app"}}},
+            "packages": {
+                "x86_64-linux": {"default": {}, "docs": {}},
+                "aarch64-darwin": {"docs": {}}
+            },
+            "devShells": {"x86_64-linux": {"default": {}}}
+        });
+
+        let outputs = parse_flake_outputs(&json);
+        assert_eq!(outputs.len(), 3);
+        assert!(outputs.contains("serve"));
+        assert!(outputs.contains("docs"));
+        assert!(outputs.contains("default"));
+    }
+
+    #[test]
+    fn test_validator_builtins() {
+        let dir = tempdir().unwrap();
+        let validator = NixValidator::default();
+        assert_eq!(
+            validator.supports_command(dir.path(), "develop"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "check"),
+            CommandSupport::Supported
+        );
+    }
+}
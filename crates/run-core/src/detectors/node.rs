@@ -9,8 +9,9 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
-use std::fs;
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem, TaskDefinition};
+use crate::manifest;
+use serde::Serialize;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -18,19 +19,9 @@ pub struct NodeValidator;
 
 impl CommandValidator for NodeValidator {
     fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
-        let package_json = working_dir.join("package.json");
-        if !package_json.exists() {
-            return CommandSupport::Unknown;
-        }
-
-        let content = match fs::read_to_string(&package_json) {
-            Ok(c) => c,
-            Err(_) => return CommandSupport::Unknown,
-        };
-
-        let json: serde_json::Value = match serde_json::from_str(&content) {
-            Ok(v) => v,
-            Err(_) => return CommandSupport::Unknown,
+        let json = match manifest::json(&working_dir.join("package.json")) {
+            Some(v) => v,
+            None => return CommandSupport::Unknown,
         };
 
         if let Some(scripts) = json.get("scripts").and_then(|s| s.as_object()) {
@@ -42,38 +33,149 @@ impl CommandValidator for NodeValidator {
 
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        manifest::json(&working_dir.join("package.json"))
+            .and_then(|json| Some(json.get("scripts")?.as_object()?.keys().cloned().collect()))
+            .unwrap_or_default()
+    }
+
+    fn definition(&self, working_dir: &Path, command: &str) -> Option<TaskDefinition> {
+        let path = working_dir.join("package.json");
+        let json = manifest::json(&path)?;
+        let body = json.get("scripts")?.get(command)?.as_str()?.to_string();
+
+        // The first `"<command>":` after the `"scripts"` key
+        let content = std::fs::read_to_string(&path).ok()?;
+        let key = serde_json::Value::from(command).to_string();
+        let scripts = content.lines().position(|l| l.contains("\"scripts\""))?;
+        let line = content
+            .lines()
+            .enumerate()
+            .skip(scripts)
+            .find_map(|(index, l)| {
+                let after = &l[l.find(&key)? + key.len()..];
+                after.trim_start().starts_with(':').then_some(index)
+            })?;
+
+        Some(TaskDefinition {
+            file: path,
+            line: line + 1,
+            body,
+        })
+    }
 }
 
 /// Get the package manager specified by Corepack in package.json
 /// Returns the package manager name (e.g., "pnpm", "yarn", "npm") if found
 /// Format: "packageManager": "pnpm@9.0.0" or "packageManager": "yarn@4.0.0+sha256.abc123"
 pub fn get_corepack_manager(dir: &Path) -> Option<String> {
-    let package_json = dir.join("package.json");
-    let content = fs::read_to_string(&package_json).ok()?;
-    let json: serde_json::Value = serde_json::from_str(&content).ok()?;
+    package_manager_pin(dir).map(|(name, _)| name)
+}
+
+/// Package manager and version pinned by `packageManager` in package.json
+///
+/// The version drops any `+sha...` integrity suffix.
+pub fn package_manager_pin(dir: &Path) -> Option<(String, Option<String>)> {
+    let json = manifest::json(&dir.join("package.json"))?;
 
     let package_manager = json.get("packageManager")?.as_str()?;
 
     // Parse format: "pnpm@9.0.0" or "yarn@4.0.0+sha256.abc123"
-    // Extract just the package manager name (before @)
-    let name = package_manager.split('@').next()?;
+    let (name, version) = match package_manager.split_once('@') {
+        Some((name, version)) => (name, version.split('+').next()),
+        None => (package_manager, None),
+    };
 
     if name.is_empty() {
         return None;
     }
 
-    Some(name.to_string())
+    let version = version.filter(|v| !v.is_empty()).map(str::to_string);
+    Some((name.to_string(), version))
+}
+
+/// Version of the package manager found on PATH, if it can be determined
+/// without running it
+///
+/// npm, pnpm and yarn installed from npm resolve to a script inside their
+/// package, whose package.json has the version. Corepack shims and
+/// standalone binaries return `None`.
+pub fn installed_version(manager: &str) -> Option<String> {
+    let path = crate::tools::find_tool(manager)?.canonicalize().ok()?;
+    path.ancestors().skip(1).take(4).find_map(|dir| {
+        let json = manifest::json(&dir.join("package.json"))?;
+        if json.get("name")?.as_str()? != manager {
+            return None;
+        }
+        json.get("version")?.as_str().map(str::to_string)
+    })
+}
+
+/// A script configured in package.json's `wireit` block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WireitScript {
+    pub name: String,
+    /// Scripts wireit runs first (`build`, `../lib:build`, ...)
+    pub dependencies: Vec<String>,
+    /// Whether `scripts.<name>` is `"wireit"`, i.e. running it goes through
+    /// wireit and builds the dependencies
+    pub via_wireit: bool,
+}
+
+/// Scripts managed by wireit, sorted by name
+pub fn wireit_scripts(dir: &Path) -> Vec<WireitScript> {
+    let Some(json) = manifest::json(&dir.join("package.json")) else {
+        return Vec::new();
+    };
+    let Some(config) = json.get("wireit").and_then(|w| w.as_object()) else {
+        return Vec::new();
+    };
+    let scripts = json.get("scripts");
+
+    let mut wireit: Vec<WireitScript> = config
+        .iter()
+        .map(|(name, script)| {
+            let dependencies = script
+                .get("dependencies")
+                .and_then(|d| d.as_array())
+                .map(|deps| {
+                    deps.iter()
+                        .filter_map(|dep| {
+                            // Either "name" or {"script": "name", "cascade": false}
+                            dep.as_str()
+                                .or_else(|| dep.get("script")?.as_str())
+                                .map(str::to_string)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            let via_wireit = scripts
+                .and_then(|s| s.get(name))
+                .and_then(|s| s.as_str())
+                .is_some_and(|s| s.trim() == "wireit");
+
+            WireitScript {
+                name: name.clone(),
+                dependencies,
+                via_wireit,
+            }
+        })
+        .collect();
+    wireit.sort_by(|a, b| a.name.cmp(&b.name));
+    wireit
 }
 
 /// Detect Node.js package managers
 /// Priority: Bun (1) > PNPM (2) > Yarn (3) > NPM (4)
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let dir = scan.path();
     let mut runners = Vec::new();
 
-    let has_package_json = dir.join("package.json").exists();
+    let has_package_json = scan.has("package.json");
     let validator: Arc<dyn CommandValidator> = Arc::new(NodeValidator);
 
-    // Check for Corepack (packageManager field)
+    // The packageManager field (Corepack) wins over whatever lockfiles exist
     if has_package_json {
         if let Some(manager) = get_corepack_manager(dir) {
             let (priority, name) = match manager.as_str() {
@@ -95,9 +197,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for Bun (priority 1)
-    let bun_lockb = dir.join("bun.lockb");
-    let bun_lock = dir.join("bun.lock");
-    if bun_lockb.exists() && has_package_json {
+    if scan.has("bun.lockb") && has_package_json {
         runners.push(DetectedRunner::with_validator(
             "bun",
             "bun.lockb",
@@ -105,7 +205,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
             1,
             Arc::clone(&validator),
         ));
-    } else if bun_lock.exists() && has_package_json {
+    } else if scan.has("bun.lock") && has_package_json {
         runners.push(DetectedRunner::with_validator(
             "bun",
             "bun.lock",
@@ -116,8 +216,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for PNPM (priority 2)
-    let pnpm_lock = dir.join("pnpm-lock.yaml");
-    if pnpm_lock.exists() && has_package_json {
+    if scan.has("pnpm-lock.yaml") && has_package_json {
         runners.push(DetectedRunner::with_validator(
             "pnpm",
             "pnpm-lock.yaml",
@@ -128,8 +227,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for Yarn (priority 3)
-    let yarn_lock = dir.join("yarn.lock");
-    if yarn_lock.exists() && has_package_json {
+    if scan.has("yarn.lock") && has_package_json {
         runners.push(DetectedRunner::with_validator(
             "yarn",
             "yarn.lock",
@@ -140,8 +238,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for NPM (priority 4)
-    let npm_lock = dir.join("package-lock.json");
-    if npm_lock.exists() && has_package_json {
+    if scan.has("package-lock.json") && has_package_json {
         runners.push(DetectedRunner::with_validator(
             "npm",
             "package-lock.json",
@@ -175,7 +272,7 @@ mod tests {
         File::create(dir.path().join("package.json")).unwrap();
         File::create(dir.path().join("bun.lockb")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "bun");
         assert_eq!(runners[0].detected_file, "bun.lockb");
@@ -187,7 +284,7 @@ mod tests {
         File::create(dir.path().join("package.json")).unwrap();
         File::create(dir.path().join("bun.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "bun");
         assert_eq!(runners[0].detected_file, "bun.lock");
@@ -199,7 +296,7 @@ mod tests {
         File::create(dir.path().join("package.json")).unwrap();
         File::create(dir.path().join("pnpm-lock.yaml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "pnpm");
     }
@@ -210,7 +307,7 @@ mod tests {
         File::create(dir.path().join("package.json")).unwrap();
         File::create(dir.path().join("yarn.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "yarn");
     }
@@ -221,7 +318,7 @@ mod tests {
         File::create(dir.path().join("package.json")).unwrap();
         File::create(dir.path().join("package-lock.json")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "npm");
     }
@@ -231,7 +328,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("package.json")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "npm");
         assert_eq!(runners[0].detected_file, "package.json");
@@ -244,7 +341,7 @@ mod tests {
         File::create(dir.path().join("package-lock.json")).unwrap();
         File::create(dir.path().join("yarn.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 2);
         // Should have both yarn and npm
         let names: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
@@ -257,7 +354,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("yarn.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 
@@ -271,7 +368,7 @@ mod tests {
         writeln!(file, r#"{{"scripts": {{"test": "jest", "build": "tsc"}}}}"#).unwrap();
         File::create(dir.path().join("package-lock.json")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "npm");
 
@@ -288,6 +385,7 @@ mod tests {
             runners[0].supports_command("nonexistent", dir.path()),
             CommandSupport::NotSupported
         );
+        assert_eq!(runners[0].list_commands(dir.path()), vec!["build", "test"]);
     }
 
     #[test]
@@ -359,7 +457,7 @@ mod tests {
 
         // No lockfiles
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1, "Expected 1 runner, found {:?}", runners);
         assert_eq!(runners[0].name, "pnpm");
     }
@@ -374,10 +472,85 @@ mod tests {
         // yarn.lock exists
         File::create(dir.path().join("yarn.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         // Should detect BOTH yarn (file) and pnpm (packageManager)
         let names: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
         assert!(names.contains(&"yarn"), "Should contain yarn");
         assert!(names.contains(&"pnpm"), "Should contain pnpm");
     }
+
+    #[test]
+    fn test_package_manager_pin() {
+        let dir = tempdir().unwrap();
+        let pin = |value: &str| {
+            std::fs::write(
+                dir.path().join("package.json"),
+                format!(r#"{{"packageManager": "{}"}}"#, value),
+            )
+            .unwrap();
+            package_manager_pin(dir.path())
+        };
+
+        assert_eq!(
+            pin("yarn@4.0.0+sha256.abc123"),
+            Some(("yarn".to_string(), Some("4.0.0".to_string())))
+        );
+        assert_eq!(pin("pnpm"), Some(("pnpm".to_string(), None)));
+        assert_eq!(pin("@1.0.0"), None);
+    }
+
+    #[test]
+    fn test_wireit_scripts() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{
+                "scripts": {"build": "wireit", "bundle": "rollup -c"},
+                "wireit": {
+                    "build": {"command": "tsc", "dependencies": ["../lib:build"]},
+                    "bundle": {
+                        "command": "rollup -c",
+                        "dependencies": [{"script": "build", "cascade": false}]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            wireit_scripts(dir.path()),
+            vec![
+                WireitScript {
+                    name: "build".to_string(),
+                    dependencies: vec!["../lib:build".to_string()],
+                    via_wireit: true,
+                },
+                WireitScript {
+                    name: "bundle".to_string(),
+                    dependencies: vec!["build".to_string()],
+                    via_wireit: false,
+                },
+            ]
+        );
+
+        let plain = tempdir().unwrap();
+        std::fs::write(plain.path().join("package.json"), "{}").unwrap();
+        assert!(wireit_scripts(plain.path()).is_empty());
+    }
+
+    #[test]
+    fn test_script_definition() {
+        let dir = tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            "{\n  \"name\": \"build\",\n  \"scripts\": {\n    \"build\" : \"tsc -p .\",\n    \"test\": \"jest\"\n  }\n}\n",
+        )
+        .unwrap();
+
+        let definition = NodeValidator.definition(dir.path(), "build").unwrap();
+        assert_eq!(definition.file, dir.path().join("package.json"));
+        assert_eq!(definition.line, 4);
+        assert_eq!(definition.body, "tsc -p .");
+        assert!(NodeValidator.definition(dir.path(), "lint").is_none());
+    }
 }
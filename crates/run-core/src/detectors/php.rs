@@ -9,8 +9,8 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
-use std::fs;
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -18,19 +18,9 @@ pub struct PhpValidator;
 
 impl CommandValidator for PhpValidator {
     fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
-        let composer_json = working_dir.join("composer.json");
-        if !composer_json.exists() {
-            return CommandSupport::Unknown;
-        }
-
-        let content = match fs::read_to_string(&composer_json) {
-            Ok(c) => c,
-            Err(_) => return CommandSupport::Unknown,
-        };
-
-        let json: serde_json::Value = match serde_json::from_str(&content) {
-            Ok(v) => v,
-            Err(_) => return CommandSupport::Unknown,
+        let json = match manifest::json(&working_dir.join("composer.json")) {
+            Some(v) => v,
+            None => return CommandSupport::Unknown,
         };
 
         if let Some(scripts) = json.get("scripts").and_then(|s| s.as_object()) {
@@ -42,18 +32,22 @@ impl CommandValidator for PhpValidator {
 
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        manifest::json(&working_dir.join("composer.json"))
+            .and_then(|json| Some(json.get("scripts")?.as_object()?.keys().cloned().collect()))
+            .unwrap_or_default()
+    }
 }
 
 /// Detect PHP package manager (Composer)
 /// Priority: 10
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
-    let composer_json = dir.join("composer.json");
-    let composer_lock = dir.join("composer.lock");
     let validator: Arc<dyn CommandValidator> = Arc::new(PhpValidator);
 
-    if composer_lock.exists() && composer_json.exists() {
+    if scan.has("composer.lock") && scan.has("composer.json") {
         runners.push(DetectedRunner::with_validator(
             "composer",
             "composer.lock",
@@ -61,7 +55,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
             10,
             Arc::clone(&validator),
         ));
-    } else if composer_json.exists() {
+    } else if scan.has("composer.json") {
         runners.push(DetectedRunner::with_validator(
             "composer",
             "composer.json",
@@ -86,7 +80,7 @@ mod tests {
         File::create(dir.path().join("composer.json")).unwrap();
         File::create(dir.path().join("composer.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "composer");
         assert_eq!(runners[0].detected_file, "composer.lock");
@@ -97,7 +91,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("composer.json")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "composer");
         assert_eq!(runners[0].detected_file, "composer.json");
@@ -107,7 +101,7 @@ mod tests {
     fn test_no_composer() {
         let dir = tempdir().unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 }
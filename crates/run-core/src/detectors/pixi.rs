@@ -0,0 +1,187 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Validator for pixi tasks
+///
+/// Tasks come from `[tasks]` and `[feature.<name>.tasks]` in pixi.toml, or
+/// the same tables under `[tool.pixi]` in pyproject.toml.
+pub struct PixiValidator;
+
+impl CommandValidator for PixiValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        match tasks(working_dir) {
+            Some(tasks) if tasks.iter().any(|task| task == command) => CommandSupport::Supported,
+            Some(_) => CommandSupport::NotSupported,
+            None => CommandSupport::Unknown,
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        tasks(working_dir).unwrap_or_default()
+    }
+}
+
+/// Names of the tasks of the workspace and of its features, `None` without
+/// a pixi.toml or a `[tool.pixi]` table in pyproject.toml
+fn tasks(working_dir: &Path) -> Option<Vec<String>> {
+    let workspace = if let Some(pixi) = manifest::toml(&working_dir.join("pixi.toml")) {
+        pixi.as_ref().clone()
+    } else {
+        manifest::toml(&working_dir.join("pyproject.toml"))?
+            .get("tool")?
+            .get("pixi")?
+            .clone()
+    };
+
+    let names = |table: &toml::Value| -> Vec<String> {
+        table
+            .get("tasks")
+            .and_then(|tasks| tasks.as_table())
+            .map(|tasks| tasks.keys().cloned().collect())
+            .unwrap_or_default()
+    };
+    let mut tasks = names(&workspace);
+    if let Some(features) = workspace.get("feature").and_then(|f| f.as_table()) {
+        tasks.extend(features.values().flat_map(names));
+    }
+    Some(tasks)
+}
+
+/// Detect pixi workspaces
+/// Priority: 32
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if let Some(&file) = ["pixi.lock", "pixi.toml"].iter().find(|f| scan.has(f)) {
+        let validator: Arc<dyn CommandValidator> = Arc::new(PixiValidator);
+        runners.push(DetectedRunner::with_validator(
+            "pixi",
+            file,
+            Ecosystem::Pixi,
+            32,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_pixi() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("pixi.toml"), "").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "pixi");
+        assert_eq!(runners[0].detected_file, "pixi.toml");
+        assert_eq!(
+            runners[0].build_command("test", &[]),
+            vec!["pixi", "run", "test"]
+        );
+
+        fs::write(dir.path().join("pixi.lock"), "").unwrap();
+        assert_eq!(
+            detect(&DirScan::new(dir.path()))[0].detected_file,
+            "pixi.lock"
+        );
+    }
+
+    #[test]
+    fn test_list_pixi_tasks() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pixi.toml"),
+            "[tasks]\ntest = \"pytest\"\n\n[feature.docs.tasks]\ndocs = \"mkdocs serve\"\n",
+        )
+        .unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners[0].list_commands(dir.path()), ["docs", "test"]);
+
+        // A pixi.lock next to a pyproject.toml with [tool.pixi]
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("pixi.lock"), "").unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[tool.pixi.tasks]\nserve = \"python -m demo\"\n",
+        )
+        .unwrap();
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners[0].list_commands(dir.path()), ["serve"]);
+    }
+
+    #[test]
+    fn test_pixi_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("pixi.toml"),
+            r#"
+[workspace]
+name = "demo"
+
+[tasks]
+test = "pytest"
+lint = { cmd = "ruff check" }
+
+[feature.docs.tasks]
+docs = "mkdocs serve"
+"#,
+        )
+        .unwrap();
+
+        let validator = PixiValidator;
+        for task in ["test", "lint", "docs"] {
+            assert_eq!(
+                validator.supports_command(dir.path(), task),
+                CommandSupport::Supported,
+                "{}",
+                task
+            );
+        }
+        assert_eq!(
+            validator.supports_command(dir.path(), "build"),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_pixi_validator_pyproject() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("pixi.lock"), "").unwrap();
+        fs::write(
+            dir.path().join("pyproject.toml"),
+            "[project]\nname = \"demo\"\n\n[tool.pixi.tasks]\nserve = \"python -m demo\"\n",
+        )
+        .unwrap();
+
+        let validator = PixiValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "serve"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "test"),
+            CommandSupport::NotSupported
+        );
+    }
+}
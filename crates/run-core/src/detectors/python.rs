@@ -9,8 +9,8 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
-use std::fs;
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -18,19 +18,9 @@ pub struct PythonValidator;
 
 impl CommandValidator for PythonValidator {
     fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
-        let pyproject = working_dir.join("pyproject.toml");
-        if !pyproject.exists() {
-            return CommandSupport::Unknown;
-        }
-
-        let content = match fs::read_to_string(&pyproject) {
-            Ok(c) => c,
-            Err(_) => return CommandSupport::Unknown,
-        };
-
-        let toml_value: toml::Value = match toml::from_str(&content) {
-            Ok(v) => v,
-            Err(_) => return CommandSupport::Unknown,
+        let toml_value = match manifest::toml(&working_dir.join("pyproject.toml")) {
+            Some(v) => v,
+            None => return CommandSupport::Unknown,
         };
 
         // Check [project.scripts] (PEP 621 - modern style, Poetry 2.0+ and UV)
@@ -61,19 +51,36 @@ impl CommandValidator for PythonValidator {
         // So we return Unknown to allow fallback behavior
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        let Some(toml_value) = manifest::toml(&working_dir.join("pyproject.toml")) else {
+            return Vec::new();
+        };
+        let project = toml_value.get("project").and_then(|p| p.get("scripts"));
+        let poetry = toml_value
+            .get("tool")
+            .and_then(|t| t.get("poetry"))
+            .and_then(|p| p.get("scripts"));
+
+        [project, poetry]
+            .into_iter()
+            .flatten()
+            .filter_map(|scripts| scripts.as_table())
+            .flat_map(|scripts| scripts.keys().cloned())
+            .collect()
+    }
 }
 
 /// Detect Python package managers
 /// Priority: UV (5) > Poetry (6) > Pipenv (7) > Pip (8)
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
-    let has_pyproject = dir.join("pyproject.toml").exists();
+    let has_pyproject = scan.has("pyproject.toml");
     let validator: Arc<dyn CommandValidator> = Arc::new(PythonValidator);
 
     // Check for UV (priority 5)
-    let uv_lock = dir.join("uv.lock");
-    if uv_lock.exists() && has_pyproject {
+    if scan.has("uv.lock") && has_pyproject {
         runners.push(DetectedRunner::with_validator(
             "uv",
             "uv.lock",
@@ -84,8 +91,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for Poetry (priority 6)
-    let poetry_lock = dir.join("poetry.lock");
-    if poetry_lock.exists() && has_pyproject {
+    if scan.has("poetry.lock") && has_pyproject {
         runners.push(DetectedRunner::with_validator(
             "poetry",
             "poetry.lock",
@@ -96,9 +102,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for Pipenv (priority 7)
-    let pipfile = dir.join("Pipfile");
-    let pipfile_lock = dir.join("Pipfile.lock");
-    if pipfile_lock.exists() && pipfile.exists() {
+    if scan.has("Pipfile.lock") && scan.has("Pipfile") {
         runners.push(DetectedRunner::with_validator(
             "pipenv",
             "Pipfile.lock",
@@ -109,8 +113,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for Pip (priority 8) - fallback
-    let requirements = dir.join("requirements.txt");
-    if requirements.exists() {
+    if scan.has("requirements.txt") {
         runners.push(DetectedRunner::with_validator(
             "pip",
             "requirements.txt",
@@ -145,7 +148,7 @@ mod tests {
         File::create(dir.path().join("pyproject.toml")).unwrap();
         File::create(dir.path().join("uv.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "uv");
     }
@@ -156,7 +159,7 @@ mod tests {
         File::create(dir.path().join("pyproject.toml")).unwrap();
         File::create(dir.path().join("poetry.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "poetry");
     }
@@ -167,7 +170,7 @@ mod tests {
         File::create(dir.path().join("Pipfile")).unwrap();
         File::create(dir.path().join("Pipfile.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "pipenv");
     }
@@ -177,7 +180,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("requirements.txt")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "pip");
         assert_eq!(runners[0].detected_file, "requirements.txt");
@@ -188,7 +191,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("pyproject.toml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "pip");
         assert_eq!(runners[0].detected_file, "pyproject.toml");
@@ -199,7 +202,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("uv.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 
@@ -323,7 +326,7 @@ myapp = "example:main"
         .unwrap();
         File::create(dir.path().join("uv.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "uv");
 
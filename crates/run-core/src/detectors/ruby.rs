@@ -9,7 +9,7 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
 use std::fs;
 use std::path::Path;
 use std::sync::Arc;
@@ -41,18 +41,44 @@ impl CommandValidator for RubyValidator {
 
         CommandSupport::Unknown
     }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        fs::read_to_string(working_dir.join("Rakefile"))
+            .map(|content| extract_rake_tasks(&content))
+            .unwrap_or_default()
+    }
+}
+
+/// Task names declared in a Rakefile: `task :name`, `task name: deps`,
+/// `task "name"` or `task 'name'`
+fn extract_rake_tasks(content: &str) -> Vec<String> {
+    let is_name = |c: char| c.is_alphanumeric() || c == '_';
+    content
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("task "))
+        .filter_map(|rest| {
+            let rest = rest.trim_start();
+            let name = if let Some(symbol) = rest.strip_prefix(':') {
+                symbol.split(|c: char| !is_name(c)).next()?
+            } else if let Some(quote) = rest.chars().next().filter(|c| *c == '"' || *c == '\'') {
+                rest[1..].split(quote).next()?
+            } else {
+                let end = rest.find(|c: char| !is_name(c))?;
+                rest[end..].starts_with(':').then(|| &rest[..end])?
+            };
+            (!name.is_empty()).then(|| name.to_string())
+        })
+        .collect()
 }
 
 /// Detect Ruby package managers
 /// Priority: Bundler (13) > Rake (14)
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
     let validator: Arc<dyn CommandValidator> = Arc::new(RubyValidator);
 
     // Check for Bundler (priority 13)
-    let gemfile = dir.join("Gemfile");
-    let gemfile_lock = dir.join("Gemfile.lock");
-    if gemfile_lock.exists() && gemfile.exists() {
+    if scan.has("Gemfile.lock") && scan.has("Gemfile") {
         runners.push(DetectedRunner::with_validator(
             "bundler",
             "Gemfile.lock",
@@ -60,7 +86,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
             13,
             Arc::clone(&validator),
         ));
-    } else if gemfile.exists() {
+    } else if scan.has("Gemfile") {
         runners.push(DetectedRunner::with_validator(
             "bundler",
             "Gemfile",
@@ -71,8 +97,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
     }
 
     // Check for Rake (priority 14)
-    let rakefile = dir.join("Rakefile");
-    if rakefile.exists() {
+    if scan.has("Rakefile") {
         runners.push(DetectedRunner::with_validator(
             "rake",
             "Rakefile",
@@ -97,7 +122,7 @@ mod tests {
         File::create(dir.path().join("Gemfile")).unwrap();
         File::create(dir.path().join("Gemfile.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "bundler");
         assert_eq!(runners[0].detected_file, "Gemfile.lock");
@@ -108,7 +133,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Gemfile")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "bundler");
         assert_eq!(runners[0].detected_file, "Gemfile");
@@ -119,7 +144,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Rakefile")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "rake");
     }
@@ -130,9 +155,30 @@ mod tests {
         File::create(dir.path().join("Gemfile")).unwrap();
         File::create(dir.path().join("Rakefile")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 2);
         assert!(runners.iter().any(|r| r.name == "bundler"));
         assert!(runners.iter().any(|r| r.name == "rake"));
     }
+
+    #[test]
+    fn test_extract_rake_tasks() {
+        let rakefile = r#"
+require "rake/testtask"
+
+task :build do
+  sh "gem build"
+end
+
+task default: :test
+task "release" => [:build]
+task 'lint'
+  task :indented
+taskname = 1
+"#;
+        assert_eq!(
+            extract_rake_tasks(rakefile),
+            vec!["build", "default", "release", "lint", "indented"]
+        );
+    }
 }
@@ -9,60 +9,64 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{CommandSupport, CommandValidator, DetectedRunner, Ecosystem};
-use std::fs;
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
 use std::path::Path;
 use std::sync::Arc;
 
+/// Cargo's built-in subcommands
+pub(crate) static CARGO_BUILTIN: &[&str] = &[
+    "build",
+    "b",
+    "check",
+    "c",
+    "clean",
+    "doc",
+    "d",
+    "new",
+    "init",
+    "add",
+    "remove",
+    "run",
+    "r",
+    "test",
+    "t",
+    "bench",
+    "update",
+    "search",
+    "publish",
+    "install",
+    "uninstall",
+    "clippy",
+    "fmt",
+    "fix",
+    "tree",
+    "vendor",
+    "verify-project",
+    "version",
+    "yank",
+    "help",
+    "generate-lockfile",
+    "locate-project",
+    "metadata",
+    "pkgid",
+    "fetch",
+    "login",
+    "logout",
+    "owner",
+    "package",
+    "report",
+    "rustc",
+    "rustdoc",
+];
+
+/// Reported as the detected file when custom tasks are routed through xtask
+pub(crate) const XTASK_FILE: &str = "xtask/Cargo.toml";
+
 pub struct RustValidator;
 
 impl CommandValidator for RustValidator {
     fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
-        static CARGO_BUILTIN: &[&str] = &[
-            "build",
-            "b",
-            "check",
-            "c",
-            "clean",
-            "doc",
-            "d",
-            "new",
-            "init",
-            "add",
-            "remove",
-            "run",
-            "r",
-            "test",
-            "t",
-            "bench",
-            "update",
-            "search",
-            "publish",
-            "install",
-            "uninstall",
-            "clippy",
-            "fmt",
-            "fix",
-            "tree",
-            "vendor",
-            "verify-project",
-            "version",
-            "yank",
-            "help",
-            "generate-lockfile",
-            "locate-project",
-            "metadata",
-            "pkgid",
-            "fetch",
-            "login",
-            "logout",
-            "owner",
-            "package",
-            "report",
-            "rustc",
-            "rustdoc",
-        ];
-
         if CARGO_BUILTIN.contains(&command) {
             return CommandSupport::Supported;
         }
@@ -76,6 +80,20 @@ impl CommandValidator for RustValidator {
         // may exist in parent directories or $CARGO_HOME, so return Unknown
         CommandSupport::Unknown
     }
+
+    /// Aliases from the project's `.cargo/config(.toml)`
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        let dot_cargo = working_dir.join(".cargo");
+        ["config", "config.toml"]
+            .iter()
+            .filter_map(|file| manifest::toml(&dot_cargo.join(file)))
+            .filter_map(|config| {
+                let alias = config.get("alias")?.as_table()?;
+                Some(alias.keys().cloned().collect::<Vec<_>>())
+            })
+            .flatten()
+            .collect()
+    }
 }
 
 /// Check if a command is defined as an alias in cargo config files.
@@ -83,11 +101,9 @@ impl CommandValidator for RustValidator {
 /// Respects Cargo's precedence: extensionless `config` over `config.toml`.
 fn check_cargo_alias(dir: &Path, command: &str) -> bool {
     let check_file = |path: &Path| -> bool {
-        if let Ok(content) = fs::read_to_string(path) {
-            if let Ok(config) = content.parse::<toml::Value>() {
-                if let Some(alias) = config.get("alias").and_then(|v| v.as_table()) {
-                    return alias.contains_key(command);
-                }
+        if let Some(config) = manifest::toml(path) {
+            if let Some(alias) = config.get("alias").and_then(|v| v.as_table()) {
+                return alias.contains_key(command);
             }
         }
         false
@@ -120,16 +136,45 @@ fn check_cargo_alias(dir: &Path, command: &str) -> bool {
     false
 }
 
+/// Whether the project follows the cargo xtask pattern: an `xtask/` member
+/// or `[workspace.metadata.xtask]` in the root manifest
+fn has_xtask(scan: &DirScan) -> bool {
+    if scan.has("xtask") && scan.path().join(XTASK_FILE).is_file() {
+        return true;
+    }
+    manifest::toml(&scan.path().join("Cargo.toml"))
+        .and_then(|cargo| {
+            cargo
+                .get("workspace")?
+                .get("metadata")?
+                .get("xtask")
+                .cloned()
+        })
+        .is_some()
+}
+
 /// Detect Rust package manager (Cargo)
 /// Priority: 9
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+///
+/// In xtask projects the runner reports `xtask/Cargo.toml`, and tasks that
+/// are not Cargo built-ins run as `cargo run -p xtask -- <task>`.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
-    let cargo_toml = dir.join("Cargo.toml");
-    let cargo_lock = dir.join("Cargo.lock");
     let validator: Arc<dyn CommandValidator> = Arc::new(RustValidator);
 
-    if cargo_toml.exists() && cargo_lock.exists() {
+    if scan.has("Cargo.toml") && has_xtask(scan) {
+        runners.push(DetectedRunner::with_validator(
+            "cargo",
+            XTASK_FILE,
+            Ecosystem::Rust,
+            9,
+            validator,
+        ));
+        return runners;
+    }
+
+    if scan.has("Cargo.toml") && scan.has("Cargo.lock") {
         runners.push(DetectedRunner::with_validator(
             "cargo",
             "Cargo.toml",
@@ -137,7 +182,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
             9,
             Arc::clone(&validator),
         ));
-    } else if cargo_toml.exists() {
+    } else if scan.has("Cargo.toml") {
         // Even without lock file, Cargo.toml is sufficient
         runners.push(DetectedRunner::with_validator(
             "cargo",
@@ -154,7 +199,7 @@ pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::fs::File;
+    use std::fs::{self, File};
     use tempfile::tempdir;
 
     #[test]
@@ -163,7 +208,7 @@ mod tests {
         File::create(dir.path().join("Cargo.toml")).unwrap();
         File::create(dir.path().join("Cargo.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "cargo");
     }
@@ -173,7 +218,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Cargo.toml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "cargo");
     }
@@ -183,7 +228,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Cargo.lock")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 
@@ -194,7 +239,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Cargo.toml")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "cargo");
 
@@ -305,6 +350,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_detect_xtask_member() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("Cargo.toml")).unwrap();
+        fs::create_dir(dir.path().join("xtask")).unwrap();
+        File::create(dir.path().join("xtask").join("Cargo.toml")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "cargo");
+        assert_eq!(runners[0].detected_file, XTASK_FILE);
+        assert_eq!(
+            runners[0].build_command("dist", &["--release".to_string()]),
+            vec!["cargo", "run", "-p", "xtask", "--", "dist", "--release"]
+        );
+        assert_eq!(runners[0].build_command("test", &[]), vec!["cargo", "test"]);
+        // The conventional `cargo xtask` alias is left to cargo
+        assert_eq!(
+            runners[0].build_command("xtask", &[]),
+            vec!["cargo", "xtask"]
+        );
+    }
+
+    #[test]
+    fn test_detect_xtask_workspace_metadata() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"tools/*\"]\n\n[workspace.metadata.xtask]\n",
+        )
+        .unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners[0].detected_file, XTASK_FILE);
+
+        // An `xtask` directory without a manifest is not a member
+        let plain = tempdir().unwrap();
+        File::create(plain.path().join("Cargo.toml")).unwrap();
+        fs::create_dir(plain.path().join("xtask")).unwrap();
+        let runners = detect(&DirScan::new(plain.path()));
+        assert_eq!(runners[0].detected_file, "Cargo.toml");
+        assert_eq!(runners[0].build_command("dist", &[]), vec!["cargo", "dist"]);
+    }
+
     #[test]
     fn test_cargo_alias_array_format() {
         use std::io::Write;
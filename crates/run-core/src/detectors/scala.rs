@@ -0,0 +1,103 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{DetectedRunner, DirScan, Ecosystem};
+
+/// Mill build files, newest convention first
+const MILL_FILES: &[&str] = &["build.mill", "build.sc"];
+
+/// Detect Scala build tools
+/// Priority: Mill (26) > sbt (27)
+///
+/// Tasks are passed straight through, so `run foo.test` runs `mill foo.test`
+/// and `run compile` runs `sbt compile`.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if let Some(&file) = MILL_FILES.iter().find(|f| scan.has(f)) {
+        runners.push(DetectedRunner::new("mill", file, Ecosystem::Scala, 26));
+    }
+
+    if scan.has("build.sbt") {
+        runners.push(DetectedRunner::new(
+            "sbt",
+            "build.sbt",
+            Ecosystem::Scala,
+            27,
+        ));
+    }
+
+    runners
+}
+
+/// Build the sbt command for `task`
+///
+/// sbt reads each argument as a command of its own, so arguments are joined
+/// with the task into one: `run testOnly app.Spec` runs
+/// `sbt "testOnly app.Spec"` rather than `testOnly` and then `app.Spec`.
+pub fn sbt_command(task: &str, extra_args: &[String]) -> Vec<String> {
+    let command = std::iter::once(task)
+        .chain(extra_args.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ");
+    vec!["sbt".to_string(), command]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_mill() {
+        for file in ["build.mill", "build.sc"] {
+            let dir = tempdir().unwrap();
+            File::create(dir.path().join(file)).unwrap();
+
+            let runners = detect(&DirScan::new(dir.path()));
+            assert_eq!(runners.len(), 1);
+            assert_eq!(runners[0].name, "mill");
+            assert_eq!(runners[0].detected_file, file);
+            assert_eq!(
+                runners[0].build_command("app.test", &[]),
+                vec!["mill", "app.test"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_detect_sbt() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("build.sbt")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "sbt");
+        assert_eq!(runners[0].priority, 27);
+        assert_eq!(
+            runners[0].build_command("compile", &[]),
+            vec!["sbt", "compile"]
+        );
+        assert_eq!(
+            runners[0].build_command("testOnly", &["app.Spec".to_string()]),
+            vec!["sbt", "testOnly app.Spec"]
+        );
+
+        // Mill wins when both build files are present
+        File::create(dir.path().join("build.mill")).unwrap();
+        let names: Vec<_> = detect(&DirScan::new(dir.path()))
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        assert_eq!(names, vec!["mill", "sbt"]);
+    }
+}
@@ -0,0 +1,155 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Locations snakemake looks for its workflow, in order
+const SNAKEFILES: &[&str] = &["Snakefile", "workflow/Snakefile"];
+
+/// Validator for snakemake rules
+///
+/// Targets can also be output files, so anything that isn't a rule is
+/// `Unknown` rather than unsupported.
+pub struct SnakemakeValidator;
+
+impl CommandValidator for SnakemakeValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        let Some(content) = SNAKEFILES
+            .iter()
+            .find_map(|file| fs::read_to_string(working_dir.join(file)).ok())
+        else {
+            return CommandSupport::Unknown;
+        };
+
+        if extract_rules(&content).contains(command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::Unknown
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        SNAKEFILES
+            .iter()
+            .find_map(|file| fs::read_to_string(working_dir.join(file)).ok())
+            .map(|content| extract_rules(&content).into_iter().collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Named `rule` and `checkpoint` declarations in a Snakefile
+fn extract_rules(content: &str) -> HashSet<String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            line.strip_prefix("rule ")
+                .or_else(|| line.strip_prefix("checkpoint "))
+        })
+        .filter_map(|rest| rest.trim_end().strip_suffix(':'))
+        .map(str::trim)
+        .filter(|name| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Detect Snakemake workflows
+/// Priority: 35
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    let found = if scan.has(SNAKEFILES[0]) {
+        Some(SNAKEFILES[0])
+    } else if scan.has("workflow") && scan.path().join(SNAKEFILES[1]).is_file() {
+        Some(SNAKEFILES[1])
+    } else {
+        None
+    };
+    if let Some(file) = found {
+        let validator: Arc<dyn CommandValidator> = Arc::new(SnakemakeValidator);
+        runners.push(DetectedRunner::with_validator(
+            "snakemake",
+            file,
+            Ecosystem::Snakemake,
+            35,
+            validator,
+        ));
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_snakemake() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("workflow")).unwrap();
+        fs::write(dir.path().join("workflow/Snakefile"), "").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "snakemake");
+        assert_eq!(runners[0].detected_file, "workflow/Snakefile");
+        assert_eq!(
+            runners[0].build_command("all", &["--cores".to_string(), "4".to_string()]),
+            vec!["snakemake", "all", "--cores", "4"]
+        );
+
+        let empty = tempdir().unwrap();
+        assert!(detect(&DirScan::new(empty.path())).is_empty());
+    }
+
+    #[test]
+    fn test_snakemake_validator() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Snakefile"),
+            r#"configfile: "config.yaml"
+
+rule all:
+    input: "results/summary.txt"
+
+checkpoint split_samples:
+    output: directory("split")
+
+rule:
+    shell: "true"
+
+use rule all from other as other_all
+"#,
+        )
+        .unwrap();
+
+        let validator = SnakemakeValidator;
+        for rule in ["all", "split_samples"] {
+            assert_eq!(
+                validator.supports_command(dir.path(), rule),
+                CommandSupport::Supported,
+                "{}",
+                rule
+            );
+        }
+        assert_eq!(
+            validator.supports_command(dir.path(), "results/summary.txt"),
+            CommandSupport::Unknown
+        );
+    }
+}
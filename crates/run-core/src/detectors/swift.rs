@@ -9,16 +9,14 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{DetectedRunner, Ecosystem};
-use std::path::Path;
+use super::{DetectedRunner, DirScan, Ecosystem};
 
 /// Detect Swift Package Manager projects
 /// Priority: 19
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
-    let package_swift = dir.join("Package.swift");
-    if package_swift.exists() {
+    if scan.has("Package.swift") {
         runners.push(DetectedRunner::new(
             "swift",
             "Package.swift",
@@ -41,7 +39,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("Package.swift")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "swift");
     }
@@ -50,7 +48,7 @@ mod tests {
     fn test_no_swift() {
         let dir = tempdir().unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 }
@@ -0,0 +1,205 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::custom::CustomValidator;
+use super::{DetectedRunner, DirScan, Ecosystem};
+use crate::manifest;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Platform override key in tasks.json (`"linux": {...}`)
+const PLATFORM_KEY: &str = if cfg!(windows) {
+    "windows"
+} else if cfg!(target_os = "macos") {
+    "osx"
+} else {
+    "linux"
+};
+
+/// Command line for a VS Code task, or `None` if it can't run outside the editor
+fn task_command(task: &Value, workspace: &str) -> Option<String> {
+    // Platform-specific properties override the generic ones
+    let get = |key: &str| {
+        task.get(PLATFORM_KEY)
+            .and_then(|platform| platform.get(key))
+            .or_else(|| task.get(key))
+    };
+    let expand = |s: &str| s.replace("${workspaceFolder}", workspace);
+
+    let kind = get("type").and_then(Value::as_str).unwrap_or("process");
+    if kind == "npm" {
+        let script = get("script")?.as_str()?;
+        return Some(shell_words::join(["npm", "run", script]));
+    }
+
+    let command = expand(get("command")?.as_str()?);
+    let args: Vec<String> = get("args")
+        .and_then(Value::as_array)
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| {
+                    // Either "arg" or {"value": "arg", "quoting": "..."}
+                    arg.as_str().or_else(|| arg.get("value")?.as_str())
+                })
+                .map(expand)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    match kind {
+        "shell" => {
+            let mut line = command;
+            for arg in &args {
+                line.push(' ');
+                line.push_str(&shell_words::quote(arg));
+            }
+            Some(shell_invocation(&line))
+        }
+        "process" => Some(shell_words::join(std::iter::once(&command).chain(&args))),
+        _ => None,
+    }
+}
+
+/// Run `line` through the platform shell; extra arguments are passed along
+#[cfg(not(windows))]
+fn shell_invocation(line: &str) -> String {
+    shell_words::join(["sh", "-c", &format!("{} \"$@\"", line), "sh"])
+}
+
+#[cfg(windows)]
+fn shell_invocation(line: &str) -> String {
+    shell_words::join(["cmd", "/C", line])
+}
+
+/// Detect VS Code tasks (`.vscode/tasks.json`)
+/// Priority: 23 (after every build tool, so native commands win on shared names)
+///
+/// Each task label becomes a command running the task's `command` and `args`
+/// (`shell` tasks through the platform shell, `npm` tasks via `npm run`).
+/// Tasks that only exist in the editor (`dependsOn` groups, extension task
+/// types) are skipped.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    if !scan.has(".vscode") {
+        return vec![];
+    }
+    let Some(config) = manifest::jsonc(&scan.path().join(".vscode").join("tasks.json")) else {
+        return vec![];
+    };
+    let Some(tasks) = config.get("tasks").and_then(Value::as_array) else {
+        return vec![];
+    };
+
+    let workspace = scan.path().to_string_lossy();
+    let commands: HashMap<String, String> = tasks
+        .iter()
+        .filter_map(|task| {
+            let label = task.get("label")?.as_str()?;
+            Some((label.to_string(), task_command(task, &workspace)?))
+        })
+        .collect();
+
+    if commands.is_empty() {
+        return vec![];
+    }
+
+    vec![DetectedRunner::with_custom_commands(
+        "vscode",
+        ".vscode/tasks.json",
+        Ecosystem::Editor,
+        23,
+        Arc::new(CustomValidator::new(commands.clone())),
+        commands,
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detectors::CommandSupport;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn write_tasks(dir: &std::path::Path, content: &str) {
+        fs::create_dir_all(dir.join(".vscode")).unwrap();
+        fs::write(dir.join(".vscode").join("tasks.json"), content).unwrap();
+    }
+
+    #[test]
+    fn test_detect_vscode_tasks() {
+        let dir = tempdir().unwrap();
+        write_tasks(
+            dir.path(),
+            r#"{
+                // Comments are allowed in tasks.json
+                "version": "2.0.0",
+                "tasks": [
+                    {"label": "compile", "type": "process", "command": "cargo", "args": ["build", "--release"]},
+                    {"label": "lint", "type": "npm", "script": "lint"},
+                    {"label": "all", "dependsOn": ["compile", "lint"]}
+                ]
+            }"#,
+        );
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        let runner = &runners[0];
+        assert_eq!(runner.name, "vscode");
+        assert_eq!(runner.ecosystem, Ecosystem::Editor);
+
+        assert_eq!(
+            runner.build_command("compile", &["-v".to_string()]),
+            vec!["cargo", "build", "--release", "-v"]
+        );
+        assert_eq!(
+            runner.build_command("lint", &[]),
+            vec!["npm", "run", "lint"]
+        );
+        assert_eq!(
+            runner.supports_command("all", dir.path()),
+            CommandSupport::NotSupported
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_shell_task_runs_through_sh() {
+        let dir = tempdir().unwrap();
+        write_tasks(
+            dir.path(),
+            r#"{"tasks": [{
+                "label": "greet",
+                "type": "shell",
+                "command": "echo ${workspaceFolder} && echo",
+                "args": ["hello world"]
+            }]}"#,
+        );
+
+        let runners = detect(&DirScan::new(dir.path()));
+        let command = runners[0].build_command("greet", &["again".to_string()]);
+        assert_eq!(command[..2], ["sh", "-c"]);
+        assert_eq!(
+            command[2],
+            format!("echo {} && echo 'hello world' \"$@\"", dir.path().display())
+        );
+        assert_eq!(command[3..], ["sh", "again"]);
+    }
+
+    #[test]
+    fn test_no_tasks_json() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join(".vscode")).unwrap();
+        assert!(detect(&DirScan::new(dir.path())).is_empty());
+
+        write_tasks(dir.path(), r#"{"tasks": []}"#);
+        assert!(detect(&DirScan::new(dir.path())).is_empty());
+    }
+}
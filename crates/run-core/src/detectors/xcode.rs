@@ -0,0 +1,225 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+use super::{CommandSupport, CommandValidator, DetectedRunner, DirScan, Ecosystem};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Tuist manifest; the Xcode project next to it is generated from it
+pub const TUIST_FILE: &str = "Project.swift";
+
+/// Build actions `xcodebuild` accepts
+pub const XCODEBUILD_ACTIONS: &[&str] = &[
+    "build",
+    "build-for-testing",
+    "analyze",
+    "archive",
+    "test",
+    "test-without-building",
+    "docbuild",
+    "installsrc",
+    "install",
+    "clean",
+];
+
+/// Command line for `task`:
+/// `xcodebuild -workspace|-project <container> [-scheme <scheme>] <action>`
+pub fn command(container: &str, scheme: Option<&str>, task: &str, args: &[String]) -> Vec<String> {
+    let kind = if container.ends_with(".xcworkspace") {
+        "-workspace"
+    } else {
+        "-project"
+    };
+    let mut cmd = vec![
+        "xcodebuild".to_string(),
+        kind.to_string(),
+        container.to_string(),
+    ];
+    // A scheme given by the user replaces the detected one
+    if let Some(scheme) = scheme.filter(|_| !args.iter().any(|a| a == "-scheme")) {
+        cmd.push("-scheme".to_string());
+        cmd.push(scheme.to_string());
+    }
+    cmd.push(task.to_string());
+    cmd.extend(args.iter().cloned());
+    cmd
+}
+
+/// The workspace (preferred, as CocoaPods and SwiftPM setups need it) or
+/// project in the directory, first by name
+fn xcode_container(scan: &DirScan) -> Option<&str> {
+    [".xcworkspace", ".xcodeproj"]
+        .iter()
+        .find_map(|ext| scan.names().find(|name| name.ends_with(ext)))
+}
+
+/// First shared scheme of `container`, by name
+///
+/// Only shared schemes (`xcshareddata/xcschemes`) are committed, so they're
+/// the ones every checkout can build.
+fn shared_scheme(dir: &Path, container: &str) -> Option<String> {
+    let mut schemes: Vec<String> = fs::read_dir(dir.join(container).join("xcshareddata/xcschemes"))
+        .ok()?
+        .flatten()
+        .filter_map(|e| {
+            e.file_name()
+                .to_str()?
+                .strip_suffix(".xcscheme")
+                .map(str::to_string)
+        })
+        .collect();
+    schemes.sort();
+    schemes.into_iter().next()
+}
+
+/// Validator for `xcodebuild` actions
+pub struct XcodebuildValidator;
+
+impl CommandValidator for XcodebuildValidator {
+    fn supports_command(&self, _working_dir: &Path, command: &str) -> CommandSupport {
+        if XCODEBUILD_ACTIONS.contains(&command) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::NotSupported
+        }
+    }
+
+    fn list_commands(&self, _working_dir: &Path) -> Vec<String> {
+        XCODEBUILD_ACTIONS.iter().map(|a| a.to_string()).collect()
+    }
+}
+
+/// Detect Tuist and Xcode projects
+/// Priority: 34
+///
+/// A Tuist project wins over the Xcode project it generates.
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
+    let mut runners = Vec::new();
+
+    if scan.has(TUIST_FILE) {
+        runners.push(DetectedRunner::new(
+            "tuist",
+            TUIST_FILE,
+            Ecosystem::Xcode,
+            34,
+        ));
+    } else if let Some(container) = xcode_container(scan) {
+        let validator: Arc<dyn CommandValidator> = Arc::new(XcodebuildValidator);
+        let mut runner = DetectedRunner::with_validator(
+            "xcodebuild",
+            container,
+            Ecosystem::Xcode,
+            34,
+            validator,
+        );
+        // A workspace's schemes usually live in the projects it includes
+        let scheme = shared_scheme(scan.path(), container).or_else(|| {
+            scan.names()
+                .filter(|name| name.ends_with(".xcodeproj"))
+                .find_map(|project| shared_scheme(scan.path(), project))
+        });
+        if let Some(scheme) = scheme {
+            runner = runner.with_target(scheme);
+        }
+        runners.push(runner);
+    }
+
+    runners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_tuist() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(TUIST_FILE), "").unwrap();
+        fs::create_dir(dir.path().join("App.xcodeproj")).unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "tuist");
+        assert_eq!(runners[0].build_command("test", &[]), vec!["tuist", "test"]);
+    }
+
+    #[test]
+    fn test_detect_xcodebuild() {
+        let dir = tempdir().unwrap();
+        let schemes = dir.path().join("App.xcodeproj/xcshareddata/xcschemes");
+        fs::create_dir_all(&schemes).unwrap();
+        fs::write(schemes.join("AppTests.xcscheme"), "").unwrap();
+        fs::write(schemes.join("App.xcscheme"), "").unwrap();
+
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "xcodebuild");
+        assert_eq!(runners[0].detected_file, "App.xcodeproj");
+        assert_eq!(runners[0].target.as_deref(), Some("App"));
+        assert_eq!(
+            runners[0].build_command("build", &[]),
+            vec![
+                "xcodebuild",
+                "-project",
+                "App.xcodeproj",
+                "-scheme",
+                "App",
+                "build"
+            ]
+        );
+
+        // The workspace is preferred over the project, whose schemes it uses
+        fs::create_dir(dir.path().join("App.xcworkspace")).unwrap();
+        let runners = detect(&DirScan::new(dir.path()));
+        assert_eq!(runners[0].detected_file, "App.xcworkspace");
+        assert_eq!(runners[0].target.as_deref(), Some("App"));
+
+        let bare = tempdir().unwrap();
+        fs::create_dir(bare.path().join("App.xcworkspace")).unwrap();
+        assert_eq!(detect(&DirScan::new(bare.path()))[0].target, None);
+    }
+
+    #[test]
+    fn test_command() {
+        assert_eq!(
+            command("App.xcworkspace", None, "test", &[]),
+            vec!["xcodebuild", "-workspace", "App.xcworkspace", "test"]
+        );
+        let args = ["-scheme".to_string(), "Other".to_string()];
+        assert_eq!(
+            command("App.xcodeproj", Some("App"), "test", &args),
+            vec![
+                "xcodebuild",
+                "-project",
+                "App.xcodeproj",
+                "test",
+                "-scheme",
+                "Other"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_xcodebuild_validator() {
+        let dir = tempdir().unwrap();
+        let validator = XcodebuildValidator;
+        assert_eq!(
+            validator.supports_command(dir.path(), "archive"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "lint"),
+            CommandSupport::NotSupported
+        );
+    }
+}
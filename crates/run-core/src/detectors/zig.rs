@@ -9,16 +9,14 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
-use super::{DetectedRunner, Ecosystem};
-use std::path::Path;
+use super::{DetectedRunner, DirScan, Ecosystem};
 
 /// Detect Zig Build projects
 /// Priority: 20
-pub fn detect(dir: &Path) -> Vec<DetectedRunner> {
+pub fn detect(scan: &DirScan) -> Vec<DetectedRunner> {
     let mut runners = Vec::new();
 
-    let build_zig = dir.join("build.zig");
-    if build_zig.exists() {
+    if scan.has("build.zig") {
         runners.push(DetectedRunner::new("zig", "build.zig", Ecosystem::Zig, 20));
     }
 
@@ -36,7 +34,7 @@ mod tests {
         let dir = tempdir().unwrap();
         File::create(dir.path().join("build.zig")).unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert_eq!(runners.len(), 1);
         assert_eq!(runners[0].name, "zig");
     }
@@ -45,7 +43,7 @@ mod tests {
     fn test_no_zig() {
         let dir = tempdir().unwrap();
 
-        let runners = detect(dir.path());
+        let runners = detect(&DirScan::new(dir.path()));
         assert!(runners.is_empty());
     }
 }
@@ -17,14 +17,19 @@ pub mod exit_codes {
     pub const GENERIC_ERROR: i32 = 1;
     pub const RUNNER_NOT_FOUND: i32 = 2;
     pub const LOCKFILE_CONFLICT: i32 = 3;
+    pub const TIMEOUT: i32 = 124;
     pub const TOOL_NOT_INSTALLED: i32 = 127;
 }
 
+/// Errors produced while detecting, selecting or running a command
 #[derive(Error, Debug)]
 pub enum RunError {
     #[error("No runner found in {0} levels above the current directory")]
     RunnerNotFound(u8),
 
+    #[error("No runner found above the current directory, and several projects below it could run this: {0}. Use --cwd <dir> to pick one")]
+    AmbiguousProject(String),
+
     #[error("Lockfile conflict detected: {0}")]
     LockfileConflict(String),
 
@@ -45,14 +50,31 @@ pub enum RunError {
 
     #[error("Invalid argument: {0}")]
     InvalidArgument(String),
+
+    #[error("Command timed out after {0:?}")]
+    Timeout(std::time::Duration),
+
+    #[error("Symlink loop: {0}")]
+    SymlinkLoop(String),
+
+    #[error("Task '{0}' was not run: {1}")]
+    NotConfirmed(String, String),
+
+    #[error("Interrupted by signal {0}")]
+    Interrupted(i32),
 }
 
 impl RunError {
+    /// Process exit code associated with this error
     pub fn exit_code(&self) -> i32 {
         match self {
-            RunError::RunnerNotFound(_) => exit_codes::RUNNER_NOT_FOUND,
+            RunError::RunnerNotFound(_) | RunError::AmbiguousProject(_) => {
+                exit_codes::RUNNER_NOT_FOUND
+            }
             RunError::LockfileConflict(_) => exit_codes::LOCKFILE_CONFLICT,
             RunError::ToolNotInstalled(_) => exit_codes::TOOL_NOT_INSTALLED,
+            RunError::Timeout(_) => exit_codes::TIMEOUT,
+            RunError::Interrupted(signal) => 128 + signal,
             _ => exit_codes::GENERIC_ERROR,
         }
     }
@@ -0,0 +1,87 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Hashes that are kept on disk.
+//!
+//! `DefaultHasher` may change its algorithm in any Rust release, and the
+//! `Hash` impls of std types may change what they feed it, so a hash written
+//! by one build could mean nothing to the next. [`StableHasher`] is 64-bit
+//! FNV-1a over the bytes it is given, and nothing else.
+
+const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// 64-bit FNV-1a, the same on every platform and toolchain
+#[derive(Debug, Clone)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StableHasher {
+    pub fn new() -> Self {
+        StableHasher(OFFSET_BASIS)
+    }
+
+    /// Add `bytes`, then their length, so that consecutive fields can't
+    /// run together (`"ab", "c"` and `"a", "bc"` hash differently)
+    pub fn write(&mut self, bytes: &[u8]) {
+        self.bytes(bytes);
+        self.bytes(&(bytes.len() as u64).to_le_bytes());
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(PRIME);
+        }
+    }
+
+    pub fn finish(&self) -> u64 {
+        self.0
+    }
+
+    /// The hash as 16 hex digits, for file names and cache files
+    pub fn hex(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stable_hasher() {
+        // Published FNV-1a test vectors, before the length is added
+        let mut hasher = StableHasher::new();
+        assert_eq!(hasher.finish(), 0xcbf29ce484222325);
+        hasher.bytes(b"a");
+        assert_eq!(hasher.finish(), 0xaf63dc4c8601ec8c);
+        let mut hasher = StableHasher::new();
+        hasher.bytes(b"foobar");
+        assert_eq!(hasher.hex(), "85944171f73967e8");
+
+        let hash = |fields: &[&str]| {
+            let mut hasher = StableHasher::new();
+            for field in fields {
+                hasher.write(field.as_bytes());
+            }
+            hasher.finish()
+        };
+        assert_eq!(hash(&["ab", "c"]), hash(&["ab", "c"]));
+        assert_ne!(hash(&["ab", "c"]), hash(&["a", "bc"]));
+        assert_ne!(hash(&[""]), hash(&[]));
+    }
+}
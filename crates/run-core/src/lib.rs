@@ -0,0 +1,54 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! # run-core
+//!
+//! Detection, validation and command construction behind the `run` CLI.
+//!
+//! This crate has no terminal, argument parsing or network dependencies, so
+//! editor extensions and other tools can embed the same detection matrix:
+//!
+//! ```no_run
+//! use run_core::{detect_all, CommandSupport};
+//! use std::path::Path;
+//!
+//! let dir = Path::new(".");
+//! for runner in detect_all(dir, &[]) {
+//!     if runner.supports_command("test", dir) != CommandSupport::NotSupported {
+//!         println!("{}", runner.build_command("test", &[]).join(" "));
+//!         break;
+//!     }
+//! }
+//! ```
+//!
+//! ## Stability
+//!
+//! Items re-exported from the crate root follow semver. Individual detector
+//! modules (`detectors::node`, `detectors::python`, ...) are public for
+//! testing and advanced use, but their helpers may change in minor releases.
+
+pub mod detectors;
+pub mod error;
+pub mod hash;
+pub mod manifest;
+pub mod registry;
+pub mod report;
+pub mod tools;
+pub mod walk;
+pub mod workspace;
+
+pub use detectors::{
+    detect_all, detect_all_with, is_tool_installed, CommandSupport, CommandValidator,
+    DetectedRunner, Ecosystem, UnknownValidator,
+};
+pub use error::{exit_codes, RunError};
+pub use registry::{PatternValidator, ValidatorRegistry};
+pub use report::{detect_report, DetectionReport, RunnerReport};
@@ -0,0 +1,265 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Process-wide cache of parsed manifests.
+//!
+//! Detection, validation, conflict resolution and task listing all look at the
+//! same few files (`package.json`, `composer.json`, `pyproject.toml`,
+//! `moon.yml`, ...).
+//! Going through this module means each file is read and parsed at most once
+//! per process. Entries are keyed by path and revalidated against the file's
+//! modification time and size, so long-running hosts (`run serve`) still pick
+//! up edits.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Format {
+    Json,
+    Jsonc,
+    Toml,
+    Yaml,
+}
+
+#[derive(Clone)]
+enum Parsed {
+    Json(Arc<serde_json::Value>),
+    Toml(Arc<toml::Value>),
+    Yaml(Arc<serde_yaml::Value>),
+}
+
+/// Modification time and size, used to notice when a cached file changed
+type Stamp = (Option<SystemTime>, u64);
+
+struct Entry {
+    stamp: Stamp,
+    /// `None` when the file could not be read or parsed
+    value: Option<Parsed>,
+}
+
+type Cache = Mutex<HashMap<(PathBuf, Format), Entry>>;
+
+fn cache() -> &'static Cache {
+    static CACHE: OnceLock<Cache> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Parsed contents of a JSON file, or `None` if missing or invalid
+pub fn json(path: &Path) -> Option<Arc<serde_json::Value>> {
+    match load(path, Format::Json)? {
+        Parsed::Json(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Like [`json`], but also accepts `//` and `/* */` comments (deno.jsonc, tsconfig)
+pub fn jsonc(path: &Path) -> Option<Arc<serde_json::Value>> {
+    match load(path, Format::Jsonc)? {
+        Parsed::Json(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Parsed contents of a TOML file, or `None` if missing or invalid
+pub fn toml(path: &Path) -> Option<Arc<toml::Value>> {
+    match load(path, Format::Toml)? {
+        Parsed::Toml(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Parsed contents of a YAML file, or `None` if missing or invalid
+pub fn yaml(path: &Path) -> Option<Arc<serde_yaml::Value>> {
+    match load(path, Format::Yaml)? {
+        Parsed::Yaml(value) => Some(value),
+        _ => None,
+    }
+}
+
+/// Drop every cached entry
+pub fn clear() {
+    cache().lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+fn load(path: &Path, format: Format) -> Option<Parsed> {
+    let stamp = fs::metadata(path)
+        .ok()
+        .map(|m| (m.modified().ok(), m.len()))?;
+    let key = (path.to_path_buf(), format);
+
+    if let Some(entry) = cache().lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+        if entry.stamp == stamp {
+            return entry.value.clone();
+        }
+    }
+
+    // Parse outside the lock so slow files don't serialize other lookups
+    let value = fs::read_to_string(path)
+        .ok()
+        .and_then(|content| parse(&content, format));
+
+    cache().lock().unwrap_or_else(|e| e.into_inner()).insert(
+        key,
+        Entry {
+            stamp,
+            value: value.clone(),
+        },
+    );
+    value
+}
+
+fn parse(content: &str, format: Format) -> Option<Parsed> {
+    match format {
+        Format::Json => serde_json::from_str(content)
+            .ok()
+            .map(|v| Parsed::Json(Arc::new(v))),
+        Format::Jsonc => serde_json::from_str(content)
+            .or_else(|_| serde_json::from_str(&strip_jsonc_comments(content)))
+            .ok()
+            .map(|v| Parsed::Json(Arc::new(v))),
+        Format::Toml => toml::from_str(content)
+            .ok()
+            .map(|v| Parsed::Toml(Arc::new(v))),
+        Format::Yaml => serde_yaml::from_str(content)
+            .ok()
+            .map(|v| Parsed::Yaml(Arc::new(v))),
+    }
+}
+
+/// Simple JSONC comment stripper
+pub(crate) fn strip_jsonc_comments(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+    let mut escape = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            result.push(c);
+            if escape {
+                escape = false;
+            } else if c == '\\' {
+                escape = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+        } else {
+            match c {
+                '"' => {
+                    result.push(c);
+                    in_string = true;
+                }
+                '/' => {
+                    if let Some(&next) = chars.peek() {
+                        if next == '/' {
+                            // Line comment
+                            chars.next(); // Consume second slash
+                            for c in chars.by_ref() {
+                                if c == '\n' {
+                                    result.push(c); // Keep newline
+                                    break;
+                                }
+                            }
+                        } else if next == '*' {
+                            // Block comment
+                            chars.next(); // Consume asterisk
+                            while let Some(c) = chars.next() {
+                                if c == '*' {
+                                    if let Some(&next) = chars.peek() {
+                                        if next == '/' {
+                                            chars.next(); // Consume slash
+                                            break;
+                                        }
+                                    }
+                                }
+                            }
+                        } else {
+                            result.push(c);
+                        }
+                    } else {
+                        result.push(c);
+                    }
+                }
+                _ => result.push(c),
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_json_parsed_once() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("package.json");
+        fs::write(&path, r#"{"scripts": {"test": "jest"}}"#).unwrap();
+
+        let first = json(&path).unwrap();
+        let second = json(&path).unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first["scripts"]["test"], "jest");
+    }
+
+    #[test]
+    fn test_changed_file_is_reparsed() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("package.json");
+        fs::write(&path, r#"{"name": "a"}"#).unwrap();
+        assert_eq!(json(&path).unwrap()["name"], "a");
+
+        fs::write(&path, r#"{"name": "longer"}"#).unwrap();
+        assert_eq!(json(&path).unwrap()["name"], "longer");
+    }
+
+    #[test]
+    fn test_missing_and_invalid() {
+        let dir = tempdir().unwrap();
+        assert!(json(&dir.path().join("missing.json")).is_none());
+
+        let path = dir.path().join("broken.json");
+        fs::write(&path, "{ not json").unwrap();
+        assert!(json(&path).is_none());
+        assert!(json(&path).is_none());
+    }
+
+    #[test]
+    fn test_jsonc_and_toml() {
+        let dir = tempdir().unwrap();
+        let deno = dir.path().join("deno.jsonc");
+        fs::write(
+            &deno,
+            "{\n  // tasks\n  \"tasks\": {\"dev\": \"deno run\"}\n}",
+        )
+        .unwrap();
+        assert!(json(&deno).is_none());
+        assert_eq!(jsonc(&deno).unwrap()["tasks"]["dev"], "deno run");
+
+        let pyproject = dir.path().join("pyproject.toml");
+        fs::write(&pyproject, "[project]\nname = \"x\"").unwrap();
+        assert_eq!(
+            toml(&pyproject).unwrap()["project"]["name"].as_str(),
+            Some("x")
+        );
+
+        let moon = dir.path().join("moon.yml");
+        fs::write(&moon, "tasks:\n  build:\n    command: cargo build\n").unwrap();
+        assert!(yaml(&moon).unwrap()["tasks"].get("build").is_some());
+        assert!(toml(&moon).is_none());
+    }
+}
@@ -0,0 +1,275 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Runtime registration of additional command validators.
+//!
+//! Registered validators are layered on top of a runner's built-in validator.
+//! Precedence when checking a command:
+//!
+//! 1. Custom commands (run.toml `[commands]`) are always supported
+//! 2. Registered validators, in registration order; the first answer other
+//!    than `Unknown` wins
+//! 3. The built-in validator of the detector
+
+use crate::detectors::{CommandSupport, CommandValidator, DetectedRunner, TaskDefinition};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Validators registered per runner name
+#[derive(Clone, Default)]
+pub struct ValidatorRegistry {
+    validators: HashMap<String, Vec<Arc<dyn CommandValidator>>>,
+}
+
+impl ValidatorRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a validator for a runner (e.g. "npm"), after any already registered
+    pub fn register(&mut self, runner: &str, validator: Arc<dyn CommandValidator>) {
+        self.validators
+            .entry(runner.to_ascii_lowercase())
+            .or_default()
+            .push(validator);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.validators.is_empty()
+    }
+
+    /// Layer the registered validators onto matching runners
+    pub fn apply(&self, runners: &mut [DetectedRunner]) {
+        for runner in runners {
+            if let Some(validators) = self.validators.get(&runner.name.to_ascii_lowercase()) {
+                for validator in validators.iter().rev() {
+                    runner.layer_validator(Arc::clone(validator));
+                }
+            }
+        }
+    }
+}
+
+/// Validator that consults `layer` first and falls back to `base` on `Unknown`
+pub struct LayeredValidator {
+    layer: Arc<dyn CommandValidator>,
+    base: Arc<dyn CommandValidator>,
+}
+
+impl LayeredValidator {
+    pub fn new(layer: Arc<dyn CommandValidator>, base: Arc<dyn CommandValidator>) -> Self {
+        Self { layer, base }
+    }
+}
+
+impl CommandValidator for LayeredValidator {
+    fn supports_command(&self, working_dir: &Path, command: &str) -> CommandSupport {
+        match self.layer.supports_command(working_dir, command) {
+            CommandSupport::Unknown => self.base.supports_command(working_dir, command),
+            answer => answer,
+        }
+    }
+
+    fn list_commands(&self, working_dir: &Path) -> Vec<String> {
+        let mut commands = self.layer.list_commands(working_dir);
+        commands.extend(self.base.list_commands(working_dir));
+        commands
+    }
+
+    fn definition(&self, working_dir: &Path, command: &str) -> Option<TaskDefinition> {
+        self.layer
+            .definition(working_dir, command)
+            .or_else(|| self.base.definition(working_dir, command))
+    }
+}
+
+/// Validator driven by glob-like task name patterns (`*` matches any text).
+///
+/// `unsupported` patterns are checked before `supported` ones; commands
+/// matching neither are `Unknown`.
+#[derive(Debug, Clone, Default)]
+pub struct PatternValidator {
+    supported: Vec<String>,
+    unsupported: Vec<String>,
+}
+
+impl PatternValidator {
+    pub fn new(supported: Vec<String>, unsupported: Vec<String>) -> Self {
+        Self {
+            supported,
+            unsupported,
+        }
+    }
+}
+
+impl CommandValidator for PatternValidator {
+    fn supports_command(&self, _working_dir: &Path, command: &str) -> CommandSupport {
+        if self.unsupported.iter().any(|p| matches_pattern(p, command)) {
+            CommandSupport::NotSupported
+        } else if self.supported.iter().any(|p| matches_pattern(p, command)) {
+            CommandSupport::Supported
+        } else {
+            CommandSupport::Unknown
+        }
+    }
+}
+
+/// Match `text` against a pattern where `*` matches any sequence of characters
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || text.len() < first.len() + last.len() {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(idx) => rest = &rest[idx + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::detectors::Ecosystem;
+    use tempfile::tempdir;
+
+    struct Fixed(CommandSupport);
+
+    impl CommandValidator for Fixed {
+        fn supports_command(&self, _working_dir: &Path, _command: &str) -> CommandSupport {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("deploy", "deploy"));
+        assert!(!matches_pattern("deploy", "deploy:prod"));
+        assert!(matches_pattern("deploy:*", "deploy:prod"));
+        assert!(matches_pattern("*:ci", "test:ci"));
+        assert!(matches_pattern("a*b*c", "a-x-b-y-c"));
+        assert!(!matches_pattern("a*b*c", "a-x-c"));
+        assert!(!matches_pattern("ab*ba", "aba"));
+        assert!(matches_pattern("*", "anything"));
+    }
+
+    #[test]
+    fn test_pattern_validator() {
+        let dir = tempdir().unwrap();
+        let validator =
+            PatternValidator::new(vec!["ci:*".to_string()], vec!["ci:internal".to_string()]);
+        assert_eq!(
+            validator.supports_command(dir.path(), "ci:lint"),
+            CommandSupport::Supported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "ci:internal"),
+            CommandSupport::NotSupported
+        );
+        assert_eq!(
+            validator.supports_command(dir.path(), "build"),
+            CommandSupport::Unknown
+        );
+    }
+
+    #[test]
+    fn test_registry_precedence() {
+        let dir = tempdir().unwrap();
+        let mut registry = ValidatorRegistry::new();
+        registry.register("npm", Arc::new(Fixed(CommandSupport::Unknown)));
+        registry.register("NPM", Arc::new(Fixed(CommandSupport::Supported)));
+        registry.register("npm", Arc::new(Fixed(CommandSupport::NotSupported)));
+
+        let mut runners = vec![
+            DetectedRunner::with_validator(
+                "npm",
+                "package.json",
+                Ecosystem::NodeJs,
+                4,
+                Arc::new(Fixed(CommandSupport::NotSupported)),
+            ),
+            DetectedRunner::new("make", "Makefile", Ecosystem::Generic, 21),
+        ];
+        registry.apply(&mut runners);
+
+        // The first registered validator with an answer wins
+        assert_eq!(
+            runners[0].supports_command("build", dir.path()),
+            CommandSupport::Supported
+        );
+        // Runners without registered validators keep their built-in behaviour
+        assert_eq!(
+            runners[1].supports_command("build", dir.path()),
+            CommandSupport::Unknown
+        );
+    }
+
+    #[test]
+    fn test_layered_list_commands() {
+        struct Listed(&'static [&'static str]);
+        impl CommandValidator for Listed {
+            fn supports_command(&self, _: &Path, _: &str) -> CommandSupport {
+                CommandSupport::Unknown
+            }
+            fn list_commands(&self, _: &Path) -> Vec<String> {
+                self.0.iter().map(|c| c.to_string()).collect()
+            }
+        }
+
+        let dir = tempdir().unwrap();
+        let mut registry = ValidatorRegistry::new();
+        registry.register("make", Arc::new(Listed(&["lint", "build"])));
+        let mut runners = vec![DetectedRunner::with_validator(
+            "make",
+            "Makefile",
+            Ecosystem::Generic,
+            21,
+            Arc::new(Listed(&["build", "test"])),
+        )];
+        registry.apply(&mut runners);
+
+        assert_eq!(
+            runners[0].list_commands(dir.path()),
+            vec!["build", "lint", "test"]
+        );
+    }
+
+    #[test]
+    fn test_registry_falls_back_to_builtin() {
+        let dir = tempdir().unwrap();
+        let mut registry = ValidatorRegistry::new();
+        registry.register("npm", Arc::new(Fixed(CommandSupport::Unknown)));
+
+        let mut runners = vec![DetectedRunner::with_validator(
+            "npm",
+            "package.json",
+            Ecosystem::NodeJs,
+            4,
+            Arc::new(Fixed(CommandSupport::NotSupported)),
+        )];
+        registry.apply(&mut runners);
+
+        assert_eq!(
+            runners[0].supports_command("build", dir.path()),
+            CommandSupport::NotSupported
+        );
+    }
+}
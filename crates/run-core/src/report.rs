@@ -0,0 +1,92 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Serializable detection report.
+
+use crate::detectors::{detect_all, DetectedRunner};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+/// Everything detected in a directory, ready to be serialized
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectionReport {
+    /// Version of run-core that produced the report
+    pub version: &'static str,
+    /// Directory that was inspected
+    pub dir: PathBuf,
+    /// Detected runners, highest priority first
+    pub runners: Vec<RunnerReport>,
+}
+
+/// A detected runner together with its installation status
+#[derive(Debug, Clone, Serialize)]
+pub struct RunnerReport {
+    #[serde(flatten)]
+    pub runner: DetectedRunner,
+    /// Whether the runner's tool was found on PATH
+    pub installed: bool,
+}
+
+impl DetectionReport {
+    /// Highest priority runner, if any was detected
+    pub fn primary(&self) -> Option<&DetectedRunner> {
+        self.runners.first().map(|r| &r.runner)
+    }
+}
+
+/// Detect all runners in `dir` and build a serializable report
+pub fn detect_report(dir: &Path) -> DetectionReport {
+    let runners = detect_all(dir, &[])
+        .into_iter()
+        .map(|runner| RunnerReport {
+            installed: runner.is_installed(),
+            runner,
+        })
+        .collect();
+
+    DetectionReport {
+        version: env!("CARGO_PKG_VERSION"),
+        dir: dir.to_path_buf(),
+        runners,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_detect_report_json() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Makefile"), "build:\n\techo build\n").unwrap();
+        fs::write(dir.path().join("package.json"), "{}").unwrap();
+
+        let report = detect_report(dir.path());
+        assert_eq!(report.primary().unwrap().name, "npm");
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(json["runners"][0]["name"], "npm");
+        assert_eq!(json["runners"][0]["ecosystem"], "Node.js");
+        assert_eq!(json["runners"][1]["name"], "make");
+        assert!(json["runners"][1]["installed"].is_boolean());
+    }
+
+    #[test]
+    fn test_detect_report_empty() {
+        let dir = tempdir().unwrap();
+        let report = detect_report(dir.path());
+        assert!(report.runners.is_empty());
+        assert!(report.primary().is_none());
+    }
+}
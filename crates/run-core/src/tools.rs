@@ -0,0 +1,336 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Memoized tool lookups.
+//!
+//! Conflict resolution, the pre-execution tool check and detection reports all
+//! ask `which` about the same few tools, and every lookup scans each `PATH`
+//! entry. Results are memoized for the lifetime of the process and dropped as
+//! soon as `PATH` changes.
+//!
+//! [`enable_persistent_cache`] additionally keeps results on disk across
+//! invocations. That file is keyed by a hash of `PATH` and the modification
+//! times of its directories, so installing or removing a tool invalidates it,
+//! and by [`CACHE_VERSION`], so a file in another format is ignored.
+
+use crate::hash::StableHasher;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::UNIX_EPOCH;
+
+/// Format of the persistent cache file; files of another version are ignored
+const CACHE_VERSION: u32 = 2;
+
+#[derive(Default)]
+struct ToolCache {
+    /// `PATH` the memoized entries were resolved against
+    path_var: Option<OsString>,
+    tools: HashMap<String, Option<PathBuf>>,
+    persistent: Option<PathBuf>,
+    /// Whether `tools` was seeded from the persistent file
+    loaded: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheFile {
+    version: u32,
+    path_hash: String,
+    tools: HashMap<String, Option<PathBuf>>,
+}
+
+fn cache() -> &'static Mutex<ToolCache> {
+    static CACHE: OnceLock<Mutex<ToolCache>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Full path of `tool` on `PATH`, memoized
+pub fn find_tool(tool: &str) -> Option<PathBuf> {
+    let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+
+    let path_var = env::var_os("PATH");
+    if cache.path_var != path_var {
+        cache.path_var = path_var;
+        cache.tools.clear();
+        cache.loaded = false;
+    }
+    if !cache.loaded {
+        cache.loaded = true;
+        if let Some(file) = cache.persistent.clone() {
+            cache.tools = read_cache_file(&file).unwrap_or_default();
+        }
+    }
+
+    if let Some(found) = cache.tools.get(tool) {
+        return found.clone();
+    }
+
+    let found = which::which(tool).ok();
+    cache.tools.insert(tool.to_string(), found.clone());
+    if let Some(file) = &cache.persistent {
+        write_cache_file(file, &cache.tools);
+    }
+    found
+}
+
+/// Also keep lookups in `file`, so later invocations skip the `PATH` scan
+pub fn enable_persistent_cache(file: PathBuf) {
+    let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache.persistent = Some(file);
+    cache.loaded = false;
+}
+
+/// Forget every memoized lookup (the persistent file is left alone)
+pub fn clear() {
+    let mut cache = cache().lock().unwrap_or_else(|e| e.into_inner());
+    cache.tools.clear();
+    cache.loaded = false;
+}
+
+/// Command line to spawn for `parts`
+///
+/// `CreateProcess` only looks for `.exe` files on Windows and cannot start
+/// script shims (`npm.cmd`, `gradlew.bat`, `pnpm.ps1`) by bare name. There the
+/// program is first resolved to its full path through `PATHEXT`; batch files
+/// are then started by the standard library, which quotes their arguments for
+/// `cmd.exe`, and PowerShell scripts go through `powershell -File`. Elsewhere
+/// `parts` is returned unchanged.
+pub fn spawn_command(parts: &[String], cwd: &Path) -> Vec<String> {
+    if cfg!(windows) {
+        windows_command(parts, env::var_os("PATH"), cwd)
+    } else {
+        parts.to_vec()
+    }
+}
+
+fn windows_command(parts: &[String], path_var: Option<OsString>, cwd: &Path) -> Vec<String> {
+    let Some((program, args)) = parts.split_first() else {
+        return Vec::new();
+    };
+    // PATHEXT rarely lists .PS1, so PowerShell-only shims are looked up explicitly
+    let Ok(resolved) = which::which_in(program, path_var.clone(), cwd)
+        .or_else(|_| which::which_in(format!("{}.ps1", program), path_var, cwd))
+    else {
+        // Let the spawn fail with the usual "not found" error
+        return parts.to_vec();
+    };
+
+    let resolved = resolved.to_string_lossy().into_owned();
+    let is_powershell = Path::new(&resolved)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("ps1"));
+    let mut command = if is_powershell {
+        [
+            "powershell",
+            "-NoProfile",
+            "-ExecutionPolicy",
+            "Bypass",
+            "-File",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .chain(std::iter::once(resolved))
+        .collect()
+    } else {
+        vec![resolved]
+    };
+    command.extend(args.iter().cloned());
+    command
+}
+
+/// Hash of `PATH` and the modification time of each of its directories
+fn path_hash() -> String {
+    let mut hasher = StableHasher::new();
+    if let Some(path_var) = env::var_os("PATH") {
+        for dir in env::split_paths(&path_var) {
+            hasher.write(dir.as_os_str().as_encoded_bytes());
+            let modified = fs::metadata(&dir)
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok());
+            // Nothing at all for a directory that doesn't exist
+            hasher
+                .write(&modified.map_or(Vec::new(), |time| time.as_nanos().to_le_bytes().to_vec()));
+        }
+    }
+    hasher.hex()
+}
+
+fn read_cache_file(file: &Path) -> Option<HashMap<String, Option<PathBuf>>> {
+    let content = fs::read_to_string(file).ok()?;
+    let cached: CacheFile = serde_json::from_str(&content).ok()?;
+    if cached.version != CACHE_VERSION || cached.path_hash != path_hash() {
+        return None;
+    }
+    Some(cached.tools)
+}
+
+fn write_cache_file(file: &Path, tools: &HashMap<String, Option<PathBuf>>) {
+    let cached = CacheFile {
+        version: CACHE_VERSION,
+        path_hash: path_hash(),
+        tools: tools.clone(),
+    };
+    if let Some(parent) = file.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Ok(json) = serde_json::to_string(&cached) {
+        let _ = fs::write(file, json);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_tool_is_memoized() {
+        let first = find_tool("definitely-not-a-real-tool-xyz");
+        assert!(first.is_none());
+        assert!(cache()
+            .lock()
+            .unwrap()
+            .tools
+            .contains_key("definitely-not-a-real-tool-xyz"));
+        assert_eq!(find_tool("definitely-not-a-real-tool-xyz"), first);
+    }
+
+    #[test]
+    fn test_cache_file_roundtrip_and_invalidation() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("which.json");
+
+        let mut tools = HashMap::new();
+        tools.insert("npm".to_string(), Some(PathBuf::from("/usr/bin/npm")));
+        tools.insert("yarn".to_string(), None);
+        write_cache_file(&file, &tools);
+        assert_eq!(read_cache_file(&file), Some(tools.clone()));
+
+        // A file written for a different PATH is ignored
+        let stale = CacheFile {
+            version: CACHE_VERSION,
+            path_hash: "0000000000000000".to_string(),
+            tools: tools.clone(),
+        };
+        fs::write(&file, serde_json::to_string(&stale).unwrap()).unwrap();
+        assert_eq!(read_cache_file(&file), None);
+
+        // So is one in another format, or from before formats were versioned
+        let old = CacheFile {
+            version: CACHE_VERSION - 1,
+            path_hash: path_hash(),
+            tools: tools.clone(),
+        };
+        fs::write(&file, serde_json::to_string(&old).unwrap()).unwrap();
+        assert_eq!(read_cache_file(&file), None);
+        let unversioned = serde_json::json!({ "path_hash": path_hash(), "tools": tools });
+        fs::write(&file, unversioned.to_string()).unwrap();
+        assert_eq!(read_cache_file(&file), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_powershell_shim() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("pnpm.ps1");
+        fs::write(&script, "").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let parts = vec!["pnpm".to_string(), "install".to_string()];
+        let command = windows_command(&parts, Some(dir.path().into()), dir.path());
+        assert_eq!(
+            command,
+            vec![
+                "powershell",
+                "-NoProfile",
+                "-ExecutionPolicy",
+                "Bypass",
+                "-File",
+                &script.to_string_lossy(),
+                "install",
+            ]
+        );
+
+        // Unknown programs are left for the spawn to report
+        let parts = vec!["not-a-tool".to_string()];
+        assert_eq!(
+            windows_command(&parts, Some(dir.path().into()), dir.path()),
+            parts
+        );
+        assert_eq!(spawn_command(&parts, dir.path()), parts);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_batch_shims() {
+        let dir = tempdir().unwrap();
+        let bin = dir.path().join("bin");
+        fs::create_dir(&bin).unwrap();
+        fs::write(bin.join("npm.cmd"), "@echo off\r\n").unwrap();
+        fs::write(dir.path().join("gradlew.bat"), "@echo off\r\n").unwrap();
+
+        let command = windows_command(
+            &["npm".to_string(), "run".to_string(), "a b".to_string()],
+            Some(bin.clone().into()),
+            dir.path(),
+        );
+        assert_eq!(
+            command,
+            vec![
+                bin.join("npm.cmd").to_string_lossy().into_owned(),
+                "run".to_string(),
+                "a b".to_string(),
+            ]
+        );
+
+        // Relative wrappers resolve against the working directory
+        let command = windows_command(
+            &[".\\gradlew".to_string(), "build".to_string()],
+            Some(bin.into()),
+            dir.path(),
+        );
+        assert!(command[0].to_lowercase().ends_with("gradlew.bat"));
+        assert_eq!(command[1], "build");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_batch_shim_arguments_are_quoted() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("echo-args.cmd"), "@echo %*\r\n").unwrap();
+
+        let command = windows_command(
+            &[
+                "echo-args".to_string(),
+                "a b".to_string(),
+                "c&d".to_string(),
+            ],
+            Some(dir.path().into()),
+            dir.path(),
+        );
+        let output = std::process::Command::new(&command[0])
+            .args(&command[1..])
+            .output()
+            .unwrap();
+        // Spaces and metacharacters survive cmd.exe instead of being interpreted
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout).trim(),
+            "\"a b\" \"c&d\""
+        );
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Directory scans and symlink handling.
+//!
+//! Detection itself only looks upwards, but features that look below a
+//! directory (workspace members, listing every project's tasks, watching)
+//! must not wander into dependency and build output trees. Those directories
+//! are huge and never contain the project's own tasks.
+//!
+//! The upward search starts from [`search_start`], which decides whether a
+//! symlinked project is searched from its target or from where it was
+//! reached (pnpm and Nix both link projects around).
+
+use crate::error::RunError;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Links followed before a chain is considered a loop (Linux's own limit)
+const MAX_LINK_HOPS: usize = 40;
+
+/// Directory names skipped by default
+pub const DEFAULT_SKIP_DIRS: &[&str] = &["node_modules", "vendor", "target", ".venv", ".git"];
+
+/// [`DEFAULT_SKIP_DIRS`] as owned strings, for configs that don't override it
+pub fn default_skip_dirs() -> Vec<String> {
+    DEFAULT_SKIP_DIRS.iter().map(|s| s.to_string()).collect()
+}
+
+/// Whether any component of `path` below `root` is a skipped directory
+pub fn is_skipped(path: &Path, root: &Path, skip: &[String]) -> bool {
+    path.strip_prefix(root).is_ok_and(|relative| {
+        relative
+            .components()
+            .any(|c| skip.iter().any(|s| c.as_os_str() == s.as_str()))
+    })
+}
+
+/// Directories below `root`, up to `max_depth` levels deep, sorted
+///
+/// Skipped directories are not entered, and symlinks are not followed.
+pub fn subdirectories(root: &Path, max_depth: usize, skip: &[String]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    let mut level = vec![root.to_path_buf()];
+
+    for _ in 0..max_depth {
+        let mut next = Vec::new();
+        for dir in &level {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+                let name = entry.file_name();
+                if is_dir && !skip.iter().any(|s| name == s.as_str()) {
+                    next.push(entry.path());
+                }
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+        found.extend(next.iter().cloned());
+        level = next;
+    }
+
+    found.sort();
+    found
+}
+
+/// Directory to search upwards from
+///
+/// With `follow_symlinks`, symlinks in `dir` are resolved, so the search
+/// walks the real parents of the project. Otherwise the path is kept as the
+/// user reached it: `$PWD` when it names the same directory (the OS reports
+/// the resolved working directory), else `dir` itself.
+pub fn search_start(dir: &Path, follow_symlinks: bool) -> Result<PathBuf, RunError> {
+    let resolved = resolve(dir)?;
+    if follow_symlinks {
+        return Ok(resolved);
+    }
+    let logical = env::var_os("PWD")
+        .map(PathBuf::from)
+        .filter(|pwd| pwd.is_absolute() && pwd.canonicalize().is_ok_and(|p| p == resolved));
+    Ok(logical.unwrap_or_else(|| dir.to_path_buf()))
+}
+
+/// `path` with every symlink resolved; a loop is reported with its chain
+pub fn resolve(path: &Path) -> Result<PathBuf, RunError> {
+    path.canonicalize().map_err(|e| match symlink_loop(path) {
+        Some(chain) => RunError::SymlinkLoop(
+            chain
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> "),
+        ),
+        None => RunError::IoError(e),
+    })
+}
+
+/// The chain of links that leads back into itself when resolving `path` or
+/// one of its ancestors, if any
+pub fn symlink_loop(path: &Path) -> Option<Vec<PathBuf>> {
+    path.ancestors().find_map(|start| {
+        let mut chain = vec![start.to_path_buf()];
+        let mut seen = HashSet::from([start.to_path_buf()]);
+        let mut current = start.to_path_buf();
+        while let Ok(target) = fs::read_link(&current) {
+            let next = match current.parent() {
+                Some(parent) if target.is_relative() => parent.join(target),
+                _ => target,
+            };
+            chain.push(next.clone());
+            if !seen.insert(next.clone()) || chain.len() > MAX_LINK_HOPS {
+                return Some(chain);
+            }
+            current = next;
+        }
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_subdirectories_skip_dependencies() {
+        let dir = tempdir().unwrap();
+        for sub in [
+            "packages/app/src",
+            "packages/app/node_modules/dep",
+            "target/debug",
+            ".git/objects",
+            "crates/core",
+        ] {
+            fs::create_dir_all(dir.path().join(sub)).unwrap();
+        }
+
+        let found: Vec<PathBuf> = subdirectories(dir.path(), 2, &default_skip_dirs())
+            .into_iter()
+            .map(|p| p.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            found,
+            ["crates", "crates/core", "packages", "packages/app"]
+                .iter()
+                .map(PathBuf::from)
+                .collect::<Vec<_>>()
+        );
+
+        // A custom list replaces the defaults
+        let found = subdirectories(dir.path(), 1, &["crates".to_string()]);
+        assert!(found.contains(&dir.path().join("target")));
+        assert!(!found.contains(&dir.path().join("crates")));
+    }
+
+    #[test]
+    fn test_is_skipped() {
+        let root = Path::new("/repo");
+        let skip = default_skip_dirs();
+        assert!(is_skipped(
+            Path::new("/repo/web/node_modules/x/package.json"),
+            root,
+            &skip
+        ));
+        assert!(is_skipped(Path::new("/repo/.git/index"), root, &skip));
+        assert!(!is_skipped(
+            Path::new("/repo/web/package.json"),
+            root,
+            &skip
+        ));
+        // Only components below the root count
+        assert!(!is_skipped(
+            Path::new("/vendor/repo/Makefile"),
+            Path::new("/vendor/repo"),
+            &skip
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loops() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        symlink(root.join("b"), root.join("a")).unwrap();
+        symlink("a", root.join("b")).unwrap();
+
+        assert_eq!(
+            symlink_loop(&root.join("a").join("project")),
+            Some(vec![root.join("a"), root.join("b"), root.join("a")])
+        );
+        let err = resolve(&root.join("a")).unwrap_err();
+        assert!(matches!(err, RunError::SymlinkLoop(_)));
+        assert!(err.to_string().contains(" -> "));
+
+        fs::create_dir(root.join("real")).unwrap();
+        symlink(root.join("real"), root.join("link")).unwrap();
+        assert_eq!(symlink_loop(&root.join("link")), None);
+        assert_eq!(resolve(&root.join("link")).unwrap(), root.join("real"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_start() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("real")).unwrap();
+        symlink(root.join("real"), root.join("link")).unwrap();
+
+        let link = root.join("link");
+        assert_eq!(search_start(&link, true).unwrap(), root.join("real"));
+        // Not following keeps the path as given ($PWD names another directory)
+        assert_eq!(search_start(&link, false).unwrap(), link);
+    }
+}
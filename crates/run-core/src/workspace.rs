@@ -0,0 +1,399 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Workspace member targeting for Cargo and Go workspaces.
+//!
+//! Inside a workspace member, package-selecting commands are scoped to that
+//! member explicitly: `cargo test -p <crate>`, or `go test ./<dir>/...` for
+//! the module (or the part of it) `run` was invoked from. With
+//! [`Scope::Workspace`] they cover every member instead.
+
+use crate::manifest;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Cargo subcommands that accept `-p`
+const CARGO_MEMBER_COMMANDS: &[&str] = &[
+    "build", "b", "check", "c", "test", "t", "bench", "run", "r", "clippy", "doc", "d", "fix",
+    "tree",
+];
+
+/// Cargo subcommands that accept `--workspace`
+const CARGO_WORKSPACE_COMMANDS: &[&str] = &[
+    "build", "b", "check", "c", "test", "t", "bench", "clippy", "doc", "d", "fix", "tree",
+];
+
+/// Go subcommands that take package patterns
+const GO_PACKAGE_COMMANDS: &[&str] = &["build", "test", "vet", "install", "generate", "list"];
+
+/// Which packages a command covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// The workspace member containing the invocation directory
+    Member,
+    /// Every member of the workspace (`run --workspace`)
+    Workspace,
+}
+
+/// Add the member or workspace selection to `command` (as built for `task`)
+///
+/// `working_dir` is where the runner was detected and `invocation_dir` where
+/// `run` was started. Nothing is added outside a workspace, for commands that
+/// don't select packages, or when `user_args` already select packages.
+pub fn apply_scope(
+    command: &mut Vec<String>,
+    runner: &str,
+    task: &str,
+    user_args: &[String],
+    working_dir: &Path,
+    invocation_dir: &Path,
+    scope: Scope,
+) {
+    match runner {
+        "cargo" => {
+            let selects = user_args.iter().any(|arg| {
+                ["-p", "--package", "--workspace", "--all", "--manifest-path"]
+                    .iter()
+                    .any(|flag| arg == flag || arg.starts_with(&format!("{}=", flag)))
+            });
+            // Only plain `cargo <task> ...` commands are scoped
+            if selects || command.get(1).map(String::as_str) != Some(task) {
+                return;
+            }
+            if let Some(args) = cargo_scope(task, working_dir, scope) {
+                command.splice(2..2, args);
+            }
+        }
+        "go" if user_args.is_empty() && GO_PACKAGE_COMMANDS.contains(&task) => {
+            command.extend(go_scope(working_dir, invocation_dir, scope).unwrap_or_default());
+        }
+        _ => {}
+    }
+}
+
+/// `-p <crate>` for a workspace member, or `--workspace`
+fn cargo_scope(task: &str, working_dir: &Path, scope: Scope) -> Option<Vec<String>> {
+    let root = cargo_workspace_root(working_dir)?;
+    match scope {
+        Scope::Member if CARGO_MEMBER_COMMANDS.contains(&task) && root != working_dir => {
+            let cargo = manifest::toml(&working_dir.join("Cargo.toml"))?;
+            let name = cargo.get("package")?.get("name")?.as_str()?;
+            Some(vec!["-p".to_string(), name.to_string()])
+        }
+        Scope::Workspace if CARGO_WORKSPACE_COMMANDS.contains(&task) => {
+            Some(vec!["--workspace".to_string()])
+        }
+        _ => None,
+    }
+}
+
+/// Root of the cargo workspace `dir`'s package belongs to (possibly `dir`
+/// itself)
+pub fn cargo_workspace_root(dir: &Path) -> Option<PathBuf> {
+    let cargo = manifest::toml(&dir.join("Cargo.toml"))?;
+    if cargo.get("workspace").is_some() {
+        return Some(dir.to_path_buf());
+    }
+    // An explicit `package.workspace` points at the root
+    if let Some(root) = cargo
+        .get("package")
+        .and_then(|p| p.get("workspace"))
+        .and_then(|w| w.as_str())
+    {
+        return Some(dir.join(root));
+    }
+
+    dir.ancestors().skip(1).find_map(|ancestor| {
+        let root = manifest::toml(&ancestor.join("Cargo.toml"))?;
+        let workspace = root.get("workspace")?;
+        let relative = dir.strip_prefix(ancestor).ok()?;
+        let listed = |key: &str| {
+            workspace
+                .get(key)
+                .and_then(|v| v.as_array())
+                .is_some_and(|patterns| {
+                    patterns
+                        .iter()
+                        .filter_map(|p| p.as_str())
+                        .any(|pattern| matches_path(pattern, relative))
+                })
+        };
+        (listed("members") && !listed("exclude")).then(|| ancestor.to_path_buf())
+    })
+}
+
+/// Whether `path` matches a workspace glob (`*` within one component)
+fn matches_path(pattern: &str, path: &Path) -> bool {
+    let pattern: Vec<&str> = pattern
+        .trim_end_matches('/')
+        .split('/')
+        .filter(|c| !c.is_empty() && *c != ".")
+        .collect();
+    let components: Vec<String> = path
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => Some(name.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    pattern.len() == components.len()
+        && pattern
+            .iter()
+            .zip(&components)
+            .all(|(p, c)| matches_component(p, c))
+}
+
+fn matches_component(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(tail) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            (0..=tail.len())
+                .filter(|&i| tail.is_char_boundary(i))
+                .any(|i| matches_component(rest, &tail[i..]))
+        }
+    }
+}
+
+/// Package patterns for a module in a `go.work` workspace
+fn go_scope(working_dir: &Path, invocation_dir: &Path, scope: Scope) -> Option<Vec<String>> {
+    let work_dir = working_dir
+        .ancestors()
+        .find(|dir| dir.join("go.work").is_file())?;
+
+    match scope {
+        Scope::Member => {
+            // The two may differ in how symlinks were resolved
+            let relative = match invocation_dir.strip_prefix(working_dir) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => fs::canonicalize(invocation_dir)
+                    .ok()?
+                    .strip_prefix(fs::canonicalize(working_dir).ok()?)
+                    .ok()?
+                    .to_path_buf(),
+            };
+            Some(vec![relative_pattern(&relative)])
+        }
+        Scope::Workspace => {
+            let content = fs::read_to_string(work_dir.join("go.work")).ok()?;
+            let patterns = go_work_modules(&content)
+                .iter()
+                .filter_map(|module| {
+                    relative_between(working_dir, &work_dir.join(module))
+                        .map(|p| relative_pattern(&p))
+                })
+                .collect();
+            Some(patterns)
+        }
+    }
+}
+
+/// `./dir/...` (`./...` for the directory itself)
+fn relative_pattern(relative: &Path) -> String {
+    let mut pattern = String::from(".");
+    for component in relative.components() {
+        match component {
+            Component::Normal(name) => {
+                pattern.push('/');
+                pattern.push_str(&name.to_string_lossy());
+            }
+            Component::ParentDir => {
+                if pattern == "." {
+                    pattern = "..".to_string();
+                } else {
+                    pattern.push_str("/..");
+                }
+            }
+            _ => {}
+        }
+    }
+    pattern.push_str("/...");
+    pattern
+}
+
+/// Path from `from` to `to`, both below a common workspace directory
+fn relative_between(from: &Path, to: &Path) -> Option<PathBuf> {
+    let from: Vec<Component> = from.components().collect();
+    let to: Vec<Component> = to
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return None;
+    }
+    let mut relative = PathBuf::new();
+    for _ in common..from.len() {
+        relative.push("..");
+    }
+    for component in &to[common..] {
+        relative.push(component.as_os_str());
+    }
+    Some(relative)
+}
+
+/// Module directories listed by `use` directives in a go.work file
+pub fn go_work_modules(content: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+    let mut in_block = false;
+    for line in content.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        if in_block {
+            if line == ")" {
+                in_block = false;
+            } else if !line.is_empty() {
+                modules.push(line.trim_matches('"').to_string());
+            }
+        } else if let Some(rest) = line
+            .strip_prefix("use")
+            .filter(|rest| rest.starts_with(|c: char| c.is_whitespace() || c == '('))
+        {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+            } else if !rest.is_empty() {
+                modules.push(rest.trim_matches('"').to_string());
+            }
+        }
+    }
+    modules
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn cargo_workspace() -> tempfile::TempDir {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/legacy\"]\n",
+        )
+        .unwrap();
+        for name in ["core", "legacy"] {
+            let member = dir.path().join("crates").join(name);
+            fs::create_dir_all(&member).unwrap();
+            fs::write(
+                member.join("Cargo.toml"),
+                format!("[package]\nname = \"app-{}\"\n", name),
+            )
+            .unwrap();
+        }
+        dir
+    }
+
+    fn scoped(runner: &str, task: &str, dir: &Path, invoked: &Path, scope: Scope) -> Vec<String> {
+        let mut command = vec![runner.to_string(), task.to_string()];
+        apply_scope(&mut command, runner, task, &[], dir, invoked, scope);
+        command
+    }
+
+    #[test]
+    fn test_cargo_member() {
+        let dir = cargo_workspace();
+        let core = dir.path().join("crates").join("core");
+
+        assert_eq!(
+            scoped("cargo", "test", &core, &core.join("src"), Scope::Member),
+            vec!["cargo", "test", "-p", "app-core"]
+        );
+        assert_eq!(
+            scoped("cargo", "test", &core, &core, Scope::Workspace),
+            vec!["cargo", "test", "--workspace"]
+        );
+        // `cargo run --workspace` isn't a thing
+        assert_eq!(
+            scoped("cargo", "run", &core, &core, Scope::Workspace),
+            vec!["cargo", "run"]
+        );
+        // The root and excluded crates are left alone
+        assert_eq!(
+            scoped("cargo", "test", dir.path(), dir.path(), Scope::Member),
+            vec!["cargo", "test"]
+        );
+        let legacy = dir.path().join("crates").join("legacy");
+        assert_eq!(
+            scoped("cargo", "test", &legacy, &legacy, Scope::Member),
+            vec!["cargo", "test"]
+        );
+
+        // Explicit selection by the user wins
+        let mut command = vec!["cargo".to_string(), "test".to_string(), "-p".to_string()];
+        apply_scope(
+            &mut command,
+            "cargo",
+            "test",
+            &["-p".to_string(), "other".to_string()],
+            &core,
+            &core,
+            Scope::Member,
+        );
+        assert_eq!(command, vec!["cargo", "test", "-p"]);
+    }
+
+    #[test]
+    fn test_go_work() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("go.work"),
+            "go 1.22\n\nuse (\n\t./api // service\n\t./lib\n)\n",
+        )
+        .unwrap();
+        let api = dir.path().join("api");
+        fs::create_dir_all(api.join("internal").join("db")).unwrap();
+
+        assert_eq!(
+            scoped("go", "test", &api, &api, Scope::Member),
+            vec!["go", "test", "./..."]
+        );
+        assert_eq!(
+            scoped("go", "test", &api, &api.join("internal/db"), Scope::Member),
+            vec!["go", "test", "./internal/db/..."]
+        );
+        assert_eq!(
+            scoped("go", "test", &api, &api, Scope::Workspace),
+            vec!["go", "test", "./...", "../lib/..."]
+        );
+        // `go mod tidy` and friends don't take packages
+        assert_eq!(
+            scoped("go", "mod", &api, &api, Scope::Member),
+            vec!["go", "mod"]
+        );
+
+        // Modules outside a go.work are untouched
+        let solo = tempdir().unwrap();
+        assert_eq!(
+            scoped("go", "test", solo.path(), solo.path(), Scope::Member),
+            vec!["go", "test"]
+        );
+    }
+
+    #[test]
+    fn test_go_work_modules() {
+        assert_eq!(
+            go_work_modules("go 1.21\nuse ./one\nuse (\n  \"./two\"\n)\n"),
+            vec!["./one", "./two"]
+        );
+        assert!(go_work_modules("go 1.21\nuser ./x\n").is_empty());
+    }
+
+    #[test]
+    fn test_matches_path() {
+        assert!(matches_path("crates/*", Path::new("crates/core")));
+        assert!(matches_path("./tools/gen/", Path::new("tools/gen")));
+        assert!(matches_path("crates/run-*", Path::new("crates/run-ffi")));
+        assert!(!matches_path("crates/*", Path::new("crates/core/sub")));
+        assert!(!matches_path("crates/run-*", Path::new("crates/other")));
+    }
+}
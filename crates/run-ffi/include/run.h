@@ -0,0 +1,43 @@
+/*
+ * run-ffi: C bindings for run-core detection.
+ *
+ * Strings returned as `char *` are NUL-terminated UTF-8 JSON owned by the
+ * caller and must be released with run_string_free().
+ */
+
+#ifndef RUN_FFI_H
+#define RUN_FFI_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Detection report for `path` as JSON, or NULL on invalid arguments. */
+char *run_detect(const char *path);
+
+/*
+ * Runner and command line for `task` in `path`:
+ *   {"runner": {...}, "command": ["npm", "run", "lint"], "conflicts": []}
+ * or {"error": "..."} when no runner supports the task.
+ * Returns NULL on invalid arguments.
+ *
+ * The runner is picked by priority alone. Unlike the CLI, same-ecosystem
+ * conflicts (e.g. two lockfiles) are not resolved or refused; the other
+ * runners of the picked runner's ecosystem are listed in "conflicts".
+ */
+char *run_build_command(const char *path, const char *task,
+                        const char *const *args, size_t args_len);
+
+/* run-core version (static string, do not free). */
+const char *run_core_version(void);
+
+/* Free a string returned by this library. NULL is ignored. */
+void run_string_free(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RUN_FFI_H */
@@ -0,0 +1,243 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! # run-ffi
+//!
+//! C ABI over `run-core`, so editors and tools written in other languages can
+//! reuse the detection matrix without spawning the `run` binary.
+//!
+//! Every function returning `char *` hands ownership of a NUL-terminated UTF-8
+//! JSON string to the caller, which must release it with [`run_string_free`].
+//! See `include/run.h` for the C declarations.
+
+use run_core::{detect_all, detect_report, CommandSupport, DetectedRunner};
+use serde_json::{json, Value};
+use std::ffi::{c_char, CStr, CString};
+use std::path::Path;
+use std::ptr;
+
+/// Detect runners in `path` and return the detection report as JSON.
+///
+/// Returns NULL if `path` is NULL or not valid UTF-8.
+///
+/// # Safety
+///
+/// `path` must be NULL or point to a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn run_detect(path: *const c_char) -> *mut c_char {
+    let Some(path) = str_arg(path) else {
+        return ptr::null_mut();
+    };
+
+    let report = detect_report(Path::new(path));
+    into_c_string(serde_json::to_value(report).unwrap_or(Value::Null))
+}
+
+/// Select the runner for `task` in `path` and build its command line.
+///
+/// Returns `{"runner": {...}, "command": [...], "conflicts": [...]}`, or
+/// `{"error": "..."}` when no detected runner supports the task. Returns NULL
+/// on invalid arguments.
+///
+/// The runner is picked by priority alone (see [`select`]). The CLI also
+/// checks runners of one ecosystem found together, such as two lockfiles,
+/// and may refuse them as ambiguous; those are not resolved here, but their
+/// names are listed in `conflicts` (empty when there is none).
+///
+/// # Safety
+///
+/// `path` and `task` must point to valid NUL-terminated strings. `args` must be
+/// NULL (with `args_len` 0) or point to `args_len` valid NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn run_build_command(
+    path: *const c_char,
+    task: *const c_char,
+    args: *const *const c_char,
+    args_len: usize,
+) -> *mut c_char {
+    let (Some(path), Some(task)) = (str_arg(path), str_arg(task)) else {
+        return ptr::null_mut();
+    };
+
+    let mut extra_args = Vec::with_capacity(args_len);
+    if args_len > 0 {
+        if args.is_null() {
+            return ptr::null_mut();
+        }
+        for i in 0..args_len {
+            match str_arg(*args.add(i)) {
+                Some(arg) => extra_args.push(arg.to_string()),
+                None => return ptr::null_mut(),
+            }
+        }
+    }
+
+    let dir = Path::new(path);
+    let runners = detect_all(dir, &[]);
+    let result = match select(&runners, task, dir) {
+        Some(runner) => json!({
+            "runner": runner,
+            "command": runner.build_command(task, &extra_args),
+            "conflicts": conflicts(&runners, runner),
+        }),
+        None if runners.is_empty() => json!({"error": "No runner found"}),
+        None => json!({
+            "error": format!("Command '{}' not supported by any detected runner", task),
+        }),
+    };
+    into_c_string(result)
+}
+
+/// Version of the underlying run-core library (static, do not free)
+#[no_mangle]
+pub extern "C" fn run_core_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr().cast()
+}
+
+/// Free a string returned by this library. NULL is ignored.
+///
+/// # Safety
+///
+/// `s` must be NULL or a pointer returned by this library that was not
+/// freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn run_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The first runner that supports the task, else the first that may. The CLI
+/// starts from the same candidates but then resolves same-ecosystem conflicts
+/// (corepack, installed tools), which this does not
+fn select<'a>(runners: &'a [DetectedRunner], task: &str, dir: &Path) -> Option<&'a DetectedRunner> {
+    let mut unknown = None;
+    for runner in runners {
+        match runner.supports_command(task, dir) {
+            CommandSupport::Supported => return Some(runner),
+            CommandSupport::Unknown if unknown.is_none() => unknown = Some(runner),
+            _ => {}
+        }
+    }
+    unknown
+}
+
+/// Names of the other runners of `runner`'s ecosystem, which the CLI would
+/// have to choose between
+fn conflicts(runners: &[DetectedRunner], runner: &DetectedRunner) -> Vec<String> {
+    runners
+        .iter()
+        .filter(|r| r.ecosystem == runner.ecosystem && r.name != runner.name)
+        .map(|r| r.name.clone())
+        .collect()
+}
+
+unsafe fn str_arg<'a>(s: *const c_char) -> Option<&'a str> {
+    if s.is_null() {
+        return None;
+    }
+    CStr::from_ptr(s).to_str().ok()
+}
+
+fn into_c_string(value: Value) -> *mut c_char {
+    // serde_json escapes control characters, so the output never contains NUL
+    CString::new(value.to_string())
+        .map(CString::into_raw)
+        .unwrap_or(ptr::null_mut())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn take(s: *mut c_char) -> Value {
+        assert!(!s.is_null());
+        let value = unsafe { serde_json::from_str(CStr::from_ptr(s).to_str().unwrap()).unwrap() };
+        unsafe { run_string_free(s) };
+        value
+    }
+
+    fn c(s: &str) -> CString {
+        CString::new(s).unwrap()
+    }
+
+    #[test]
+    fn test_run_detect() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"").unwrap();
+        let path = c(dir.path().to_str().unwrap());
+
+        let report = take(unsafe { run_detect(path.as_ptr()) });
+        assert_eq!(report["runners"][0]["name"], "cargo");
+    }
+
+    #[test]
+    fn test_run_build_command() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"lint": "eslint ."}}"#,
+        )
+        .unwrap();
+        let path = c(dir.path().to_str().unwrap());
+        let task = c("lint");
+        let arg = c("--fix");
+        let args = [arg.as_ptr()];
+
+        let result =
+            take(unsafe { run_build_command(path.as_ptr(), task.as_ptr(), args.as_ptr(), 1) });
+        assert_eq!(result["runner"]["name"], "npm");
+        assert_eq!(result["command"], json!(["npm", "run", "lint", "--fix"]));
+        assert_eq!(result["conflicts"], json!([]));
+
+        let task = c("missing");
+        let result =
+            take(unsafe { run_build_command(path.as_ptr(), task.as_ptr(), ptr::null(), 0) });
+        assert!(result["error"].as_str().unwrap().contains("not supported"));
+    }
+
+    #[test]
+    fn test_run_build_command_conflicts() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"lint": "eslint ."}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("package-lock.json"), "{}").unwrap();
+        fs::write(dir.path().join("yarn.lock"), "").unwrap();
+        let path = c(dir.path().to_str().unwrap());
+        let task = c("lint");
+
+        // Yarn wins on priority; npm is reported rather than resolved
+        let result =
+            take(unsafe { run_build_command(path.as_ptr(), task.as_ptr(), ptr::null(), 0) });
+        assert_eq!(result["runner"]["name"], "yarn");
+        assert_eq!(result["conflicts"], json!(["npm"]));
+    }
+
+    #[test]
+    fn test_null_arguments() {
+        unsafe {
+            assert!(run_detect(ptr::null()).is_null());
+            assert!(run_build_command(ptr::null(), ptr::null(), ptr::null(), 0).is_null());
+            run_string_free(ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn test_run_core_version() {
+        let version = unsafe { CStr::from_ptr(run_core_version()) };
+        assert_eq!(version.to_str().unwrap(), env!("CARGO_PKG_VERSION"));
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Incremental task skipping.
+//!
+//! A run.toml task that declares `inputs` (and optionally `outputs`) globs
+//! is skipped when its inputs hash the same as after its last successful run
+//! and every output glob still matches a file. The hash covers each input's
+//! path and contents plus the task's definition and arguments, so editing
+//! the task also runs it again.
+//!
+//! The last hash of each task is kept under the config dir
+//! (`~/.config/run/cache/`), one small file per project and task, after the
+//! format version: a file in another format never counts as fresh.
+
+use crate::walk;
+use run_core::hash::StableHasher;
+use run_core::registry::matches_pattern;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Format of the state files, written before the hash
+const CACHE_VERSION: u32 = 2;
+
+/// The inputs of a task as they are now, and where their last hash is kept
+#[derive(Debug)]
+pub struct TaskCache {
+    state: PathBuf,
+    fingerprint: String,
+    outputs: bool,
+}
+
+impl TaskCache {
+    /// Hash the `inputs` of `task` in `dir`; `definition` is anything else
+    /// a change to which should run the task again (its command, its
+    /// arguments). `None` without a config dir to keep the state in.
+    pub fn new(
+        dir: &Path,
+        task: &str,
+        definition: &str,
+        inputs: &[String],
+        outputs: &[String],
+        skip: &[String],
+    ) -> Option<Self> {
+        let mut name = StableHasher::new();
+        name.write(dir.as_os_str().as_encoded_bytes());
+        name.write(task.as_bytes());
+        let state = dirs::config_dir()?
+            .join("run")
+            .join("cache")
+            .join(name.hex());
+        let mut hasher = StableHasher::new();
+        hasher.write(definition.as_bytes());
+        for file in files(dir, inputs, skip) {
+            let relative = file.strip_prefix(dir).unwrap_or(&file);
+            hasher.write(relative.as_os_str().as_encoded_bytes());
+            hasher.write(&fs::read(&file).unwrap_or_default());
+        }
+        Some(TaskCache {
+            state,
+            fingerprint: format!("{} {}", CACHE_VERSION, hasher.hex()),
+            outputs: outputs
+                .iter()
+                .all(|pattern| !files(dir, std::slice::from_ref(pattern), skip).is_empty()),
+        })
+    }
+
+    /// Whether the inputs are unchanged since the last recorded run and the
+    /// outputs are all still there
+    pub fn is_fresh(&self) -> bool {
+        self.outputs
+            && fs::read_to_string(&self.state).is_ok_and(|last| last.trim() == self.fingerprint)
+    }
+
+    /// Remember the inputs as they were for this (successful) run
+    pub fn record(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.state.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.state, &self.fingerprint)
+    }
+}
+
+/// Files below `dir` matching any of `patterns`, sorted and deduplicated
+///
+/// Patterns are `/`-separated paths relative to `dir`, where `*` matches
+/// within a path segment and a `**` segment any number of them; a pattern
+/// naming a directory matches every file in it. Skipped directories are not
+/// entered unless a pattern names them, and symlinks to directories are
+/// not followed.
+pub fn files(dir: &Path, patterns: &[String], skip: &[String]) -> Vec<PathBuf> {
+    let mut found = Vec::new();
+    for pattern in patterns {
+        let segments: Vec<&str> = pattern
+            .split('/')
+            .filter(|s| !s.is_empty() && *s != ".")
+            .collect();
+        // Walk from the literal part of the pattern only
+        let literal = segments.iter().take_while(|s| !s.contains('*')).count();
+        let base = segments[..literal]
+            .iter()
+            .fold(dir.to_path_buf(), |path, s| path.join(s));
+        if literal == segments.len() {
+            if base.is_file() {
+                found.push(base);
+            } else if base.is_dir() {
+                collect(&base, &base, &[], skip, &mut found);
+            }
+        } else {
+            collect(&base, &base, &segments[literal..], skip, &mut found);
+        }
+    }
+    found.sort();
+    found.dedup();
+    found
+}
+
+/// Add the files below `current` whose path relative to `base` matches
+/// `pattern`, or every file when `pattern` is empty
+fn collect(
+    base: &Path,
+    current: &Path,
+    pattern: &[&str],
+    skip: &[String],
+    found: &mut Vec<PathBuf>,
+) {
+    let Ok(entries) = fs::read_dir(current) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        if file_type.is_dir() {
+            if !walk::is_skipped(&path, base, skip) {
+                collect(base, &path, pattern, skip, found);
+            }
+            continue;
+        }
+        let Ok(relative) = path.strip_prefix(base) else {
+            continue;
+        };
+        let relative: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let relative: Vec<&str> = relative.iter().map(String::as_str).collect();
+        if pattern.is_empty() || glob_match(pattern, &relative) {
+            found.push(path);
+        }
+    }
+}
+
+/// Whether the path `segments` match the pattern `segments`
+fn glob_match(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_match(rest, &path[skip..])),
+        Some((segment, rest)) => path
+            .split_first()
+            .is_some_and(|(name, path)| matches_pattern(segment, name) && glob_match(rest, path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_files() {
+        let dir = tempdir().unwrap();
+        for file in [
+            "main.rs",
+            "src/lib.rs",
+            "src/a/b.rs",
+            "src/a/notes.md",
+            "target/out.rs",
+        ] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, file).unwrap();
+        }
+        let skip = walk::default_skip_dirs();
+        let names = |patterns: &[&str]| -> Vec<String> {
+            let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+            files(dir.path(), &patterns, &skip)
+                .iter()
+                .map(|f| f.strip_prefix(dir.path()).unwrap().display().to_string())
+                .map(|f| f.replace('\\', "/"))
+                .collect()
+        };
+
+        assert_eq!(
+            names(&["src/**"]),
+            ["src/a/b.rs", "src/a/notes.md", "src/lib.rs"]
+        );
+        assert_eq!(names(&["src"]), names(&["src/**"]));
+        assert_eq!(names(&["**/*.rs"]), ["main.rs", "src/a/b.rs", "src/lib.rs"]);
+        assert_eq!(names(&["src/*.rs", "main.rs"]), ["main.rs", "src/lib.rs"]);
+        assert_eq!(names(&["target/*.rs"]), ["target/out.rs"]);
+        assert!(names(&["dist/**", "missing.txt"]).is_empty());
+    }
+
+    #[test]
+    fn test_task_cache_state() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("input.txt"), "a").unwrap();
+        let inputs = ["input.txt".to_string()];
+        let cache = TaskCache::new(dir.path(), "build", "make", &inputs, &[], &[])
+            .expect("a config directory to keep the cache in");
+        let cache = TaskCache {
+            state: dir.path().join("state"),
+            ..cache
+        };
+
+        // The same on every toolchain, so a record survives upgrades
+        assert_eq!(cache.fingerprint, "2 67909e9d5022c1f4");
+        assert!(!cache.is_fresh());
+        cache.record().unwrap();
+        assert!(cache.is_fresh());
+
+        // A hash written in an older format doesn't count
+        fs::write(&cache.state, "67909e9d5022c1f4").unwrap();
+        assert!(!cache.is_fresh());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match(&["**"], &["a", "b"]));
+        assert!(glob_match(&["**", "*.rs"], &["lib.rs"]));
+        assert!(glob_match(&["a", "**", "c"], &["a", "c"]));
+        assert!(glob_match(&["a", "**", "c"], &["a", "b", "b", "c"]));
+        assert!(!glob_match(&["*.rs"], &["a", "lib.rs"]));
+        assert!(!glob_match(&["a", "*"], &["a"]));
+    }
+}
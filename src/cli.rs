@@ -9,7 +9,16 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
+use crate::config::parse_duration;
+use crate::dotenv;
+use crate::output::ColorChoice;
+use crate::runner::StdinSource;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Separates chained tasks: `run build +then deploy`
+pub const THEN: &str = "+then";
 
 /// Universal task runner - automatically detects and runs project commands
 #[derive(Parser, Debug, Clone)]
@@ -29,43 +38,179 @@ use clap::{Parser, Subcommand};
   Elixir:   mix
   Swift:    swift
   Zig:      zig
+  Scala:    mill, sbt
+  Clojure:  lein
+  Nix:      nix (flakes)
+  Bazel:    bazel
+  CMake:    cmake
+  Docker:   docker (Dockerfile, docker-bake.hcl)
+  Earthly:  earthly
+  Actions:  act (.github/workflows, when act is installed)
+  Devbox:   devbox
+  Pixi:     pixi
+  Mask:     mask (maskfile.md)
+  Xcode:    tuist (Project.swift), xcodebuild (*.xcworkspace, *.xcodeproj)
+  Data:     snakemake (Snakefile), dbt (dbt_project.yml)
   Generic:  make
 
 EXAMPLES:
   run test                      # Run test command using detected runner
   run build -- --verbose        # Pass extra arguments after --
+  run build,test,lint           # Run tasks in order (not `run build test`: test is an argument)
   run lint --levels=5           # Search up to 5 levels above current dir
   run start --ignore=npm,yarn   # Skip specific runners
-  run deploy --dry-run          # Show command without executing")]
+  run deploy --dry-run          # Show command without executing
+  run list                      # Show the tasks the detected runners know about
+  run which test                # Explain which runner and command `run test` uses
+  run test --in-devcontainer    # Run inside .devcontainer via the devcontainer CLI
+  run test --workspace          # Test every workspace member, not just this one
+  run --cwd api test            # Run api's test task without cd-ing into it")]
 pub struct Cli {
-    /// Command to run (e.g., test, build, start)
+    /// Command to run (e.g., test, build, start), or several separated by
+    /// commas (build,test,lint)
     #[arg(value_name = "COMMAND")]
     pub command: Option<String>,
 
-    /// Arguments to pass to the command
-    #[arg(value_name = "ARGS", trailing_var_arg = true)]
+    /// Arguments to pass to the command; words after a space are never
+    /// tasks of their own (`run deploy staging` passes `staging` to deploy)
+    #[arg(value_name = "ARGS")]
     pub args: Vec<String>,
 
-    /// How many directory levels to search above current dir
-    #[arg(short, long, default_value = "3", value_parser = clap::value_parser!(u8).range(0..=10))]
-    pub levels: u8,
+    /// Arguments after `--`, passed to the command (to every task when
+    /// running several)
+    #[arg(value_name = "PASSTHROUGH", last = true)]
+    pub passthrough: Vec<String>,
+
+    /// Detect and run from this directory instead of the current one
+    #[arg(short = 'C', long, value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Set an environment variable for the task (repeatable)
+    #[arg(short, long = "env", value_name = "KEY=VALUE", value_parser = parse_env_var)]
+    pub env: Vec<(String, String)>,
+
+    /// Load environment variables from a dotenv file (repeatable; --env wins)
+    #[arg(long = "env-file", value_name = "FILE")]
+    pub env_files: Vec<PathBuf>,
+
+    /// How many directory levels to search above current dir (default: 3)
+    #[arg(short, long, value_parser = clap::value_parser!(u8).range(0..=10))]
+    pub levels: Option<u8>,
 
     /// Runners to ignore (comma-separated or multiple flags)
     #[arg(short, long = "ignore", value_delimiter = ',')]
     pub ignore: Vec<String>,
 
-    /// Show detailed detection information
-    #[arg(short, long)]
-    pub verbose: bool,
+    /// Show more details: -v detection, -vv each detector and validator
+    /// answer, -vvv the merged config and the task environment
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    pub verbose: u8,
 
     /// Suppress all output except errors and command output
     #[arg(short, long)]
     pub quiet: bool,
 
+    /// Print diagnostics as newline-delimited JSON (stderr) and the results
+    /// of list, which and doctor as JSON (stdout)
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// When to color output
+    #[arg(long, global = true, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+
+    /// Stop the task (and everything it started) after this long, e.g. 90s,
+    /// 5m or 1h30m; exits with 124
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub timeout: Option<Duration>,
+
+    /// Re-run a failing task up to N more times (overrides a task's
+    /// `retries` in run.toml)
+    #[arg(long, value_name = "N", conflicts_with_all = ["parallel", "watch", "watch_paths"])]
+    pub retry: Option<u32>,
+
+    /// Wait this long before the first retry, doubling it each time
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "1s")]
+    pub retry_delay: Duration,
+
+    /// What the task reads on stdin: inherit (default), null, or
+    /// file:<path>
+    #[arg(long, value_name = "SOURCE", conflicts_with_all = ["parallel", "watch", "watch_paths"])]
+    pub stdin: Option<StdinSource>,
+
+    /// When no runner is found upwards, look this many levels into
+    /// subdirectories (default 2) and use the one project found there
+    #[arg(long, value_name = "N", num_args = 0..=1, require_equals = true, default_missing_value = "2")]
+    pub down: Option<usize>,
+
+    /// Also append the tasks' output, timestamped, to this file
+    #[arg(long, value_name = "PATH")]
+    pub log_file: Option<PathBuf>,
+
+    /// Run tasks under a pseudo-terminal even when the output isn't a
+    /// terminal, so they keep their colors and progress bars (Unix; by
+    /// default only output `run` reads and prints to a terminal, with
+    /// --log-file or --parallel, goes through one)
+    #[arg(long)]
+    pub pty: bool,
+
+    /// Skip asking runners whether they know the task and forward it to the
+    /// selected one (for Makefiles or build plugins that validators miss)
+    #[arg(long)]
+    pub no_validate: bool,
+
+    /// Pick the tool from a menu when lockfiles conflict (default: when
+    /// prompts are allowed, see `interactive`)
+    #[arg(long)]
+    pub choose: bool,
+
+    /// Run tasks marked `confirm` in run.toml without asking
+    #[arg(short, long)]
+    pub yes: bool,
+
     /// Show command without executing
     #[arg(long)]
     pub dry_run: bool,
 
+    /// Run the command inside the project's dev container (devcontainer CLI)
+    #[arg(long)]
+    pub in_devcontainer: bool,
+
+    /// Run cargo/go workspace commands for every member, not just the current one
+    #[arg(long)]
+    pub workspace: bool,
+
+    /// Run several tasks at the same time (`run -p dev:server,dev:client`),
+    /// prefixing their output
+    #[arg(short, long)]
+    pub parallel: bool,
+
+    /// Then run this task, with its arguments (`--then 'deploy --prod'`),
+    /// if the ones before it succeeded; repeatable, like `+then`
+    #[arg(long, value_name = "TASK", conflicts_with = "parallel")]
+    pub then: Vec<String>,
+
+    /// When running several tasks, run the rest after one fails
+    #[arg(long)]
+    pub keep_going: bool,
+
+    /// Report how long config loading, detection, validation and the task
+    /// itself took
+    #[arg(long)]
+    pub profile: bool,
+
+    /// Re-run the task when project files change
+    #[arg(short, long)]
+    pub watch: bool,
+
+    /// Only re-run for changes matching GLOB (repeatable; implies --watch)
+    #[arg(long = "watch-path", value_name = "GLOB")]
+    pub watch_paths: Vec<String>,
+
+    /// List the tasks of the runner `run` picks (same as `run list`)
+    #[arg(long)]
+    pub list: bool,
+
     /// Force immediate update check
     #[arg(long)]
     pub update: bool,
@@ -78,19 +223,251 @@ pub struct Cli {
 pub enum Commands {
     /// Generate shell completions
     Completions {
-        /// Shell to generate completions for
-        #[arg(value_enum)]
-        shell: clap_complete::Shell,
+        /// Shell to generate completions for (with --install, defaults to $SHELL)
+        #[arg(value_enum, required_unless_present = "install")]
+        shell: Option<clap_complete::Shell>,
+
+        /// Write the script to the shell's completion directory instead of stdout
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// List the tasks, scripts and targets of the runner `run` picks
+    #[command(visible_alias = "tasks")]
+    List {
+        /// Also list the other runners detected in the directory
+        #[arg(long)]
+        all: bool,
+    },
+
+    /// Show how a task resolves: directories searched, runners detected and
+    /// what each answered, and the command that would run
+    Which {
+        /// Task to resolve
+        task: String,
+
+        /// Arguments the task would be given
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Show where a task is defined, what it runs and the command `run`
+    /// would execute, without running it
+    Explain {
+        /// Task to explain
+        task: String,
+
+        /// Arguments the task would be given
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Find tasks whose name fuzzily matches a keyword, or whose body
+    /// contains it, best matches first
+    Search {
+        /// Word to look for (`dpl` finds `deploy`)
+        keyword: String,
+    },
+
+    /// Pass the arguments as they are to the detected tool, without looking
+    /// for a task (`run exec --prefix web install lodash`)
+    Exec {
+        /// Arguments for the tool
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
+    /// Update the detected tool itself to its latest release (`rustup
+    /// update`, `corepack prepare pnpm@latest`, ...)
+    UpgradeTool {
+        /// Print the upgrade command without running it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Write a starter run.toml listing the detected tasks as commented-out
+    /// commands
+    Init {
+        /// Overwrite an existing run.toml
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Print the JSON Schema of run.toml, for editor validation and completion
+    Schema,
+
+    /// Check that the detected runners' tools are installed, the config files
+    /// parse and no lockfiles conflict
+    Doctor,
+
+    /// Read or change the global configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// Add, list or remove task aliases (`run alias add t test -- --coverage`)
+    Alias {
+        /// Change ./run.toml instead of the global config
+        #[arg(long, global = true)]
+        local: bool,
+
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+
+    /// Start a JSON-RPC server for editor integrations
+    Serve {
+        /// Communicate over stdin/stdout (LSP-style framing)
+        #[arg(long)]
+        stdio: bool,
+    },
+
+    /// Watch project roots and answer detection queries from a warm cache
+    Daemon {
+        /// Socket to listen on (defaults to $RUN_DAEMON_SOCKET or the runtime dir)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+
+        /// Project roots to watch (defaults to the current directory)
+        roots: Vec<PathBuf>,
     },
 }
 
+/// `run config` actions on the global config file
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Print a setting (dotted for sections, e.g. update.enabled)
+    Get { key: String },
+
+    /// Change a setting; VALUE is read as TOML (false, 5, ["npm"]) or a string
+    Set { key: String, value: String },
+
+    /// Remove a setting, restoring its default
+    Unset { key: String },
+
+    /// Open the file in $VISUAL or $EDITOR
+    Edit,
+
+    /// Print where the file is
+    Path,
+
+    /// Print the settings in effect here (global, manifests, run.toml,
+    /// environment)
+    Show,
+}
+
+/// `run alias` actions
+#[derive(Subcommand, Debug, Clone)]
+pub enum AliasAction {
+    /// Make NAME run the given task and arguments, replacing any alias it had
+    Add {
+        name: String,
+
+        /// Task and arguments NAME expands to
+        #[arg(required = true, trailing_var_arg = true, allow_hyphen_values = true)]
+        expansion: Vec<String>,
+    },
+
+    /// Print the aliases in effect here
+    #[command(visible_alias = "ls")]
+    List,
+
+    /// Remove an alias
+    #[command(visible_alias = "remove")]
+    Rm { name: String },
+}
+
 impl Cli {
+    /// The tasks to run, in order, each with its arguments
+    ///
+    /// `run build --release +then deploy prod` and `run build --then deploy`
+    /// chain tasks that each take their own arguments, those after `--`
+    /// going to the last `+then` task. `run build,test` runs both tasks, each
+    /// with the arguments after `--`. Otherwise the extra words are arguments
+    /// of the one task, followed by `--` and the rest, as they were typed:
+    /// space-separated words are never split into tasks, since `run deploy
+    /// staging` can't tell a task from an argument.
+    ///
+    /// The `--` is kept even with nothing before it, so that an alias can
+    /// tell the two apart; `Session::resolve` drops it after expanding.
+    pub fn tasks(&self) -> Vec<(String, Vec<String>)> {
+        let Some(command) = &self.command else {
+            return Vec::new();
+        };
+        if self.chained() {
+            let words: Vec<String> = std::iter::once(command)
+                .chain(&self.args)
+                .cloned()
+                .collect();
+            let mut tasks: Vec<(String, Vec<String>)> = words
+                .split(|word| word == THEN)
+                .filter_map(|words| words.split_first())
+                .map(|(task, args)| (task.clone(), args.to_vec()))
+                .collect();
+            if let Some((_, args)) = tasks.last_mut() {
+                args.extend(self.passthrough_args());
+            }
+            tasks.extend(self.then.iter().filter_map(|task| {
+                let mut words = shell_words::split(task)
+                    .unwrap_or_else(|_| task.split_whitespace().map(String::from).collect());
+                (!words.is_empty()).then(|| (words.remove(0), words))
+            }));
+            return tasks;
+        }
+        let split = |word: &String| {
+            word.split(',')
+                .filter(|task| !task.is_empty())
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+
+        if command.contains(',') {
+            return std::iter::once(command)
+                .chain(&self.args)
+                .flat_map(split)
+                .map(|task| (task, self.passthrough_args()))
+                .collect();
+        }
+
+        let mut args = self.args.clone();
+        args.extend(self.passthrough_args());
+        vec![(command.clone(), args)]
+    }
+
+    /// `--` and the words after it, or nothing
+    fn passthrough_args(&self) -> Vec<String> {
+        if self.passthrough.is_empty() {
+            return Vec::new();
+        }
+        std::iter::once("--".to_string())
+            .chain(self.passthrough.iter().cloned())
+            .collect()
+    }
+
+    /// Whether tasks are chained with `+then` or `--then`, so each runs
+    /// only after the previous one succeeded
+    pub fn chained(&self) -> bool {
+        !self.then.is_empty() || self.args.iter().any(|word| word == THEN)
+    }
+
     /// Check if a runner should be ignored
     pub fn should_ignore(&self, runner: &str) -> bool {
         self.ignore.iter().any(|i| i.eq_ignore_ascii_case(runner))
     }
 }
 
+/// `--env KEY=VALUE`
+fn parse_env_var(pair: &str) -> Result<(String, String), String> {
+    let (key, value) = pair
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", pair))?;
+    if !dotenv::is_valid_key(key) {
+        return Err(format!("invalid variable name '{}'", key));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,6 +478,30 @@ mod tests {
         Cli::command().debug_assert();
     }
 
+    #[test]
+    fn test_env_vars() {
+        let cli = Cli::parse_from([
+            "run",
+            "-e",
+            "A=1",
+            "test",
+            "--env",
+            "B=x=y",
+            "--env-file",
+            ".env",
+        ]);
+        assert_eq!(
+            cli.env,
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "x=y".to_string())
+            ]
+        );
+        assert_eq!(cli.env_files, vec![PathBuf::from(".env")]);
+        assert!(Cli::try_parse_from(["run", "test", "--env", "A"]).is_err());
+        assert!(Cli::try_parse_from(["run", "test", "--env", "1A=x"]).is_err());
+    }
+
     #[test]
     fn test_basic_command() {
         let cli = Cli::parse_from(["run", "test"]);
@@ -112,7 +513,89 @@ mod tests {
     fn test_command_with_args() {
         let cli = Cli::parse_from(["run", "test", "--", "--coverage", "--verbose"]);
         assert_eq!(cli.command, Some("test".to_string()));
-        assert_eq!(cli.args, vec!["--coverage", "--verbose"]);
+        assert_eq!(cli.passthrough, vec!["--coverage", "--verbose"]);
+        assert_eq!(
+            cli.tasks(),
+            vec![(
+                "test".to_string(),
+                vec![
+                    "--".to_string(),
+                    "--coverage".to_string(),
+                    "--verbose".to_string()
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_several_tasks() {
+        let owned = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+
+        let cli = Cli::parse_from(["run", "build,test,lint", "--", "-v"]);
+        let tasks = cli.tasks();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[2], ("lint".to_string(), owned(&["--", "-v"])));
+
+        let cli = Cli::parse_from(["run", "build,test,", "--keep-going", "-p"]);
+        assert!(cli.keep_going);
+        assert!(cli.parallel);
+        let names: Vec<String> = cli.tasks().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(names, owned(&["build", "test"]));
+
+        // Without a comma, the extra words are arguments, `--` included,
+        // even when they name tasks too
+        let cli = Cli::parse_from(["run", "deploy", "staging"]);
+        assert_eq!(
+            cli.tasks(),
+            vec![("deploy".to_string(), owned(&["staging"]))]
+        );
+        let cli = Cli::parse_from(["run", "test", "unit", "--", "--nocapture"]);
+        assert_eq!(
+            cli.tasks(),
+            vec![("test".to_string(), owned(&["unit", "--", "--nocapture"]))]
+        );
+    }
+
+    #[test]
+    fn test_chained_tasks() {
+        let owned = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+
+        let cli = Cli::parse_from([
+            "run", "build", "release", "+then", "deploy", "prod", "--", "--force",
+        ]);
+        assert!(cli.chained());
+        assert_eq!(
+            cli.tasks(),
+            vec![
+                ("build".to_string(), owned(&["release"])),
+                ("deploy".to_string(), owned(&["prod", "--", "--force"])),
+            ]
+        );
+
+        let cli = Cli::parse_from([
+            "run",
+            "build",
+            "--then",
+            "deploy --prod",
+            "--then",
+            "notify",
+        ]);
+        assert_eq!(
+            cli.tasks(),
+            vec![
+                ("build".to_string(), vec![]),
+                ("deploy".to_string(), owned(&["--prod"])),
+                ("notify".to_string(), vec![]),
+            ]
+        );
+        assert!(Cli::try_parse_from(["run", "build", "-p", "--then", "deploy"]).is_err());
+    }
+
+    #[test]
+    fn test_cwd() {
+        let cli = Cli::parse_from(["run", "-C", "api", "test"]);
+        assert_eq!(cli.cwd, Some(PathBuf::from("api")));
+        assert_eq!(cli.command, Some("test".to_string()));
     }
 
     #[test]
@@ -140,29 +623,107 @@ mod tests {
     #[test]
     fn test_levels() {
         let cli = Cli::parse_from(["run", "test", "--levels=5"]);
-        assert_eq!(cli.levels, 5);
+        assert_eq!(cli.levels, Some(5));
     }
 
     #[test]
     fn test_default_levels() {
+        // Left unset so config files and RUN_LEVELS apply
         let cli = Cli::parse_from(["run", "test"]);
-        assert_eq!(cli.levels, 3);
+        assert_eq!(cli.levels, None);
     }
 
     #[test]
     fn test_verbose_and_quiet() {
         let cli = Cli::parse_from(["run", "test", "-v"]);
-        assert!(cli.verbose);
+        assert_eq!(cli.verbose, 1);
         assert!(!cli.quiet);
 
+        let cli = Cli::parse_from(["run", "test", "-vv", "--verbose"]);
+        assert_eq!(cli.verbose, 3);
+
         let cli = Cli::parse_from(["run", "test", "-q"]);
-        assert!(!cli.verbose);
+        assert_eq!(cli.verbose, 0);
         assert!(cli.quiet);
     }
 
+    #[test]
+    fn test_serve_stdio() {
+        let cli = Cli::parse_from(["run", "serve", "--stdio"]);
+        assert!(matches!(
+            cli.subcommand,
+            Some(Commands::Serve { stdio: true })
+        ));
+    }
+
+    #[test]
+    fn test_daemon_roots() {
+        let cli = Cli::parse_from(["run", "daemon", "--socket", "/tmp/run.sock", "a", "b"]);
+        match cli.subcommand {
+            Some(Commands::Daemon { socket, roots }) => {
+                assert_eq!(socket, Some(PathBuf::from("/tmp/run.sock")));
+                assert_eq!(roots, vec![PathBuf::from("a"), PathBuf::from("b")]);
+            }
+            other => panic!("unexpected subcommand: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_completions_install() {
+        let cli = Cli::parse_from(["run", "completions", "--install"]);
+        assert!(matches!(
+            cli.subcommand,
+            Some(Commands::Completions {
+                shell: None,
+                install: true
+            })
+        ));
+        assert!(Cli::try_parse_from(["run", "completions"]).is_err());
+    }
+
+    #[test]
+    fn test_list() {
+        assert!(matches!(
+            Cli::parse_from(["run", "list"]).subcommand,
+            Some(Commands::List { all: false })
+        ));
+        assert!(matches!(
+            Cli::parse_from(["run", "tasks", "--all"]).subcommand,
+            Some(Commands::List { all: true })
+        ));
+        let cli = Cli::parse_from(["run", "--list"]);
+        assert!(cli.list);
+        assert_eq!(cli.command, None);
+    }
+
+    #[test]
+    fn test_which() {
+        let cli = Cli::parse_from(["run", "which", "test", "--", "--watch"]);
+        match cli.subcommand {
+            Some(Commands::Which { task, args }) => {
+                assert_eq!(task, "test");
+                assert_eq!(args, vec!["--watch"]);
+            }
+            other => panic!("unexpected subcommand: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_dry_run() {
         let cli = Cli::parse_from(["run", "test", "--dry-run"]);
         assert!(cli.dry_run);
     }
+
+    #[test]
+    fn test_in_devcontainer() {
+        let cli = Cli::parse_from(["run", "test", "--in-devcontainer"]);
+        assert!(cli.in_devcontainer);
+        assert!(!Cli::parse_from(["run", "test"]).in_devcontainer);
+    }
+
+    #[test]
+    fn test_workspace() {
+        assert!(Cli::parse_from(["run", "test", "--workspace"]).workspace);
+        assert!(!Cli::parse_from(["run", "test"]).workspace);
+    }
 }
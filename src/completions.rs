@@ -0,0 +1,139 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Shell completion installation (`run completions --install`).
+
+use crate::cli::Cli;
+use crate::error::RunError;
+use clap::CommandFactory;
+use clap_complete::{generate, Shell};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where a shell's completion script goes, and what (if anything) the user
+/// still has to add to their shell rc
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstallTarget {
+    pub path: PathBuf,
+    pub rc_snippet: Option<String>,
+}
+
+/// The user's shell, from `$SHELL`
+pub fn detect_shell() -> Option<Shell> {
+    Shell::from_shell_path(env::var_os("SHELL")?)
+}
+
+/// Completion file location for `shell` under `home`
+///
+/// `data_home` and `config_home` are `$XDG_DATA_HOME` and `$XDG_CONFIG_HOME`
+/// when set. Shells without a completion directory (PowerShell, Elvish) load
+/// completions from their profile, so there is nothing to install.
+pub fn install_target(
+    shell: Shell,
+    home: &Path,
+    data_home: Option<PathBuf>,
+    config_home: Option<PathBuf>,
+) -> Result<InstallTarget, RunError> {
+    let target = match shell {
+        // bash-completion loads this directory on demand
+        Shell::Bash => InstallTarget {
+            path: data_home
+                .unwrap_or_else(|| home.join(".local").join("share"))
+                .join("bash-completion")
+                .join("completions")
+                .join("run"),
+            rc_snippet: None,
+        },
+        Shell::Zsh => {
+            let dir = home.join(".zsh").join("completions");
+            InstallTarget {
+                path: dir.join("_run"),
+                rc_snippet: Some(format!(
+                    "fpath=({} $fpath)\nautoload -Uz compinit && compinit",
+                    dir.display()
+                )),
+            }
+        }
+        Shell::Fish => InstallTarget {
+            path: config_home
+                .unwrap_or_else(|| home.join(".config"))
+                .join("fish")
+                .join("completions")
+                .join("run.fish"),
+            rc_snippet: None,
+        },
+        other => {
+            return Err(RunError::InvalidArgument(format!(
+                "Completions can't be installed automatically for {}; add the output of `run completions {}` to your profile instead",
+                other, other
+            )))
+        }
+    };
+    Ok(target)
+}
+
+/// Write the completion script for `shell` to its standard location
+pub fn install(shell: Shell) -> Result<InstallTarget, RunError> {
+    let home = dirs::home_dir().ok_or_else(|| {
+        RunError::ConfigError("Could not determine the home directory".to_string())
+    })?;
+    let target = install_target(
+        shell,
+        &home,
+        env::var_os("XDG_DATA_HOME").map(PathBuf::from),
+        env::var_os("XDG_CONFIG_HOME").map(PathBuf::from),
+    )?;
+
+    let mut script = Vec::new();
+    let mut cmd = Cli::command();
+    let name = cmd.get_name().to_string();
+    generate(shell, &mut cmd, name, &mut script);
+
+    if let Some(parent) = target.path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&target.path, script)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_install_targets() {
+        let home = Path::new("/home/user");
+
+        let bash = install_target(Shell::Bash, home, None, None).unwrap();
+        assert_eq!(
+            bash.path,
+            home.join(".local/share/bash-completion/completions/run")
+        );
+        assert_eq!(bash.rc_snippet, None);
+
+        let zsh = install_target(Shell::Zsh, home, None, None).unwrap();
+        assert_eq!(zsh.path, home.join(".zsh/completions/_run"));
+        assert!(zsh
+            .rc_snippet
+            .unwrap()
+            .starts_with("fpath=(/home/user/.zsh/completions $fpath)"));
+
+        let fish =
+            install_target(Shell::Fish, home, None, Some(PathBuf::from("/xdg/config"))).unwrap();
+        assert_eq!(
+            fish.path,
+            PathBuf::from("/xdg/config/fish/completions/run.fish")
+        );
+
+        assert!(install_target(Shell::PowerShell, home, None, None).is_err());
+    }
+}
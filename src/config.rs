@@ -9,16 +9,60 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
+use run_core::detectors::custom::{self, CommandEntry, Shell, TaskSettings};
+use run_core::detectors::{DetectedRunner, Ecosystem};
+use run_core::error::RunError;
+use run_core::manifest;
+use run_core::registry::{PatternValidator, ValidatorRegistry};
+use run_core::walk;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Variables set by CI services that don't (always) set `CI`
+const CI_VARS: &[&str] = &[
+    "GITHUB_ACTIONS",
+    "GITLAB_CI",
+    "BUILDKITE",
+    "CIRCLECI",
+    "TRAVIS",
+    "TF_BUILD",
+    "JENKINS_URL",
+    "TEAMCITY_VERSION",
+    "BITBUCKET_BUILD_NUMBER",
+    "CODEBUILD_BUILD_ID",
+    "DRONE",
+    "APPVEYOR",
+];
+
+/// Whether we are running under a CI service: `CI` set to anything but
+/// "false"/"0", or one of the service-specific variables set
+///
+/// On CI, the updater is off, colors are off unless forced, prompts are
+/// skipped and output is timestamped, unless configured otherwise.
+pub fn is_ci() -> bool {
+    ci_from(|name| std::env::var(name).ok())
+}
+
+fn ci_from(var: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(ci) = var("CI") {
+        return !ci.is_empty() && ci != "false" && ci != "0";
+    }
+    CI_VARS
+        .iter()
+        .any(|name| var(name).is_some_and(|v| !v.is_empty()))
+}
 
 /// Default interval between update checks in hours
 const DEFAULT_CHECK_INTERVAL_HOURS: u64 = 2;
 
 /// Configuration for the auto-update system
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(default)]
 pub struct UpdateConfig {
     /// Enable auto-update (default: true)
@@ -28,9 +72,9 @@ pub struct UpdateConfig {
 }
 
 impl UpdateConfig {
-    /// Get whether updates are enabled (default: true)
+    /// Get whether updates are enabled (default: true, false on CI)
     pub fn get_enabled(&self) -> bool {
-        self.enabled.unwrap_or(true)
+        self.enabled.unwrap_or(!is_ci())
     }
 
     /// Get the check interval in hours (default: 2)
@@ -48,8 +92,18 @@ impl UpdateConfig {
     }
 }
 
+/// Extra task patterns layered on top of a runner's built-in validator
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(default)]
+pub struct ValidatorConfig {
+    /// Task patterns treated as supported (`*` matches any text)
+    pub supported: Vec<String>,
+    /// Task patterns treated as not supported, checked first
+    pub unsupported: Vec<String>,
+}
+
 /// Configuration structure for the run CLI
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 #[serde(default)]
 pub struct Config {
     /// Maximum levels to search above current directory
@@ -64,16 +118,158 @@ pub struct Config {
     pub quiet: Option<bool>,
     /// Update configuration section
     pub update: Option<UpdateConfig>,
-    /// Custom commands overrides
-    pub commands: Option<HashMap<String, String>>,
+    /// Custom commands overrides, and per-task settings
+    #[serde(deserialize_with = "custom::deserialize_commands")]
+    #[schemars(schema_with = "custom::commands_schema")]
+    pub commands: Option<HashMap<String, CommandEntry>>,
+    /// Rhai script with hooks (check, env, command)
+    pub script: Option<String>,
+    /// Additional validators per runner (`[validators.npm]`)
+    pub validators: Option<HashMap<String, ValidatorConfig>>,
+    /// Keep tool lookups on disk across invocations
+    pub tool_cache: Option<bool>,
+    /// Directory names skipped when scanning below a directory (replaces
+    /// the defaults: node_modules, vendor, target, .venv, .git)
+    pub skip_dirs: Option<Vec<String>>,
+    /// Run the package manager pinned by `packageManager` through corepack
+    pub corepack: Option<bool>,
+    /// Run commands inside the project's Nix dev shell when the tool is missing
+    pub nix_develop: Option<bool>,
+    /// Search from a symlinked directory's target rather than from where it
+    /// was reached (default: true)
+    pub follow_symlinks: Option<bool>,
+    /// Prefix CLI messages with the time (default: on CI only)
+    pub timestamps: Option<bool>,
+    /// Allow interactive prompts (default: off on CI and without a terminal)
+    pub interactive: Option<bool>,
+    /// Always use this runner instead of resolving one
+    #[serde(alias = "default_runner")]
+    pub runner: Option<String>,
+    /// Task aliases (`t = "test -- --coverage"`), expanded before detection
+    pub aliases: Option<HashMap<String, String>>,
+    /// Environment variables set for every command
+    pub env: Option<HashMap<String, ConfigValue>>,
+    /// Values for `{{name}}` in custom commands (`[vars]`)
+    pub vars: Option<HashMap<String, ConfigValue>>,
+    /// Dotenv files loaded for every command, relative to the project;
+    /// `env` wins over them
+    pub env_files: Option<Vec<String>>,
+    /// Append task output to this file, as `--log-file` does
+    pub log_file: Option<String>,
+    /// Tasks run before and after every task (`[hooks]`)
+    pub hooks: Option<HooksConfig>,
+    /// Shell for custom commands without their own `shell`
+    pub shell: Option<Shell>,
+    /// Config files merged below this one, later ones winning; paths are
+    /// relative to the including file
+    pub include: Vec<String>,
+}
+
+/// An `[env]` or `[vars]` value: the value itself, or a table that can mark
+/// it secret (`{ value = "...", secret = true }`), so the CLI masks it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ConfigValue {
+    Plain(String),
+    Table {
+        value: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        secret: bool,
+    },
+}
+
+impl ConfigValue {
+    pub fn value(&self) -> &str {
+        match self {
+            ConfigValue::Plain(value) | ConfigValue::Table { value, .. } => value,
+        }
+    }
+
+    pub fn is_secret(&self) -> bool {
+        matches!(self, ConfigValue::Table { secret: true, .. })
+    }
+}
+
+impl From<&str> for ConfigValue {
+    fn from(value: &str) -> Self {
+        ConfigValue::Plain(value.to_string())
+    }
+}
+
+/// Tasks to run around every task, each a task name and its arguments
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, JsonSchema)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run first; a failure stops the task from running
+    pub pre: Vec<String>,
+    /// Run afterwards, even when the task failed
+    pub post: Vec<String>,
+}
+
+/// Parse a duration such as `90s`, `5m`, `1h30m` or `500ms`; a bare number
+/// is seconds
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration '{}' (e.g. 90s, 5m, 1h30m)", text);
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(secs) = text.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::ZERO;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let value: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let part = match &rest[..unit] {
+            "ms" => Duration::from_millis(value),
+            "s" => Duration::from_secs(value),
+            "m" => Duration::from_secs(value * 60),
+            "h" => Duration::from_secs(value * 3600),
+            _ => return Err(invalid()),
+        };
+        total += part;
+        rest = &rest[unit..];
+    }
+    Ok(total)
+}
+
+/// Merge two maps, entries from `over` winning
+fn merge_map<V>(
+    base: Option<HashMap<String, V>>,
+    over: Option<HashMap<String, V>>,
+) -> Option<HashMap<String, V>> {
+    match (base, over) {
+        (Some(mut base), Some(over)) => {
+            base.extend(over);
+            Some(base)
+        }
+        (base, over) => over.or(base),
+    }
 }
 
 impl Config {
     /// Load configuration from default locations with precedence:
     /// 1. Defaults (hardcoded)
     /// 2. Global config (~/.config/run/config.toml)
-    /// 3. Local config (./run.toml)
+    /// 3. Manifest metadata (`"run"` in package.json, `[package.metadata.run]`
+    ///    in Cargo.toml)
+    /// 4. Local config (./run.toml, else run.yaml, run.yml or run.json)
+    /// 5. Environment (`RUN_RUNNER`, `RUN_IGNORE`, `RUN_LEVELS`)
     pub fn load() -> Self {
+        Self::load_from(Path::new("."))
+    }
+
+    /// Like [`Config::load`], reading the local files from `dir`
+    pub fn load_from(dir: &Path) -> Self {
         let mut config = Config::default();
 
         // Load global config
@@ -83,13 +279,290 @@ impl Config {
             }
         }
 
-        // Load local config
-        let local_path = PathBuf::from("run.toml");
-        if let Ok(local_config) = Self::load_from_file(&local_path) {
-            config = config.merge(local_config);
+        // Projects can keep their settings in the manifest they already have
+        for manifest_config in Self::from_manifests(dir) {
+            config = config.merge(manifest_config);
+        }
+
+        // Load local config: run.toml, else run.yaml, run.yml or run.json
+        if let Some(local_path) = custom::config_file(dir) {
+            if let Ok(local_config) = Self::load_from_file(&local_path) {
+                config = config.merge(local_config);
+            }
+        }
+
+        config.merge(Self::from_env())
+    }
+
+    /// Settings embedded in the project's manifests: Cargo.toml's
+    /// `[workspace.metadata.run]` and `[package.metadata.run]`, then
+    /// package.json's `"run"` section, in increasing precedence
+    pub fn from_manifests(dir: &Path) -> Vec<Self> {
+        let mut configs = Vec::new();
+
+        if let Some(cargo) = manifest::toml(&dir.join("Cargo.toml")) {
+            for section in ["workspace", "package"] {
+                let run = cargo
+                    .get(section)
+                    .and_then(|s| s.get("metadata"))
+                    .and_then(|m| m.get("run"));
+                if let Some(Ok(config)) = run.map(|run| run.clone().try_into()) {
+                    configs.push(config);
+                }
+            }
         }
 
-        config
+        if let Some(package) = manifest::json(&dir.join("package.json")) {
+            if let Some(Ok(config)) = package
+                .get("run")
+                .map(|run| serde_json::from_value(run.clone()))
+            {
+                configs.push(config);
+            }
+        }
+
+        configs
+    }
+
+    /// Settings for `task` from a `[commands]` table entry; the run.toml next
+    /// to the detected runners wins over the merged config
+    pub fn task_settings(&self, task: &str, working_dir: &Path) -> TaskSettings {
+        if let Some(entry) = custom::entries(working_dir).get(task) {
+            return entry.settings();
+        }
+        self.commands
+            .as_ref()
+            .and_then(|commands| commands.get(task))
+            .map(CommandEntry::settings)
+            .unwrap_or_default()
+    }
+
+    /// The tasks to run before `task`, following `depends_on` transitively:
+    /// each once, every dependency before the tasks that need it. A cycle
+    /// is a [`RunError::ConfigError`] naming it.
+    pub fn dependencies(&self, task: &str, working_dir: &Path) -> Result<Vec<String>, RunError> {
+        let mut order = Vec::new();
+        self.visit_dependencies(task, working_dir, &mut Vec::new(), &mut order)?;
+        order.pop();
+        Ok(order)
+    }
+
+    /// Depth-first step of [`Self::dependencies`]; `path` holds the tasks
+    /// being visited, so meeting one of them again closes a cycle
+    fn visit_dependencies(
+        &self,
+        task: &str,
+        working_dir: &Path,
+        path: &mut Vec<String>,
+        order: &mut Vec<String>,
+    ) -> Result<(), RunError> {
+        if let Some(start) = path.iter().position(|t| t == task) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(task.to_string());
+            return Err(RunError::ConfigError(format!(
+                "Dependency cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+        if order.iter().any(|t| t == task) {
+            return Ok(());
+        }
+        path.push(task.to_string());
+        for dependency in self.task_settings(task, working_dir).depends_on {
+            self.visit_dependencies(&dependency, working_dir, path, order)?;
+        }
+        path.pop();
+        order.push(task.to_string());
+        Ok(())
+    }
+
+    /// The values for `{{name}}` in custom commands: `[vars]`, overridden by
+    /// `RUN_VAR_<name>` variables of `env`
+    pub fn template_vars(
+        &self,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> HashMap<String, String> {
+        let mut vars: HashMap<String, String> = self
+            .vars
+            .iter()
+            .flatten()
+            .map(|(name, value)| (name.clone(), value.value().to_string()))
+            .collect();
+        vars.extend(
+            env.into_iter().filter_map(|(key, value)| {
+                Some((key.strip_prefix("RUN_VAR_")?.to_string(), value))
+            }),
+        );
+        vars
+    }
+
+    /// The variables of `[env]`, for every command
+    pub fn env_vars(&self) -> HashMap<String, String> {
+        self.env
+            .iter()
+            .flatten()
+            .map(|(key, value)| (key.clone(), value.value().to_string()))
+            .collect()
+    }
+
+    /// The values to mask in output: `[env]` entries marked secret, and the
+    /// values in `vars` (from [`Self::template_vars`]) of `[vars]` entries
+    /// marked secret
+    pub fn secrets(&self, vars: &HashMap<String, String>) -> Vec<String> {
+        let env = self
+            .env
+            .iter()
+            .flatten()
+            .filter(|(_, value)| value.is_secret())
+            .map(|(_, value)| value.value().to_string());
+        let template = self
+            .vars
+            .iter()
+            .flatten()
+            .filter(|(_, value)| value.is_secret())
+            .filter_map(|(name, _)| vars.get(name).cloned());
+        env.chain(template)
+            .filter(|secret| !secret.is_empty())
+            .collect()
+    }
+
+    /// The detected runner `task` is handed to with `use`, and the runner's
+    /// task followed by its leading arguments (`task`, else `task` itself)
+    pub fn delegation(&self, task: &str, working_dir: &Path) -> Option<(String, Vec<String>)> {
+        let settings = self.task_settings(task, working_dir);
+        let runner = settings.runner?;
+        let mut words = custom::words(settings.task.as_deref().unwrap_or(task));
+        if words.is_empty() {
+            words.push(task.to_string());
+        }
+        Some((runner, words))
+    }
+
+    /// The combinations of `task`'s `matrix` values, each `matrix.<name>`
+    /// and its value for `{{...}}`, in the order of the names then of their
+    /// values; a single empty one for a task without a matrix
+    pub fn task_matrix(&self, task: &str, working_dir: &Path) -> Vec<Vec<(String, String)>> {
+        let mut combinations = vec![Vec::new()];
+        for (name, values) in self.task_settings(task, working_dir).matrix {
+            let name = format!("matrix.{}", name);
+            let mut expanded = Vec::new();
+            for combination in &combinations {
+                for value in &values {
+                    let mut combination = combination.clone();
+                    combination.push((name.clone(), value.clone()));
+                    expanded.push(combination);
+                }
+            }
+            combinations = expanded;
+        }
+        combinations
+    }
+
+    /// The dotenv files for `task`, resolved from `working_dir`: `env_files`,
+    /// then the task's `env_file`, later ones winning
+    pub fn task_env_files(&self, task: &str, working_dir: &Path) -> Vec<PathBuf> {
+        self.env_files
+            .iter()
+            .flatten()
+            .chain(&self.task_settings(task, working_dir).env_file)
+            .map(|path| working_dir.join(path))
+            .collect()
+    }
+
+    /// The hooks to run before and after `task`: the global `[hooks]`
+    /// around the task's own `pre` and `post`. Global hooks that run `task`
+    /// itself are left out, so `run lint` with `pre = ["lint"]` runs it once.
+    pub fn task_hooks(&self, task: &str, working_dir: &Path) -> HooksConfig {
+        let global = self.hooks.clone().unwrap_or_default();
+        let settings = self.task_settings(task, working_dir);
+        let other = |hook: &String| {
+            shell_words::split(hook)
+                .ok()
+                .and_then(|words| words.into_iter().next())
+                .is_none_or(|hook| hook != task)
+        };
+        HooksConfig {
+            pre: global
+                .pre
+                .into_iter()
+                .filter(other)
+                .chain(settings.pre)
+                .collect(),
+            post: settings
+                .post
+                .into_iter()
+                .chain(global.post.into_iter().filter(other))
+                .collect(),
+        }
+    }
+
+    /// The shell `task`'s custom command runs in: its own `shell`, else the
+    /// global one; `None` runs it directly
+    pub fn task_shell(&self, task: &str, working_dir: &Path) -> Option<Shell> {
+        self.task_settings(task, working_dir)
+            .shell
+            .or_else(|| self.shell.clone())
+    }
+
+    /// The task and arguments `task` with `args` expands to when it is an
+    /// alias
+    ///
+    /// Both sides are split on their first `--`, as `Cli::tasks` leaves it:
+    /// the alias's arguments come first, then the given ones, then
+    /// everything after either `--` behind a single one.
+    /// `t = "test -- --coverage"` makes `run t unit -- --watch` run
+    /// `run test unit -- --coverage --watch`.
+    pub fn expand_alias(&self, task: &str, args: &[String]) -> Option<(String, Vec<String>)> {
+        let expansion = self.aliases.as_ref()?.get(task)?;
+        let words = shell_words::split(expansion).ok()?;
+        let (task, words) = words.split_first()?;
+        let split = |words: &[String]| match words.iter().position(|word| word == "--") {
+            Some(index) => (words[..index].to_vec(), words[index + 1..].to_vec()),
+            None => (words.to_vec(), Vec::new()),
+        };
+
+        let (mut expanded, mut passthrough) = split(words);
+        let (given, given_passthrough) = split(args);
+        expanded.extend(given);
+        passthrough.extend(given_passthrough);
+        if !passthrough.is_empty() {
+            expanded.push("--".to_string());
+            expanded.extend(passthrough);
+        }
+        Some((task.clone(), expanded))
+    }
+
+    /// Settings taken from `RUN_*` environment variables, so CI pipelines
+    /// and direnv setups can steer detection without editing files
+    pub fn from_env() -> Self {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Self {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        Config {
+            runner: var("RUN_RUNNER").map(|runner| runner.trim().to_string()),
+            ignore_tools: var("RUN_IGNORE")
+                .map(|tools| {
+                    tools
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|tool| !tool.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default(),
+            max_levels: var("RUN_LEVELS").and_then(|levels| levels.trim().parse().ok()),
+            ..Config::default()
+        }
+    }
+
+    /// JSON Schema of the config format (run.toml, run.yaml, run.json and
+    /// the global config), derived from these structs
+    pub fn schema() -> schemars::Schema {
+        let mut schema = schemars::schema_for!(Config);
+        schema.insert("title".to_string(), "run configuration".into());
+        schema
     }
 
     /// Get the path to the global configuration file
@@ -107,11 +580,27 @@ impl Config {
         dirs::config_dir().map(|p| p.join("run").join("last_update_check"))
     }
 
-    /// Load configuration from a specific file
+    /// Get the path to the persistent tool lookup cache
+    pub fn tool_cache_path() -> Option<PathBuf> {
+        dirs::cache_dir().map(|p| p.join("run").join("which.json"))
+    }
+
+    /// Load configuration from a specific file, merged over the files it
+    /// includes
     pub fn load_from_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
         let content = fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&content)?;
-        Ok(config)
+        let config: Config = custom::parse(path, &content)?;
+        if config.include.is_empty() {
+            return Ok(config);
+        }
+
+        let mut merged = Config::default();
+        let mut files = custom::config_files(path)?;
+        files.pop();
+        for (included, content) in files {
+            merged = merged.merge(custom::parse(&included, &content)?);
+        }
+        Ok(merged.merge(config))
     }
 
     /// Merge two configs, with other taking precedence
@@ -132,18 +621,43 @@ impl Config {
                 (Some(base), None) => Some(base),
                 (None, None) => None,
             },
-            commands: match (self.commands, other.commands) {
-                (Some(mut base), Some(over)) => {
-                    base.extend(over);
-                    Some(base)
-                }
-                (None, Some(over)) => Some(over),
-                (Some(base), None) => Some(base),
-                (None, None) => None,
-            },
+            commands: merge_map(self.commands, other.commands),
+            script: other.script.or(self.script),
+            validators: merge_map(self.validators, other.validators),
+            tool_cache: other.tool_cache.or(self.tool_cache),
+            skip_dirs: other.skip_dirs.or(self.skip_dirs),
+            corepack: other.corepack.or(self.corepack),
+            nix_develop: other.nix_develop.or(self.nix_develop),
+            follow_symlinks: other.follow_symlinks.or(self.follow_symlinks),
+            timestamps: other.timestamps.or(self.timestamps),
+            interactive: other.interactive.or(self.interactive),
+            runner: other.runner.or(self.runner),
+            aliases: merge_map(self.aliases, other.aliases),
+            env: merge_map(self.env, other.env),
+            vars: merge_map(self.vars, other.vars),
+            env_files: other.env_files.or(self.env_files),
+            log_file: other.log_file.or(self.log_file),
+            hooks: other.hooks.or(self.hooks),
+            shell: other.shell.or(self.shell),
+            include: [self.include, other.include].concat(),
         }
     }
 
+    /// Build the registry of validators declared in `[validators]`
+    pub fn validator_registry(&self) -> ValidatorRegistry {
+        let mut registry = ValidatorRegistry::new();
+        for (runner, validator) in self.validators.iter().flatten() {
+            registry.register(
+                runner,
+                Arc::new(PatternValidator::new(
+                    validator.supported.clone(),
+                    validator.unsupported.clone(),
+                )),
+            );
+        }
+        registry
+    }
+
     /// Get max levels with default fallback
     pub fn get_max_levels(&self) -> u8 {
         self.max_levels.unwrap_or(3)
@@ -156,7 +670,7 @@ impl Config {
         if let Some(ref update) = self.update {
             return update.get_enabled();
         }
-        self.auto_update.unwrap_or(true)
+        self.auto_update.unwrap_or(!is_ci())
     }
 
     /// Get the update configuration, creating a default if not set
@@ -169,6 +683,44 @@ impl Config {
         self.verbose.unwrap_or(false)
     }
 
+    /// Get tool cache setting with default fallback
+    pub fn get_tool_cache(&self) -> bool {
+        self.tool_cache.unwrap_or(false)
+    }
+
+    /// Directory names skipped by downward scans, with default fallback
+    pub fn get_skip_dirs(&self) -> Vec<String> {
+        self.skip_dirs
+            .clone()
+            .unwrap_or_else(walk::default_skip_dirs)
+    }
+
+    /// Get corepack setting with default fallback
+    pub fn get_corepack(&self) -> bool {
+        self.corepack.unwrap_or(false)
+    }
+
+    /// Get follow_symlinks setting with default fallback
+    pub fn get_follow_symlinks(&self) -> bool {
+        self.follow_symlinks.unwrap_or(true)
+    }
+
+    /// Get timestamps setting, defaulting to whether we run on CI
+    pub fn get_timestamps(&self) -> bool {
+        self.timestamps.unwrap_or_else(is_ci)
+    }
+
+    /// Whether prompts may wait for input: not on CI, and only with a terminal
+    pub fn get_interactive(&self) -> bool {
+        self.interactive
+            .unwrap_or_else(|| !is_ci() && std::io::stdin().is_terminal())
+    }
+
+    /// Get Nix dev shell setting with default fallback
+    pub fn get_nix_develop(&self) -> bool {
+        self.nix_develop.unwrap_or(false)
+    }
+
     /// Get quiet setting with default fallback
     pub fn get_quiet(&self) -> bool {
         self.quiet.unwrap_or(false)
@@ -189,6 +741,171 @@ impl Config {
     }
 }
 
+/// Contents of a starter run.toml (`run init`): every task the detected
+/// runners enumerate, commented out under `[commands]` with the command `run`
+/// currently runs for it, ready to be uncommented and edited
+pub fn starter_toml(runners: &[DetectedRunner], working_dir: &Path) -> String {
+    let mut out = String::from(
+        "# Project settings for `run`. Settings from ~/.config/run/config.toml\n\
+         # apply unless overridden here.\n\
+         \n\
+         # Always use this runner instead of resolving one\n\
+         # runner = \"npm\"\n\
+         \n\
+         # Shortcuts expanded before detection\n\
+         # [aliases]\n\
+         # t = \"test -- --coverage\"\n\
+         \n\
+         # Commands defined here win over every detected runner.\n\
+         [commands]\n",
+    );
+
+    let mut listed = false;
+    for runner in runners.iter().filter(|r| r.ecosystem != Ecosystem::Custom) {
+        let tasks = runner.list_commands(working_dir);
+        if tasks.is_empty() {
+            continue;
+        }
+        out.push_str(&format!(
+            "\n# From {} ({})\n",
+            runner.name, runner.detected_file
+        ));
+        for task in tasks {
+            let command = shell_words::join(runner.build_command(&task, &[]));
+            out.push_str(&format!(
+                "# {} = {}\n",
+                toml_key(&task),
+                toml_string(&command)
+            ));
+        }
+        listed = true;
+    }
+    if !listed {
+        out.push_str("# build = \"cargo build --release\"\n");
+    }
+
+    out
+}
+
+/// `key` as a TOML key, quoted unless it's a valid bare key
+fn toml_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        toml_string(key)
+    }
+}
+
+fn toml_string(value: &str) -> String {
+    toml::Value::String(value.to_string()).to_string()
+}
+
+/// `run config get`: the value at dotted `key` in a config file's contents,
+/// strings unquoted
+pub fn get_setting(content: &str, key: &str) -> Result<Option<String>, RunError> {
+    let doc = parse_document(content)?;
+    let mut item = doc.as_item();
+    for part in key.split('.') {
+        match item.get(part) {
+            Some(next) => item = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(match item.as_str() {
+        Some(text) => text.to_string(),
+        None => item.to_string().trim().to_string(),
+    }))
+}
+
+/// `run config set`: `content` with dotted `key` set, keeping comments
+///
+/// `value` is read as TOML (`false`, `5`, `["npm"]`) when that gives a valid
+/// setting, and as a string otherwise.
+pub fn set_setting(content: &str, key: &str, value: &str) -> Result<String, RunError> {
+    let typed = value.parse::<toml_edit::Value>().ok();
+    let mut last_error = None;
+    for candidate in typed
+        .into_iter()
+        .chain(std::iter::once(toml_edit::Value::from(value)))
+    {
+        let mut doc = parse_document(content)?;
+        let (parents, name) = split_key(key)?;
+        let mut table = doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+        for part in parents {
+            let entry = table
+                .entry(part)
+                .or_insert(toml_edit::Item::Table(toml_edit::Table::new()));
+            table = entry
+                .as_table_like_mut()
+                .ok_or_else(|| RunError::ConfigError(format!("'{}' is not a table", part)))?;
+        }
+        table.insert(name, toml_edit::value(candidate));
+
+        let updated = doc.to_string();
+        match check_setting(&updated, key) {
+            Ok(()) => return Ok(updated),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.expect("at least one candidate value"))
+}
+
+/// `run config unset`: `content` without dotted `key`, or `None` if it
+/// wasn't set
+pub fn unset_setting(content: &str, key: &str) -> Result<Option<String>, RunError> {
+    let mut doc = parse_document(content)?;
+    let (parents, name) = split_key(key)?;
+    let mut table = doc.as_table_mut() as &mut dyn toml_edit::TableLike;
+    for part in parents {
+        match table.get_mut(part).and_then(|t| t.as_table_like_mut()) {
+            Some(next) => table = next,
+            None => return Ok(None),
+        }
+    }
+    Ok(table.remove(name).map(|_| doc.to_string()))
+}
+
+fn parse_document(content: &str) -> Result<toml_edit::DocumentMut, RunError> {
+    content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| RunError::ConfigError(e.to_string().trim_end().to_string()))
+}
+
+fn split_key(key: &str) -> Result<(Vec<&str>, &str), RunError> {
+    let mut parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|p| p.is_empty()) {
+        return Err(RunError::ConfigError(format!(
+            "Invalid setting name '{}'",
+            key
+        )));
+    }
+    let name = parts.pop().unwrap_or_default();
+    Ok((parts, name))
+}
+
+/// Whether `content` is a valid config that still has `key` once read back
+/// (unknown settings are dropped when reading)
+fn check_setting(content: &str, key: &str) -> Result<(), RunError> {
+    let config: Config = toml::from_str(content).map_err(|e| {
+        RunError::ConfigError(format!("Invalid value for '{}': {}", key, e.message()))
+    })?;
+    let known = toml::Value::try_from(&config).map_err(|e| RunError::ConfigError(e.to_string()))?;
+    let mut item = &known;
+    for part in key.split('.') {
+        match item.get(part) {
+            Some(next) => item = next,
+            None => {
+                return Err(RunError::ConfigError(format!("Unknown setting '{}'", key)));
+            }
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -198,7 +915,7 @@ mod tests {
     fn test_default_config() {
         let config = Config::default();
         assert_eq!(config.get_max_levels(), 3);
-        assert!(config.get_auto_update());
+        assert_eq!(config.get_auto_update(), !is_ci());
         assert!(!config.get_verbose());
         assert!(!config.get_quiet());
     }
@@ -213,6 +930,24 @@ mod tests {
             quiet: None,
             update: None,
             commands: None,
+            script: None,
+            validators: None,
+            tool_cache: None,
+            skip_dirs: None,
+            corepack: None,
+            nix_develop: None,
+            follow_symlinks: None,
+            timestamps: None,
+            interactive: None,
+            runner: None,
+            aliases: None,
+            env: None,
+            vars: None,
+            env_files: None,
+            log_file: None,
+            hooks: None,
+            shell: None,
+            include: Vec::new(),
         };
 
         let override_config = Config {
@@ -223,6 +958,24 @@ mod tests {
             quiet: None,
             update: None,
             commands: None,
+            script: None,
+            validators: None,
+            tool_cache: None,
+            skip_dirs: None,
+            corepack: None,
+            nix_develop: None,
+            follow_symlinks: None,
+            timestamps: None,
+            interactive: None,
+            runner: None,
+            aliases: None,
+            env: None,
+            vars: None,
+            env_files: None,
+            log_file: None,
+            hooks: None,
+            shell: None,
+            include: Vec::new(),
         };
 
         let merged = base.merge(override_config);
@@ -235,8 +988,8 @@ mod tests {
     #[test]
     fn test_merge_commands() {
         let mut base_cmds = HashMap::new();
-        base_cmds.insert("base".to_string(), "echo base".to_string());
-        base_cmds.insert("both".to_string(), "echo base_both".to_string());
+        base_cmds.insert("base".to_string(), "echo base".into());
+        base_cmds.insert("both".to_string(), "echo base_both".into());
 
         let base = Config {
             commands: Some(base_cmds),
@@ -244,8 +997,8 @@ mod tests {
         };
 
         let mut override_cmds = HashMap::new();
-        override_cmds.insert("over".to_string(), "echo over".to_string());
-        override_cmds.insert("both".to_string(), "echo over_both".to_string());
+        override_cmds.insert("over".to_string(), "echo over".into());
+        override_cmds.insert("both".to_string(), "echo over_both".into());
 
         let override_config = Config {
             commands: Some(override_cmds),
@@ -255,9 +1008,9 @@ mod tests {
         let merged = base.merge(override_config);
         let cmds = merged.commands.unwrap();
 
-        assert_eq!(cmds.get("base").unwrap(), "echo base");
-        assert_eq!(cmds.get("over").unwrap(), "echo over");
-        assert_eq!(cmds.get("both").unwrap(), "echo over_both");
+        assert_eq!(cmds["base"].command(), "echo base");
+        assert_eq!(cmds["over"].command(), "echo over");
+        assert_eq!(cmds["both"].command(), "echo over_both");
     }
 
     #[test]
@@ -293,10 +1046,221 @@ verbose = true
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_secrets() {
+        let config: Config = toml::from_str(
+            "[env]\n\
+             TOKEN = { value = \"s3cr3t\", secret = true }\n\
+             LEVEL = { value = \"debug\" }\n\
+             PLAIN = \"visible\"\n\
+             [vars]\n\
+             password = { value = \"hunter2\", secret = true }\n\
+             user = \"bob\"\n",
+        )
+        .unwrap();
+
+        let env = config.env_vars();
+        assert_eq!(
+            (env["TOKEN"].as_str(), env["LEVEL"].as_str()),
+            ("s3cr3t", "debug")
+        );
+        let vars = config.template_vars([]);
+        assert_eq!(vars["password"], "hunter2");
+
+        let mut secrets = config.secrets(&vars);
+        secrets.sort();
+        assert_eq!(secrets, vec!["hunter2", "s3cr3t"]);
+        // An override is what gets masked
+        let vars = config.template_vars([("RUN_VAR_password".to_string(), "other".to_string())]);
+        assert!(config.secrets(&vars).contains(&"other".to_string()));
+    }
+
+    #[test]
+    fn test_template_vars() {
+        let config: Config =
+            toml::from_str("[vars]\nregistry = \"ghcr.io/acme\"\ntag = \"dev\"\n").unwrap();
+        let vars = config.template_vars([
+            ("RUN_VAR_tag".to_string(), "v2".to_string()),
+            ("RUN_VAR_region".to_string(), "eu".to_string()),
+            ("TAG".to_string(), "ignored".to_string()),
+        ]);
+
+        assert_eq!(vars.len(), 3);
+        assert_eq!(vars["registry"], "ghcr.io/acme");
+        assert_eq!(vars["tag"], "v2");
+        assert_eq!(vars["region"], "eu");
+    }
+
+    #[test]
+    fn test_delegation() {
+        let dir = tempdir().unwrap();
+        let config: Config = toml::from_str(
+            "[commands]\n\
+             test = { use = \"cargo\", task = \"nextest run --no-fail-fast\" }\n\
+             build = { use = \"make\" }\n\
+             lint = \"eslint .\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.delegation("test", dir.path()),
+            Some((
+                "cargo".to_string(),
+                vec![
+                    "nextest".to_string(),
+                    "run".to_string(),
+                    "--no-fail-fast".to_string()
+                ]
+            ))
+        );
+        assert_eq!(
+            config.delegation("build", dir.path()),
+            Some(("make".to_string(), vec!["build".to_string()]))
+        );
+        assert_eq!(config.delegation("lint", dir.path()), None);
+        assert_eq!(config.delegation("missing", dir.path()), None);
+    }
+
+    #[test]
+    fn test_task_matrix() {
+        let dir = tempdir().unwrap();
+        let config: Config = toml::from_str(
+            "[commands.build]\n\
+             cmd = \"cargo build --target {{matrix.target}}\"\n\
+             matrix = { target = [\"x86_64\", \"aarch64\"], profile = [\"dev\", \"release\"] }\n",
+        )
+        .unwrap();
+        let pair = |name: &str, value: &str| (name.to_string(), value.to_string());
+
+        assert_eq!(
+            config.task_matrix("build", dir.path()),
+            vec![
+                vec![
+                    pair("matrix.profile", "dev"),
+                    pair("matrix.target", "x86_64")
+                ],
+                vec![
+                    pair("matrix.profile", "dev"),
+                    pair("matrix.target", "aarch64")
+                ],
+                vec![
+                    pair("matrix.profile", "release"),
+                    pair("matrix.target", "x86_64")
+                ],
+                vec![
+                    pair("matrix.profile", "release"),
+                    pair("matrix.target", "aarch64")
+                ],
+            ]
+        );
+        assert_eq!(config.task_matrix("test", dir.path()), vec![Vec::new()]);
+    }
+
+    #[test]
+    fn test_task_env_files() {
+        let dir = tempdir().unwrap();
+        let config: Config = toml::from_str(
+            "env_files = [\".env\", \".env.local\"]\n\
+             [commands]\n\
+             test = { env_file = \".env.test\" }\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.task_env_files("test", dir.path()),
+            vec![
+                dir.path().join(".env"),
+                dir.path().join(".env.local"),
+                dir.path().join(".env.test")
+            ]
+        );
+        assert_eq!(config.task_env_files("build", dir.path()).len(), 2);
+        assert!(Config::default()
+            .task_env_files("test", dir.path())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_load_includes() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("ci")).unwrap();
+        fs::write(
+            dir.path().join("shared.toml"),
+            "max_levels = 2\nquiet = true\n[commands]\nlint = \"eslint .\"\nbuild = \"make\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("ci/run.toml"),
+            "include = [\"../shared.toml\"]\nmax_levels = 4\n",
+        )
+        .unwrap();
+        let path = dir.path().join("run.toml");
+        fs::write(
+            &path,
+            "include = [\"shared.toml\", \"ci/run.toml\"]\nquiet = false\n[commands]\nbuild = \"cargo build\"\n",
+        )
+        .unwrap();
+
+        let config = Config::load_from_file(&path).unwrap();
+        // Later includes win over earlier ones, the file itself over both
+        assert_eq!(config.max_levels, Some(4));
+        assert_eq!(config.quiet, Some(false));
+        let commands = config.commands.unwrap();
+        assert_eq!(commands["lint"].command(), "eslint .");
+        assert_eq!(commands["build"].command(), "cargo build");
+
+        fs::write(dir.path().join("shared.toml"), "include = [\"run.toml\"]\n").unwrap();
+        let error = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(error.contains("Include cycle"), "{error}");
+
+        fs::write(&path, "include = [\"missing.toml\"]\n").unwrap();
+        let error = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(error.contains("missing.toml"), "{error}");
+    }
+
+    #[test]
+    fn test_load_yaml_and_json() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.yml"),
+            "max_levels: 2\nenv:\n  TOKEN: { value: abc, secret: true }\ncommands:\n  test:\n    timeout: 90s\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("run.json"), r#"{ "max_levels": 5 }"#).unwrap();
+
+        let config = Config::load_from(dir.path());
+        assert_eq!(config.max_levels, Some(2));
+        assert_eq!(config.env_vars()["TOKEN"], "abc");
+        assert_eq!(
+            config.task_settings("test", dir.path()).timeout.as_deref(),
+            Some("90s")
+        );
+
+        fs::remove_file(dir.path().join("run.yml")).unwrap();
+        assert_eq!(Config::load_from(dir.path()).max_levels, Some(5));
+
+        let path = dir.path().join("run.json");
+        fs::write(&path, "{ \"max_levels\": [] }").unwrap();
+        let error = Config::load_from_file(&path).unwrap_err().to_string();
+        assert!(error.contains("run.json"), "{error}");
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema = serde_json::to_value(Config::schema()).unwrap();
+        assert_eq!(schema["title"], "run configuration");
+        assert!(schema["properties"]["max_levels"].is_object());
+        // Unknown task keys make a namespace, so tasks reject them
+        let task = &schema["$defs"]["TaskSettings"];
+        assert_eq!(task["additionalProperties"], false);
+        assert!(task["properties"]["use"].is_object());
+        assert!(task["properties"]["condition"].is_object());
+    }
+
     #[test]
     fn test_update_config_defaults() {
         let update_config = UpdateConfig::default();
-        assert!(update_config.get_enabled());
+        assert_eq!(update_config.get_enabled(), !is_ci());
         assert_eq!(update_config.get_check_interval_hours(), 2);
     }
 
@@ -367,4 +1331,368 @@ enabled = false
         // [update].enabled should override legacy auto_update
         assert!(!config.get_auto_update());
     }
+
+    #[test]
+    fn test_validator_registry_from_config() {
+        let dir = tempdir().unwrap();
+        let config_path = dir.path().join("run.toml");
+        fs::write(
+            &config_path,
+            r#"
+[validators.npm]
+supported = ["ci:*"]
+unsupported = ["ci:legacy"]
+"#,
+        )
+        .unwrap();
+
+        let config = Config::load_from_file(&config_path).unwrap();
+        let mut runners = vec![crate::DetectedRunner::new(
+            "npm",
+            "package.json",
+            run_core::Ecosystem::NodeJs,
+            4,
+        )];
+        config.validator_registry().apply(&mut runners);
+
+        assert_eq!(
+            runners[0].supports_command("ci:lint", dir.path()),
+            run_core::CommandSupport::Supported
+        );
+        assert_eq!(
+            runners[0].supports_command("ci:legacy", dir.path()),
+            run_core::CommandSupport::NotSupported
+        );
+    }
+
+    #[test]
+    fn test_skip_dirs() {
+        assert_eq!(Config::default().get_skip_dirs(), walk::default_skip_dirs());
+
+        let config: Config = toml::from_str("skip_dirs = [\"dist\"]").unwrap();
+        let merged = Config::default().merge(config);
+        assert_eq!(merged.get_skip_dirs(), vec!["dist".to_string()]);
+    }
+
+    #[test]
+    fn test_env_layer() {
+        let vars: HashMap<&str, &str> = [
+            ("RUN_RUNNER", "pnpm"),
+            ("RUN_IGNORE", "npm, yarn,"),
+            ("RUN_LEVELS", "5"),
+        ]
+        .into_iter()
+        .collect();
+        let env = Config::from_vars(|name| vars.get(name).map(|v| v.to_string()));
+        assert_eq!(env.runner.as_deref(), Some("pnpm"));
+        assert_eq!(
+            env.ignore_tools,
+            vec!["npm".to_string(), "yarn".to_string()]
+        );
+        assert_eq!(env.get_max_levels(), 5);
+
+        // The environment overrides config files; unset variables don't
+        let file: Config = toml::from_str("max_levels = 2\nignore_tools = [\"bun\"]").unwrap();
+        let merged = file.clone().merge(env);
+        assert_eq!(merged.get_max_levels(), 5);
+        assert_eq!(
+            merged.ignore_tools,
+            vec!["npm".to_string(), "yarn".to_string()]
+        );
+
+        let empty = Config::from_vars(|name| (name == "RUN_LEVELS").then(|| "lots".to_string()));
+        let merged = file.merge(empty);
+        assert_eq!(merged.get_max_levels(), 2);
+        assert_eq!(merged.ignore_tools, vec!["bun".to_string()]);
+        assert_eq!(merged.runner, None);
+    }
+
+    #[test]
+    fn test_manifest_metadata() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            r#"
+[package]
+name = "app"
+
+[package.metadata.run]
+default_runner = "cargo"
+
+[package.metadata.run.commands]
+serve = "cargo run -- serve"
+
+[package.metadata.run.env]
+RUST_LOG = "debug"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"run": {"aliases": {"t": "test -- --coverage"}, "env": {"RUST_LOG": "info"}}}"#,
+        )
+        .unwrap();
+
+        let mut config = Config::default();
+        for layer in Config::from_manifests(dir.path()) {
+            config = config.merge(layer);
+        }
+        assert_eq!(config.runner.as_deref(), Some("cargo"));
+        assert_eq!(
+            config
+                .commands
+                .unwrap()
+                .get("serve")
+                .map(|entry| entry.command().into_owned())
+                .as_deref(),
+            Some("cargo run -- serve")
+        );
+        // package.json is applied after Cargo.toml
+        assert_eq!(config.env.unwrap()["RUST_LOG"].value(), "info");
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        let owned = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+        let config = Config {
+            aliases: Some(HashMap::from([
+                ("t".to_string(), "test -- --coverage".to_string()),
+                ("u".to_string(), "test unit".to_string()),
+                ("empty".to_string(), "".to_string()),
+            ])),
+            ..Config::default()
+        };
+        let expand = |task: &str, args: &[&str]| config.expand_alias(task, &owned(args));
+
+        assert_eq!(
+            expand("t", &[]),
+            Some(("test".to_string(), owned(&["--", "--coverage"])))
+        );
+        // Given arguments go before the alias's `--`, and the words after
+        // both `--` end up behind a single one
+        assert_eq!(
+            expand("t", &["api"]),
+            Some(("test".to_string(), owned(&["api", "--", "--coverage"])))
+        );
+        assert_eq!(
+            expand("t", &["api", "--", "--watch"]),
+            Some((
+                "test".to_string(),
+                owned(&["api", "--", "--coverage", "--watch"])
+            ))
+        );
+        assert_eq!(
+            expand("t", &["--", "--ci"]),
+            Some(("test".to_string(), owned(&["--", "--coverage", "--ci"])))
+        );
+        assert_eq!(
+            expand("u", &["api", "--", "--watch"]),
+            Some(("test".to_string(), owned(&["unit", "api", "--", "--watch"])))
+        );
+        assert_eq!(expand("empty", &[]), None);
+        assert_eq!(expand("test", &[]), None);
+    }
+
+    #[test]
+    fn test_ci_detection() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == name)
+                    .map(|(_, v)| v.to_string())
+            }
+        };
+        assert!(!ci_from(env(&[])));
+        assert!(ci_from(env(&[("CI", "true")])));
+        assert!(ci_from(env(&[("GITHUB_ACTIONS", "true")])));
+        assert!(ci_from(env(&[("JENKINS_URL", "https://ci.example.com")])));
+        // An explicit CI=false wins over service variables
+        assert!(!ci_from(env(&[("CI", "false"), ("GITLAB_CI", "true")])));
+        assert!(!ci_from(env(&[
+            ("CI", "false"),
+            ("GITHUB_ACTIONS", "true")
+        ])));
+        assert!(!ci_from(env(&[("CI", "0")])));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+        for invalid in ["", "m", "5x", "1.5s", "-3s"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_task_hooks() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            "[commands]\ntest = { pre = [\"lint\"], post = [\"cleanup --all\"] }\n",
+        )
+        .unwrap();
+        let config = Config {
+            hooks: Some(HooksConfig {
+                pre: vec!["check-env".to_string()],
+                post: vec!["notify".to_string()],
+            }),
+            ..Config::default()
+        };
+
+        let hooks = config.task_hooks("test", dir.path());
+        assert_eq!(hooks.pre, vec!["check-env", "lint"]);
+        assert_eq!(hooks.post, vec!["cleanup --all", "notify"]);
+        assert_eq!(
+            config.task_hooks("build", dir.path()).pre,
+            vec!["check-env"]
+        );
+        assert_eq!(
+            Config::default().task_hooks("test", dir.path()).post,
+            vec!["cleanup --all"]
+        );
+        // A global hook doesn't run around itself
+        let hooks = config.task_hooks("check-env", dir.path());
+        assert!(hooks.pre.is_empty());
+        assert_eq!(hooks.post, vec!["notify"]);
+    }
+
+    #[test]
+    fn test_dependencies() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            r#"[commands]
+clean = "rm -rf dist"
+codegen = { cmd = "protoc", depends_on = ["clean"] }
+lint = { depends_on = ["codegen"] }
+build = { cmd = "cargo build", depends_on = ["clean", "codegen", "lint"] }
+ping = { cmd = "true", depends_on = ["pong"] }
+pong = { cmd = "true", depends_on = ["ping"] }
+"#,
+        )
+        .unwrap();
+        let config = Config::default();
+
+        assert_eq!(
+            config.dependencies("build", dir.path()).unwrap(),
+            vec!["clean", "codegen", "lint"]
+        );
+        assert!(config.dependencies("clean", dir.path()).unwrap().is_empty());
+        assert!(config.dependencies("test", dir.path()).unwrap().is_empty());
+
+        let cycle = config.dependencies("ping", dir.path()).unwrap_err();
+        assert!(cycle.to_string().contains("ping -> pong -> ping"));
+    }
+
+    #[test]
+    fn test_task_settings() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            "[commands]\ntest = { timeout = \"90s\" }\n",
+        )
+        .unwrap();
+        let config = Config {
+            commands: Some(
+                [
+                    ("test".to_string(), "ignored".into()),
+                    (
+                        "e2e".to_string(),
+                        CommandEntry::Task(Box::new(TaskSettings {
+                            cmd: Some("playwright test".into()),
+                            timeout: Some("10m".to_string()),
+                            ..TaskSettings::default()
+                        })),
+                    ),
+                ]
+                .into(),
+            ),
+            ..Config::default()
+        };
+
+        assert_eq!(
+            config.task_settings("test", dir.path()).timeout.as_deref(),
+            Some("90s")
+        );
+        assert_eq!(
+            config.task_settings("e2e", dir.path()).timeout.as_deref(),
+            Some("10m")
+        );
+        assert_eq!(
+            config.task_settings("lint", dir.path()),
+            TaskSettings::default()
+        );
+    }
+
+    #[test]
+    fn test_set_and_unset_setting() {
+        let content = "# my settings\nmax_levels = 2\n";
+
+        let updated = set_setting(content, "auto_update", "false").unwrap();
+        assert!(updated.starts_with("# my settings\n"));
+        let updated = set_setting(&updated, "update.check_interval_hours", "12").unwrap();
+        // Not valid TOML, so a string
+        let updated = set_setting(&updated, "runner", "pnpm").unwrap();
+        let updated = set_setting(&updated, "ignore_tools", r#"["npm", "yarn"]"#).unwrap();
+
+        let config: Config = toml::from_str(&updated).unwrap();
+        assert_eq!(config.auto_update, Some(false));
+        assert_eq!(config.get_update_config().get_check_interval_hours(), 12);
+        assert_eq!(config.runner.as_deref(), Some("pnpm"));
+        assert_eq!(config.ignore_tools, vec!["npm", "yarn"]);
+
+        assert_eq!(
+            get_setting(&updated, "runner").unwrap().as_deref(),
+            Some("pnpm")
+        );
+        assert_eq!(
+            get_setting(&updated, "max_levels").unwrap().as_deref(),
+            Some("2")
+        );
+        assert_eq!(get_setting(&updated, "quiet").unwrap(), None);
+
+        let removed = unset_setting(&updated, "max_levels").unwrap().unwrap();
+        assert_eq!(get_setting(&removed, "max_levels").unwrap(), None);
+        assert!(unset_setting(&removed, "max_levels").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_set_setting_rejects_invalid() {
+        assert!(set_setting("", "max_lvls", "5")
+            .unwrap_err()
+            .to_string()
+            .contains("Unknown setting 'max_lvls'"));
+        assert!(set_setting("", "max_levels", "many")
+            .unwrap_err()
+            .to_string()
+            .contains("Invalid value"));
+        assert!(set_setting("max_levels = 2", "max_levels.x", "1").is_err());
+    }
+
+    #[test]
+    fn test_starter_toml() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"scripts": {"build:prod": "vite build", "test": "vitest"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("package-lock.json"), "{}").unwrap();
+
+        let runners = run_core::detectors::detect_all(dir.path(), &[]);
+        let starter = starter_toml(&runners, dir.path());
+        assert!(starter.contains("# From npm (package-lock.json)"));
+        assert!(starter.contains("# \"build:prod\" = \"npm run build:prod\"\n"));
+
+        // Valid as written, and once a task is uncommented
+        let config: Config = toml::from_str(&starter).unwrap();
+        assert!(config.commands.unwrap().is_empty());
+        let edited = starter.replace("# test = ", "test = ");
+        let config: Config = toml::from_str(&edited).unwrap();
+        assert_eq!(config.commands.unwrap()["test"].command(), "npm test");
+    }
 }
@@ -0,0 +1,323 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Opt-in background indexing daemon (`run daemon`).
+//!
+//! The daemon watches one or more project roots and keeps per-directory
+//! detection results in memory, dropping entries as soon as a file in that
+//! directory changes. It speaks the same JSON-RPC protocol as `run serve`
+//! over a Unix socket, one session per connection.
+//!
+//! The CLI asks the daemon to `resolve` a task before detecting on its own.
+//! Any failure (no daemon, timeout, different `PATH`, unresolvable task)
+//! falls back to local detection, so errors are always reported by the CLI
+//! itself. Set `RUN_NO_DAEMON` to skip the daemon entirely.
+
+use crate::detectors::{detect_all, DetectedRunner};
+use crate::error::RunError;
+use crate::serve::{self, DetectFn};
+use crate::walk;
+use notify::{RecursiveMode, Watcher};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How long the CLI waits for the daemon before detecting locally
+const CLIENT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Socket the daemon listens on: `RUN_DAEMON_SOCKET`, or `run/daemon.sock`
+/// in the runtime (falling back to the cache) directory
+pub fn default_socket_path() -> Option<PathBuf> {
+    if let Some(socket) = env::var_os("RUN_DAEMON_SOCKET") {
+        return Some(PathBuf::from(socket));
+    }
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join("run").join("daemon.sock"))
+}
+
+/// Detection results for directories under the watched roots
+pub struct DetectionCache {
+    roots: Vec<PathBuf>,
+    /// Changes below these directories (dependencies, build output) are ignored
+    skip_dirs: Vec<String>,
+    entries: Mutex<HashMap<PathBuf, Vec<DetectedRunner>>>,
+}
+
+impl DetectionCache {
+    pub fn new(roots: Vec<PathBuf>, skip_dirs: Vec<String>) -> Self {
+        Self {
+            roots,
+            skip_dirs,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Detect runners in `dir`, from the cache when `dir` is watched
+    pub fn detect(&self, dir: &Path, ignore_list: &[String]) -> Vec<DetectedRunner> {
+        if !self.roots.iter().any(|root| dir.starts_with(root)) {
+            return detect_all(dir, ignore_list);
+        }
+
+        let cached = self.lock().get(dir).cloned();
+        let runners = match cached {
+            Some(runners) => runners,
+            None => {
+                // Cache the unfiltered result; each request has its own ignore list
+                let runners = detect_all(dir, &[]);
+                self.lock().insert(dir.to_path_buf(), runners.clone());
+                runners
+            }
+        };
+
+        runners
+            .into_iter()
+            .filter(|runner| {
+                !ignore_list
+                    .iter()
+                    .any(|i| i.eq_ignore_ascii_case(&runner.name))
+            })
+            .collect()
+    }
+
+    /// Forget `path` and its parent directory (a changed file affects the
+    /// directory it lives in)
+    pub fn invalidate(&self, path: &Path) {
+        if self
+            .roots
+            .iter()
+            .any(|root| walk::is_skipped(path, root, &self.skip_dirs))
+        {
+            return;
+        }
+        let mut entries = self.lock();
+        entries.remove(path);
+        if let Some(parent) = path.parent() {
+            entries.remove(parent);
+        }
+    }
+
+    /// Forget every entry
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Vec<DetectedRunner>>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Run the daemon in the foreground until interrupted
+pub fn run_daemon(
+    socket: &Path,
+    roots: &[PathBuf],
+    skip_dirs: Vec<String>,
+) -> Result<(), RunError> {
+    let roots = roots
+        .iter()
+        .map(|root| {
+            root.canonicalize()
+                .map_err(|e| RunError::InvalidArgument(format!("{}: {}", root.display(), e)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    prepare_socket(socket)?;
+
+    let cache = Arc::new(DetectionCache::new(roots.clone(), skip_dirs));
+    let watcher_cache = Arc::clone(&cache);
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<notify::Event>| match result {
+            Ok(event) if !event.need_rescan() => {
+                for path in &event.paths {
+                    watcher_cache.invalidate(path);
+                }
+            }
+            // Events were dropped: nothing in the cache can be trusted
+            _ => watcher_cache.clear(),
+        })
+        .map_err(io::Error::other)?;
+    for root in &roots {
+        watcher
+            .watch(root, RecursiveMode::Recursive)
+            .map_err(io::Error::other)?;
+    }
+
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    let served = rt.block_on(accept_loop(socket, cache));
+    let _ = fs::remove_file(socket);
+    served?;
+    Ok(())
+}
+
+/// Refuse to start twice, and clean up a socket left by a crashed daemon
+fn prepare_socket(socket: &Path) -> Result<(), RunError> {
+    if socket.exists() {
+        if UnixStream::connect(socket).is_ok() {
+            return Err(RunError::ConfigError(format!(
+                "A daemon is already listening on {}",
+                socket.display()
+            )));
+        }
+        fs::remove_file(socket)?;
+    }
+    if let Some(parent) = socket.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    Ok(())
+}
+
+async fn accept_loop(socket: &Path, cache: Arc<DetectionCache>) -> io::Result<()> {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let listener = tokio::net::UnixListener::bind(socket)?;
+    fs::set_permissions(socket, fs::Permissions::from_mode(0o600))?;
+    let mut terminate = signal(SignalKind::terminate())?;
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                let (stream, _) = accepted?;
+                let cache = Arc::clone(&cache);
+                let detect: DetectFn = Arc::new(move |dir, ignore| cache.detect(dir, ignore));
+                tokio::spawn(async move {
+                    let (reader, writer) = stream.into_split();
+                    let _ = serve::serve_with(reader, writer, detect).await;
+                });
+            }
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = terminate.recv() => return Ok(()),
+        }
+    }
+}
+
+/// Ask a running daemon which runner handles `task`
+///
+/// Returns `None` whenever the daemon cannot answer, so the caller detects
+/// locally and reports errors itself.
+pub fn try_resolve(
+    current_dir: &Path,
+    task: &str,
+    max_levels: u8,
+    ignore_list: &[String],
+) -> Option<(DetectedRunner, PathBuf)> {
+    if env::var_os("RUN_NO_DAEMON").is_some() {
+        return None;
+    }
+    let socket = default_socket_path()?;
+    let stream = UnixStream::connect(socket).ok()?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok()?;
+
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "resolve",
+        "params": {
+            "task": task,
+            "dir": current_dir,
+            "levels": max_levels,
+            "ignore": ignore_list,
+            "path": env::var("PATH").unwrap_or_default(),
+        },
+    });
+    let response = request_response(stream, &request).ok()?;
+
+    let result = response.get("result")?;
+    let runner = serde_json::from_value(result.get("runner")?.clone()).ok()?;
+    let working_dir = PathBuf::from(result.get("working_dir")?.as_str()?);
+    Some((runner, working_dir))
+}
+
+/// Send one framed request and read the framed response
+fn request_response(mut stream: UnixStream, request: &Value) -> io::Result<Value> {
+    let body = request.to_string();
+    write!(stream, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    stream.flush()?;
+
+    let mut reader = BufReader::new(stream);
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = content_length.ok_or(io::ErrorKind::InvalidData)?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    serde_json::from_slice(&body).map_err(io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::File;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_cache_is_invalidated_by_changes() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        File::create(root.join("Cargo.toml")).unwrap();
+
+        let cache = DetectionCache::new(vec![root.clone()], walk::default_skip_dirs());
+        let names = |runners: Vec<DetectedRunner>| -> Vec<String> {
+            runners.into_iter().map(|r| r.name).collect()
+        };
+        assert_eq!(names(cache.detect(&root, &[])), vec!["cargo"]);
+
+        // Without a notification the stale result is served
+        File::create(root.join("Makefile")).unwrap();
+        assert_eq!(names(cache.detect(&root, &[])), vec!["cargo"]);
+
+        // Changes inside dependency directories don't count
+        cache.invalidate(&root.join("node_modules"));
+        assert_eq!(names(cache.detect(&root, &[])), vec!["cargo"]);
+
+        cache.invalidate(&root.join("Makefile"));
+        assert_eq!(names(cache.detect(&root, &[])), vec!["cargo", "make"]);
+        assert_eq!(
+            names(cache.detect(&root, &["CARGO".to_string()])),
+            vec!["make"]
+        );
+    }
+
+    #[test]
+    fn test_unwatched_dirs_are_not_cached() {
+        let watched = tempdir().unwrap();
+        let other = tempdir().unwrap();
+        File::create(other.path().join("Cargo.toml")).unwrap();
+
+        let cache = DetectionCache::new(vec![watched.path().to_path_buf()], Vec::new());
+        assert_eq!(cache.detect(other.path(), &[]).len(), 1);
+        assert!(cache.lock().is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Dev containers (`run --in-devcontainer`).
+//!
+//! Commands are executed through the devcontainer CLI
+//! (`devcontainer exec --workspace-folder <dir> ...`), so the project's
+//! toolchain only has to exist inside the container. The CLI starts commands
+//! in the container's workspace folder; when `run` was invoked from a
+//! subdirectory, the command changes into the same relative directory first.
+
+use std::path::{Path, PathBuf};
+
+/// Locations of the dev container configuration, relative to the workspace
+const CONFIG_FILES: &[&str] = &[".devcontainer/devcontainer.json", ".devcontainer.json"];
+
+/// A dev container workspace containing the working directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevContainer {
+    /// Directory holding the dev container configuration
+    pub workspace: PathBuf,
+    /// Working directory relative to `workspace`
+    pub subdir: PathBuf,
+}
+
+impl DevContainer {
+    /// Nearest workspace at or above `dir` with a dev container configuration
+    pub fn find(dir: &Path) -> Option<Self> {
+        let dir = dir.canonicalize().ok()?;
+        let workspace = dir.ancestors().find(|ancestor| {
+            CONFIG_FILES
+                .iter()
+                .any(|file| ancestor.join(file).is_file())
+        })?;
+        Some(DevContainer {
+            workspace: workspace.to_path_buf(),
+            subdir: dir.strip_prefix(workspace).ok()?.to_path_buf(),
+        })
+    }
+
+    /// `command` executed inside the container
+    pub fn wrap(&self, command: &[String]) -> Vec<String> {
+        let mut wrapped = vec![
+            "devcontainer".to_string(),
+            "exec".to_string(),
+            "--workspace-folder".to_string(),
+            self.workspace.to_string_lossy().into_owned(),
+        ];
+        if !self.subdir.as_os_str().is_empty() {
+            // The container's paths differ from the host's, but the layout
+            // below the workspace folder is the same
+            wrapped.extend([
+                "sh".to_string(),
+                "-c".to_string(),
+                "cd \"$0\" && exec \"$@\"".to_string(),
+                self.subdir.to_string_lossy().into_owned(),
+            ]);
+        }
+        wrapped.extend(command.iter().cloned());
+        wrapped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_and_wrap() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        assert_eq!(DevContainer::find(&root), None);
+
+        fs::create_dir(root.join(".devcontainer")).unwrap();
+        fs::write(
+            root.join(".devcontainer").join("devcontainer.json"),
+            r#"{"image": "mcr.microsoft.com/devcontainers/rust:1"}"#,
+        )
+        .unwrap();
+        let command = vec!["cargo".to_string(), "test".to_string()];
+
+        let container = DevContainer::find(&root).unwrap();
+        assert_eq!(container.workspace, root);
+        assert_eq!(
+            container.wrap(&command),
+            vec![
+                "devcontainer".to_string(),
+                "exec".to_string(),
+                "--workspace-folder".to_string(),
+                root.to_string_lossy().into_owned(),
+                "cargo".to_string(),
+                "test".to_string(),
+            ]
+        );
+
+        let sub = root.join("crates").join("core");
+        fs::create_dir_all(&sub).unwrap();
+        let container = DevContainer::find(&sub).unwrap();
+        assert_eq!(container.workspace, root);
+        assert_eq!(
+            container.wrap(&command)[4..],
+            [
+                "sh".to_string(),
+                "-c".to_string(),
+                "cd \"$0\" && exec \"$@\"".to_string(),
+                "crates/core".to_string(),
+                "cargo".to_string(),
+                "test".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_root_devcontainer_json() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join(".devcontainer.json"), "{}").unwrap();
+        let container = DevContainer::find(dir.path()).unwrap();
+        assert_eq!(container.workspace, dir.path().canonicalize().unwrap());
+        assert!(container.subdir.as_os_str().is_empty());
+    }
+}
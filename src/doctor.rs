@@ -0,0 +1,396 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Environment diagnostics (`run doctor`).
+//!
+//! Runs the same detection as `run <task>` and reports what would get in its
+//! way: config files that don't parse, runners whose tool is missing, and
+//! lockfile conflicts that make resolution fail or guess.
+
+use crate::config::Config;
+use crate::detectors::{custom, detect_all, java, node, DetectedRunner, Ecosystem};
+use crate::runner::{detect_with_config, forced_runner};
+use crate::script::ScriptHooks;
+use crate::tools;
+use serde::Serialize;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a tool gets to print its version
+const VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Ok,
+    /// Works, but probably not the way the user expects
+    Warning,
+    /// `run` will fail because of it
+    Problem,
+}
+
+/// One line of the report
+#[derive(Debug, Clone, Serialize)]
+pub struct Check {
+    pub status: Status,
+    pub message: String,
+}
+
+impl Check {
+    fn ok(message: impl Into<String>) -> Self {
+        Check {
+            status: Status::Ok,
+            message: message.into(),
+        }
+    }
+
+    fn warning(message: impl Into<String>) -> Self {
+        Check {
+            status: Status::Warning,
+            message: message.into(),
+        }
+    }
+
+    fn problem(message: impl Into<String>) -> Self {
+        Check {
+            status: Status::Problem,
+            message: message.into(),
+        }
+    }
+}
+
+/// Check the config files read from `current_dir`, then the runners detected
+/// from it.
+///
+/// `config` is the merged configuration; files that don't parse are left out
+/// of it by [`Config::load`], which is why they're re-read here.
+pub fn diagnose(
+    current_dir: &Path,
+    max_levels: u8,
+    ignore_list: &[String],
+    config: &Config,
+) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    if let Some(path) = Config::global_config_path() {
+        checks.extend(check_config_file(&path));
+    }
+    // Only the first project config file present is read
+    let mut configs = custom::CONFIG_FILES
+        .iter()
+        .filter(|name| current_dir.join(name).is_file());
+    if let Some(used) = configs.next() {
+        checks.extend(check_config_file(&current_dir.join(used)));
+        checks.extend(configs.map(|shadowed| {
+            Check::warning(format!(
+                "{} is ignored: {} takes precedence",
+                shadowed, used
+            ))
+        }));
+    }
+
+    if let Some(script) = &config.script {
+        let path = current_dir.join(script);
+        match ScriptHooks::load(&path) {
+            Ok(_) => checks.push(Check::ok(format!("Script {} loads", path.display()))),
+            Err(e) => checks.push(Check::problem(e.to_string())),
+        }
+    }
+    let mut aliases: Vec<&String> = config.aliases.iter().flat_map(|a| a.keys()).collect();
+    aliases.sort();
+    for alias in aliases {
+        if config.expand_alias(alias, &[]).is_none() {
+            checks.push(Check::warning(format!(
+                "Alias '{}' doesn't expand to a command and is ignored",
+                alias
+            )));
+        }
+    }
+
+    let (runners, working_dir) = match detect_with_config(
+        current_dir,
+        max_levels,
+        ignore_list,
+        config,
+        false,
+        detect_all,
+    ) {
+        Ok(result) => result,
+        Err(e) => {
+            checks.push(Check::problem(e.to_string()));
+            return checks;
+        }
+    };
+    checks.push(Check::ok(format!(
+        "Detected runners in {}",
+        working_dir.display()
+    )));
+
+    for runner in runners.iter().filter(|r| r.ecosystem != Ecosystem::Custom) {
+        // A conflicting runner that is installed is used instead
+        let replaced = runners.iter().any(|other| {
+            other.ecosystem == runner.ecosystem && other.name != runner.name && other.is_installed()
+        });
+        checks.push(check_tool(runner, &working_dir, replaced));
+    }
+
+    if let Some(name) = &config.runner {
+        if let Err(e) = forced_runner(&runners, name) {
+            checks.push(Check::problem(e.to_string()));
+        }
+    }
+    checks.extend(check_conflicts(&runners, &working_dir));
+
+    checks
+}
+
+/// Whether any check found something that makes `run` fail
+pub fn has_problems(checks: &[Check]) -> bool {
+    checks.iter().any(|c| c.status == Status::Problem)
+}
+
+/// Parse a config file, if it exists
+fn check_config_file(path: &Path) -> Option<Check> {
+    if !path.is_file() {
+        return None;
+    }
+    Some(match Config::load_from_file(path) {
+        Ok(_) => Check::ok(format!("Config {} is valid", path.display())),
+        Err(e) => Check::problem(format!(
+            "Config {} is invalid and was ignored: {}",
+            path.display(),
+            e.to_string().trim_end()
+        )),
+    })
+}
+
+/// Where the runner's program lives and which version it is
+fn check_tool(runner: &DetectedRunner, working_dir: &Path, replaced: bool) -> Check {
+    let label = format!("{} ({})", runner.name, runner.detected_file);
+    if !runner.is_installed() {
+        let message = format!("{}: {} is not installed", label, runner.name);
+        return if replaced {
+            Check::warning(message)
+        } else {
+            Check::problem(message)
+        };
+    }
+
+    // A wrapper script is the detected file itself
+    let wrapper =
+        [java::GRADLE_WRAPPER, java::MAVEN_WRAPPER].contains(&runner.detected_file.as_str());
+    let path = if wrapper {
+        Some(working_dir.join(&runner.detected_file))
+    } else {
+        tools::find_tool(&runner.name)
+    };
+
+    match path {
+        Some(path) => match tool_version(&path, &runner.name, working_dir) {
+            Some(version) => Check::ok(format!("{}: {} ({})", label, path.display(), version)),
+            None => Check::ok(format!("{}: {}", label, path.display())),
+        },
+        None => Check::ok(label),
+    }
+}
+
+/// Argument that makes `tool` print its version
+fn version_flag(tool: &str) -> &'static str {
+    match tool {
+        "go" | "zig" | "lein" | "devbox" | "tuist" => "version",
+        "xcodebuild" => "-version",
+        // `--version` boots the sbt server
+        "sbt" => "--script-version",
+        _ => "--version",
+    }
+}
+
+/// First line the tool prints for its version, if it answers in time
+fn tool_version(path: &Path, tool: &str, working_dir: &Path) -> Option<String> {
+    let mut child = Command::new(path)
+        .arg(version_flag(tool))
+        .current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    let deadline = Instant::now() + VERSION_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+
+    let output = child.wait_with_output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// Runners of the same ecosystem found together, the way
+/// [`crate::runner::check_conflicts`] would resolve them
+fn check_conflicts(runners: &[DetectedRunner], working_dir: &Path) -> Vec<Check> {
+    let mut groups: Vec<(Ecosystem, Vec<&DetectedRunner>)> = Vec::new();
+    for runner in runners.iter().filter(|r| r.ecosystem != Ecosystem::Custom) {
+        match groups.iter_mut().find(|(e, _)| *e == runner.ecosystem) {
+            Some((_, group)) => group.push(runner),
+            None => groups.push((runner.ecosystem, vec![runner])),
+        }
+    }
+
+    let mut checks = Vec::new();
+    for (ecosystem, group) in groups.iter().filter(|(_, g)| g.len() > 1) {
+        let files: Vec<&str> = group.iter().map(|r| r.detected_file.as_str()).collect();
+
+        if *ecosystem == Ecosystem::NodeJs {
+            if let Some(pinned) = node::get_corepack_manager(working_dir) {
+                if group.iter().any(|r| r.name == pinned) {
+                    checks.push(Check::ok(format!(
+                        "Found {}; packageManager selects {}",
+                        files.join(" and "),
+                        pinned
+                    )));
+                    continue;
+                }
+            }
+        }
+
+        let installed: Vec<&str> = group
+            .iter()
+            .filter(|r| r.is_installed())
+            .map(|r| r.name.as_str())
+            .collect();
+        match installed.len() {
+            // Already reported as missing tools
+            0 => {}
+            1 => {
+                let others: Vec<&str> = group
+                    .iter()
+                    .filter(|r| r.name != installed[0])
+                    .map(|r| r.detected_file.as_str())
+                    .collect();
+                checks.push(Check::warning(format!(
+                    "Found {} but only {} is installed; consider removing {}",
+                    files.join(" and "),
+                    installed[0],
+                    others.join(", ")
+                )));
+            }
+            _ => checks.push(Check::problem(format!(
+                "Conflicting {} files ({}) with {} installed; remove the outdated lockfile or use --ignore=<tool>",
+                ecosystem.as_str(),
+                files.join(", "),
+                installed.join(", ")
+            ))),
+        }
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn problems(checks: &[Check]) -> Vec<&str> {
+        checks
+            .iter()
+            .filter(|c| c.status == Status::Problem)
+            .map(|c| c.message.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_invalid_run_toml() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("run.toml"), "max_levels = [").unwrap();
+        fs::write(dir.path().join("Makefile"), "build:\n\ttrue\n").unwrap();
+
+        let checks = diagnose(dir.path(), 0, &[], &Config::default());
+        assert!(has_problems(&checks));
+        assert!(problems(&checks)
+            .iter()
+            .any(|p| p.contains("run.toml") && p.contains("invalid")));
+    }
+
+    #[test]
+    fn test_shadowed_config_files() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("run.yaml"), "commands:\n  build: make\n").unwrap();
+        fs::write(dir.path().join("run.json"), "{}").unwrap();
+
+        let checks = diagnose(dir.path(), 0, &[], &Config::default());
+        assert!(checks
+            .iter()
+            .any(|c| c.status == Status::Ok && c.message.contains("run.yaml is valid")));
+        assert!(checks.iter().any(|c| c.status == Status::Warning
+            && c.message == "run.json is ignored: run.yaml takes precedence"));
+    }
+
+    #[test]
+    fn test_missing_tool_and_forced_runner() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("run.toml"), "").unwrap();
+        fs::write(dir.path().join("maskfile.md"), "## build\n").unwrap();
+
+        let config = Config {
+            runner: Some("just".to_string()),
+            ..Config::default()
+        };
+        let checks = diagnose(dir.path(), 0, &["mask".to_string()], &config);
+        // Ignoring the only runner leaves nothing to detect
+        assert_eq!(problems(&checks).len(), 1);
+        assert!(checks
+            .iter()
+            .any(|c| c.status == Status::Ok && c.message.contains("run.toml")));
+
+        let checks = diagnose(dir.path(), 0, &[], &config);
+        assert!(problems(&checks)
+            .iter()
+            .any(|p| p.contains("Runner 'just' was requested but not detected")));
+    }
+
+    #[test]
+    fn test_invalid_alias() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Makefile"), "build:\n\ttrue\n").unwrap();
+
+        let config = Config {
+            aliases: Some([("b".to_string(), "build 'oops".to_string())].into()),
+            ..Config::default()
+        };
+        let checks = diagnose(dir.path(), 0, &[], &config);
+        assert!(checks
+            .iter()
+            .any(|c| c.status == Status::Warning && c.message.contains("Alias 'b'")));
+    }
+
+    #[test]
+    fn test_version_flag() {
+        assert_eq!(version_flag("go"), "version");
+        assert_eq!(version_flag("cargo"), "--version");
+    }
+}
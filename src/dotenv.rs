@@ -0,0 +1,199 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! `.env` files (`--env-file`).
+//!
+//! One `KEY=VALUE` per line, optionally prefixed with `export`. Blank lines
+//! and lines starting with `#` are skipped, as is a ` #` comment after an
+//! unquoted value. Single-quoted values are literal; double-quoted ones
+//! understand `\n`, `\t`, `\r`, `\"`, `\\` and `\$`. Quoted values may span
+//! several lines. Variables are not expanded.
+
+use crate::error::RunError;
+use std::fs;
+use std::path::Path;
+
+/// Read and parse a dotenv file
+pub fn load(path: &Path) -> Result<Vec<(String, String)>, RunError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| RunError::InvalidArgument(format!("Cannot read {}: {}", path.display(), e)))?;
+    parse(&content).map_err(|e| RunError::InvalidArgument(format!("{}: {}", path.display(), e)))
+}
+
+/// Variables in the order they appear; a later duplicate wins when applied
+pub fn parse(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut vars = Vec::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line
+            .strip_prefix("export ")
+            .map(str::trim_start)
+            .unwrap_or(line);
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected KEY=VALUE", number));
+        };
+        let key = key.trim();
+        if !is_valid_key(key) {
+            return Err(format!("line {}: invalid variable name '{}'", number, key));
+        }
+
+        let value = value.trim_start();
+        let value = match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                let mut raw = value[1..].to_string();
+                let end = loop {
+                    if let Some(end) = closing_quote(&raw, quote) {
+                        break end;
+                    }
+                    match lines.next() {
+                        Some((_, next)) => {
+                            raw.push('\n');
+                            raw.push_str(next);
+                        }
+                        None => return Err(format!("line {}: missing closing {}", number, quote)),
+                    }
+                };
+                let rest = raw[end + 1..].trim();
+                if !rest.is_empty() && !rest.starts_with('#') {
+                    return Err(format!(
+                        "line {}: unexpected '{}' after closing quote",
+                        number, rest
+                    ));
+                }
+                if quote == '"' {
+                    unescape(&raw[..end])
+                } else {
+                    raw[..end].to_string()
+                }
+            }
+            _ => strip_comment(value).trim_end().to_string(),
+        };
+        vars.push((key.to_string(), value));
+    }
+
+    Ok(vars)
+}
+
+/// Names a shell would accept: letters, digits and `_`, not starting with a digit
+pub fn is_valid_key(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Byte offset of the quote that ends a value (`\"` doesn't, in double quotes)
+fn closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// An unquoted value up to a `#` that starts a comment
+fn strip_comment(value: &str) -> &str {
+    value
+        .char_indices()
+        .find(|&(i, c)| c == '#' && (i == 0 || value[..i].ends_with(char::is_whitespace)))
+        .map_or(value, |(i, _)| &value[..i])
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('t') => result.push('\t'),
+            Some('r') => result.push('\r'),
+            Some(c @ ('"' | '\\' | '$')) => result.push(c),
+            Some(c) => {
+                result.push('\\');
+                result.push(c);
+            }
+            None => result.push('\\'),
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+        vars.iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let content = r#"
+# Database
+export DB_HOST=localhost
+DB_PORT = 5432 # default port
+EMPTY=
+URL=http://example.com/#anchor
+SINGLE='raw \n $HOME # not a comment'
+DOUBLE="line\nbreak \"quoted\" \$HOME" # comment
+KEY="-----BEGIN KEY-----
+abc
+-----END KEY-----"
+"#;
+        assert_eq!(
+            parse(content).unwrap(),
+            pairs(&[
+                ("DB_HOST", "localhost"),
+                ("DB_PORT", "5432"),
+                ("EMPTY", ""),
+                ("URL", "http://example.com/#anchor"),
+                ("SINGLE", "raw \\n $HOME # not a comment"),
+                ("DOUBLE", "line\nbreak \"quoted\" $HOME"),
+                ("KEY", "-----BEGIN KEY-----\nabc\n-----END KEY-----"),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_dotenv_errors() {
+        assert_eq!(
+            parse("A=1\nnot a pair").unwrap_err(),
+            "line 2: expected KEY=VALUE"
+        );
+        assert!(parse("1A=x").unwrap_err().contains("invalid variable name"));
+        assert!(parse("A=\"open\nB=2")
+            .unwrap_err()
+            .contains("missing closing"));
+        assert!(parse("A='x' y")
+            .unwrap_err()
+            .contains("after closing quote"));
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Lifecycle events for embedders.
+//!
+//! Observers registered on an [`EventBus`] receive every [`RunEvent`] emitted
+//! while detecting and running a task. The CLI prints its own messages through
+//! [`crate::output::CliObserver`], so embedders see exactly what the CLI sees.
+
+use crate::detectors::DetectedRunner;
+use std::path::Path;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Which stream an output chunk came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Something that happened while detecting or running a task
+#[derive(Debug)]
+pub enum RunEvent<'a> {
+    /// Runners were detected (including config-defined commands)
+    DetectionComplete {
+        working_dir: &'a Path,
+        runners: &'a [DetectedRunner],
+    },
+    /// A runner and final command line were chosen for the task
+    CommandSelected {
+        runner: &'a DetectedRunner,
+        task: &'a str,
+        command: &'a [String],
+        dry_run: bool,
+    },
+    /// The child process was started
+    Spawned { pid: u32, attempt: u32 },
+    /// A chunk of captured output (only with `StdioPolicy::Capture`)
+    Output { stream: Stream, data: &'a [u8] },
+    /// An attempt failed and the command is re-run after `delay`; `status`
+    /// is `None` when the attempt timed out
+    Retry {
+        attempt: u32,
+        max_attempts: u32,
+        status: Option<ExitStatus>,
+        delay: Duration,
+    },
+    /// The command finished
+    Exit { status: ExitStatus, attempts: u32 },
+}
+
+/// Receives lifecycle events
+pub trait RunObserver: Send + Sync {
+    fn on_event(&self, event: &RunEvent<'_>);
+}
+
+impl<F> RunObserver for F
+where
+    F: Fn(&RunEvent<'_>) + Send + Sync,
+{
+    fn on_event(&self, event: &RunEvent<'_>) {
+        self(event)
+    }
+}
+
+/// A set of observers, cheap to clone
+#[derive(Clone, Default)]
+pub struct EventBus {
+    observers: Vec<Arc<dyn RunObserver>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an observer; observers are notified in registration order
+    pub fn subscribe(&mut self, observer: Arc<dyn RunObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Builder-style [`EventBus::subscribe`]
+    pub fn with(mut self, observer: Arc<dyn RunObserver>) -> Self {
+        self.subscribe(observer);
+        self
+    }
+
+    pub fn emit(&self, event: &RunEvent<'_>) {
+        for observer in &self.observers {
+            observer.on_event(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_observers_called_in_order() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let first = Arc::clone(&seen);
+        let second = Arc::clone(&seen);
+        let bus = EventBus::new()
+            .with(Arc::new(move |_: &RunEvent<'_>| {
+                first.lock().unwrap().push("first")
+            }))
+            .with(Arc::new(move |_: &RunEvent<'_>| {
+                second.lock().unwrap().push("second")
+            }));
+
+        bus.emit(&RunEvent::Retry {
+            attempt: 1,
+            max_attempts: 2,
+            status: None,
+            delay: Duration::ZERO,
+        });
+        assert_eq!(*seen.lock().unwrap(), vec!["first", "second"]);
+    }
+}
@@ -15,15 +15,39 @@
 //!
 //! Automatically detects the project's package manager or build tool
 //! and runs commands through the appropriate tool.
+//!
+//! Detection, validation and command construction live in the `run-core`
+//! crate and are re-exported here; this crate adds the CLI, configuration,
+//! execution and self-update layers.
+
+pub use run_core::{detectors, error, tools, walk, workspace};
 
+pub mod cache;
 pub mod cli;
+pub mod completions;
 pub mod config;
-pub mod detectors;
-pub mod error;
+#[cfg(all(unix, feature = "daemon"))]
+pub mod daemon;
+pub mod devcontainer;
+pub mod doctor;
+pub mod dotenv;
+pub mod events;
+#[cfg(feature = "net")]
 pub mod http;
+pub mod logfile;
+pub mod nix;
 pub mod output;
+pub mod prompt;
+#[cfg(unix)]
+pub mod pty;
 pub mod runner;
+pub mod script;
+pub mod search;
+pub mod serve;
+#[cfg(feature = "updater")]
 pub mod update;
+#[cfg(feature = "watch")]
+pub mod watch;
 
 pub use cli::Cli;
 pub use config::Config;
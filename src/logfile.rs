@@ -0,0 +1,182 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Copies of task output in a file (`--log-file`).
+//!
+//! The file is appended to, so one log can hold several runs. Each run starts
+//! with a header naming the task and command; every output line is stamped
+//! with the time it arrived and the stream it came from, and the run ends
+//! with its exit status.
+
+use crate::error::RunError;
+use crate::events::Stream;
+use crate::output;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// A log file shared by the threads relaying a task's output
+#[derive(Debug)]
+pub struct TaskLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl TaskLog {
+    /// Open `path` for appending, creating it and its directory if needed
+    pub fn open(path: &Path) -> Result<Self, RunError> {
+        let cannot_open = |e: std::io::Error| {
+            RunError::InvalidArgument(format!("Cannot open log file {}: {}", path.display(), e))
+        };
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(cannot_open)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(cannot_open)?;
+        Ok(TaskLog {
+            path: path.to_path_buf(),
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Record that `task` starts running `command`
+    pub fn start(&self, task: &str, command: &[String], attempt: u32) {
+        let retry = if attempt > 1 {
+            format!(" (attempt {})", attempt)
+        } else {
+            String::new()
+        };
+        self.write(&format!(
+            "{} === {}: {}{}",
+            now(),
+            task,
+            shell_words::join(command),
+            retry
+        ));
+    }
+
+    /// Record one line of output
+    pub fn line(&self, stream: Stream, line: &str) {
+        let tag = match stream {
+            Stream::Stdout => "out",
+            Stream::Stderr => "err",
+        };
+        self.write(&format!("{} {} {}", now(), tag, line));
+    }
+
+    /// Record how the task ended (`None` when it timed out)
+    pub fn finish(&self, task: &str, status: Option<ExitStatus>) {
+        let outcome = match status {
+            Some(status) => status.to_string(),
+            None => "timed out".to_string(),
+        };
+        self.write(&format!("{} === {}: {}", now(), task, outcome));
+    }
+
+    fn write(&self, line: &str) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = writeln!(file, "{}", output::redact(line));
+        }
+    }
+}
+
+/// Splits a stream's chunks into lines for a [`TaskLog`]
+pub struct LineWriter<'a> {
+    log: &'a TaskLog,
+    stream: Stream,
+    pending: Vec<u8>,
+}
+
+impl<'a> LineWriter<'a> {
+    pub fn new(log: &'a TaskLog, stream: Stream) -> Self {
+        LineWriter {
+            log,
+            stream,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Log every line `chunk` completes
+    pub fn write(&mut self, chunk: &[u8]) {
+        self.pending.extend_from_slice(chunk);
+        while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            self.log.line(self.stream, clean(&line[..end]).as_ref());
+        }
+    }
+}
+
+impl Drop for LineWriter<'_> {
+    /// A last line without a newline is logged as well
+    fn drop(&mut self) {
+        if !self.pending.is_empty() {
+            self.log.line(self.stream, clean(&self.pending).as_ref());
+        }
+    }
+}
+
+/// A line without its `\r`, lossily decoded
+fn clean(line: &[u8]) -> std::borrow::Cow<'_, str> {
+    String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+fn now() -> humantime::Rfc3339Timestamp {
+    humantime::format_rfc3339_millis(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_log_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("logs/build.log");
+        let log = TaskLog::open(&path).unwrap();
+
+        log.start("build", &["make".to_string(), "build".to_string()], 1);
+        {
+            let mut out = LineWriter::new(&log, Stream::Stdout);
+            out.write(b"compil");
+            out.write(b"ing\r\nlinking\npartial");
+            LineWriter::new(&log, Stream::Stderr).write(b"warning\n");
+        }
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content
+            .lines()
+            .map(|line| line.split_once(' ').unwrap().1)
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "=== build: make build",
+                "out compiling",
+                "out linking",
+                "err warning",
+                "out partial",
+            ]
+        );
+        let stamp = content.split(' ').next().unwrap();
+        assert!(humantime::parse_rfc3339(stamp).is_ok(), "{}", stamp);
+    }
+}
@@ -10,63 +10,221 @@
 // GNU Affero General Public License for more details.
 
 use clap::{CommandFactory, Parser};
-use clap_complete::generate;
-use run_cli::cli::{Cli, Commands};
-use run_cli::config::Config;
-use run_cli::detectors::{DetectedRunner, Ecosystem, UnknownValidator};
-use run_cli::error::exit_codes;
-use run_cli::output;
-use run_cli::runner::{check_conflicts, execute, search_runners, select_runner};
+use clap_complete::{generate, Shell};
+use run_cli::cache::TaskCache;
+use run_cli::cli::{AliasAction, Cli, Commands, ConfigAction};
+use run_cli::completions;
+use run_cli::config::{
+    get_setting, parse_duration, set_setting, starter_toml, unset_setting, Config,
+};
+#[cfg(all(unix, feature = "daemon"))]
+use run_cli::daemon;
+use run_cli::detectors::{custom, detect_all, DetectedRunner, Ecosystem};
+use run_cli::doctor;
+use run_cli::dotenv;
+use run_cli::error::{exit_codes, RunError};
+use run_cli::events::{EventBus, RunEvent, Stream};
+use run_cli::logfile::TaskLog;
+use run_cli::output::{self, CliObserver, JsonObserver};
+use run_cli::prompt;
+use run_cli::runner::{
+    check_conflicts, conflict_choices, detect_traced, detect_with_config, execute_parallel,
+    exit_code_of, forced_runner, package_manager_warning, resolve_runner, resolve_tool,
+    resolve_unvalidated, search_down, trace_resolution, wireit_warning, ExecutionBuilder,
+    OutputLine, ParallelExit, Resolution, RunResult, StdinSource, TaskOutcome, TaskReport,
+};
+use run_cli::script::ScriptHooks;
+use run_cli::search;
+use run_cli::serve;
+use run_cli::tools;
+#[cfg(feature = "updater")]
 use run_cli::update;
+#[cfg(feature = "watch")]
+use run_cli::watch;
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::env;
+use std::fs;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 fn main() {
     // Check for internal update flag (used by background updater)
-    let args: Vec<String> = env::args().collect();
-    if args.len() > 1 && args[1] == "--internal-update-check" {
-        // Run update check in background
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .unwrap();
-        let _ = rt.block_on(update::perform_update_check());
+    #[cfg(feature = "updater")]
+    if env::args_os()
+        .nth(1)
+        .is_some_and(|arg| arg == update::INTERNAL_CHECK_FLAG)
+    {
+        let _ = update::perform_update_check();
         return;
     }
 
     // Parse CLI arguments
     let cli = Cli::parse();
+    output::set_json(cli.json);
+    output::set_color(cli.color);
+
+    // Everything below, run.toml included, behaves as if started from --cwd
+    if let Some(dir) = &cli.cwd {
+        if let Err(e) = env::set_current_dir(dir) {
+            output::error(&format!("Cannot change to {}: {}", dir.display(), e));
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+        // Scripts reading $PWD would otherwise see the caller's directory
+        if let Ok(dir) = env::current_dir() {
+            env::set_var("PWD", dir);
+        }
+    }
 
     // Load configuration
-    let config = Config::load();
+    let profile = Profile::default();
+    let started = Instant::now();
+    let config = profile.time("config", Config::load);
 
     // Merge config with CLI arguments
-    let verbose = cli.verbose || config.get_verbose();
+    let verbosity = cli.verbose.max(config.get_verbose() as u8);
+    output::set_verbosity(verbosity);
+    output::set_secrets(config.secrets(&config.template_vars(env::vars())));
+    let verbose = verbosity > 0;
+    if output::verbosity() >= 3 {
+        match toml::to_string_pretty(&config) {
+            Ok(merged) => output::info(&format!(
+                "Merged config:\n{}",
+                output::redact(merged.trim_end())
+            )),
+            Err(e) => output::warning(&format!("Could not show the merged config: {}", e)),
+        }
+    }
     let quiet = cli.quiet || config.get_quiet();
-    let max_levels = cli.levels;
+    let max_levels = cli.levels.unwrap_or(config.get_max_levels());
     let mut ignore_list = config.ignore_tools.clone();
     ignore_list.extend(cli.ignore.clone());
 
-    // Check for update notification
-    update::check_update_notification(quiet);
+    // Persist tool lookups across invocations when enabled
+    if config.get_tool_cache() {
+        if let Some(path) = Config::tool_cache_path() {
+            tools::enable_persistent_cache(path);
+        }
+    }
 
     // Handle subcommands
-    if let Some(Commands::Completions { shell }) = cli.subcommand {
-        let mut cmd = Cli::command();
-        let name = cmd.get_name().to_string();
-        generate(shell, &mut cmd, name, &mut io::stdout());
-        return;
+    match cli.subcommand {
+        Some(Commands::Completions { shell, install }) => {
+            if install {
+                install_completions(shell, quiet);
+            } else if let Some(shell) = shell {
+                let mut cmd = Cli::command();
+                let name = cmd.get_name().to_string();
+                generate(shell, &mut cmd, name, &mut io::stdout());
+            }
+            return;
+        }
+        Some(Commands::List { all }) => {
+            list_tasks(all, max_levels, &ignore_list, &config, verbose);
+            return;
+        }
+        Some(Commands::Which { task, args }) => {
+            which(
+                &task,
+                &args,
+                cli.workspace,
+                &config,
+                max_levels,
+                &ignore_list,
+                output::resolution,
+            );
+            return;
+        }
+        Some(Commands::Explain { task, args }) => {
+            which(
+                &task,
+                &args,
+                cli.workspace,
+                &config,
+                max_levels,
+                &ignore_list,
+                output::explanation,
+            );
+            return;
+        }
+        Some(Commands::Search { keyword }) => {
+            search_tasks(&keyword, max_levels, &ignore_list, &config, verbose);
+            return;
+        }
+        Some(Commands::UpgradeTool { dry_run }) => {
+            let code = upgrade_tool(
+                dry_run || cli.dry_run,
+                max_levels,
+                &ignore_list,
+                &config,
+                verbose,
+                quiet,
+            );
+            process::exit(code);
+        }
+        Some(Commands::Init { force }) => {
+            init(force, max_levels, &ignore_list, &config, quiet);
+            return;
+        }
+        Some(Commands::Schema) => {
+            match serde_json::to_string_pretty(&Config::schema()) {
+                Ok(schema) => println!("{}", schema),
+                Err(e) => {
+                    output::error(&format!("Failed to serialize the schema: {}", e));
+                    process::exit(exit_codes::GENERIC_ERROR);
+                }
+            }
+            return;
+        }
+        Some(Commands::Doctor) => {
+            doctor(max_levels, &ignore_list, &config);
+            return;
+        }
+        Some(Commands::Config { action }) => {
+            if let Err(e) = config_command(&action, &config, quiet) {
+                output::error(&e.to_string());
+                process::exit(e.exit_code());
+            }
+            return;
+        }
+        Some(Commands::Alias { local, action }) => {
+            if let Err(e) = alias_command(&action, local, &config, quiet) {
+                output::error(&e.to_string());
+                process::exit(e.exit_code());
+            }
+            return;
+        }
+        Some(Commands::Serve { stdio }) => {
+            if !stdio {
+                output::error("Only --stdio transport is supported");
+                process::exit(exit_codes::GENERIC_ERROR);
+            }
+            if let Err(e) = serve::serve_stdio() {
+                output::error(&e.to_string());
+                process::exit(e.exit_code());
+            }
+            return;
+        }
+        Some(Commands::Daemon { socket, roots }) => {
+            run_daemon(socket, roots, &config);
+            return;
+        }
+        // Runs like a task, below
+        Some(Commands::Exec { .. }) | None => {}
     }
 
     // Handle --update flag
+    #[cfg(not(feature = "updater"))]
+    if cli.update {
+        output::error("run was built without the `updater` feature");
+        process::exit(exit_codes::GENERIC_ERROR);
+    }
+    #[cfg(feature = "updater")]
     if cli.update {
-        let rt = tokio::runtime::Builder::new_current_thread()
-            .enable_all()
-            .build()
-            .unwrap();
-        match rt.block_on(update::perform_blocking_update(quiet)) {
+        match update::perform_blocking_update(quiet) {
             Ok(_) => process::exit(exit_codes::SUCCESS),
             Err(e) => {
                 output::error(&format!("Update failed: {}", e));
@@ -75,16 +233,22 @@ fn main() {
         }
     }
 
+    if cli.list {
+        list_tasks(false, max_levels, &ignore_list, &config, verbose);
+        return;
+    }
+
     // Require a command
-    let command = match &cli.command {
-        Some(cmd) => cmd.clone(),
-        None => {
-            // If no command, just show help
-            Cli::command().print_help().unwrap();
-            println!();
-            process::exit(exit_codes::SUCCESS);
-        }
+    let exec = match &cli.subcommand {
+        Some(Commands::Exec { args }) => Some(args),
+        _ => None,
     };
+    if cli.command.is_none() && exec.is_none() {
+        // If no command, just show help
+        Cli::command().print_help().unwrap();
+        println!();
+        process::exit(exit_codes::SUCCESS);
+    }
 
     // Get current directory
     let current_dir = match env::current_dir() {
@@ -95,126 +259,1478 @@ fn main() {
         }
     };
 
-    // Search for runners
-    let search_result = search_runners(
-        &current_dir,
-        max_levels,
-        &ignore_list,
-        verbose,
-    );
-
-    // Prepare to inject custom commands
-    // Filter empty commands
-    let valid_config_commands: Option<std::collections::HashMap<String, String>> =
-        config.commands.as_ref().map(|cmds| {
-            cmds.iter()
-                .filter(|(_, cmd)| !cmd.trim().is_empty())
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect()
-        });
+    let tasks = cli.tasks();
 
-    let has_valid_commands = valid_config_commands
-        .as_ref()
-        .map_or(false, |c| !c.is_empty());
+    // Load scripting hooks (relative paths are resolved from the current directory)
+    let script = match &config.script {
+        Some(path) => match ScriptHooks::load(&current_dir.join(path)) {
+            Ok(hooks) => Some(hooks),
+            Err(e) => {
+                output::error(&e.to_string());
+                process::exit(e.exit_code());
+            }
+        },
+        None => None,
+    };
 
-    let (mut runners, working_dir) = match search_result {
-        Ok(result) => result,
-        Err(e) => {
-            if has_valid_commands {
-                // If we have custom commands, we can proceed even without detected runners
-                (Vec::new(), current_dir.clone())
-            } else {
+    // Log file for task output (a configured path is relative to the current directory)
+    let log = match cli
+        .log_file
+        .clone()
+        .or_else(|| config.log_file.as_ref().map(PathBuf::from))
+    {
+        Some(path) => match TaskLog::open(&current_dir.join(path)) {
+            Ok(log) => Some(Arc::new(log)),
+            Err(e) => {
+                output::error(&e.to_string());
+                process::exit(e.exit_code());
+            }
+        },
+        None => None,
+    };
+
+    // Variables for every task: the dotenv files in order, then --env
+    let mut env_vars = Vec::new();
+    for path in &cli.env_files {
+        match dotenv::load(&current_dir.join(path)) {
+            Ok(vars) => env_vars.extend(vars),
+            Err(e) => {
                 output::error(&e.to_string());
-                eprintln!("Hint: Use --levels=N to increase search depth or check if you're in the right directory.");
                 process::exit(e.exit_code());
             }
         }
+    }
+    env_vars.extend(cli.env.iter().cloned());
+
+    let session = Session {
+        cli: &cli,
+        config: &config,
+        current_dir,
+        max_levels,
+        ignore_list: &ignore_list,
+        verbose,
+        quiet,
+        // The CLI prints its messages through the same events embedders receive
+        events: if cli.json {
+            EventBus::new().with(Arc::new(JsonObserver::new()))
+        } else {
+            EventBus::new().with(Arc::new(
+                CliObserver::new(verbose, quiet).timestamps(config.get_timestamps()),
+            ))
+        },
+        script,
+        log,
+        env: env_vars,
+        profile: &profile,
+        detected: RefCell::default(),
+        ran: RefCell::default(),
+        confirmed: RefCell::default(),
+    };
+    let report_profile = || {
+        if cli.profile {
+            output::profile(&profile.phases(), started.elapsed());
+        }
     };
 
-    // Inject custom commands from config
-    if let Some(valid_config_commands) = valid_config_commands {
-        if !valid_config_commands.is_empty() {
-            // Check if we already have a custom runner
-            if let Some(idx) = runners.iter().position(|r| r.ecosystem == Ecosystem::Custom) {
-                // Merge config commands into existing runner (local overrides global)
-                let mut merged_commands = valid_config_commands.clone();
-                if let Some(existing_cmds) = &runners[idx].custom_commands {
-                    merged_commands.extend(existing_cmds.clone());
-                }
+    let exit_code = if let Some(args) = exec {
+        match session.exec(args) {
+            Ok(result) => exit_code_of(result.exit_status),
+            Err(e) => {
+                report_error(&e);
+                report_profile();
+                process::exit(e.exit_code());
+            }
+        }
+    } else if cli.watch || !cli.watch_paths.is_empty() {
+        session.watch(&tasks)
+    } else if cli.parallel && !cli.chained() {
+        // A single task may still be a matrix to run in parallel
+        session.run_parallel(&tasks)
+    } else if let [(task, args)] = tasks.as_slice() {
+        match session.run_task(task, args) {
+            // Exit with the same code as the executed command
+            Ok(result) => exit_code_of(result.exit_status),
+            Err(e) => {
+                report_error(&e);
+                report_profile();
+                process::exit(e.exit_code());
+            }
+        }
+    } else {
+        session.run_sequence(&tasks)
+    };
+    report_profile();
 
-                // Update the runner
-                let old_runner = &runners[idx];
-                let new_runner = DetectedRunner::with_custom_commands(
-                    &old_runner.name,
-                    &old_runner.detected_file,
-                    old_runner.ecosystem,
-                    old_runner.priority,
-                    Arc::new(UnknownValidator),
-                    merged_commands,
-                );
-                runners[idx] = new_runner;
-            } else {
-                // Create new runner
-                let new_runner = DetectedRunner::with_custom_commands(
-                    "custom",
-                    "config.toml",
-                    Ecosystem::Custom,
+    // For dry run, always exit successfully
+    if cli.dry_run {
+        process::exit(exit_codes::SUCCESS);
+    }
+
+    // Update bookkeeping happens after the command, so it never delays the task
+    #[cfg(feature = "updater")]
+    {
+        update::check_update_notification(quiet || !config.get_auto_update());
+        // The function checks config internally and respects the throttle interval
+        update::spawn_background_update(&config);
+    }
+
+    process::exit(exit_code);
+}
+
+/// Print a task's error, with a hint when nothing was detected
+fn report_error(e: &RunError) {
+    output::error(&e.to_string());
+    if matches!(e, RunError::RunnerNotFound(_)) && !output::json() {
+        eprintln!("Hint: Use --levels=N to increase search depth or check if you're in the right directory.");
+    }
+}
+
+/// Keep `losers` out of future runs in `working_dir` by adding them to the
+/// `ignore_tools` of its run.toml, which otherwise starts from the ignore
+/// list in effect
+fn remember_choice(
+    working_dir: &Path,
+    losers: &[String],
+    config: &Config,
+) -> Result<PathBuf, RunError> {
+    let path = editable_config(working_dir)?;
+    let content = read_config_file(&path)?;
+    let mut ignored = toml::from_str::<Config>(&content)
+        .map(|local| local.ignore_tools)
+        .unwrap_or_default();
+    if ignored.is_empty() {
+        ignored = config.ignore_tools.clone();
+    }
+    for loser in losers {
+        if !ignored.contains(loser) {
+            ignored.push(loser.clone());
+        }
+    }
+    let value = toml::Value::Array(ignored.into_iter().map(toml::Value::String).collect());
+    fs::write(
+        &path,
+        set_setting(&content, "ignore_tools", &value.to_string())?,
+    )?;
+    Ok(path)
+}
+
+/// A task resolved to the runner that runs it
+#[derive(Clone)]
+struct Resolved {
+    runner: DetectedRunner,
+    working_dir: PathBuf,
+    /// The task as run.toml knows it, for its settings
+    command: String,
+    /// What the runner runs: `command`, or the task it is delegated to
+    task: String,
+    args: Vec<String>,
+    /// --timeout, else the task's `timeout` in run.toml
+    timeout: Option<Duration>,
+    /// --retry, else the task's `retries` in run.toml
+    retries: u32,
+    /// Why the task's `condition` doesn't hold, when it doesn't
+    skip: Option<String>,
+    /// Variables from the `env_files` and `env_file` of run.toml
+    env_files: Vec<(String, String)>,
+    /// This run's values of the task's `matrix`, as `{{matrix.<name>}}`
+    matrix: Vec<(String, String)>,
+}
+
+impl Resolved {
+    /// `task` with this run's matrix values (`build[x86_64,release]`), to
+    /// tell the runs of a matrix task apart
+    fn label(&self, task: &str) -> String {
+        if self.matrix.is_empty() {
+            return task.to_string();
+        }
+        let values: Vec<&str> = self
+            .matrix
+            .iter()
+            .map(|(_, value)| value.as_str())
+            .collect();
+        format!("{}[{}]", task, values.join(","))
+    }
+}
+
+/// Time spent in each phase of an invocation, for `--profile`
+#[derive(Default)]
+struct Profile {
+    phases: RefCell<Vec<(&'static str, Duration)>>,
+}
+
+impl Profile {
+    /// Run `f`, adding the time it takes to `phase`
+    fn time<T>(&self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        let elapsed = started.elapsed();
+        let mut phases = self.phases.borrow_mut();
+        match phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => phases.push((phase, elapsed)),
+        }
+        result
+    }
+
+    /// Each phase with its total time, in the order they first ran
+    fn phases(&self) -> Vec<(&'static str, Duration)> {
+        self.phases.borrow().clone()
+    }
+}
+
+/// What every task of one invocation shares
+struct Session<'a> {
+    cli: &'a Cli,
+    config: &'a Config,
+    current_dir: PathBuf,
+    max_levels: u8,
+    ignore_list: &'a [String],
+    verbose: bool,
+    quiet: bool,
+    events: EventBus,
+    script: Option<ScriptHooks>,
+    /// From --log-file or `log_file`
+    log: Option<Arc<TaskLog>>,
+    /// From --env-file and --env
+    env: Vec<(String, String)>,
+    profile: &'a Profile,
+    /// Runners found above the current directory, detected once for all tasks
+    detected: RefCell<Option<(Vec<DetectedRunner>, PathBuf)>>,
+    /// Tasks already run, so a shared dependency runs only once
+    ran: RefCell<HashSet<String>>,
+    /// Tasks marked `confirm` that were confirmed, so they are asked about once
+    confirmed: RefCell<HashSet<String>>,
+}
+
+impl Session<'_> {
+    /// Resolve `task` (after alias expansion) and run it after its
+    /// dependencies and `pre` hooks, then its `post` hooks. The result of
+    /// the first failure stands for the task's.
+    fn run_task(&self, task: &str, args: &[String]) -> Result<RunResult, RunError> {
+        let matrix = self.resolve_matrix(task, args)?;
+        self.run_resolved(&matrix)
+    }
+
+    /// [`Self::run_task`] for a resolved task, run once for each of its
+    /// `matrix` combinations
+    fn run_resolved(&self, matrix: &[Resolved]) -> Result<RunResult, RunError> {
+        let resolved = &matrix[0];
+        // A skipped task's dependencies and hooks are skipped with it
+        if resolved.skip.is_some() {
+            return self.execution(resolved).run();
+        }
+        if let Some(failed) = self.run_dependencies(resolved)? {
+            return Ok(failed);
+        }
+        let cache = self.task_cache(resolved);
+        if cache.as_ref().is_some_and(TaskCache::is_fresh) {
+            output::success(&format!(
+                "'{}' is cached: inputs unchanged",
+                resolved.command
+            ));
+            self.ran.borrow_mut().insert(resolved.command.clone());
+            return Ok(RunResult {
+                exit_status: process::ExitStatus::default(),
+                runner: resolved.runner.clone(),
+                working_dir: resolved.working_dir.clone(),
+                attempts: 0,
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            });
+        }
+        let hooks = self
+            .config
+            .task_hooks(&resolved.command, &resolved.working_dir);
+        if let Some(failed) = self.run_hooks(&hooks.pre)? {
+            return Ok(failed);
+        }
+        let outcome = self.profile.time("execution", || self.run_matrix(matrix));
+        let post = self.run_hooks(&hooks.post);
+
+        let result = match outcome {
+            Ok(result) if result.exit_status.success() => result,
+            failed => return failed,
+        };
+        if let Some(Err(e)) = cache.as_ref().map(TaskCache::record) {
+            output::warning(&format!("Could not cache '{}': {}", resolved.command, e));
+        }
+        self.ran.borrow_mut().insert(resolved.command.clone());
+        Ok(post?.unwrap_or(result))
+    }
+
+    /// Run each combination of a matrix task in turn, stopping at the first
+    /// that fails; the result of the last one run stands for them all
+    fn run_matrix(&self, matrix: &[Resolved]) -> Result<RunResult, RunError> {
+        let mut result = None;
+        for resolved in matrix {
+            let run = self.execution(resolved).run()?;
+            if !run.exit_status.success() {
+                return Ok(run);
+            }
+            result = Some(run);
+        }
+        Ok(result.expect("a matrix has at least one combination"))
+    }
+
+    /// The cache of a task that declares `inputs`, hashed as they are now;
+    /// `None` for other tasks and on a dry run
+    fn task_cache(&self, resolved: &Resolved) -> Option<TaskCache> {
+        let settings = self
+            .config
+            .task_settings(&resolved.command, &resolved.working_dir);
+        if settings.inputs.is_empty() || self.cli.dry_run {
+            return None;
+        }
+        let definition = format!(
+            "{} {} {:?} {:?}",
+            resolved.runner.name, resolved.task, resolved.args, settings
+        );
+        TaskCache::new(
+            &resolved.working_dir,
+            &resolved.command,
+            &definition,
+            &settings.inputs,
+            &settings.outputs,
+            &self.config.get_skip_dirs(),
+        )
+    }
+
+    /// Run hook entries, each a task and its arguments, stopping at the
+    /// first that fails and returning its result
+    fn run_hooks(&self, hooks: &[String]) -> Result<Option<RunResult>, RunError> {
+        for hook in hooks {
+            let words = shell_words::split(hook)
+                .map_err(|e| RunError::ConfigError(format!("hook '{}': {}", hook, e)))?;
+            let Some((task, args)) = words.split_first() else {
+                continue;
+            };
+            let matrix = self.resolve_matrix(task, args)?;
+            let result = self
+                .profile
+                .time("execution", || self.run_matrix(&matrix))?;
+            if !result.exit_status.success() {
+                return Ok(Some(result));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Run the `depends_on` tasks of `resolved` that haven't run yet, in
+    /// order, returning the result of the first one that fails
+    fn run_dependencies(&self, resolved: &Resolved) -> Result<Option<RunResult>, RunError> {
+        let dependencies = self
+            .config
+            .dependencies(&resolved.command, &resolved.working_dir)?;
+        for dependency in dependencies {
+            if self.ran.borrow().contains(&dependency) {
+                continue;
+            }
+            let matrix = self.resolve_matrix(&dependency, &[])?;
+            let result = self
+                .profile
+                .time("execution", || self.run_matrix(&matrix))?;
+            if !result.exit_status.success() {
+                return Ok(Some(result));
+            }
+            self.ran.borrow_mut().insert(dependency);
+        }
+        Ok(None)
+    }
+
+    /// Ask whether to run a task marked `confirm`, once per invocation.
+    /// `--yes`, dry runs and skipped tasks don't ask; where prompts aren't
+    /// allowed (see `interactive`) the task doesn't run.
+    fn confirm(&self, resolved: &Resolved) -> Result<(), RunError> {
+        let Some(question) = self
+            .config
+            .task_settings(&resolved.command, &resolved.working_dir)
+            .confirm
+            .and_then(|confirm| confirm.question(&resolved.command))
+        else {
+            return Ok(());
+        };
+        if self.cli.yes
+            || self.cli.dry_run
+            || resolved.skip.is_some()
+            || self.confirmed.borrow().contains(&resolved.command)
+        {
+            return Ok(());
+        }
+        let not_run =
+            |reason: &str| RunError::NotConfirmed(resolved.command.clone(), reason.to_string());
+        if !self.config.get_interactive() || output::json() {
+            return Err(not_run(
+                "it asks for confirmation; pass --yes to run it without a prompt",
+            ));
+        }
+        if !prompt::confirm(&question) {
+            return Err(not_run("not confirmed"));
+        }
+        self.confirmed.borrow_mut().insert(resolved.command.clone());
+        Ok(())
+    }
+
+    /// Detect the runners above the current directory or, with `--down`, in
+    /// the one project below it that could run `command`
+    fn detect(&self, command: Option<&str>) -> Result<(Vec<DetectedRunner>, PathBuf), RunError> {
+        if let Some(detected) = self.detected.borrow().clone() {
+            return Ok(detected);
+        }
+        let detected = detect_with_config(
+            &self.current_dir,
+            self.max_levels,
+            self.ignore_list,
+            self.config,
+            self.verbose,
+            detect_traced,
+        );
+        if let Ok(found) = &detected {
+            *self.detected.borrow_mut() = Some(found.clone());
+        }
+        let (Err(RunError::RunnerNotFound(_)), Some(depth)) = (&detected, self.cli.down) else {
+            return detected;
+        };
+
+        let mut found = search_down(
+            &self.current_dir,
+            depth,
+            command,
+            self.ignore_list,
+            &self.config.get_skip_dirs(),
+        );
+        let relative = |dir: &Path| {
+            dir.strip_prefix(&self.current_dir)
+                .unwrap_or(dir)
+                .display()
+                .to_string()
+        };
+        match found.len() {
+            0 => detected,
+            1 => {
+                let (dir, runners) = found.remove(0);
+                if !self.quiet {
+                    output::info(&format!("Using the project in {}", relative(&dir)));
+                }
+                // Configured commands and validators apply there too
+                detect_with_config(
+                    &dir,
                     0,
-                    Arc::new(UnknownValidator),
-                    valid_config_commands,
-                );
-                runners.push(new_runner);
-                // Sort by priority (0 first)
-                runners.sort_by_key(|r| r.priority);
+                    self.ignore_list,
+                    self.config,
+                    self.verbose,
+                    |_, _| runners.clone(),
+                )
             }
+            _ => Err(RunError::AmbiguousProject(
+                found
+                    .iter()
+                    .map(|(dir, runners)| {
+                        let names: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
+                        format!("{} ({})", relative(dir), names.join(", "))
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )),
         }
     }
 
-    // Check for conflicts and select runner based on command support
-    let runner = match check_conflicts(&runners, &working_dir, verbose) {
-        Ok(_) => match select_runner(&runners, &command, &working_dir, verbose) {
-            Ok(r) => r,
+    /// `run exec`: hand `args` to the detected tool as they are
+    fn exec(&self, args: &[String]) -> Result<RunResult, RunError> {
+        let (runners, working_dir) = self.profile.time("detection", || self.detect(None))?;
+        self.events.emit(&RunEvent::DetectionComplete {
+            working_dir: &working_dir,
+            runners: &runners,
+        });
+        let runner = self
+            .profile
+            .time("validation", || match &self.config.runner {
+                Some(name) => forced_runner(&runners, name),
+                None => self.choose_runner(runners, &working_dir, None),
+            })?;
+
+        // The first word stands in for the task in messages and script hooks
+        let resolved = Resolved {
+            runner,
+            working_dir,
+            command: args[0].clone(),
+            task: args[0].clone(),
+            args: args[1..].to_vec(),
+            timeout: self.cli.timeout,
+            retries: self.cli.retry.unwrap_or(0),
+            skip: None,
+            env_files: Vec::new(),
+            matrix: Vec::new(),
+        };
+        self.profile.time("execution", || {
+            self.execution(&resolved).passthrough(true).run()
+        })
+    }
+
+    /// Pick the runner for `task`, expanding aliases
+    fn resolve(&self, task: &str, args: &[String]) -> Result<Resolved, RunError> {
+        let config = self.config;
+
+        let (command, args) = expand_task(config, task, args);
+
+        // A running daemon answers from its warm caches; verbose runs stay local
+        // so every detection message is shown, forced runners and --no-validate
+        // skip validation, and only this process knows the logical ($PWD) path
+        #[cfg(all(unix, feature = "daemon"))]
+        let resolved = if self.verbose
+            || self.cli.no_validate
+            || config.runner.is_some()
+            || config.delegation(&command, &self.current_dir).is_some()
+            || !config.get_follow_symlinks()
+        {
+            None
+        } else {
+            self.profile.time("detection", || {
+                daemon::try_resolve(
+                    &self.current_dir,
+                    &command,
+                    self.max_levels,
+                    self.ignore_list,
+                )
+            })
+        };
+        #[cfg(not(all(unix, feature = "daemon")))]
+        let resolved = None;
+
+        let (runner, working_dir) = match resolved {
+            Some(resolved) => resolved,
+            None => {
+                let (runners, working_dir) = self
+                    .profile
+                    .time("detection", || self.detect(Some(&command)))?;
+
+                self.events.emit(&RunEvent::DetectionComplete {
+                    working_dir: &working_dir,
+                    runners: &runners,
+                });
+
+                // Check for conflicts and select runner based on command support,
+                // unless a runner was forced or the task names one with `use`
+                let delegation = config.delegation(&command, &working_dir);
+                let runner =
+                    self.profile
+                        .time("validation", || match (&delegation, &config.runner) {
+                            (Some((name, _)), _) | (None, Some(name)) => {
+                                forced_runner(&runners, name)
+                            }
+                            (None, None) => {
+                                self.choose_runner(runners, &working_dir, Some(&command))
+                            }
+                        })?;
+                (runner, working_dir)
+            }
+        };
+
+        if !self.quiet {
+            if let Some(warning) = wireit_warning(&runner, &command, &working_dir) {
+                output::warning(&warning);
+            }
+            if !config.get_corepack() {
+                if let Some(warning) = package_manager_warning(&runner, &working_dir) {
+                    output::warning(&warning);
+                }
+            }
+        }
+
+        let (task, args) = match config.delegation(&command, &working_dir) {
+            Some((_, mut words)) => {
+                let task = words.remove(0);
+                words.extend(args);
+                (task, words)
+            }
+            None => (command.clone(), args),
+        };
+        let settings = config.task_settings(&command, &working_dir);
+        let skip = settings.condition.as_ref().and_then(|condition| {
+            condition.unmet(&working_dir, |var| {
+                env::var_os(var).is_some()
+                    || config.env.as_ref().is_some_and(|env| env.contains_key(var))
+                    || self.env.iter().any(|(key, _)| key == var)
+            })
+        });
+        let mut env_files = Vec::new();
+        if skip.is_none() {
+            for path in config.task_env_files(&command, &working_dir) {
+                env_files.extend(dotenv::load(&path)?);
+            }
+        }
+        let timeout = match (self.cli.timeout, settings.timeout) {
+            (Some(timeout), _) => Some(timeout),
+            (None, Some(timeout)) => Some(parse_duration(&timeout).map_err(|e| {
+                RunError::ConfigError(format!("timeout of task '{}': {}", command, e))
+            })?),
+            (None, None) => None,
+        };
+
+        Ok(Resolved {
+            runner,
+            working_dir,
+            command,
+            task,
+            args,
+            timeout,
+            retries: self.cli.retry.or(settings.retries).unwrap_or(0),
+            skip,
+            env_files,
+            matrix: Vec::new(),
+        })
+    }
+
+    /// Resolve `task` once for each combination of its `matrix` values,
+    /// once its `confirm` question is answered
+    fn resolve_matrix(&self, task: &str, args: &[String]) -> Result<Vec<Resolved>, RunError> {
+        let resolved = self.resolve(task, args)?;
+        self.confirm(&resolved)?;
+        Ok(self
+            .config
+            .task_matrix(&resolved.command, &resolved.working_dir)
+            .into_iter()
+            .map(|matrix| Resolved {
+                matrix,
+                ..resolved.clone()
+            })
+            .collect())
+    }
+
+    /// [`resolve_runner`] (or [`resolve_tool`] without a `command`), asking
+    /// which tool to use when lockfiles conflict and prompts are allowed
+    /// (`--choose`)
+    fn choose_runner(
+        &self,
+        mut runners: Vec<DetectedRunner>,
+        working_dir: &Path,
+        command: Option<&str>,
+    ) -> Result<DetectedRunner, RunError> {
+        let choose = self.cli.choose || (self.config.get_interactive() && !output::json());
+        loop {
+            let resolved = match command {
+                Some(command) if self.cli.no_validate => {
+                    resolve_unvalidated(&runners, working_dir, command, self.verbose)
+                }
+                Some(command) => resolve_runner(&runners, working_dir, command, self.verbose),
+                None => resolve_tool(&runners, working_dir, self.verbose),
+            };
+            let message = match resolved {
+                Err(RunError::LockfileConflict(message)) if choose => message,
+                result => return result,
+            };
+            let choices = conflict_choices(&runners);
+            let options: Vec<String> = choices
+                .iter()
+                .map(|r| format!("{} ({})", r.name, r.detected_file))
+                .collect();
+            let question = message.lines().next().unwrap_or_default();
+            let Some(index) = prompt::select(question, &options) else {
+                return Err(RunError::LockfileConflict(message));
+            };
+
+            let chosen = &choices[index];
+            let losers: Vec<String> = choices
+                .iter()
+                .filter(|r| r.name != chosen.name)
+                .map(|r| r.name.clone())
+                .collect();
+            if prompt::confirm(&format!(
+                "Remember {} for this project in run.toml?",
+                chosen.name
+            )) {
+                match remember_choice(working_dir, &losers, self.config) {
+                    Ok(path) => output::success(&format!(
+                        "Added {} to ignore_tools in {}",
+                        losers.join(", "),
+                        path.display()
+                    )),
+                    Err(e) => output::warning(&format!("Could not remember the choice: {}", e)),
+                }
+            }
+            runners.retain(|r| !losers.contains(&r.name));
+        }
+    }
+
+    /// How `resolved` is executed with this invocation's settings
+    fn execution<'s>(&'s self, resolved: &'s Resolved) -> ExecutionBuilder<'s> {
+        let config = self.config;
+        let settings = config.task_settings(&resolved.command, &resolved.working_dir);
+        // run.toml's dotenv files, then [env], then --env-file and --env
+        let execution = ExecutionBuilder::new(&resolved.runner, &resolved.task)
+            .args(&resolved.args)
+            .envs(resolved.env_files.iter().cloned())
+            .envs(config.env_vars())
+            .cwd(&resolved.working_dir)
+            .dry_run(self.cli.dry_run)
+            .corepack(config.get_corepack())
+            .nix_develop(config.get_nix_develop())
+            .devcontainer(self.cli.in_devcontainer)
+            .invocation_dir(&self.current_dir)
+            .workspace(self.cli.workspace)
+            .script(self.script.as_ref())
+            .log_file(self.log.clone())
+            .events(self.events.clone())
+            .envs(self.env.iter().cloned())
+            .shell(config.task_shell(&resolved.command, &resolved.working_dir))
+            .task_script(settings.script.map(|script| script.text()))
+            .vars(
+                config
+                    .template_vars(env::vars())
+                    .into_iter()
+                    .chain(resolved.matrix.iter().cloned())
+                    .collect(),
+            )
+            .skip(resolved.skip.clone())
+            .retries(resolved.retries)
+            .retry_delay(self.cli.retry_delay)
+            .group(settings.group)
+            .pty(self.cli.pty);
+        // A relative stdin file is where the user ran `run`, not the project
+        let execution = match &self.cli.stdin {
+            Some(StdinSource::File(path)) => {
+                execution.stdin(StdinSource::File(self.current_dir.join(path)))
+            }
+            Some(source) => execution.stdin(source.clone()),
+            None => execution,
+        };
+        match resolved.timeout {
+            Some(timeout) => execution.timeout(timeout),
+            None => execution,
+        }
+    }
+
+    /// Run the task, and again whenever a project file changes (`--watch`)
+    fn watch(&self, tasks: &[(String, Vec<String>)]) -> i32 {
+        let [(task, args)] = tasks else {
+            output::error("--watch runs a single task");
+            return exit_codes::GENERIC_ERROR;
+        };
+        let prepared = self.resolve(task, args).and_then(|resolved| {
+            let command = self.execution(&resolved).prepare()?;
+            Ok((resolved.working_dir, command))
+        });
+        let (working_dir, command) = match prepared {
+            Ok(prepared) => prepared,
             Err(e) => {
-                output::error(&e.to_string());
-                process::exit(e.exit_code());
+                report_error(&e);
+                return e.exit_code();
             }
-        },
+        };
+        if self.cli.dry_run {
+            return exit_codes::SUCCESS;
+        }
+
+        #[cfg(feature = "watch")]
+        {
+            let filter = watch::WatchFilter::new(
+                &working_dir,
+                &self.current_dir,
+                &self.cli.watch_paths,
+                self.config.get_skip_dirs(),
+            );
+            let e = match watch::watch(&command, &filter, self.quiet) {
+                Ok(()) => return exit_codes::SUCCESS,
+                Err(e) => e,
+            };
+            report_error(&e);
+            e.exit_code()
+        }
+        #[cfg(not(feature = "watch"))]
+        {
+            let _ = (working_dir, command);
+            output::error("This build of run was compiled without the watch feature");
+            exit_codes::GENERIC_ERROR
+        }
+    }
+
+    /// Run `tasks` at the same time, each combination of a matrix task on
+    /// its own, each output line prefixed with its task; returns the exit
+    /// code of the first run (in order) that failed. A failure stops the
+    /// others unless `--keep-going`.
+    fn run_parallel(&self, tasks: &[(String, Vec<String>)]) -> i32 {
+        // Everything is resolved first, so an unknown task fails before any
+        // starts, then dependencies and pre hooks run one at a time
+        let mut resolved = Vec::new();
+        for (task, args) in tasks {
+            match self.resolve_matrix(task, args) {
+                Ok(matrix) => resolved.push(matrix),
+                Err(e) => {
+                    report_error(&e);
+                    return e.exit_code();
+                }
+            }
+        }
+        // A lone task without a matrix has nothing to run alongside
+        if let [matrix] = resolved.as_slice() {
+            if matrix.len() == 1 {
+                return match self.run_resolved(matrix) {
+                    Ok(result) => exit_code_of(result.exit_status),
+                    Err(e) => {
+                        report_error(&e);
+                        e.exit_code()
+                    }
+                };
+            }
+        }
+        // Tasks whose condition doesn't hold only say they are skipped (a
+        // skipped execution can't fail)
+        let (tasks, resolved): (Vec<_>, Vec<_>) = tasks
+            .iter()
+            .zip(resolved)
+            .filter(|(_, matrix)| {
+                let skipped = matrix[0].skip.is_some();
+                if skipped {
+                    let _ = self.execution(&matrix[0]).run();
+                }
+                !skipped
+            })
+            .unzip();
+        let hooks: Vec<_> = resolved
+            .iter()
+            .map(|matrix| {
+                self.config
+                    .task_hooks(&matrix[0].command, &matrix[0].working_dir)
+            })
+            .collect();
+        for (matrix, hooks) in resolved.iter().zip(&hooks) {
+            let task = &matrix[0];
+            let before = self.run_dependencies(task).and_then(|failed| match failed {
+                Some(failed) => Ok(Some(failed)),
+                None => self.run_hooks(&hooks.pre),
+            });
+            match before {
+                Ok(None) => {}
+                Ok(Some(failed)) => return exit_code_of(failed.exit_status),
+                Err(e) => {
+                    report_error(&e);
+                    return e.exit_code();
+                }
+            }
+        }
+        // Each combination of a matrix task runs on its own
+        let (names, resolved): (Vec<_>, Vec<_>) = tasks
+            .iter()
+            .zip(&resolved)
+            .flat_map(|((task, _), matrix)| matrix.iter().map(|run| (run.label(task), run)))
+            .unzip();
+        let mut prepared = Vec::new();
+        for task in resolved {
+            match self.execution(task).prepare() {
+                Ok(command) => prepared.push(command),
+                Err(e) => {
+                    report_error(&e);
+                    return e.exit_code();
+                }
+            }
+        }
+        if self.cli.dry_run {
+            return exit_codes::SUCCESS;
+        }
+
+        let width = names.iter().map(String::len).max().unwrap_or(0);
+        let results = self.profile.time("execution", || {
+            execute_parallel(&prepared, !self.cli.keep_going, |index, line| {
+                if let Some(log) = &self.log {
+                    let (stream, text) = match &line {
+                        OutputLine::Stdout(text) => (Stream::Stdout, text),
+                        OutputLine::Stderr(text) => (Stream::Stderr, text),
+                    };
+                    log.line(stream, &format!("{} | {}", names[index], text));
+                }
+                output::prefixed_line(&names[index], index, width, &line)
+            })
+        });
+
+        let mut reports = Vec::new();
+        let mut exit_code = exit_codes::SUCCESS;
+        for (task, (exit, duration)) in names.iter().zip(results) {
+            let report = match exit {
+                ParallelExit::Exited(status) => {
+                    TaskReport::finished(task, exit_code_of(status), duration)
+                }
+                ParallelExit::Stopped => TaskReport::stopped(task, duration),
+                ParallelExit::Failed(e) => {
+                    report_error(&e);
+                    TaskReport::finished(task, e.exit_code(), duration)
+                }
+            };
+            if let (TaskOutcome::Finished(code), exit_codes::SUCCESS) = (report.outcome, exit_code)
+            {
+                exit_code = code;
+            }
+            reports.push(report);
+        }
+        for hooks in &hooks {
+            let code = match self.run_hooks(&hooks.post) {
+                Ok(None) => continue,
+                Ok(Some(failed)) => exit_code_of(failed.exit_status),
+                Err(e) => {
+                    report_error(&e);
+                    e.exit_code()
+                }
+            };
+            if exit_code == exit_codes::SUCCESS {
+                exit_code = code;
+            }
+        }
+
+        if !self.quiet {
+            output::task_summary(&reports);
+        }
+        exit_code
+    }
+
+    /// Run `tasks` one after another, stopping at the first failure unless
+    /// `--keep-going`; returns the exit code of the first failure
+    fn run_sequence(&self, tasks: &[(String, Vec<String>)]) -> i32 {
+        let mut reports = Vec::new();
+        let mut exit_code = exit_codes::SUCCESS;
+        let mut interrupted = false;
+
+        for (task, args) in tasks {
+            // An interrupted run stops even with --keep-going
+            if exit_code != exit_codes::SUCCESS && (!self.cli.keep_going || interrupted) {
+                reports.push(TaskReport::skipped(task));
+                continue;
+            }
+
+            let started = Instant::now();
+            let code = match self.run_task(task, args) {
+                Ok(result) => exit_code_of(result.exit_status),
+                Err(e) => {
+                    interrupted = matches!(e, RunError::Interrupted(_));
+                    report_error(&e);
+                    e.exit_code()
+                }
+            };
+            reports.push(TaskReport::finished(task, code, started.elapsed()));
+            if exit_code == exit_codes::SUCCESS {
+                exit_code = code;
+            }
+        }
+
+        if !self.quiet {
+            output::task_summary(&reports);
+        }
+        exit_code
+    }
+}
+
+/// `task` and `args` with aliases expanded, arguments given on the command
+/// line following the alias's own. A `--` with no arguments before it has
+/// nothing to separate and is dropped: `run test -- --coverage` runs
+/// `test --coverage`.
+fn expand_task(config: &Config, task: &str, args: &[String]) -> (String, Vec<String>) {
+    let (task, mut args) = config
+        .expand_alias(task, args)
+        .unwrap_or_else(|| (task.to_string(), args.to_vec()));
+    if args.first().is_some_and(|word| word == "--") {
+        args.remove(0);
+    }
+    (task, args)
+}
+
+/// `run list`: print the tasks of the runner `run` picks, with the file it
+/// was detected from; `all` adds the other runners detected in the same
+/// directory. Runners that can't enumerate their tasks are skipped.
+fn list_tasks(all: bool, max_levels: u8, ignore_list: &[String], config: &Config, verbose: bool) {
+    let current_dir = match env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            output::error(&format!("Failed to get current directory: {}", e));
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+    };
+    let (mut runners, working_dir) = match detect_with_config(
+        &current_dir,
+        max_levels,
+        ignore_list,
+        config,
+        verbose,
+        detect_traced,
+    ) {
+        Ok(result) => result,
         Err(e) => {
             output::error(&e.to_string());
             process::exit(e.exit_code());
         }
     };
 
-    // Execute the command
-    let result = match execute(
-        &runner,
-        &command,
-        &cli.args,
-        &working_dir,
-        cli.dry_run,
+    // The runner a task goes to first: run.toml's, else the conflict winner
+    if !all {
+        let picked = check_conflicts(&runners, &working_dir, verbose)
+            .map(|runner| runner.name)
+            .ok()
+            .or_else(|| runners.first().map(|runner| runner.name.clone()));
+        runners.retain(|runner| Some(&runner.name) == picked.as_ref());
+    }
+
+    let mut listed = false;
+    for runner in &runners {
+        let tasks = runner.list_commands(&working_dir);
+        if !tasks.is_empty() {
+            let namespaced = runner.ecosystem == Ecosystem::Custom;
+            output::task_list(&runner.name, &runner.detected_file, &tasks, namespaced);
+            listed = true;
+        }
+    }
+    if !listed {
+        let names: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
+        output::warning(&format!(
+            "No tasks to list; {} can't enumerate their tasks",
+            names.join(", ")
+        ));
+    }
+}
+
+/// `run search <keyword>`: print the matching tasks of the detected runners,
+/// exiting non-zero when none match
+fn search_tasks(
+    keyword: &str,
+    max_levels: u8,
+    ignore_list: &[String],
+    config: &Config,
+    verbose: bool,
+) {
+    let current_dir = match env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            output::error(&format!("Failed to get current directory: {}", e));
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+    };
+    let (runners, working_dir) = match detect_with_config(
+        &current_dir,
+        max_levels,
+        ignore_list,
+        config,
         verbose,
-        quiet,
+        detect_traced,
     ) {
-        Ok(r) => r,
+        Ok(result) => result,
         Err(e) => {
             output::error(&e.to_string());
             process::exit(e.exit_code());
         }
     };
 
-    // For dry run, always exit successfully
-    if cli.dry_run {
-        process::exit(exit_codes::SUCCESS);
+    let matches = search::search(&runners, &working_dir, keyword);
+    if matches.is_empty() {
+        output::warning(&format!("No task matches '{}'", keyword));
+        process::exit(exit_codes::GENERIC_ERROR);
     }
+    output::search_results(&matches);
+}
 
-    // Spawn background update check (after command completes)
-    // The function checks config internally and respects the throttle interval
-    update::spawn_background_update(&config);
+/// `run which <task>` and `run explain <task>`: print how the task resolves
+/// with `report`, exiting non-zero when it doesn't
+fn which(
+    task: &str,
+    args: &[String],
+    scope_workspace: bool,
+    config: &Config,
+    max_levels: u8,
+    ignore_list: &[String],
+    report: fn(&Resolution),
+) {
+    let current_dir = match env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            output::error(&format!("Failed to get current directory: {}", e));
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+    };
+    let (task, args) = expand_task(config, task, args);
 
-    // Exit with the same code as the executed command
-    let exit_code = result
-        .exit_status
-        .code()
-        .unwrap_or(exit_codes::GENERIC_ERROR);
-    process::exit(exit_code);
+    match trace_resolution(
+        &current_dir,
+        &task,
+        &args,
+        max_levels,
+        ignore_list,
+        config,
+        scope_workspace,
+    ) {
+        Ok(resolution) => {
+            report(&resolution);
+            if resolution.error.is_some() {
+                process::exit(exit_codes::GENERIC_ERROR);
+            }
+        }
+        Err(e) => {
+            output::error(&e.to_string());
+            process::exit(e.exit_code());
+        }
+    }
+}
+
+/// `run init`: write a starter run.toml in the current directory
+fn init(force: bool, max_levels: u8, ignore_list: &[String], config: &Config, quiet: bool) {
+    let current_dir = match env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            output::error(&format!("Failed to get current directory: {}", e));
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+    };
+    let path = current_dir.join("run.toml");
+    match custom::config_file(&current_dir) {
+        Some(existing) if existing != path => {
+            output::error(&format!(
+                "{} already exists, and a run.toml would take its place",
+                existing.display()
+            ));
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+        Some(_) if !force => {
+            output::error("run.toml already exists; use --force to overwrite it");
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+        _ => {}
+    }
+
+    // Nothing detected still makes a useful (empty) starter
+    let (runners, working_dir) = detect_with_config(
+        &current_dir,
+        max_levels,
+        ignore_list,
+        config,
+        false,
+        detect_all,
+    )
+    .unwrap_or_else(|_| (Vec::new(), current_dir.clone()));
+
+    if let Err(e) = fs::write(&path, starter_toml(&runners, &working_dir)) {
+        output::error(&format!("Failed to write {}: {}", path.display(), e));
+        process::exit(exit_codes::GENERIC_ERROR);
+    }
+    if !quiet {
+        output::success(&format!("Created {}", path.display()));
+    }
+}
+
+/// `run upgrade-tool`: update the tool `run exec` would use, returning the
+/// exit code
+fn upgrade_tool(
+    dry_run: bool,
+    max_levels: u8,
+    ignore_list: &[String],
+    config: &Config,
+    verbose: bool,
+    quiet: bool,
+) -> i32 {
+    let current_dir = match env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            output::error(&format!("Failed to get current directory: {}", e));
+            return exit_codes::GENERIC_ERROR;
+        }
+    };
+    let resolved = detect_with_config(
+        &current_dir,
+        max_levels,
+        ignore_list,
+        config,
+        verbose,
+        detect_traced,
+    )
+    .and_then(|(runners, working_dir)| {
+        let runner = match &config.runner {
+            Some(name) => forced_runner(&runners, name),
+            None => resolve_tool(&runners, &working_dir, verbose),
+        }?;
+        let command = runner.upgrade_command().ok_or_else(|| {
+            RunError::InvalidArgument(format!(
+                "run doesn't know how to upgrade {}; update it the way it was installed",
+                runner.name
+            ))
+        })?;
+        Ok((command, working_dir))
+    });
+    let (command, working_dir) = match resolved {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            report_error(&e);
+            return e.exit_code();
+        }
+    };
+
+    if dry_run {
+        println!("{}", command.join(" "));
+        return exit_codes::SUCCESS;
+    }
+    if !quiet {
+        output::executing(&command.join(" "));
+    }
+    let command = tools::spawn_command(&command, &working_dir);
+    match process::Command::new(&command[0])
+        .args(&command[1..])
+        .current_dir(&working_dir)
+        .status()
+    {
+        Ok(status) => exit_code_of(status),
+        Err(e) => {
+            output::error(&format!("Failed to execute {}: {}", command[0], e));
+            exit_codes::GENERIC_ERROR
+        }
+    }
+}
+
+/// `run doctor`: report on tools and config, exiting non-zero on problems
+fn doctor(max_levels: u8, ignore_list: &[String], config: &Config) {
+    let current_dir = match env::current_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            output::error(&format!("Failed to get current directory: {}", e));
+            process::exit(exit_codes::GENERIC_ERROR);
+        }
+    };
+
+    let checks = doctor::diagnose(&current_dir, max_levels, ignore_list, config);
+    output::diagnostics(&checks);
+    if doctor::has_problems(&checks) {
+        process::exit(exit_codes::GENERIC_ERROR);
+    }
+}
+
+/// `run config`: read or change the global config file
+fn config_command(action: &ConfigAction, config: &Config, quiet: bool) -> Result<(), RunError> {
+    let path = global_config_path()?;
+    let content = read_config_file(&path)?;
+
+    match action {
+        ConfigAction::Get { key } => match get_setting(&content, key)? {
+            Some(value) => println!("{}", value),
+            // Unset: nothing to print, like `git config`
+            None => process::exit(exit_codes::GENERIC_ERROR),
+        },
+        ConfigAction::Set { key, value } => {
+            let updated = set_setting(&content, key, value)?;
+            Config::ensure_config_dir()?;
+            fs::write(&path, updated)?;
+            if !quiet {
+                output::success(&format!("Set {} in {}", key, path.display()));
+            }
+        }
+        ConfigAction::Unset { key } => match unset_setting(&content, key)? {
+            Some(updated) => {
+                fs::write(&path, updated)?;
+                if !quiet {
+                    output::success(&format!("Removed {} from {}", key, path.display()));
+                }
+            }
+            None => {
+                if !quiet {
+                    output::warning(&format!("{} is not set in {}", key, path.display()));
+                }
+            }
+        },
+        ConfigAction::Edit => {
+            if !path.exists() {
+                Config::ensure_config_dir()?;
+                fs::write(&path, "")?;
+            }
+            let editor = env::var("VISUAL")
+                .or_else(|_| env::var("EDITOR"))
+                .unwrap_or_else(|_| if cfg!(windows) { "notepad" } else { "vi" }.to_string());
+            let mut words = shell_words::split(&editor)
+                .ok()
+                .filter(|words| !words.is_empty())
+                .ok_or_else(|| RunError::ConfigError(format!("Invalid editor '{}'", editor)))?;
+            let program = words.remove(0);
+            let status = process::Command::new(&program)
+                .args(words)
+                .arg(&path)
+                .status()
+                .map_err(|e| {
+                    RunError::CommandFailed(format!("Failed to start {}: {}", program, e))
+                })?;
+            if !status.success() {
+                return Err(RunError::CommandFailed(format!(
+                    "{} exited with {}",
+                    program, status
+                )));
+            }
+            if let Err(e) = Config::load_from_file(&path) {
+                output::warning(&format!(
+                    "{} is invalid and will be ignored: {}",
+                    path.display(),
+                    e.to_string().trim_end()
+                ));
+            }
+        }
+        ConfigAction::Path => println!("{}", path.display()),
+        ConfigAction::Show => {
+            let effective = toml::Value::try_from(config)
+                .and_then(|value| toml::to_string(&value))
+                .map_err(|e| RunError::ConfigError(e.to_string()))?;
+            print!("{}", output::redact(&effective));
+        }
+    }
+    Ok(())
+}
+
+fn global_config_path() -> Result<PathBuf, RunError> {
+    Config::global_config_path()
+        .ok_or_else(|| RunError::ConfigError("Could not determine config directory".to_string()))
+}
+
+/// A config file's contents; a missing file reads as empty
+/// The run.toml in `dir` to write settings to. A project configured in
+/// run.yaml, run.yml or run.json is edited by hand: a new run.toml would
+/// take its place.
+fn editable_config(dir: &Path) -> Result<PathBuf, RunError> {
+    match custom::config_file(dir) {
+        Some(path) if path.extension().is_some_and(|ext| ext != "toml") => {
+            Err(RunError::ConfigError(format!(
+                "{} can't be edited by run; change it by hand",
+                path.display()
+            )))
+        }
+        _ => Ok(dir.join("run.toml")),
+    }
+}
+
+fn read_config_file(path: &Path) -> Result<String, RunError> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// `run alias`: aliases live under `[aliases]` in the global config, or in
+/// ./run.toml with `--local`
+fn alias_command(
+    action: &AliasAction,
+    local: bool,
+    config: &Config,
+    quiet: bool,
+) -> Result<(), RunError> {
+    let path = if local {
+        editable_config(Path::new("."))?
+    } else {
+        global_config_path()?
+    };
+    let content = read_config_file(&path)?;
+
+    match action {
+        AliasAction::Add { name, expansion } => {
+            check_alias_name(name)?;
+            let updated = set_setting(
+                &content,
+                &format!("aliases.{}", name),
+                &shell_words::join(expansion),
+            )?;
+            if !local {
+                Config::ensure_config_dir()?;
+            }
+            fs::write(&path, updated)?;
+            if !quiet {
+                output::success(&format!(
+                    "`run {}` now runs `run {}` ({})",
+                    name,
+                    shell_words::join(expansion),
+                    path.display()
+                ));
+            }
+        }
+        AliasAction::List => {
+            let mut aliases: Vec<(&String, &String)> = config.aliases.iter().flatten().collect();
+            aliases.sort();
+            if aliases.is_empty() && !quiet && !output::json() {
+                output::info("No aliases defined (add one with `run alias add NAME TASK...`)");
+            }
+            output::alias_list(&aliases);
+        }
+        AliasAction::Rm { name } => {
+            check_alias_name(name)?;
+            match unset_setting(&content, &format!("aliases.{}", name))? {
+                Some(updated) => {
+                    fs::write(&path, updated)?;
+                    if !quiet {
+                        output::success(&format!("Removed alias {} from {}", name, path.display()));
+                    }
+                }
+                None => {
+                    return Err(RunError::ConfigError(format!(
+                        "No alias '{}' in {}",
+                        name,
+                        path.display()
+                    )));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Alias names are typed as tasks: one word, not a task list
+fn check_alias_name(name: &str) -> Result<(), RunError> {
+    if name.is_empty()
+        || name.starts_with('-')
+        || name.contains(|c: char| c.is_whitespace() || c == ',' || c == '.')
+    {
+        return Err(RunError::InvalidArgument(format!(
+            "Invalid alias name '{}'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// `run completions --install`: write the script where the shell finds it
+fn install_completions(shell: Option<Shell>, quiet: bool) {
+    let Some(shell) = shell.or_else(completions::detect_shell) else {
+        output::error("Could not detect your shell from $SHELL; pass it explicitly (e.g. `run completions zsh --install`)");
+        process::exit(exit_codes::GENERIC_ERROR);
+    };
+    match completions::install(shell) {
+        Ok(target) => {
+            if !quiet {
+                output::success(&format!(
+                    "Installed {} completions to {}",
+                    shell,
+                    target.path.display()
+                ));
+            }
+            // Printed even when quiet: completions won't load without it
+            if let Some(snippet) = target.rc_snippet {
+                println!(
+                    "Add this to your shell rc if it isn't there yet:\n\n{}",
+                    snippet
+                );
+            }
+        }
+        Err(e) => {
+            output::error(&e.to_string());
+            process::exit(e.exit_code());
+        }
+    }
+}
+
+/// `run daemon`: serve detection from a warm cache until interrupted
+#[cfg(all(unix, feature = "daemon"))]
+fn run_daemon(socket: Option<PathBuf>, roots: Vec<PathBuf>, config: &Config) {
+    let Some(socket) = socket.or_else(daemon::default_socket_path) else {
+        output::error("Could not determine a socket path; pass --socket");
+        process::exit(exit_codes::GENERIC_ERROR);
+    };
+    let roots = if roots.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        roots
+    };
+
+    output::info(&format!("Listening on {}", socket.display()));
+    if let Err(e) = daemon::run_daemon(&socket, &roots, config.get_skip_dirs()) {
+        output::error(&e.to_string());
+        process::exit(e.exit_code());
+    }
+}
+
+#[cfg(not(all(unix, feature = "daemon")))]
+fn run_daemon(_socket: Option<PathBuf>, _roots: Vec<PathBuf>, _config: &Config) {
+    output::error("run was built without daemon support (Unix only, `daemon` feature)");
+    process::exit(exit_codes::GENERIC_ERROR);
 }
@@ -0,0 +1,141 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Nix development shells.
+//!
+//! Projects that keep their toolchain in Nix often have nothing on `PATH`
+//! outside the dev shell. When the runner's tool is missing, the command can
+//! be wrapped to run inside the project's shell instead.
+
+use crate::detectors::is_tool_installed;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A Nix dev shell definition found in or above the working directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DevShell {
+    /// `flake.nix` declaring `devShells` (or the legacy `devShell`)
+    Flake(PathBuf),
+    /// `shell.nix`
+    Shell(PathBuf),
+}
+
+impl DevShell {
+    /// Nearest dev shell at or above `dir`; a flake wins over `shell.nix` in
+    /// the same directory
+    pub fn find(dir: &Path) -> Option<Self> {
+        let dir = dir.canonicalize().ok()?;
+        dir.ancestors().find_map(|dir| {
+            let flake = dir.join("flake.nix");
+            if fs::read_to_string(&flake).is_ok_and(|content| content.contains("devShell")) {
+                return Some(DevShell::Flake(dir.to_path_buf()));
+            }
+            let shell = dir.join("shell.nix");
+            shell.is_file().then_some(DevShell::Shell(shell))
+        })
+    }
+
+    /// File to mention in messages
+    pub fn file(&self) -> PathBuf {
+        match self {
+            DevShell::Flake(dir) => dir.join("flake.nix"),
+            DevShell::Shell(file) => file.clone(),
+        }
+    }
+
+    /// Whether the Nix command needed to enter this shell is installed
+    pub fn is_available(&self) -> bool {
+        match self {
+            DevShell::Flake(_) => is_tool_installed("nix"),
+            DevShell::Shell(_) => is_tool_installed("nix-shell"),
+        }
+    }
+
+    /// `command` run inside the shell
+    pub fn wrap(&self, command: &[String]) -> Vec<String> {
+        match self {
+            DevShell::Flake(dir) => {
+                let mut wrapped = vec![
+                    "nix".to_string(),
+                    "develop".to_string(),
+                    dir.to_string_lossy().into_owned(),
+                    "-c".to_string(),
+                ];
+                wrapped.extend(command.iter().cloned());
+                wrapped
+            }
+            DevShell::Shell(file) => vec![
+                "nix-shell".to_string(),
+                file.to_string_lossy().into_owned(),
+                "--run".to_string(),
+                shell_words::join(command),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_find_flake_dev_shell_from_subdirectory() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        fs::write(
+            root.join("flake.nix"),
+            "{ outputs = { self, nixpkgs }: { devShells.x86_64-linux.default = null; }; }",
+        )
+        .unwrap();
+        let sub = root.join("api");
+        fs::create_dir(&sub).unwrap();
+
+        let shell = DevShell::find(&sub).unwrap();
+        assert_eq!(shell, DevShell::Flake(root.clone()));
+        assert_eq!(
+            shell.wrap(&["cargo".to_string(), "build".to_string()]),
+            vec![
+                "nix".to_string(),
+                "develop".to_string(),
+                root.to_string_lossy().into_owned(),
+                "-c".to_string(),
+                "cargo".to_string(),
+                "build".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_shell_nix_and_flakes_without_dev_shell() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        // A flake that only builds packages has no shell to enter
+        fs::write(root.join("flake.nix"), "{ outputs = _: { }; }").unwrap();
+        assert_eq!(DevShell::find(&root), None);
+
+        fs::write(
+            root.join("shell.nix"),
+            "{ pkgs ? import <nixpkgs> {} }: null",
+        )
+        .unwrap();
+        let shell = DevShell::find(&root).unwrap();
+        assert_eq!(shell, DevShell::Shell(root.join("shell.nix")));
+        assert_eq!(
+            shell.wrap(&[
+                "npm".to_string(),
+                "run".to_string(),
+                "dev server".to_string()
+            ])[2..],
+            ["--run".to_string(), "npm run 'dev server'".to_string()]
+        );
+    }
+}
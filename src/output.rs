@@ -9,16 +9,118 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
+use crate::config::is_ci;
+use crate::detectors::CommandSupport;
+use crate::doctor::{Check, Status};
+use crate::events::{RunEvent, RunObserver};
+use crate::runner::{OutputLine, Resolution, TaskOutcome, TaskReport};
+use crate::search::SearchMatch;
 use owo_colors::OwoColorize;
+use serde_json::json;
 use std::env;
+use std::io::{self, IsTerminal};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
-/// Check if colors should be disabled
+/// Whether output is newline-delimited JSON (`--json`)
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// The `--color` choice, as a [`ColorChoice`] discriminant
+static COLOR: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// How much `run` explains itself (`-v`, `-vv`, `-vvv`)
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Values shown as `****` in commands, config dumps and log files
+static SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// When to color output (`--color`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorChoice {
+    /// On terminals, unless `NO_COLOR` is set or on CI
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Color output always, never, or depending on the environment
+pub fn set_color(choice: ColorChoice) {
+    COLOR.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Switch every message and report to JSON lines: diagnostics on stderr,
+/// the results of `list`, `which` and `doctor` on stdout
+pub fn set_json(enabled: bool) {
+    JSON.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `--json` is on
+pub fn json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+/// Print a diagnostic event as one JSON line to stderr
+fn emit(event: serde_json::Value) {
+    eprintln!("{}", event);
+}
+
+/// In JSON mode, print a message event and return true
+fn json_message(level: &str, message: &str) -> bool {
+    if json() {
+        emit(json!({"event": "message", "level": level, "message": message}));
+    }
+    json()
+}
+
+/// Check if colors should be disabled on stderr, where messages go
 pub fn colors_disabled() -> bool {
-    env::var("NO_COLOR").is_ok()
+    colors_disabled_on(&io::stderr())
+}
+
+/// Check if colors should be disabled on stdout, where reports go
+pub fn stdout_colors_disabled() -> bool {
+    colors_disabled_on(&io::stdout())
+}
+
+/// `--color always`/`never` decide. Otherwise colors are off with
+/// `NO_COLOR`, and when `out` is not a terminal or running on CI unless
+/// `FORCE_COLOR` / `CLICOLOR_FORCE` asks for them
+fn colors_disabled_on(out: &impl IsTerminal) -> bool {
+    match COLOR.load(Ordering::Relaxed) {
+        c if c == ColorChoice::Always as u8 => return false,
+        c if c == ColorChoice::Never as u8 => return true,
+        _ => {}
+    }
+    if env::var("NO_COLOR").is_ok() {
+        return true;
+    }
+    let forced = ["FORCE_COLOR", "CLICOLOR_FORCE"]
+        .iter()
+        .any(|var| env::var(var).is_ok_and(|v| v != "0"));
+    !forced && (is_ci() || !out.is_terminal())
+}
+
+/// Current UTC time of day (`12:04:59`), for timestamped output
+pub fn timestamp() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    format!(
+        "{:02}:{:02}:{:02}",
+        secs / 3600 % 24,
+        secs / 60 % 60,
+        secs % 60
+    )
 }
 
 /// Print a success message
 pub fn success(message: &str) {
+    if json_message("success", message) {
+        return;
+    }
     if colors_disabled() {
         eprintln!("✓ {}", message);
     } else {
@@ -28,6 +130,9 @@ pub fn success(message: &str) {
 
 /// Print a warning message
 pub fn warning(message: &str) {
+    if json_message("warning", message) {
+        return;
+    }
     if colors_disabled() {
         eprintln!("⚠ {}", message);
     } else {
@@ -37,6 +142,9 @@ pub fn warning(message: &str) {
 
 /// Print an error message
 pub fn error(message: &str) {
+    if json_message("error", message) {
+        return;
+    }
     if colors_disabled() {
         eprintln!("❌ {}", message);
     } else {
@@ -44,8 +152,49 @@ pub fn error(message: &str) {
     }
 }
 
+/// Set the verbosity level: 1 explains detection, 2 each detector and
+/// validator answer, 3 the merged config and the task environment
+pub fn set_verbosity(level: u8) {
+    VERBOSITY.store(level, Ordering::Relaxed);
+}
+
+/// The verbosity level, 0 unless `-v` or `verbose = true`
+pub fn verbosity() -> u8 {
+    VERBOSITY.load(Ordering::Relaxed)
+}
+
+/// Mask `secrets` wherever [`redact`] is applied; empty values are ignored
+pub fn set_secrets(mut secrets: Vec<String>) {
+    secrets.retain(|secret| !secret.is_empty());
+    // A secret containing another is masked whole
+    secrets.sort_by_key(|secret| std::cmp::Reverse(secret.len()));
+    if let Ok(mut current) = SECRETS.lock() {
+        *current = secrets;
+    }
+}
+
+/// `text` with every secret replaced by `****`
+pub fn redact(text: &str) -> String {
+    let Ok(secrets) = SECRETS.lock() else {
+        return text.to_string();
+    };
+    secrets.iter().fold(text.to_string(), |text, secret| {
+        text.replace(secret, "****")
+    })
+}
+
+/// Print an info message when the verbosity is at least `level`
+pub fn debug(level: u8, message: &str) {
+    if verbosity() >= level {
+        info(message);
+    }
+}
+
 /// Print an info message (for verbose mode)
 pub fn info(message: &str) {
+    if json_message("info", message) {
+        return;
+    }
     if colors_disabled() {
         eprintln!("🔍 {}", message);
     } else {
@@ -76,8 +225,373 @@ pub fn executing(command: &str) {
     }
 }
 
+/// Print `--profile`: the time spent in each phase, then the total
+pub fn profile(phases: &[(&str, Duration)], total: Duration) {
+    let ms = |duration: Duration| duration.as_micros() as f64 / 1000.0;
+    if json() {
+        let phases: Vec<_> = phases
+            .iter()
+            .map(|(phase, duration)| json!({"phase": phase, "duration_ms": ms(*duration)}))
+            .collect();
+        emit(json!({"event": "profile", "phases": phases, "total_ms": ms(total)}));
+        return;
+    }
+    let width = phases
+        .iter()
+        .map(|(phase, _)| phase.len())
+        .chain(["total".len()])
+        .max()
+        .unwrap_or(0);
+    eprintln!();
+    for (phase, duration) in phases {
+        eprintln!("{:<width$}  {:>10.1}ms", phase, ms(*duration));
+    }
+    let line = format!("{:<width$}  {:>10.1}ms", "total", ms(total));
+    if colors_disabled() {
+        eprintln!("{}", line);
+    } else {
+        eprintln!("{}", line.bold());
+    }
+}
+
+/// Print the tasks one runner knows about, to stdout; with `namespaced`,
+/// `ns:task` names are grouped under their namespace after the others
+pub fn task_list(runner: &str, file: &str, tasks: &[String], namespaced: bool) {
+    if json() {
+        println!(
+            "{}",
+            json!({"runner": runner, "file": file, "tasks": tasks})
+        );
+        return;
+    }
+    if stdout_colors_disabled() {
+        println!("{} ({})", runner, file);
+    } else {
+        println!("{} ({})", runner.blue().bold(), file.blue());
+    }
+    if !namespaced {
+        for task in tasks {
+            println!("  {}", task);
+        }
+        return;
+    }
+
+    let mut namespaces: Vec<(&str, Vec<&String>)> = Vec::new();
+    for task in tasks {
+        match task.split_once(':') {
+            Some((namespace, _)) => match namespaces.iter_mut().find(|(n, _)| *n == namespace) {
+                Some((_, grouped)) => grouped.push(task),
+                None => namespaces.push((namespace, vec![task])),
+            },
+            None => println!("  {}", task),
+        }
+    }
+    for (namespace, grouped) in namespaces {
+        if stdout_colors_disabled() {
+            println!("  {}", namespace);
+        } else {
+            println!("  {}", namespace.bold());
+        }
+        for task in grouped {
+            println!("    {}", task);
+        }
+    }
+}
+
+/// Print `run alias list`: each alias and what it expands to, aligned
+pub fn alias_list(aliases: &[(&String, &String)]) {
+    let width = aliases
+        .iter()
+        .map(|(name, _)| name.len())
+        .max()
+        .unwrap_or(0);
+    for (name, expansion) in aliases {
+        if json() {
+            println!("{}", json!({"alias": name, "expansion": expansion}));
+        } else if stdout_colors_disabled() {
+            println!("{:width$}  {}", name, expansion);
+        } else {
+            println!(
+                "{}  {}",
+                format!("{:width$}", name).blue().bold(),
+                expansion
+            );
+        }
+    }
+}
+
+/// Print a line of a parallel task's output, prefixed with the task name in
+/// a color picked by its position; stderr lines go to stderr
+pub fn prefixed_line(task: &str, index: usize, width: usize, line: &OutputLine) {
+    let prefix = format!("{:<width$} |", task);
+    let plain = match line {
+        OutputLine::Stdout(_) => stdout_colors_disabled(),
+        OutputLine::Stderr(_) => colors_disabled(),
+    };
+    let prefix = if plain {
+        prefix
+    } else {
+        match index % 5 {
+            0 => prefix.cyan().to_string(),
+            1 => prefix.magenta().to_string(),
+            2 => prefix.yellow().to_string(),
+            3 => prefix.blue().to_string(),
+            _ => prefix.green().to_string(),
+        }
+    };
+    match line {
+        OutputLine::Stdout(text) => println!("{} {}", prefix, text),
+        OutputLine::Stderr(text) => eprintln!("{} {}", prefix, text),
+    }
+}
+
+/// Print how each task of a multi-task invocation ended, to stderr
+pub fn task_summary(reports: &[TaskReport]) {
+    if json() {
+        let tasks: Vec<_> = reports
+            .iter()
+            .map(|report| {
+                let (outcome, code) = match report.outcome {
+                    TaskOutcome::Finished(code) => ("finished", Some(code)),
+                    TaskOutcome::Stopped => ("stopped", None),
+                    TaskOutcome::Skipped => ("skipped", None),
+                };
+                json!({
+                    "task": report.task,
+                    "outcome": outcome,
+                    "code": code,
+                    "duration_ms": report.duration.as_millis() as u64,
+                })
+            })
+            .collect();
+        emit(json!({"event": "summary", "tasks": tasks}));
+        return;
+    }
+    let width = reports.iter().map(|r| r.task.len()).max().unwrap_or(0);
+    eprintln!();
+    for report in reports {
+        let (symbol, detail) = match report.outcome {
+            TaskOutcome::Finished(0) => ("✓", format!("{:.1}s", report.duration.as_secs_f64())),
+            TaskOutcome::Finished(code) => (
+                "✗",
+                format!("exit {} after {:.1}s", code, report.duration.as_secs_f64()),
+            ),
+            TaskOutcome::Stopped => (
+                "-",
+                format!("stopped after {:.1}s", report.duration.as_secs_f64()),
+            ),
+            TaskOutcome::Skipped => ("-", "skipped".to_string()),
+        };
+        let line = format!("{} {:<width$}  {}", symbol, report.task, detail);
+        if colors_disabled() {
+            eprintln!("{}", line);
+        } else {
+            match report.outcome {
+                TaskOutcome::Finished(0) => eprintln!("{}", line.green()),
+                TaskOutcome::Finished(_) => eprintln!("{}", line.red()),
+                TaskOutcome::Stopped | TaskOutcome::Skipped => eprintln!("{}", line.dimmed()),
+            }
+        }
+    }
+}
+
+/// Print a resolution trace (`run which`) to stdout
+pub fn resolution(resolution: &Resolution) {
+    if json() {
+        println!("{}", redact(&json!(resolution).to_string()));
+        return;
+    }
+    let label = |name: &str| {
+        if stdout_colors_disabled() {
+            format!("{:<10}", name)
+        } else {
+            format!("{:<10}", name).bold().to_string()
+        }
+    };
+
+    println!("{}{}", label("Task:"), resolution.task);
+    for (level, dir) in resolution.searched.iter().enumerate() {
+        let name = if level == 0 { "Searched:" } else { "" };
+        println!("{}{} (level {})", label(name), dir.display(), level);
+    }
+    println!("{}{}", label("Found in:"), resolution.working_dir.display());
+
+    let width = resolution
+        .candidates
+        .iter()
+        .map(|c| c.name.len() + c.detected_file.len() + 3)
+        .max()
+        .unwrap_or(0);
+    for (i, candidate) in resolution.candidates.iter().enumerate() {
+        let name = if i == 0 { "Runners:" } else { "" };
+        let runner = format!("{} ({})", candidate.name, candidate.detected_file);
+        let support = match candidate.support {
+            CommandSupport::Supported => "supported",
+            CommandSupport::NotSupported => "not supported",
+            CommandSupport::Unknown => "unknown",
+        };
+        println!(
+            "{}{:<width$}  priority {:<3} {:<13} {}",
+            label(name),
+            runner,
+            candidate.priority,
+            if candidate.installed {
+                "installed"
+            } else {
+                "not installed"
+            },
+            support,
+        );
+    }
+
+    if let Some(selected) = &resolution.selected {
+        let forced = if resolution.forced { " (forced)" } else { "" };
+        println!("{}{}{}", label("Selected:"), selected, forced);
+    }
+    if let Some(command) = &resolution.command {
+        println!(
+            "{}{}",
+            label("Command:"),
+            redact(&shell_words::join(command))
+        );
+    }
+    if let Some(error) = &resolution.error {
+        println!("{}{}", label("Error:"), error);
+    }
+}
+
+/// Print `run explain`: the runner picked for a task, where the task is
+/// declared, what it runs and the resulting command line
+pub fn explanation(resolution: &Resolution) {
+    if json() {
+        println!("{}", redact(&json!(resolution).to_string()));
+        return;
+    }
+    let label = |name: &str| {
+        if stdout_colors_disabled() {
+            format!("{:<10}", name)
+        } else {
+            format!("{:<10}", name).bold().to_string()
+        }
+    };
+
+    println!("{}{}", label("Task:"), resolution.task);
+    let selected = resolution
+        .selected
+        .as_ref()
+        .and_then(|name| resolution.candidates.iter().find(|c| &c.name == name));
+    if let Some(runner) = selected {
+        println!(
+            "{}{} ({})",
+            label("Runner:"),
+            runner.name,
+            resolution.working_dir.join(&runner.detected_file).display()
+        );
+    }
+    match (&resolution.definition, selected) {
+        (Some(definition), _) => {
+            println!(
+                "{}{}:{}",
+                label("Defined:"),
+                definition.file.display(),
+                definition.line
+            );
+            for (i, line) in definition.body.lines().enumerate() {
+                println!("{}{}", label(if i == 0 { "Runs:" } else { "" }), line);
+            }
+        }
+        (None, Some(runner))
+            if runner.name == "custom" && runner.support == CommandSupport::Supported =>
+        {
+            println!("{}[commands] of the configuration", label("Defined:"));
+        }
+        (None, Some(runner)) => {
+            println!(
+                "{}not declared in a project file; passed to {}",
+                label("Defined:"),
+                runner.name
+            );
+        }
+        (None, None) => {}
+    }
+    if let Some(command) = &resolution.command {
+        println!(
+            "{}{}",
+            label("Command:"),
+            redact(&shell_words::join(command))
+        );
+    }
+    if let Some(error) = &resolution.error {
+        println!("{}{}", label("Error:"), error);
+    }
+}
+
+/// Print `run search` matches to stdout: each task with its runner and where
+/// it is defined, then its body indented
+pub fn search_results(matches: &[SearchMatch]) {
+    for found in matches {
+        if json() {
+            println!("{}", json!(found));
+            continue;
+        }
+        let location = match &found.definition {
+            Some(definition) => format!("{}:{}", definition.file.display(), definition.line),
+            None => found.file.clone(),
+        };
+        if stdout_colors_disabled() {
+            println!("{}  ({}, {})", found.task, found.runner, location);
+        } else {
+            println!(
+                "{}  {}",
+                found.task.blue().bold(),
+                format!("({}, {})", found.runner, location).dimmed()
+            );
+        }
+        for line in found.definition.iter().flat_map(|d| d.body.lines()) {
+            println!("    {}", line);
+        }
+    }
+}
+
+/// Print `run doctor` checks to stdout, with a summary when something's wrong
+pub fn diagnostics(checks: &[Check]) {
+    if json() {
+        for check in checks {
+            println!("{}", json!(check));
+        }
+        return;
+    }
+    for check in checks {
+        let (symbol, plain) = match check.status {
+            Status::Ok => ("✓".green().to_string(), "✓"),
+            Status::Warning => ("⚠".yellow().to_string(), "⚠"),
+            Status::Problem => ("✗".red().to_string(), "✗"),
+        };
+        if stdout_colors_disabled() {
+            println!("{} {}", plain, check.message);
+        } else {
+            println!("{} {}", symbol, check.message);
+        }
+    }
+
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let (problems, warnings) = (count(Status::Problem), count(Status::Warning));
+    if problems + warnings > 0 {
+        println!("\n{} problem(s), {} warning(s)", problems, warnings);
+    }
+}
+
 /// Print an update notification
 pub fn update_notification(from_version: &str, to_version: &str, changelog: Option<&str>) {
+    if json() {
+        emit(json!({
+            "event": "updated",
+            "from_version": from_version,
+            "to_version": to_version,
+            "changelog": changelog,
+        }));
+        return;
+    }
     if colors_disabled() {
         eprintln!("⬆ run was updated: {} → {}", from_version, to_version);
     } else {
@@ -98,3 +612,171 @@ pub fn update_notification(from_version: &str, to_version: &str, changelog: Opti
         }
     }
 }
+
+/// Prints the CLI's messages for lifecycle events
+pub struct CliObserver {
+    verbose: bool,
+    quiet: bool,
+    timestamps: bool,
+}
+
+impl CliObserver {
+    pub fn new(verbose: bool, quiet: bool) -> Self {
+        Self {
+            verbose,
+            quiet,
+            timestamps: false,
+        }
+    }
+
+    /// Prefix progress messages with the time, and report when the command ends
+    pub fn timestamps(mut self, timestamps: bool) -> Self {
+        self.timestamps = timestamps;
+        self
+    }
+
+    fn stamp(&self) {
+        if self.timestamps {
+            eprint!("[{}] ", timestamp());
+        }
+    }
+}
+
+impl RunObserver for CliObserver {
+    fn on_event(&self, event: &RunEvent<'_>) {
+        match event {
+            RunEvent::DetectionComplete {
+                working_dir,
+                runners,
+            } if self.verbose => {
+                let names: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
+                info(&format!("Found {} in {:?}", names.join(", "), working_dir));
+            }
+            RunEvent::CommandSelected {
+                runner,
+                command,
+                dry_run,
+                ..
+            } => {
+                if self.verbose {
+                    detected(&runner.name, &runner.detected_file);
+                }
+                if self.quiet {
+                    return;
+                }
+                let command = redact(&command.join(" "));
+                if *dry_run {
+                    println!("{}", command);
+                } else {
+                    self.stamp();
+                    executing(&command);
+                }
+            }
+            RunEvent::Retry {
+                attempt,
+                max_attempts,
+                status,
+                delay,
+            } if !self.quiet => {
+                let outcome = match status.map(|s| s.code()) {
+                    Some(Some(code)) => format!("exited with {}", code),
+                    Some(None) => "was terminated".to_string(),
+                    None => "timed out".to_string(),
+                };
+                let wait = if delay.is_zero() {
+                    String::new()
+                } else {
+                    format!(" in {:?}", delay)
+                };
+                self.stamp();
+                warning(&format!(
+                    "Attempt {} of {} {}, retrying{}...",
+                    attempt, max_attempts, outcome, wait
+                ));
+            }
+            RunEvent::Exit { status, .. } if self.timestamps && !self.quiet => {
+                self.stamp();
+                eprintln!("Finished ({})", status);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Observer that prints each event as a JSON line to stderr (`--json`)
+#[derive(Default)]
+pub struct JsonObserver {
+    /// When the first attempt was spawned, for the exit's duration
+    started: Mutex<Option<Instant>>,
+}
+
+impl JsonObserver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RunObserver for JsonObserver {
+    fn on_event(&self, event: &RunEvent<'_>) {
+        let event = match event {
+            RunEvent::DetectionComplete {
+                working_dir,
+                runners,
+            } => json!({
+                "event": "detection",
+                "working_dir": working_dir,
+                "runners": runners,
+            }),
+            RunEvent::CommandSelected {
+                runner,
+                task,
+                command,
+                dry_run,
+            } => json!({
+                "event": "command",
+                "task": task,
+                "runner": runner.name,
+                "file": runner.detected_file,
+                "command": command.iter().map(|word| redact(word)).collect::<Vec<_>>(),
+                "dry_run": dry_run,
+            }),
+            RunEvent::Spawned { pid, attempt } => {
+                if let Ok(mut started) = self.started.lock() {
+                    started.get_or_insert_with(Instant::now);
+                }
+                json!({"event": "spawned", "pid": pid, "attempt": attempt})
+            }
+            RunEvent::Retry {
+                attempt,
+                max_attempts,
+                status,
+                delay,
+            } => json!({
+                "event": "retry",
+                "attempt": attempt,
+                "max_attempts": max_attempts,
+                "code": status.and_then(|s| s.code()),
+                "timed_out": status.is_none(),
+                "delay_ms": delay.as_millis() as u64,
+            }),
+            RunEvent::Exit { status, attempts } => {
+                let duration = self
+                    .started
+                    .lock()
+                    .ok()
+                    .and_then(|mut started| started.take())
+                    .map(|started| started.elapsed().as_millis() as u64);
+                json!({
+                    "event": "exit",
+                    "code": status.code(),
+                    "success": status.success(),
+                    "attempts": attempts,
+                    "duration_ms": duration,
+                })
+            }
+            // Task output stays as the task wrote it
+            RunEvent::Output { .. } => return,
+        };
+        emit(event);
+    }
+}
@@ -0,0 +1,84 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Questions asked on the terminal (`--choose`).
+//!
+//! Prompts go to stderr, so they never mix with a task's stdout, and answers
+//! are read line by line from stdin. The end of input counts as no answer.
+
+use std::io::{self, BufRead, Write};
+
+/// Ask which of `options` to use; `None` if stdin ran out first
+pub fn select(question: &str, options: &[String]) -> Option<usize> {
+    select_from(&mut io::stdin().lock(), question, options)
+}
+
+/// Ask a yes/no question, `false` unless answered yes
+pub fn confirm(question: &str) -> bool {
+    confirm_from(&mut io::stdin().lock(), question)
+}
+
+fn select_from(input: &mut impl BufRead, question: &str, options: &[String]) -> Option<usize> {
+    eprintln!("{}", question);
+    for (number, option) in options.iter().enumerate() {
+        eprintln!("  {}) {}", number + 1, option);
+    }
+    loop {
+        let answer = ask(input, &format!("Choose [1-{}]: ", options.len()))?;
+        match answer.parse::<usize>() {
+            Ok(number) if (1..=options.len()).contains(&number) => return Some(number - 1),
+            _ => eprintln!("Enter a number from 1 to {}", options.len()),
+        }
+    }
+}
+
+fn confirm_from(input: &mut impl BufRead, question: &str) -> bool {
+    ask(input, &format!("{} [y/N]: ", question))
+        .is_some_and(|answer| matches!(answer.to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Print `prompt` and read one trimmed line
+fn ask(input: &mut impl BufRead, prompt: &str) -> Option<String> {
+    eprint!("{}", prompt);
+    let _ = io::stderr().flush();
+    let mut line = String::new();
+    match input.read_line(&mut line) {
+        Ok(0) | Err(_) => {
+            eprintln!();
+            None
+        }
+        Ok(_) => Some(line.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_select_retries_until_valid() {
+        let options = vec!["npm".to_string(), "pnpm".to_string()];
+        let mut input = Cursor::new("x\n3\n 2 \n");
+        assert_eq!(select_from(&mut input, "Which?", &options), Some(1));
+
+        let mut input = Cursor::new("0\n");
+        assert_eq!(select_from(&mut input, "Which?", &options), None);
+    }
+
+    #[test]
+    fn test_confirm() {
+        assert!(confirm_from(&mut Cursor::new("y\n"), "Save?"));
+        assert!(confirm_from(&mut Cursor::new("YES\n"), "Save?"));
+        assert!(!confirm_from(&mut Cursor::new("\n"), "Save?"));
+        assert!(!confirm_from(&mut Cursor::new(""), "Save?"));
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Pseudo-terminals for tasks whose output `run` reads (`--pty`).
+//!
+//! Tools check whether their output is a terminal before printing colors,
+//! progress bars or prompts, so a task writing to a pipe loses them. Under
+//! a pseudo-terminal it still has one: `run` copies what the task prints
+//! from the master side, and what is typed to it ([`Interactive`]).
+
+use std::fs::File;
+use std::io::{self, Write};
+use std::os::fd::FromRawFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// A pseudo-terminal: the task gets `slave` as its terminal, `run` reads
+/// and writes `master`
+#[derive(Debug)]
+pub struct Pty {
+    pub master: File,
+    pub slave: File,
+}
+
+impl Pty {
+    /// Open a pseudo-terminal as large as the terminal `run` prints to, or
+    /// 80x24
+    pub fn open() -> io::Result<Pty> {
+        let mut size = terminal_size().unwrap_or(libc::winsize {
+            ws_row: 24,
+            ws_col: 80,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        });
+        let (mut master, mut slave) = (-1, -1);
+        // SAFETY: openpty only writes the two descriptors, owned from here
+        unsafe {
+            if libc::openpty(
+                &mut master,
+                &mut slave,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                // A `*mut` on macOS
+                std::ptr::addr_of_mut!(size),
+            ) != 0
+            {
+                return Err(io::Error::last_os_error());
+            }
+            // Other commands must not inherit them, or the master never
+            // sees the end of the output
+            for fd in [master, slave] {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+            }
+            // Lines end in \n as in a pipe; a terminal `run` relays them to
+            // adds the \r itself
+            let mut settings: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(slave, &mut settings) == 0 {
+                settings.c_oflag &= !libc::ONLCR;
+                libc::tcsetattr(slave, libc::TCSANOW, &settings);
+            }
+            Ok(Pty {
+                master: File::from_raw_fd(master),
+                slave: File::from_raw_fd(slave),
+            })
+        }
+    }
+}
+
+/// Make the pseudo-terminal on stdout the controlling terminal of a new
+/// session, as the task's first act (between fork and exec)
+pub fn take_as_controlling() -> io::Result<()> {
+    // SAFETY: both calls are async-signal-safe and only affect this process
+    unsafe {
+        if libc::setsid() < 0 || libc::ioctl(libc::STDOUT_FILENO, libc::TIOCSCTTY as _, 0) < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// The size of the terminal on stdout (or stdin)
+fn terminal_size() -> Option<libc::winsize> {
+    [libc::STDOUT_FILENO, libc::STDIN_FILENO]
+        .into_iter()
+        .find_map(|fd| {
+            // SAFETY: TIOCGWINSZ only writes `size`
+            unsafe {
+                let mut size: libc::winsize = std::mem::zeroed();
+                (libc::ioctl(fd, libc::TIOCGWINSZ, &mut size) == 0 && size.ws_col > 0)
+                    .then_some(size)
+            }
+        })
+}
+
+/// While alive, what is typed on stdin goes to a task's pseudo-terminal
+/// key by key, with the terminal in raw mode (no echo, line editing or
+/// Ctrl+C of its own: the task's terminal does all that), and a resized
+/// terminal resizes the pseudo-terminal too. The terminal is put back as it
+/// was when dropped.
+pub struct Interactive {
+    original: libc::termios,
+    stop: Arc<AtomicBool>,
+    input: Option<JoinHandle<()>>,
+}
+
+impl Interactive {
+    /// Forward stdin to `master`; fails when stdin is not a terminal
+    pub fn start(master: File) -> io::Result<Self> {
+        // SAFETY: tcgetattr/tcsetattr only read and write the structs given
+        let original = unsafe {
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let mut raw = original;
+            libc::cfmakeraw(&mut raw);
+            // What `run` prints itself still needs \n turned into \r\n
+            raw.c_oflag |= libc::OPOST | libc::ONLCR;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            original
+        };
+        let stop = Arc::new(AtomicBool::new(false));
+        let input = {
+            let stop = Arc::clone(&stop);
+            std::thread::spawn(move || forward_input(master, &stop))
+        };
+        Ok(Interactive {
+            original,
+            stop,
+            input: Some(input),
+        })
+    }
+}
+
+impl Drop for Interactive {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(input) = self.input.take() {
+            let _ = input.join();
+        }
+        // SAFETY: puts back the settings tcgetattr gave
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Copy stdin to `master` until `stop` is set, checking every 50 ms so that
+/// nothing typed afterwards is taken from the next reader
+fn forward_input(mut master: File, stop: &AtomicBool) {
+    let mut size = None;
+    let mut buffer = [0u8; 1024];
+    while !stop.load(Ordering::SeqCst) {
+        if let Some(current) = terminal_size() {
+            if size != Some((current.ws_row, current.ws_col)) {
+                size = Some((current.ws_row, current.ws_col));
+                // SAFETY: TIOCSWINSZ only reads `current`
+                unsafe {
+                    libc::ioctl(
+                        std::os::fd::AsRawFd::as_raw_fd(&master),
+                        libc::TIOCSWINSZ,
+                        &current,
+                    );
+                }
+            }
+        }
+        let mut ready = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        // SAFETY: poll and read only touch the structs and buffer given;
+        // stdin is read directly, as its buffered reader would keep what
+        // it read ahead from whoever reads next
+        let read = unsafe {
+            if libc::poll(&mut ready, 1, 50) <= 0 {
+                continue;
+            }
+            libc::read(libc::STDIN_FILENO, buffer.as_mut_ptr().cast(), buffer.len())
+        };
+        if read <= 0 || master.write_all(&buffer[..read as usize]).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::os::unix::process::CommandExt;
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn test_pty_is_a_terminal() {
+        let pty = Pty::open().unwrap();
+        let mut command = Command::new("sh");
+        command
+            .args(["-c", "test -t 1 && echo terminal || echo pipe"])
+            .stdin(Stdio::null())
+            .stdout(pty.slave.try_clone().unwrap())
+            .stderr(Stdio::null());
+        // SAFETY: take_as_controlling only makes async-signal-safe calls
+        unsafe {
+            command.pre_exec(take_as_controlling);
+        }
+        let status = command.status().unwrap();
+        drop(command);
+        drop(pty.slave);
+
+        let mut output = Vec::new();
+        // Reading past the end of a pseudo-terminal fails with EIO
+        let _ = (&pty.master).read_to_end(&mut output);
+        assert!(status.success());
+        assert_eq!(String::from_utf8_lossy(&output), "terminal\n");
+    }
+}
@@ -9,20 +9,93 @@
 // MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
 // GNU Affero General Public License for more details.
 
+use crate::config::Config;
+use crate::detectors::custom::Shell;
 use crate::detectors::{
-    detect_all, is_tool_installed, node, CommandSupport, DetectedRunner, Ecosystem,
+    custom, detect_all, detect_all_with, is_tool_installed, node, CommandSupport, DetectedRunner,
+    Ecosystem, TaskDefinition, UnknownValidator,
 };
+use crate::devcontainer::DevContainer;
+use crate::error::exit_codes;
+use crate::events::{EventBus, RunEvent, Stream};
+use crate::logfile::{LineWriter, TaskLog};
+use crate::nix::DevShell;
 use crate::output;
+#[cfg(unix)]
+use crate::pty::{Interactive, Pty};
+use crate::script::{ScriptContext, ScriptHooks};
+use crate::tools;
+use crate::walk;
+use crate::workspace::{self, Scope};
 use crate::RunError;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{IsTerminal, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, ExitStatus, Stdio};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tempfile::TempPath;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::sync::{mpsc, watch};
 
 /// Result of running a command
 pub struct RunResult {
     pub exit_status: ExitStatus,
     pub runner: DetectedRunner,
     pub working_dir: PathBuf,
+    /// Number of attempts made (more than 1 when retries were used)
+    pub attempts: u32,
+    /// Captured stdout (empty unless `StdioPolicy::Capture` was used)
+    pub stdout: Vec<u8>,
+    /// Captured stderr (empty unless `StdioPolicy::Capture` was used)
+    pub stderr: Vec<u8>,
+}
+
+/// How a task of a multi-task invocation ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskOutcome {
+    /// Ran (or failed to start) with this exit code
+    Finished(i32),
+    /// Not run because an earlier task failed
+    Skipped,
+    /// Terminated because a task running alongside it failed
+    Stopped,
+}
+
+/// One task's line in the summary printed after several tasks ran
+#[derive(Debug, Clone)]
+pub struct TaskReport {
+    pub task: String,
+    pub outcome: TaskOutcome,
+    pub duration: Duration,
+}
+
+impl TaskReport {
+    pub fn finished(task: &str, exit_code: i32, duration: Duration) -> Self {
+        TaskReport {
+            task: task.to_string(),
+            outcome: TaskOutcome::Finished(exit_code),
+            duration,
+        }
+    }
+
+    pub fn stopped(task: &str, duration: Duration) -> Self {
+        TaskReport {
+            task: task.to_string(),
+            outcome: TaskOutcome::Stopped,
+            duration,
+        }
+    }
+
+    pub fn skipped(task: &str) -> Self {
+        TaskReport {
+            task: task.to_string(),
+            outcome: TaskOutcome::Skipped,
+            duration: Duration::ZERO,
+        }
+    }
 }
 
 /// Search for runners in the directory hierarchy
@@ -31,6 +104,81 @@ pub fn search_runners(
     max_levels: u8,
     ignore_list: &[String],
     verbose: bool,
+) -> Result<(Vec<DetectedRunner>, PathBuf), RunError> {
+    search_runners_with(start_dir, max_levels, ignore_list, verbose, detect_all)
+}
+
+/// [`detect_all`], explaining at `-vv` what each detector found in `dir`
+pub fn detect_traced(dir: &Path, ignore_list: &[String]) -> Vec<DetectedRunner> {
+    if output::verbosity() < 2 {
+        return detect_all(dir, ignore_list);
+    }
+    let describe = |runners: &[DetectedRunner]| {
+        runners
+            .iter()
+            .map(|r| format!("{} ({})", r.name, r.detected_file))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    detect_all_with(dir, ignore_list, |detector, kept, ignored| {
+        let mut found = match kept {
+            [] => "nothing".to_string(),
+            _ => format!("found {}", describe(kept)),
+        };
+        if !ignored.is_empty() {
+            found.push_str(&format!(", ignored {}", describe(ignored)));
+        }
+        output::info(&format!("Detector {}: {}", detector, found));
+    })
+}
+
+/// Projects below `root`, up to `depth` levels deep, that could run
+/// `command` (any project without one), for `--down`: directories whose
+/// runners know the task win over those where it's unknown, and the
+/// shallowest of those win
+pub fn search_down(
+    root: &Path,
+    depth: usize,
+    command: Option<&str>,
+    ignore_list: &[String],
+    skip_dirs: &[String],
+) -> Vec<(PathBuf, Vec<DetectedRunner>)> {
+    let mut best = Vec::new();
+    let mut best_rank = None;
+    for dir in walk::subdirectories(root, depth, skip_dirs) {
+        let runners = detect_all(&dir, ignore_list);
+        // 0 when a runner knows the task, 1 when one might
+        let support = runners
+            .iter()
+            .filter_map(|r| match command.map(|c| r.supports_command(c, &dir)) {
+                None | Some(CommandSupport::Supported) => Some(0),
+                Some(CommandSupport::Unknown) => Some(1),
+                Some(CommandSupport::NotSupported) => None,
+            })
+            .min();
+        let Some(support) = support else {
+            continue;
+        };
+        let rank = Some((support, dir.components().count()));
+        if best_rank.is_none() || rank < best_rank {
+            best.clear();
+            best_rank = rank;
+        }
+        if rank == best_rank {
+            best.push((dir, runners));
+        }
+    }
+    best
+}
+
+/// [`search_runners`] with a custom per-directory detection function (the
+/// daemon uses this to answer from its cache)
+pub fn search_runners_with(
+    start_dir: &Path,
+    max_levels: u8,
+    ignore_list: &[String],
+    verbose: bool,
+    mut detect: impl FnMut(&Path, &[String]) -> Vec<DetectedRunner>,
 ) -> Result<(Vec<DetectedRunner>, PathBuf), RunError> {
     let mut current_dir = start_dir.to_path_buf();
 
@@ -39,7 +187,7 @@ pub fn search_runners(
             output::info(&format!("Searching in {:?} (level {})", current_dir, level));
         }
 
-        let runners = detect_all(&current_dir, ignore_list);
+        let runners = detect(&current_dir, ignore_list);
         if !runners.is_empty() {
             return Ok((runners, current_dir));
         }
@@ -55,6 +203,204 @@ pub fn search_runners(
     Err(RunError::RunnerNotFound(max_levels))
 }
 
+/// Detect runners the way the CLI does: search upwards from `current_dir`,
+/// then merge the `[commands]` and `[validators]` declared in `config`.
+///
+/// The search starts from [`walk::search_start`], which resolves symlinks
+/// unless `follow_symlinks = false`. When `config` declares commands, finding
+/// no runner is not an error; the commands run from that directory.
+pub fn detect_with_config(
+    current_dir: &Path,
+    max_levels: u8,
+    ignore_list: &[String],
+    config: &Config,
+    verbose: bool,
+    detect: impl FnMut(&Path, &[String]) -> Vec<DetectedRunner>,
+) -> Result<(Vec<DetectedRunner>, PathBuf), RunError> {
+    let current_dir = &walk::search_start(current_dir, config.get_follow_symlinks())?;
+    let search_result = search_runners_with(current_dir, max_levels, ignore_list, verbose, detect);
+
+    // Filter empty commands
+    let valid_config_commands: Option<HashMap<String, String>> =
+        config.commands.as_ref().map(custom::command_lines);
+
+    let has_valid_commands = valid_config_commands
+        .as_ref()
+        .is_some_and(|c| !c.is_empty());
+
+    let (mut runners, working_dir) = match search_result {
+        Ok(result) => result,
+        // If we have custom commands, we can proceed even without detected runners
+        Err(_) if has_valid_commands => (Vec::new(), current_dir.to_path_buf()),
+        Err(e) => return Err(e),
+    };
+
+    let config_private = config
+        .commands
+        .as_ref()
+        .map(|commands| custom::hidden_tasks(commands, &working_dir))
+        .unwrap_or_default();
+
+    // Inject custom commands from config
+    if let Some(valid_config_commands) = valid_config_commands {
+        if !valid_config_commands.is_empty() {
+            // Check if we already have a custom runner
+            if let Some(idx) = runners
+                .iter()
+                .position(|r| r.ecosystem == Ecosystem::Custom)
+            {
+                // Merge config commands into existing runner (local overrides global)
+                let mut merged_commands = valid_config_commands.clone();
+                let mut private = config_private;
+                if let Some(existing_cmds) = &runners[idx].custom_commands {
+                    merged_commands.extend(existing_cmds.clone());
+                    private.retain(|task| !existing_cmds.contains_key(task));
+                }
+
+                // Update the runner
+                let old_runner = &runners[idx];
+                private.extend(old_runner.private.iter().cloned());
+                let new_runner = DetectedRunner::with_custom_commands(
+                    &old_runner.name,
+                    &old_runner.detected_file,
+                    old_runner.ecosystem,
+                    old_runner.priority,
+                    Arc::new(UnknownValidator),
+                    merged_commands,
+                )
+                .with_private(private);
+                runners[idx] = new_runner;
+            } else {
+                // Create new runner
+                let new_runner = DetectedRunner::with_custom_commands(
+                    "custom",
+                    "config.toml",
+                    Ecosystem::Custom,
+                    0,
+                    Arc::new(UnknownValidator),
+                    valid_config_commands,
+                )
+                .with_private(config_private);
+                runners.push(new_runner);
+                // Sort by priority (0 first)
+                runners.sort_by_key(|r| r.priority);
+            }
+        }
+    }
+
+    // Layer validators declared in config on top of the built-in ones
+    config.validator_registry().apply(&mut runners);
+
+    Ok((runners, working_dir))
+}
+
+/// Where `task` is declared: [`DetectedRunner::definition`], or for a
+/// custom command the project doesn't declare, the global config's entry
+pub fn task_definition(
+    runner: &DetectedRunner,
+    task: &str,
+    working_dir: &Path,
+) -> Option<TaskDefinition> {
+    runner.definition(task, working_dir).or_else(|| {
+        runner
+            .has_custom_command(task)
+            .then(Config::global_config_path)
+            .flatten()
+            .and_then(|path| custom::file_definition(&path, task))
+    })
+}
+
+/// The detected runner named `name` (`runner` in config, or `RUN_RUNNER`),
+/// bypassing conflict resolution and command support checks
+pub fn forced_runner(runners: &[DetectedRunner], name: &str) -> Result<DetectedRunner, RunError> {
+    runners
+        .iter()
+        .find(|r| r.name.eq_ignore_ascii_case(name))
+        .cloned()
+        .ok_or_else(|| {
+            let detected: Vec<&str> = runners.iter().map(|r| r.name.as_str()).collect();
+            RunError::ConfigError(format!(
+                "Runner '{}' was requested but not detected (found: {})",
+                name,
+                detected.join(", ")
+            ))
+        })
+}
+
+/// Resolve lockfile conflicts, then pick the runner that supports `command`
+pub fn resolve_runner(
+    runners: &[DetectedRunner],
+    working_dir: &Path,
+    command: &str,
+    verbose: bool,
+) -> Result<DetectedRunner, RunError> {
+    let winner = check_conflicts(runners, working_dir, verbose)?;
+
+    // Conflict losers (e.g. a stale lockfile, or any lockfile when package.json
+    // pins a packageManager) are out. Orchestrators (priority 0) delegate to
+    // the package manager rather than compete with it, so they stay.
+    let candidates: Vec<DetectedRunner> = runners
+        .iter()
+        .filter(|r| {
+            winner.ecosystem == Ecosystem::Custom
+                || r.ecosystem != winner.ecosystem
+                || r.priority == 0
+                || r.name == winner.name
+        })
+        .cloned()
+        .collect();
+    select_runner(&candidates, command, working_dir, verbose)
+}
+
+/// Pick the tool `run exec` hands its arguments to: lockfile conflicts are
+/// resolved as for a task, but no runner is asked whether it knows one.
+/// Custom commands and editor tasks have no tool, and orchestrators (priority
+/// 0) only count when no package manager or build tool was found.
+pub fn resolve_tool(
+    runners: &[DetectedRunner],
+    working_dir: &Path,
+    verbose: bool,
+) -> Result<DetectedRunner, RunError> {
+    let tools: Vec<DetectedRunner> = runners
+        .iter()
+        .filter(|r| r.tool_command(&[]).is_some())
+        .cloned()
+        .collect();
+    let direct: Vec<DetectedRunner> = tools.iter().filter(|r| r.priority > 0).cloned().collect();
+    if direct.is_empty() {
+        check_conflicts(&tools, working_dir, verbose)
+    } else {
+        check_conflicts(&direct, working_dir, verbose)
+    }
+}
+
+/// Pick the runner for `command` without asking validators (`--no-validate`):
+/// the conflict winner gets the task whether or not it declares it. Custom
+/// commands and editor tasks have no tool to forward to, so they only take
+/// part when they define `command`.
+pub fn resolve_unvalidated(
+    runners: &[DetectedRunner],
+    working_dir: &Path,
+    command: &str,
+    verbose: bool,
+) -> Result<DetectedRunner, RunError> {
+    let candidates: Vec<DetectedRunner> = runners
+        .iter()
+        .filter(|r| {
+            r.tool_command(&[]).is_some()
+                || r.supports_command(command, working_dir) == CommandSupport::Supported
+        })
+        .cloned()
+        .collect();
+    if candidates.is_empty() {
+        return Err(RunError::CommandNotSupported(
+            command.to_string(),
+            runners.iter().map(|r| r.name.clone()).collect(),
+        ));
+    }
+    check_conflicts(&candidates, working_dir, verbose)
+}
+
 /// Check for lockfile conflicts within the same ecosystem
 /// Uses Corepack (packageManager field) to resolve Node.js conflicts if available
 pub fn check_conflicts(
@@ -116,10 +462,8 @@ pub fn check_conflicts(
             }
 
             // Check which tools are installed
-            let installed: Vec<&&DetectedRunner> = eco_runners
-                .iter()
-                .filter(|r| is_tool_installed(&r.name))
-                .collect();
+            let installed: Vec<&&DetectedRunner> =
+                eco_runners.iter().filter(|r| r.is_installed()).collect();
 
             if installed.is_empty() {
                 // None installed - suggest installation
@@ -174,6 +518,27 @@ pub fn check_conflicts(
     Ok(runners[0].clone())
 }
 
+/// The installed runners behind a [`RunError::LockfileConflict`] from
+/// [`check_conflicts`], highest priority first, for the user to pick from
+pub fn conflict_choices(runners: &[DetectedRunner]) -> Vec<DetectedRunner> {
+    let installed: Vec<&DetectedRunner> = runners.iter().filter(|r| r.is_installed()).collect();
+    let Some(ecosystem) = runners.iter().map(|r| r.ecosystem).find(|ecosystem| {
+        runners.iter().filter(|r| r.ecosystem == *ecosystem).count() > 1
+            && installed
+                .iter()
+                .filter(|r| r.ecosystem == *ecosystem)
+                .count()
+                > 1
+    }) else {
+        return Vec::new();
+    };
+    installed
+        .into_iter()
+        .filter(|r| r.ecosystem == ecosystem)
+        .cloned()
+        .collect()
+}
+
 pub fn select_runner(
     runners: &[DetectedRunner],
     command: &str,
@@ -190,20 +555,26 @@ pub fn select_runner(
     for runner in runners {
         match runner.supports_command(command, working_dir) {
             CommandSupport::Supported => {
-                if verbose {
-                    output::info(&format!("{} supports command '{}'", runner.name, command));
-                }
+                output::debug(
+                    2,
+                    &format!("{} supports command '{}'", runner.name, command),
+                );
                 supported_runners.push(runner);
             }
             CommandSupport::NotSupported => {
-                if verbose {
-                    output::info(&format!(
-                        "{} does not support command '{}'",
-                        runner.name, command
-                    ));
-                }
+                output::debug(
+                    2,
+                    &format!("{} does not support command '{}'", runner.name, command),
+                );
             }
             CommandSupport::Unknown => {
+                output::debug(
+                    2,
+                    &format!(
+                        "{} cannot tell whether it supports '{}'",
+                        runner.name, command
+                    ),
+                );
                 unknown_runners.push(runner);
             }
         }
@@ -214,6 +585,12 @@ pub fn select_runner(
     }
 
     if let Some(runner) = unknown_runners.first() {
+        if verbose {
+            output::info(&format!(
+                "No runner declares '{}'; trying {}",
+                command, runner.name
+            ));
+        }
         return Ok((*runner).clone());
     }
 
@@ -223,159 +600,2345 @@ pub fn select_runner(
     ))
 }
 
-/// Execute a command with the detected runner
-pub fn execute(
-    runner: &DetectedRunner,
+/// How a task resolves, step by step (`run which`)
+#[derive(Debug, Clone, Serialize)]
+pub struct Resolution {
+    pub task: String,
+    /// Directories searched for runners, from the start directory upwards
+    pub searched: Vec<PathBuf>,
+    /// Directory the runners were found in, where the command runs
+    pub working_dir: PathBuf,
+    /// Every detected runner, in priority order
+    pub candidates: Vec<Candidate>,
+    /// Whether the runner was forced (`runner` in config, or `RUN_RUNNER`)
+    pub forced: bool,
+    /// Name of the runner `run` picks
+    pub selected: Option<String>,
+    /// Command line that would be executed
+    pub command: Option<Vec<String>>,
+    /// Where the selected runner's project file declares the task
+    pub definition: Option<TaskDefinition>,
+    /// Why no runner could be picked
+    pub error: Option<String>,
+}
+
+/// A detected runner and what it answered for the task
+#[derive(Debug, Clone, Serialize)]
+pub struct Candidate {
+    pub name: String,
+    pub detected_file: String,
+    pub priority: u8,
+    pub installed: bool,
+    pub support: CommandSupport,
+}
+
+/// Trace how `task` resolves from `current_dir` without running it: the same
+/// detection, conflict resolution and validator checks as `run <task>`.
+///
+/// Only failing to detect any runner is an error; a task no runner supports
+/// is reported in [`Resolution::error`].
+pub fn trace_resolution(
+    current_dir: &Path,
     task: &str,
-    extra_args: &[String],
-    working_dir: &Path,
-    dry_run: bool,
-    verbose: bool,
-    quiet: bool,
-) -> Result<RunResult, RunError> {
-    // Check if the tool is installed (skip for dry-run)
-    // Skip check for custom runners as they define their own commands
-    if !dry_run && runner.ecosystem != Ecosystem::Custom && !is_tool_installed(&runner.name) {
-        return Err(RunError::ToolNotInstalled(format!(
-            "{} is not installed. Please install it to continue.",
-            runner.name
-        )));
+    args: &[String],
+    max_levels: u8,
+    ignore_list: &[String],
+    config: &Config,
+    scope_workspace: bool,
+) -> Result<Resolution, RunError> {
+    let mut searched = Vec::new();
+    let (runners, working_dir) = detect_with_config(
+        current_dir,
+        max_levels,
+        ignore_list,
+        config,
+        false,
+        |dir: &Path, ignore: &[String]| {
+            searched.push(dir.to_path_buf());
+            detect_all(dir, ignore)
+        },
+    )?;
+
+    let candidates = runners
+        .iter()
+        .map(|runner| Candidate {
+            name: runner.name.clone(),
+            detected_file: runner.detected_file.clone(),
+            priority: runner.priority,
+            installed: runner.ecosystem == Ecosystem::Custom || runner.is_installed(),
+            support: runner.supports_command(task, &working_dir),
+        })
+        .collect();
+
+    // A task delegated with `use` runs the runner's own task
+    let delegation = config.delegation(task, &working_dir);
+    let selected = match (&delegation, &config.runner) {
+        (Some((name, _)), _) | (None, Some(name)) => forced_runner(&runners, name),
+        (None, None) => resolve_runner(&runners, &working_dir, task, false),
+    };
+    let (runs, args) = match &delegation {
+        Some((_, words)) => (words[0].as_str(), [&words[1..], args].concat()),
+        None => (task, args.to_vec()),
+    };
+    let script = config
+        .script
+        .as_ref()
+        .map(|path| ScriptHooks::load(&current_dir.join(path)))
+        .transpose()?;
+    let outcome = selected.and_then(|runner| {
+        let command = ExecutionBuilder::new(&runner, runs)
+            .args(&args)
+            .vars(config.template_vars(std::env::vars()))
+            .cwd(&working_dir)
+            .invocation_dir(current_dir)
+            .workspace(scope_workspace)
+            .corepack(config.get_corepack())
+            .script(script.as_ref())
+            .command()?;
+        let definition = task_definition(&runner, runs, &working_dir);
+        Ok((runner.name, command, definition))
+    });
+    let (selected, command, definition, error) = match outcome {
+        Ok((name, command, definition)) => (Some(name), Some(command), definition, None),
+        Err(e) => (None, None, None, Some(e.to_string())),
+    };
+
+    Ok(Resolution {
+        task: task.to_string(),
+        searched,
+        working_dir,
+        candidates,
+        forced: config.runner.is_some() || delegation.is_some(),
+        selected,
+        command,
+        definition,
+        error,
+    })
+}
+
+/// Warn when running `task` would skip the dependencies wireit declares for it,
+/// because its package.json script does not go through wireit
+pub fn wireit_warning(runner: &DetectedRunner, task: &str, working_dir: &Path) -> Option<String> {
+    if runner.ecosystem != Ecosystem::NodeJs {
+        return None;
+    }
+    let script = node::wireit_scripts(working_dir)
+        .into_iter()
+        .find(|s| s.name == task)?;
+    if script.via_wireit || script.dependencies.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "'{}' has wireit dependencies ({}) but its script does not run wireit, so they will not be built first. Set \"{}\": \"wireit\" in package.json scripts.",
+        task,
+        script.dependencies.join(", "),
+        task
+    ))
+}
+
+/// Warn when the package manager on PATH doesn't match the version pinned by
+/// `packageManager` in package.json
+pub fn package_manager_warning(runner: &DetectedRunner, working_dir: &Path) -> Option<String> {
+    if runner.ecosystem != Ecosystem::NodeJs {
+        return None;
+    }
+    let (name, pinned) = node::package_manager_pin(working_dir)?;
+    if name != runner.name {
+        return None;
     }
+    let pinned = pinned?;
+    let installed = node::installed_version(&name)?;
+    if installed == pinned {
+        return None;
+    }
+    Some(format!(
+        "package.json pins {}@{} but {} {} is installed. Run `corepack enable` or set `corepack = true` in run.toml.",
+        name, pinned, name, installed
+    ))
+}
+
+/// A command ready to spawn, as [`ExecutionBuilder::prepare`] built it
+#[derive(Debug, Clone)]
+pub struct PreparedCommand {
+    pub task: String,
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    /// Variables set on top of the inherited environment
+    pub env: HashMap<String, String>,
+    /// Longest the command may run
+    pub timeout: Option<Duration>,
+    /// Under [`execute_parallel`], commands of the same group run one at a
+    /// time, in order
+    pub group: Option<String>,
+    /// Run under a pseudo-terminal even when `run`'s output isn't a terminal
+    pub pty: bool,
+    /// The task's script file (see [`ExecutionBuilder::task_script`]),
+    /// deleted once the last copy of the command is dropped
+    pub script_file: Option<Arc<TempPath>>,
+}
+
+/// How the child process' standard streams are connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StdioPolicy {
+    /// Share the terminal with the child (default)
+    #[default]
+    Inherit,
+    /// Discard all output and close stdin
+    Null,
+    /// Collect stdout and stderr into the [`RunResult`]
+    Capture,
+}
+
+/// What the child process reads on stdin (`--stdin`), overriding the
+/// [`StdioPolicy`] default
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StdinSource {
+    /// This process' stdin: a terminal, or the pipe `run` was fed from
+    Inherit,
+    /// Nothing: reads get end-of-file, so prompts fail instead of hanging
+    Null,
+    /// The contents of a file, reopened for every attempt
+    File(PathBuf),
+}
+
+impl std::str::FromStr for StdinSource {
+    type Err = String;
 
-    // Build the command
-    let cmd_parts = runner.build_command(task, extra_args);
-    let cmd_string = cmd_parts.join(" ");
+    /// Parse `inherit`, `null` or `file:<path>`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "inherit" => Ok(Self::Inherit),
+            "null" => Ok(Self::Null),
+            _ => match s.strip_prefix("file:") {
+                Some("") => Err("file: needs a path, as in file:data.csv".to_string()),
+                Some(path) => Ok(Self::File(PathBuf::from(path))),
+                None => Err(format!(
+                    "expected inherit, null or file:<path>, got '{}'",
+                    s
+                )),
+            },
+        }
+    }
+}
 
-    if verbose {
-        output::detected(&runner.name, &runner.detected_file);
+impl StdinSource {
+    fn open(&self) -> Result<Stdio, RunError> {
+        Ok(match self {
+            Self::Inherit => Stdio::inherit(),
+            Self::Null => Stdio::null(),
+            Self::File(path) => std::fs::File::open(path)
+                .map_err(|e| {
+                    RunError::InvalidArgument(format!(
+                        "Cannot read stdin from {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?
+                .into(),
+        })
     }
+}
+
+/// Write a task's script where its shell can run it: a new file only this
+/// user can read, in the temporary directory. A file already there is never
+/// reused, and the script is deleted when the returned path is dropped
+fn write_script(script: &str, extension: &str) -> Result<TempPath, RunError> {
+    let mut file = tempfile::Builder::new()
+        .prefix("run-script-")
+        .suffix(&format!(".{}", extension))
+        .tempfile()?;
+    file.write_all(script.as_bytes())?;
+    // Closed before the shell opens it, as Windows won't share it otherwise
+    Ok(file.into_temp_path())
+}
 
-    if dry_run {
-        if !quiet {
-            println!("{}", cmd_string);
+/// Builder for running a task with a detected runner
+///
+/// ```no_run
+/// # use run_cli::runner::ExecutionBuilder;
+/// # use run_cli::DetectedRunner;
+/// # use run_cli::detectors::Ecosystem;
+/// # use std::time::Duration;
+/// let runner = DetectedRunner::new("cargo", "Cargo.toml", Ecosystem::Rust, 9);
+/// let result = ExecutionBuilder::new(&runner, "test")
+///     .args(["--release"])
+///     .cwd("/path/to/project")
+///     .timeout(Duration::from_secs(600))
+///     .retries(2)
+///     .run()?;
+/// # Ok::<(), run_cli::RunError>(())
+/// ```
+pub struct ExecutionBuilder<'a> {
+    runner: &'a DetectedRunner,
+    task: String,
+    args: Vec<String>,
+    env: HashMap<String, String>,
+    cwd: PathBuf,
+    invocation_dir: Option<PathBuf>,
+    scope: Scope,
+    stdio: StdioPolicy,
+    stdin: Option<StdinSource>,
+    timeout: Option<Duration>,
+    retries: u32,
+    retry_delay: Duration,
+    group: Option<String>,
+    pty: bool,
+    passthrough: bool,
+    shell: Option<Shell>,
+    task_script: Option<String>,
+    vars: HashMap<String, String>,
+    skip: Option<String>,
+    dry_run: bool,
+    corepack: bool,
+    nix_develop: bool,
+    devcontainer: bool,
+    script: Option<&'a ScriptHooks>,
+    log: Option<Arc<TaskLog>>,
+    events: EventBus,
+}
+
+impl<'a> ExecutionBuilder<'a> {
+    /// Run `task` with `runner` in the current directory
+    pub fn new(runner: &'a DetectedRunner, task: &str) -> Self {
+        Self {
+            runner,
+            task: task.to_string(),
+            args: Vec::new(),
+            env: HashMap::new(),
+            cwd: PathBuf::from("."),
+            invocation_dir: None,
+            scope: Scope::Member,
+            stdio: StdioPolicy::default(),
+            stdin: None,
+            timeout: None,
+            retries: 0,
+            retry_delay: Duration::ZERO,
+            group: None,
+            pty: false,
+            passthrough: false,
+            shell: None,
+            task_script: None,
+            vars: HashMap::new(),
+            skip: None,
+            dry_run: false,
+            corepack: false,
+            nix_develop: false,
+            devcontainer: false,
+            script: None,
+            log: None,
+            events: EventBus::new(),
         }
-        // Return a fake success for dry run
-        return Ok(RunResult {
-            exit_status: std::process::ExitStatus::default(),
-            runner: runner.clone(),
-            working_dir: working_dir.to_path_buf(),
-        });
     }
 
-    if !quiet {
-        output::executing(&cmd_string);
+    /// Extra arguments appended to the task
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
     }
 
-    // Execute the command
-    let program = &cmd_parts[0];
-    let args = &cmd_parts[1..];
+    /// Set an environment variable for the child (overrides script hooks)
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(key.into(), value.into());
+        self
+    }
 
-    let status = Command::new(program)
-        .args(args)
-        .current_dir(working_dir)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
-        .status()
-        .map_err(|e| RunError::CommandFailed(format!("Failed to execute {}: {}", program, e)))?;
+    /// Set several environment variables for the child
+    pub fn envs<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.env
+            .extend(vars.into_iter().map(|(k, v)| (k.into(), v.into())));
+        self
+    }
 
-    Ok(RunResult {
-        exit_status: status,
-        runner: runner.clone(),
-        working_dir: working_dir.to_path_buf(),
-    })
-}
+    /// Directory the command runs in
+    pub fn cwd(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.cwd = dir.into();
+        self
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::RunError;
-    use std::fs::File;
-    use tempfile::tempdir;
+    /// Directory `run` was invoked from, used to pick the workspace member
+    /// (defaults to [`Self::cwd`])
+    pub fn invocation_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.invocation_dir = Some(dir.into());
+        self
+    }
 
-    #[test]
-    fn test_search_runners_current_dir() {
-        let dir = tempdir().unwrap();
-        File::create(dir.path().join("package.json")).unwrap();
+    /// Cover the whole cargo or go.work workspace instead of the current
+    /// member (see [`crate::workspace`])
+    pub fn workspace(mut self, workspace: bool) -> Self {
+        self.scope = if workspace {
+            Scope::Workspace
+        } else {
+            Scope::Member
+        };
+        self
+    }
 
-        let (runners, found_dir) = search_runners(dir.path(), 3, &[], false).unwrap();
-        assert_eq!(runners.len(), 1);
-        assert_eq!(runners[0].name, "npm");
-        assert_eq!(found_dir, dir.path());
+    pub fn stdio(mut self, policy: StdioPolicy) -> Self {
+        self.stdio = policy;
+        self
     }
 
-    #[test]
-    fn test_search_runners_parent_dir() {
-        let dir = tempdir().unwrap();
-        File::create(dir.path().join("package.json")).unwrap();
+    /// Feed the child's stdin from `source` instead of what the
+    /// [`StdioPolicy`] picks
+    pub fn stdin(mut self, source: StdinSource) -> Self {
+        self.stdin = Some(source);
+        self
+    }
 
-        let subdir = dir.path().join("src");
-        std::fs::create_dir(&subdir).unwrap();
+    /// Kill the command if an attempt runs longer than `timeout`
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
 
-        let (runners, found_dir) = search_runners(&subdir, 3, &[], false).unwrap();
-        assert_eq!(runners.len(), 1);
-        assert_eq!(runners[0].name, "npm");
-        assert_eq!(found_dir, dir.path());
+    /// Re-run a failed or timed out command up to `retries` more times
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
     }
 
-    #[test]
-    fn test_search_runners_not_found() {
-        let dir = tempdir().unwrap();
-        let result = search_runners(dir.path(), 3, &[], false);
-        assert!(matches!(result, Err(RunError::RunnerNotFound(3))));
+    /// Keep the command from running alongside others of the same `group`
+    /// under [`execute_parallel`]
+    pub fn group(mut self, group: Option<String>) -> Self {
+        self.group = group;
+        self
     }
 
-    #[test]
-    fn test_search_runners_with_ignore() {
-        let dir = tempdir().unwrap();
-        File::create(dir.path().join("package.json")).unwrap();
+    /// Run the command under a pseudo-terminal (Unix) whenever `run` relays
+    /// its output, not only when that goes to a terminal (see
+    /// [`crate::pty`]); with [`StdioPolicy::Inherit`], the output is then
+    /// always relayed
+    pub fn pty(mut self, pty: bool) -> Self {
+        self.pty = pty;
+        self
+    }
 
-        let result = search_runners(dir.path(), 3, &["npm".to_string()], false);
-        assert!(matches!(result, Err(RunError::RunnerNotFound(3))));
+    /// Wait `delay` before the first retry, twice as long before the second,
+    /// and so on
+    pub fn retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
     }
 
-    #[test]
-    fn test_check_conflicts_single_runner() {
-        let dir = tempdir().unwrap();
-        let runners = vec![DetectedRunner::new(
-            "npm",
-            "package.json",
-            Ecosystem::NodeJs,
-            4,
-        )];
-        let result = check_conflicts(&runners, dir.path(), false).unwrap();
-        assert_eq!(result.name, "npm");
+    /// Hand the task and its arguments to the runner's tool as they are
+    /// (`run exec`), instead of building the task's command
+    pub fn passthrough(mut self, passthrough: bool) -> Self {
+        self.passthrough = passthrough;
+        self
     }
 
-    #[test]
-    fn test_check_conflicts_different_ecosystems() {
-        let dir = tempdir().unwrap();
-        let runners = vec![
-            DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4),
-            DetectedRunner::new("cargo", "Cargo.toml", Ecosystem::Rust, 9),
-        ];
-        let result = check_conflicts(&runners, dir.path(), false).unwrap();
-        // Should return highest priority
-        assert_eq!(result.name, "npm");
+    /// Run a custom command through `shell` instead of splitting it into
+    /// words (see [`Shell`]); detected runners' tasks are unaffected
+    pub fn shell(mut self, shell: Option<Shell>) -> Self {
+        self.shell = shell;
+        self
     }
 
-    #[test]
-    fn test_check_conflicts_corepack_resolves() {
-        use std::io::Write;
+    /// Run a custom command's `script` from a file, with [`Self::shell`] (the
+    /// platform's shell by default); the arguments become `$1`, `$2`...
+    pub fn task_script(mut self, script: Option<String>) -> Self {
+        self.task_script = script;
+        self
+    }
 
-        let dir = tempdir().unwrap();
-        // Create package.json with packageManager specifying pnpm
-        let mut file = File::create(dir.path().join("package.json")).unwrap();
-        writeln!(file, r#"{{"packageManager": "pnpm@9.0.0"}}"#).unwrap();
+    /// Values for `{{name}}` in a custom command's line or script
+    pub fn vars(mut self, vars: HashMap<String, String>) -> Self {
+        self.vars = vars;
+        self
+    }
 
-        let runners = vec![
-            DetectedRunner::new("yarn", "yarn.lock", Ecosystem::NodeJs, 3),
-            DetectedRunner::new("pnpm", "pnpm-lock.yaml", Ecosystem::NodeJs, 2),
-            DetectedRunner::new("npm", "package-lock.json", Ecosystem::NodeJs, 4),
-        ];
+    /// Don't run the task, for `reason` (an unmet `condition`): [`Self::run`]
+    /// says so and succeeds
+    pub fn skip(mut self, reason: Option<String>) -> Self {
+        self.skip = reason;
+        self
+    }
 
-        // Corepack should resolve to pnpm
-        let result = check_conflicts(&runners, dir.path(), false).unwrap();
-        assert_eq!(result.name, "pnpm");
+    /// Print the command instead of running it
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Invoke a package manager pinned by `packageManager` through
+    /// `corepack`, which fetches the pinned version
+    pub fn corepack(mut self, corepack: bool) -> Self {
+        self.corepack = corepack;
+        self
+    }
+
+    /// Run the command inside the project's Nix dev shell when the runner's
+    /// tool is not on `PATH` (see [`crate::nix`])
+    pub fn nix_develop(mut self, nix_develop: bool) -> Self {
+        self.nix_develop = nix_develop;
+        self
+    }
+
+    /// Run the command inside the project's dev container (see
+    /// [`crate::devcontainer`])
+    pub fn devcontainer(mut self, devcontainer: bool) -> Self {
+        self.devcontainer = devcontainer;
+        self
+    }
+
+    /// Whether the command goes through corepack (see [`Self::corepack`])
+    fn via_corepack(&self) -> bool {
+        self.corepack
+            && self.runner.ecosystem == Ecosystem::NodeJs
+            && !self.runs_custom_command()
+            && node::get_corepack_manager(&self.cwd).is_some_and(|pm| pm == self.runner.name)
+    }
+
+    /// Whether the task is one of the runner's custom commands
+    fn runs_custom_command(&self) -> bool {
+        !self.passthrough && self.runner.has_custom_command(&self.task)
+    }
+
+    /// Scripting hooks applied to the command (see [`crate::script`])
+    pub fn script(mut self, script: Option<&'a ScriptHooks>) -> Self {
+        self.script = script;
+        self
+    }
+
+    /// Copy the task's output into `log` (see [`crate::logfile`]). Inherited
+    /// output is piped through this process to do so, still reaching the
+    /// terminal as it arrives
+    pub fn log_file(mut self, log: Option<Arc<TaskLog>>) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Observers notified of lifecycle events (see [`crate::events`])
+    pub fn events(mut self, events: EventBus) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Build the final command line, applying the `command` script hook
+    ///
+    /// A task script's file is deleted before this returns, so the line is
+    /// only good for showing; [`Self::prepare`] keeps the file for running
+    pub fn command(&self) -> Result<Vec<String>, RunError> {
+        self.command_with_script().map(|(parts, _)| parts)
+    }
+
+    /// [`Self::command`], with the script file the command runs, if any
+    fn command_with_script(&self) -> Result<(Vec<String>, Option<TempPath>), RunError> {
+        let mut script_file = None;
+        let line = self
+            .runner
+            .custom_command(&self.task)
+            .map(|line| custom::expand_vars(line, &self.vars));
+        let mut cmd_parts = if self.passthrough {
+            let words: Vec<String> = std::iter::once(self.task.clone())
+                .chain(self.args.iter().cloned())
+                .collect();
+            self.runner.tool_command(&words).ok_or_else(|| {
+                RunError::InvalidArgument(format!(
+                    "{} has no tool to pass arguments to",
+                    self.runner.name
+                ))
+            })?
+        } else if let Some(script) = self
+            .task_script
+            .as_ref()
+            .filter(|_| self.runner.has_custom_command(&self.task))
+        {
+            let shell = self.shell.clone().unwrap_or(Shell::Enabled(true));
+            let (mut words, extension) = shell.script_invocation();
+            let script = custom::expand_vars(script, &self.vars);
+            let file = write_script(&script, extension)?;
+            words.push(file.display().to_string());
+            script_file = Some(file);
+            words.extend(self.args.iter().cloned());
+            words
+        } else if let Some((shell, line)) = self
+            .shell
+            .as_ref()
+            .and_then(Shell::invocation)
+            .zip(line.as_ref())
+        {
+            let script = custom::script(line, &self.args)
+                .map_err(|e| RunError::InvalidArgument(format!("Task '{}' {}", self.task, e)))?;
+            shell.into_iter().chain([script]).collect()
+        } else if let Some(line) = &line {
+            custom::interpolate(line, &self.args)
+                .map_err(|e| RunError::InvalidArgument(format!("Task '{}' {}", self.task, e)))?
+        } else {
+            self.runner.try_build_command(&self.task, &self.args)?
+        };
+        if !self.passthrough && !self.runner.has_custom_command(&self.task) {
+            workspace::apply_scope(
+                &mut cmd_parts,
+                &self.runner.name,
+                &self.task,
+                &self.args,
+                &self.cwd,
+                self.invocation_dir.as_deref().unwrap_or(&self.cwd),
+                self.scope,
+            );
+        }
+        if self.via_corepack() {
+            cmd_parts.insert(0, "corepack".to_string());
+        }
+        if let Some(hooks) = self.script {
+            let ctx = self.script_context(&cmd_parts);
+            if let Some(parts) = hooks.command(&ctx)? {
+                return Ok((parts, script_file));
+            }
+        }
+        Ok((cmd_parts, script_file))
+    }
+
+    fn script_context(&self, cmd_parts: &[String]) -> ScriptContext {
+        ScriptContext::new(self.runner, &self.task, &self.args, &self.cwd, cmd_parts)
+    }
+
+    /// Check the tool and apply hooks and wrappers, returning the command
+    /// [`Self::run`] would spawn (a dry run stops here)
+    pub fn prepare(&self) -> Result<PreparedCommand, RunError> {
+        let runner = self.runner;
+
+        // Inside a dev container only the devcontainer CLI has to be installed
+        let container = if self.devcontainer {
+            let container = DevContainer::find(&self.cwd).ok_or_else(|| {
+                RunError::ConfigError(format!(
+                    "No .devcontainer/devcontainer.json found in {} or above",
+                    self.cwd.display()
+                ))
+            })?;
+            if !self.dry_run && !is_tool_installed("devcontainer") {
+                return Err(RunError::ToolNotInstalled(
+                    "devcontainer is not installed. Install it with `npm install -g @devcontainers/cli`."
+                        .to_string(),
+                ));
+            }
+            Some(container)
+        } else {
+            None
+        };
+
+        // Check if the tool is installed. Custom commands are skipped as they
+        // define their own programs, and a missing tool may still be provided
+        // by the project's Nix dev shell or dev container.
+        let (tool, installed) = if self.via_corepack() {
+            ("corepack", is_tool_installed("corepack"))
+        } else {
+            (runner.name.as_str(), runner.is_installed())
+        };
+        let tool_missing = container.is_none()
+            && runner.ecosystem != Ecosystem::Custom
+            && !self.runs_custom_command()
+            && !installed;
+        let dev_shell = if tool_missing {
+            DevShell::find(&self.cwd)
+        } else {
+            None
+        };
+        let wrap_in = dev_shell
+            .as_ref()
+            .filter(|shell| self.nix_develop && shell.is_available());
+        if tool_missing && wrap_in.is_none() && !self.dry_run {
+            let mut message = format!("{} is not installed. Please install it to continue.", tool);
+            if let (Some(shell), false) = (&dev_shell, self.nix_develop) {
+                message.push_str(&format!(
+                    " {} defines a Nix dev shell; set `nix_develop = true` in run.toml to run inside it.",
+                    shell.file().display()
+                ));
+            }
+            if DevContainer::find(&self.cwd).is_some() {
+                message.push_str(
+                    " This project has a dev container; use --in-devcontainer to run inside it.",
+                );
+            }
+            return Err(RunError::ToolNotInstalled(message));
+        }
+
+        // Build the command, letting the project's script hooks rewrite it
+        let (mut cmd_parts, script_file) = self.command_with_script()?;
+        let mut env = HashMap::new();
+        if let (Some(hooks), false) = (self.script, self.dry_run) {
+            let ctx = self.script_context(&cmd_parts);
+            hooks.check(&ctx)?;
+            env = hooks.env(&ctx)?;
+        }
+        env.extend(self.env.clone());
+        if let Some(shell) = wrap_in {
+            cmd_parts = shell.wrap(&cmd_parts);
+        }
+        if let Some(container) = &container {
+            cmd_parts = container.wrap(&cmd_parts);
+        }
+
+        self.events.emit(&RunEvent::CommandSelected {
+            runner,
+            task: &self.task,
+            command: &cmd_parts,
+            dry_run: self.dry_run,
+        });
+        if output::verbosity() >= 3 {
+            // Only what `run` adds; the rest is inherited from this shell
+            let mut vars: Vec<_> = env.iter().collect();
+            vars.sort();
+            let lines: Vec<String> = vars
+                .iter()
+                .map(|(k, v)| format!("  {}={}", k, output::redact(v)))
+                .collect();
+            output::info(&format!(
+                "Environment for '{}' (on top of the inherited one):{}",
+                self.task,
+                if lines.is_empty() {
+                    " nothing added".to_string()
+                } else {
+                    format!("\n{}", lines.join("\n"))
+                }
+            ));
+        }
+
+        Ok(PreparedCommand {
+            task: self.task.clone(),
+            command: cmd_parts,
+            cwd: self.cwd.clone(),
+            env,
+            timeout: self.timeout,
+            group: self.group.clone(),
+            pty: self.pty,
+            script_file: script_file.map(Arc::new),
+        })
+    }
+
+    /// Execute the command
+    ///
+    /// It runs in its own process group (on Unix), which is handed the
+    /// terminal when the command reads from it. Interrupt, terminate and
+    /// hangup signals `run` gets meanwhile are passed on to the group, and
+    /// the run ends in [`RunError::Interrupted`] once the command exits, or
+    /// is killed for not exiting soon after.
+    pub fn run(self) -> Result<RunResult, RunError> {
+        if let Some(reason) = &self.skip {
+            output::warning(&format!("Skipping '{}': {}", self.task, reason));
+            return Ok(self.result(ExitStatus::default(), 0, Vec::new(), Vec::new()));
+        }
+        let prepared = self.prepare()?;
+        if self.dry_run {
+            // Return a fake success for dry run
+            return Ok(self.result(ExitStatus::default(), 0, Vec::new(), Vec::new()));
+        }
+
+        let mut attempt = 1;
+        loop {
+            if let Some(log) = &self.log {
+                log.start(&self.task, &prepared.command, attempt);
+            }
+            let outcome = self.run_once(&prepared.command, &prepared.env, attempt);
+            if let Some(log) = &self.log {
+                match &outcome {
+                    Ok((status, _, _)) => log.finish(&self.task, Some(*status)),
+                    Err(RunError::Timeout(_)) => log.finish(&self.task, None),
+                    Err(_) => {}
+                }
+            }
+            let failed = match &outcome {
+                Ok((status, _, _)) => (!status.success()).then_some(Some(*status)),
+                Err(RunError::Timeout(_)) => Some(None),
+                Err(_) => None,
+            };
+
+            if let (Some(status), true) = (failed, attempt <= self.retries) {
+                let delay = self.retry_delay.saturating_mul(1 << (attempt - 1).min(16));
+                self.events.emit(&RunEvent::Retry {
+                    attempt,
+                    max_attempts: self.retries + 1,
+                    status,
+                    delay,
+                });
+                std::thread::sleep(delay);
+                attempt += 1;
+                continue;
+            }
+
+            let (status, stdout, stderr) = outcome?;
+            self.events.emit(&RunEvent::Exit {
+                status,
+                attempts: attempt,
+            });
+            return Ok(self.result(status, attempt, stdout, stderr));
+        }
+    }
+
+    fn run_once(
+        &self,
+        cmd_parts: &[String],
+        env: &HashMap<String, String>,
+        attempt: u32,
+    ) -> Result<(ExitStatus, Vec<u8>, Vec<u8>), RunError> {
+        let cmd_parts = tools::spawn_command(cmd_parts, &self.cwd);
+        let program = &cmd_parts[0];
+        let mut command = Command::new(program);
+        command
+            .args(&cmd_parts[1..])
+            .current_dir(&self.cwd)
+            .envs(env);
+
+        // Output `run` relays to a terminal (logging it), or any with --pty,
+        // goes through a pseudo-terminal, so the task still has a terminal
+        #[cfg(unix)]
+        let pty = (self.stdio == StdioPolicy::Inherit
+            && (self.pty || self.log.is_some() && std::io::stdout().is_terminal()))
+        .then(Pty::open)
+        .transpose()?;
+        #[cfg(not(unix))]
+        let pty: Option<std::convert::Infallible> = None;
+        // Under one, what is typed goes to the task through it
+        let interactive = pty.is_some() && self.stdin.is_none() && std::io::stdin().is_terminal();
+
+        // The task leads its own process group, so signals and a time limit
+        // reach everything it starts. Reading the terminal, it also gets to
+        // be the terminal's foreground group, and so the keyboard's signals
+        let terminal = self.stdio == StdioPolicy::Inherit
+            && self.stdin.is_none()
+            && pty.is_none()
+            && owns_terminal();
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            if pty.is_some() {
+                // Its own session, for the pseudo-terminal to be its own
+                // SAFETY: take_as_controlling only makes async-signal-safe calls
+                unsafe {
+                    command.pre_exec(crate::pty::take_as_controlling);
+                }
+            } else {
+                command.process_group(0);
+            }
+            if terminal {
+                // SAFETY: hand_terminal only makes async-signal-safe calls
+                unsafe {
+                    command.pre_exec(|| {
+                        hand_terminal(libc::getpid());
+                        Ok(())
+                    });
+                }
+            }
+        }
+
+        // Logged output, or a pseudo-terminal's, is relayed to the terminal
+        // rather than inherited
+        let relay = self.stdio == StdioPolicy::Inherit && (self.log.is_some() || pty.is_some());
+        match self.stdio {
+            StdioPolicy::Inherit if relay => command
+                .stdin(Stdio::inherit())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+            StdioPolicy::Inherit => command
+                .stdin(Stdio::inherit())
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit()),
+            StdioPolicy::Null => command
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null()),
+            StdioPolicy::Capture => command
+                .stdin(Stdio::null())
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped()),
+        };
+        #[cfg(unix)]
+        if let Some(pty) = &pty {
+            command
+                .stdout(pty.slave.try_clone()?)
+                .stderr(pty.slave.try_clone()?);
+            if interactive {
+                command.stdin(pty.slave.try_clone()?);
+            }
+        }
+        if let Some(source) = &self.stdin {
+            command.stdin(source.open()?);
+        }
+
+        let mut child = command.spawn().map_err(|e| {
+            RunError::CommandFailed(format!("Failed to execute {}: {}", program, e))
+        })?;
+        // The task's copies of the pseudo-terminal must be the only ones
+        // left, for the output to end when it does
+        drop(command);
+
+        self.events.emit(&RunEvent::Spawned {
+            pid: child.id(),
+            attempt,
+        });
+
+        // Drain pipes on separate threads so a chatty child can't block on a full pipe
+        let drain = |reader, stream| {
+            pipe_in_thread(reader, stream, self.events.clone(), self.log.clone(), relay)
+        };
+        let stdout = child
+            .stdout
+            .take()
+            .map(|r| drain(Box::new(r), Stream::Stdout));
+        let stderr = child
+            .stderr
+            .take()
+            .map(|r| drain(Box::new(r), Stream::Stderr));
+        // Both streams come out of the pseudo-terminal as one
+        #[cfg(unix)]
+        let (stdout, _interactive) = match pty {
+            Some(pty) => {
+                drop(pty.slave);
+                let typed = match interactive {
+                    true => Interactive::start(pty.master.try_clone()?).ok(),
+                    false => None,
+                };
+                (Some(drain(Box::new(pty.master), Stream::Stdout)), typed)
+            }
+            None => (stdout, None),
+        };
+        #[cfg(not(unix))]
+        let _ = interactive;
+
+        #[cfg(unix)]
+        let _foreground = terminal.then(|| Foreground::new(child.id()));
+        let status = match wait_for(&mut child, self.timeout, terminal)? {
+            Some(status) => status,
+            None => {
+                stop_group(&mut child);
+                return Err(RunError::Timeout(self.timeout.unwrap_or_default()));
+            }
+        };
+
+        let collect = |handle: Option<std::thread::JoinHandle<Vec<u8>>>| {
+            handle.and_then(|h| h.join().ok()).unwrap_or_default()
+        };
+        Ok((status, collect(stdout), collect(stderr)))
+    }
+
+    fn result(
+        &self,
+        exit_status: ExitStatus,
+        attempts: u32,
+        stdout: Vec<u8>,
+        stderr: Vec<u8>,
+    ) -> RunResult {
+        RunResult {
+            exit_status,
+            runner: self.runner.clone(),
+            working_dir: self.cwd.clone(),
+            attempts,
+            stdout,
+            stderr,
+        }
+    }
+}
+
+/// Drain a pipe on its own thread. Each chunk is copied to `log` if there is
+/// one, then either relayed to this process's matching stream or collected
+/// and emitted as it arrives
+fn pipe_in_thread(
+    mut reader: Box<dyn Read + Send>,
+    stream: Stream,
+    events: EventBus,
+    log: Option<Arc<TaskLog>>,
+    relay: bool,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut lines = log.as_deref().map(|log| LineWriter::new(log, stream));
+        let mut captured = Vec::new();
+        let mut chunk = [0u8; 8192];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let data = &chunk[..n];
+                    if let Some(lines) = lines.as_mut() {
+                        lines.write(data);
+                    }
+                    if relay {
+                        let _ = match stream {
+                            Stream::Stdout => relay_chunk(&mut std::io::stdout(), data),
+                            Stream::Stderr => relay_chunk(&mut std::io::stderr(), data),
+                        };
+                    } else {
+                        events.emit(&RunEvent::Output { stream, data });
+                        captured.extend_from_slice(data);
+                    }
+                }
+            }
+        }
+        captured
+    })
+}
+
+fn relay_chunk(out: &mut impl Write, data: &[u8]) -> std::io::Result<()> {
+    out.write_all(data)?;
+    out.flush()
+}
+
+/// The exit code `run` passes on for a command that exited with `status`:
+/// its own, or 128 plus the signal that killed it, as shells do
+pub fn exit_code_of(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+    status.code().unwrap_or(exit_codes::GENERIC_ERROR)
+}
+
+/// Wait for the child, passing the signals `run` gets meanwhile on to its
+/// group; `None` if it is still running after `timeout`.
+///
+/// After the first signal passed on, the command has [`KILL_GRACE`] to exit
+/// before its group is killed, and the wait ends in
+/// [`RunError::Interrupted`] either way; so does a command killed by the
+/// Ctrl+C of the terminal it has (`terminal`). A suspend signal (Ctrl+Z)
+/// stops `run` along with the command instead (see [`suspend`]).
+fn wait_for(
+    child: &mut Child,
+    timeout: Option<Duration>,
+    terminal: bool,
+) -> Result<Option<ExitStatus>, RunError> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let mut interrupts = Interrupts::listen();
+    let mut interrupted: Option<(i32, Instant)> = None;
+    loop {
+        if let Some(status) = try_wait(child, terminal)? {
+            #[cfg(unix)]
+            if let (None, Some(libc::SIGINT)) = (
+                interrupted,
+                std::os::unix::process::ExitStatusExt::signal(&status),
+            ) {
+                return Err(RunError::Interrupted(libc::SIGINT));
+            }
+            return match interrupted {
+                Some((signal, _)) => Err(RunError::Interrupted(signal)),
+                None => Ok(Some(status)),
+            };
+        }
+        let now = Instant::now();
+        match interrupts.take() {
+            #[cfg(unix)]
+            Some(libc::SIGTSTP) => suspend(child.id(), terminal),
+            Some(signal) => {
+                forward_signal(child.id(), signal);
+                interrupted.get_or_insert((signal, now + KILL_GRACE));
+            }
+            None => {}
+        }
+        match interrupted {
+            Some((signal, kill_at)) if now >= kill_at => {
+                kill_group(child);
+                return Err(RunError::Interrupted(signal));
+            }
+            None if deadline.is_some_and(|deadline| now >= deadline) => return Ok(None),
+            _ => {}
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// The child's exit status, if it has exited. One with the terminal
+/// (`terminal`) that was stopped from the keyboard gets `run` suspended too
+fn try_wait(child: &mut Child, terminal: bool) -> std::io::Result<Option<ExitStatus>> {
+    #[cfg(unix)]
+    if terminal {
+        use std::os::unix::process::ExitStatusExt;
+        let mut status = 0;
+        // SAFETY: waits without blocking for our own child, writing only to
+        // `status`; `Child` is not waited on again once this reaps it
+        let pid = unsafe {
+            libc::waitpid(
+                child.id() as libc::pid_t,
+                &mut status,
+                libc::WNOHANG | libc::WUNTRACED,
+            )
+        };
+        return match pid {
+            -1 => Err(std::io::Error::last_os_error()),
+            0 => Ok(None),
+            _ if libc::WIFSTOPPED(status) => {
+                suspend(child.id(), terminal);
+                Ok(None)
+            }
+            _ => Ok(Some(ExitStatus::from_raw(status))),
+        };
+    }
+    let _ = terminal;
+    child.try_wait()
+}
+
+/// How long a command's group gets between the signal that should end it
+/// (a timeout's SIGTERM, or one passed on) and SIGKILL
+const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Stop a timed-out command and everything it started: SIGTERM to its
+/// group, then SIGKILL to whatever is left once the command exits or
+/// [`KILL_GRACE`] passes
+fn stop_group(child: &mut Child) {
+    #[cfg(unix)]
+    {
+        forward_signal(child.id(), libc::SIGTERM);
+        let deadline = Instant::now() + KILL_GRACE;
+        while Instant::now() < deadline && !matches!(child.try_wait(), Ok(Some(_))) {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+    kill_group(child);
+}
+
+/// [`stop_group`] for a command spawned with tokio, started as `pid`
+async fn stop_group_async(
+    child: &mut tokio::process::Child,
+    pid: Option<u32>,
+) -> std::io::Result<ExitStatus> {
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        forward_signal(pid, libc::SIGTERM);
+        let _ = tokio::time::timeout(KILL_GRACE, child.wait()).await;
+        forward_signal(pid, libc::SIGKILL);
+    }
+    let _ = pid;
+    let _ = child.start_kill();
+    child.wait().await
+}
+
+/// Kill a command and whatever is left of its group
+fn kill_group(child: &mut Child) {
+    // The group outlives its leader while any member is left
+    #[cfg(unix)]
+    forward_signal(child.id(), libc::SIGKILL);
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+/// Signals `run` received while [`Interrupts`] listened: how many, and the
+/// last one
+static SIGNALS: AtomicUsize = AtomicUsize::new(0);
+static LAST_SIGNAL: AtomicI32 = AtomicI32::new(0);
+/// How many [`Interrupts`] are listening
+static LISTENERS: Mutex<usize> = Mutex::new(0);
+
+/// While alive, the signals that would end (or, on Unix, suspend) `run`
+/// are recorded instead, for [`wait_for`] to pass on to its command: SIGINT,
+/// SIGTERM, SIGHUP and SIGTSTP on Unix, unless ignored, and Ctrl+C and
+/// Ctrl+Break on Windows. The handlers are only in place while some command
+/// is waited on, so `run` keeps the default behavior otherwise.
+struct Interrupts {
+    seen: usize,
+}
+
+impl Interrupts {
+    fn listen() -> Self {
+        let mut listeners = LISTENERS.lock().unwrap_or_else(PoisonError::into_inner);
+        if *listeners == 0 {
+            catch_signals(true);
+        }
+        *listeners += 1;
+        Interrupts {
+            seen: SIGNALS.load(Ordering::SeqCst),
+        }
+    }
+
+    /// The signal received since the last call, if any
+    fn take(&mut self) -> Option<i32> {
+        let received = SIGNALS.load(Ordering::SeqCst);
+        if received == self.seen {
+            return None;
+        }
+        self.seen = received;
+        Some(LAST_SIGNAL.load(Ordering::SeqCst))
+    }
+}
+
+impl Drop for Interrupts {
+    fn drop(&mut self) {
+        let mut listeners = LISTENERS.lock().unwrap_or_else(PoisonError::into_inner);
+        *listeners -= 1;
+        if *listeners == 0 {
+            catch_signals(false);
+        }
+    }
+}
+
+fn record_signal(signal: i32) {
+    LAST_SIGNAL.store(signal, Ordering::SeqCst);
+    SIGNALS.fetch_add(1, Ordering::SeqCst);
+}
+
+/// The handlers [`catch_signals`] replaced, to put back
+#[cfg(unix)]
+static REPLACED: Mutex<Vec<(i32, libc::sigaction)>> = Mutex::new(Vec::new());
+
+/// Record the signals [`Interrupts`] listens to, or (`catch` false) stop
+#[cfg(unix)]
+fn catch_signals(catch: bool) {
+    extern "C" fn handler(signal: libc::c_int) {
+        record_signal(signal);
+    }
+
+    let mut replaced = REPLACED.lock().unwrap_or_else(PoisonError::into_inner);
+    if !catch {
+        for (signal, previous) in replaced.drain(..) {
+            // SAFETY: puts back a disposition sigaction handed out
+            unsafe {
+                libc::sigaction(signal, &previous, std::ptr::null_mut());
+            }
+        }
+        return;
+    }
+    for signal in [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGTSTP] {
+        // SAFETY: the handler only stores to atomics, which is
+        // async-signal-safe; the structs are plain data
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            let mut previous: libc::sigaction = std::mem::zeroed();
+            if libc::sigaction(signal, &action, &mut previous) != 0 {
+                continue;
+            }
+            // An ignored signal (nohup, a background job) stays ignored
+            if previous.sa_sigaction == libc::SIG_IGN {
+                libc::sigaction(signal, &previous, std::ptr::null_mut());
+                continue;
+            }
+            replaced.push((signal, previous));
+        }
+    }
+}
+
+/// Record Ctrl+C and Ctrl+Break (as SIGINT: they reach every process on the
+/// console, the command included), or (`catch` false) stop
+#[cfg(windows)]
+fn catch_signals(catch: bool) {
+    use windows_sys::Win32::System::Console::{
+        SetConsoleCtrlHandler, CTRL_BREAK_EVENT, CTRL_C_EVENT,
+    };
+
+    unsafe extern "system" fn handler(event: u32) -> windows_sys::core::BOOL {
+        if event != CTRL_C_EVENT && event != CTRL_BREAK_EVENT {
+            return 0;
+        }
+        record_signal(2);
+        1
+    }
+
+    // SAFETY: registers (or removes) a handler that only stores to atomics
+    unsafe {
+        SetConsoleCtrlHandler(Some(handler), catch.into());
+    }
+}
+
+/// Whether `run` reads from a terminal it has the foreground of
+fn owns_terminal() -> bool {
+    // SAFETY: queries about the standard input, which may not be a terminal
+    #[cfg(unix)]
+    unsafe {
+        libc::isatty(libc::STDIN_FILENO) == 1
+            && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+    }
+    #[cfg(not(unix))]
+    false
+}
+
+/// Make `pgid` the foreground process group of the terminal on stdin
+#[cfg(unix)]
+fn hand_terminal(pgid: libc::pid_t) {
+    // SAFETY: only async-signal-safe calls on the standard input and this
+    // thread's signal mask, which is put back as it was
+    unsafe {
+        // Outside the foreground group, asking for the terminal would
+        // raise SIGTTOU (stopping the process) unless it is blocked
+        let mut ttou: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut ttou);
+        libc::sigaddset(&mut ttou, libc::SIGTTOU);
+        let mut previous: libc::sigset_t = std::mem::zeroed();
+        libc::pthread_sigmask(libc::SIG_BLOCK, &ttou, &mut previous);
+        libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
+        libc::pthread_sigmask(libc::SIG_SETMASK, &previous, std::ptr::null_mut());
+    }
+}
+
+/// While alive, the terminal belongs to a command's process group; `run`'s
+/// own group gets it back when dropped
+#[cfg(unix)]
+struct Foreground;
+
+#[cfg(unix)]
+impl Foreground {
+    /// Hand the terminal to the group of `pid` (the command does so too,
+    /// before it starts, so neither has to wait for the other)
+    fn new(pid: u32) -> Self {
+        hand_terminal(pid as libc::pid_t);
+        Foreground
+    }
+}
+
+#[cfg(unix)]
+impl Drop for Foreground {
+    fn drop(&mut self) {
+        // SAFETY: getpgrp can't fail
+        hand_terminal(unsafe { libc::getpgrp() });
+    }
+}
+
+/// Stop `run` with the command leading group `pid`, as a shell stops a job:
+/// the terminal (if the command had it, `terminal`) goes back to `run`'s
+/// group for the shell to take. Once continued, the command is too, with
+/// the terminal again if `run` is in the foreground.
+#[cfg(unix)]
+fn suspend(pid: u32, terminal: bool) {
+    forward_signal(pid, libc::SIGTSTP);
+    // SAFETY: getpgrp can't fail, and raise only signals this process
+    unsafe {
+        if terminal {
+            hand_terminal(libc::getpgrp());
+        }
+        libc::raise(libc::SIGSTOP);
+    }
+    if terminal && owns_terminal() {
+        hand_terminal(pid as libc::pid_t);
+    }
+    forward_signal(pid, libc::SIGCONT);
+}
+
+/// How a command started by [`execute_parallel`] ended
+#[derive(Debug)]
+pub enum ParallelExit {
+    /// The command exited on its own
+    Exited(ExitStatus),
+    /// Terminated, or never started, because another command failed
+    Stopped,
+    /// The command could not be started, or ran out of time
+    Failed(RunError),
+}
+
+/// Run `commands` at the same time and wait for all of them.
+///
+/// Output is read line by line and handed to `on_line` with the index of the
+/// command that printed it; stdin is closed. Interrupt, terminate and hangup
+/// signals are forwarded to every command (on Unix each runs in its own
+/// process group, so a terminal's Ctrl+C reaches them only through here).
+/// A command whose [`PreparedCommand::group`] is taken by a running one
+/// waits for it to exit. With `stop_on_failure`, the first command to fail
+/// gets the others terminated, and those waiting are not started. Results
+/// are in the order of `commands`, with run times.
+pub fn execute_parallel(
+    commands: &[PreparedCommand],
+    stop_on_failure: bool,
+    mut on_line: impl FnMut(usize, OutputLine),
+) -> Vec<(ParallelExit, Duration)> {
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            return commands
+                .iter()
+                .map(|_| {
+                    let error = RunError::CommandFailed(format!("Failed to start runtime: {}", e));
+                    (ParallelExit::Failed(error), Duration::ZERO)
+                })
+                .collect();
+        }
+    };
+    runtime.block_on(run_parallel(commands, stop_on_failure, &mut on_line))
+}
+
+async fn run_parallel(
+    commands: &[PreparedCommand],
+    stop_on_failure: bool,
+    on_line: &mut impl FnMut(usize, OutputLine),
+) -> Vec<(ParallelExit, Duration)> {
+    let (line_tx, mut line_rx) = mpsc::unbounded_channel::<(usize, OutputLine)>();
+    let (exit_tx, mut exit_rx) = mpsc::unbounded_channel();
+    let mut line_tx = Some(line_tx);
+    let mut results: Vec<Option<(ParallelExit, Duration)>> =
+        commands.iter().map(|_| None).collect();
+    let mut running: Vec<Option<(Option<u32>, CancellationToken)>> =
+        commands.iter().map(|_| None).collect();
+    let mut started: Vec<Option<Instant>> = commands.iter().map(|_| None).collect();
+    // Commands yet to start, and the groups of those running
+    let mut waiting: Vec<usize> = (0..commands.len()).collect();
+    let mut busy: HashSet<&str> = HashSet::new();
+
+    let mut stopping = false;
+    let mut pending = 0;
+    let mut lines_open = true;
+    let mut signals = ForwardedSignals::new();
+    while pending > 0 || lines_open {
+        if let (false, Some(lines)) = (stopping, &line_tx) {
+            waiting.retain(|&index| {
+                let group = commands[index].group.as_deref();
+                if group.is_some_and(|group| busy.contains(group)) {
+                    return true;
+                }
+                match spawn_parallel(index, &commands[index], lines, &exit_tx) {
+                    Ok(child) => {
+                        busy.extend(group);
+                        running[index] = Some(child);
+                        started[index] = Some(Instant::now());
+                        pending += 1;
+                    }
+                    Err(e) => results[index] = Some((ParallelExit::Failed(e), Duration::ZERO)),
+                }
+                false
+            });
+        }
+        // Nothing more will print once every command has started
+        if stopping || waiting.is_empty() {
+            line_tx = None;
+        }
+
+        tokio::select! {
+            line = line_rx.recv(), if lines_open => match line {
+                Some((index, line)) => on_line(index, line),
+                None => lines_open = false,
+            },
+            exit = exit_rx.recv(), if pending > 0 => {
+                let Some((index, status)) = exit else { break };
+                pending -= 1;
+                if let Some(group) = &commands[index].group {
+                    busy.remove(group.as_str());
+                }
+                let stopped = running[index].take().is_none();
+                let elapsed = started[index].map_or(Duration::ZERO, |at| at.elapsed());
+                results[index] = Some(match status {
+                    Ok(_) if stopped => (ParallelExit::Stopped, elapsed),
+                    Ok(status) => (ParallelExit::Exited(status), elapsed),
+                    Err(e) => (ParallelExit::Failed(e), elapsed),
+                });
+
+                let failed = !matches!(results[index], Some((ParallelExit::Exited(s), _)) if s.success());
+                if failed && stop_on_failure && !stopping {
+                    stopping = true;
+                    for (pid, cancel) in running.iter_mut().filter_map(Option::take) {
+                        terminate(pid, &cancel);
+                    }
+                }
+            },
+            signal = signals.recv() => {
+                if let Some(signal) = signal {
+                    for (pid, _) in running.iter().flatten() {
+                        if let Some(pid) = pid {
+                            forward_signal(*pid, signal);
+                        }
+                    }
+                }
+            },
+        }
+    }
+
+    results
+        .into_iter()
+        .map(|result| result.unwrap_or((ParallelExit::Stopped, Duration::ZERO)))
+        .collect()
+}
+
+/// Start command `index` of [`execute_parallel`], sending its output lines
+/// to `line_tx` and how it ended to `exit_tx`
+fn spawn_parallel(
+    index: usize,
+    prepared: &PreparedCommand,
+    line_tx: &mpsc::UnboundedSender<(usize, OutputLine)>,
+    exit_tx: &mpsc::UnboundedSender<(usize, Result<ExitStatus, RunError>)>,
+) -> Result<(Option<u32>, CancellationToken), RunError> {
+    let parts = tools::spawn_command(&prepared.command, &prepared.cwd);
+    let mut command = tokio::process::Command::new(&parts[0]);
+    command
+        .args(&parts[1..])
+        .current_dir(&prepared.cwd)
+        .envs(&prepared.env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+    // Printing to a terminal (or with --pty), the command gets a
+    // pseudo-terminal to print to, so it keeps its colors
+    #[cfg(unix)]
+    let pty = (prepared.pty || std::io::stdout().is_terminal())
+        .then(Pty::open)
+        .transpose()?;
+    #[cfg(unix)]
+    if let Some(pty) = &pty {
+        command
+            .stdout(pty.slave.try_clone()?)
+            .stderr(pty.slave.try_clone()?);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| RunError::CommandFailed(format!("Failed to execute {}: {}", parts[0], e)))?;
+    drop(command);
+    #[cfg(unix)]
+    if let Some(pty) = pty {
+        drop(pty.slave);
+        let line_tx = line_tx.clone();
+        std::thread::spawn(move || {
+            use std::io::BufRead;
+            for line in std::io::BufReader::new(pty.master).split(b'\n') {
+                let Ok(line) = line else { break };
+                let line = line.strip_suffix(b"\r").unwrap_or(&line);
+                let line = OutputLine::Stdout(String::from_utf8_lossy(line).into_owned());
+                if line_tx.send((index, line)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    for (reader, wrap) in [
+        (
+            child
+                .stdout
+                .take()
+                .map(|r| Box::new(r) as Box<dyn AsyncRead + Unpin + Send>),
+            OutputLine::Stdout as fn(String) -> OutputLine,
+        ),
+        (
+            child
+                .stderr
+                .take()
+                .map(|r| Box::new(r) as Box<dyn AsyncRead + Unpin + Send>),
+            OutputLine::Stderr,
+        ),
+    ] {
+        let Some(reader) = reader else { continue };
+        let line_tx = line_tx.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(reader).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                if line_tx.send((index, wrap(line))).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    let cancel = CancellationToken::new();
+    let cancelled = cancel.clone();
+    let pid = child.id();
+    let exit_tx = exit_tx.clone();
+    let timeout = prepared.timeout;
+    tokio::spawn(async move {
+        let expired = async {
+            match timeout {
+                Some(timeout) => tokio::time::sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        let status = tokio::select! {
+            status = child.wait() => status.map_err(RunError::from),
+            _ = cancelled.cancelled() => {
+                let _ = child.start_kill();
+                child.wait().await.map_err(RunError::from)
+            }
+            _ = expired => {
+                let _ = stop_group_async(&mut child, pid).await;
+                Err(RunError::Timeout(timeout.unwrap_or_default()))
+            }
+        };
+        let _ = exit_tx.send((index, status));
+    });
+    Ok((pid, cancel))
+}
+
+/// Signals relayed to the commands [`execute_parallel`] runs
+struct ForwardedSignals {
+    #[cfg(unix)]
+    streams: Vec<(i32, tokio::signal::unix::Signal)>,
+}
+
+impl ForwardedSignals {
+    #[cfg(unix)]
+    fn new() -> Self {
+        use tokio::signal::unix::{signal, SignalKind};
+        let kinds = [
+            (libc::SIGINT, SignalKind::interrupt()),
+            (libc::SIGTERM, SignalKind::terminate()),
+            (libc::SIGHUP, SignalKind::hangup()),
+        ];
+        ForwardedSignals {
+            streams: kinds
+                .into_iter()
+                .filter_map(|(number, kind)| Some((number, signal(kind).ok()?)))
+                .collect(),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        ForwardedSignals {}
+    }
+
+    /// The next signal received; pending forever when there is nothing to
+    /// listen to
+    #[cfg(unix)]
+    async fn recv(&mut self) -> Option<i32> {
+        if self.streams.is_empty() {
+            return std::future::pending().await;
+        }
+        Some(next_signal(&mut self.streams).await)
+    }
+
+    /// Ctrl+C reaches every process attached to the console, so it is only
+    /// kept from ending `run` before the commands do
+    #[cfg(not(unix))]
+    async fn recv(&mut self) -> Option<i32> {
+        let _ = tokio::signal::ctrl_c().await;
+        None
+    }
+}
+
+/// Number of whichever signal arrives first
+#[cfg(unix)]
+async fn next_signal(streams: &mut [(i32, tokio::signal::unix::Signal)]) -> i32 {
+    std::future::poll_fn(|cx| {
+        for (number, stream) in streams.iter_mut() {
+            if stream.poll_recv(cx).is_ready() {
+                return std::task::Poll::Ready(*number);
+            }
+        }
+        std::task::Poll::Pending
+    })
+    .await
+}
+
+/// Send `signal` to the process group of the command started as `pid`
+#[cfg(unix)]
+fn forward_signal(pid: u32, signal: i32) {
+    // Each command leads its own group, so its children get the signal too.
+    // SAFETY: kill has no memory-safety preconditions; `pid` is a child we
+    // spawned, never 0, so this can't signal our own group.
+    unsafe {
+        libc::kill(-(pid as i32), signal);
+    }
+}
+
+#[cfg(not(unix))]
+fn forward_signal(_pid: u32, _signal: i32) {}
+
+/// Ask a command to stop: SIGTERM to its group on Unix, a kill elsewhere
+fn terminate(pid: Option<u32>, cancel: &CancellationToken) {
+    #[cfg(unix)]
+    if let Some(pid) = pid {
+        forward_signal(pid, libc::SIGTERM);
+        return;
+    }
+    let _ = pid;
+    cancel.cancel();
+}
+
+/// Token used to cancel a running [`execute_async`] task.
+///
+/// Clones share the same state, so one clone can be handed to the task and
+/// another kept by the caller (e.g. a Ctrl+C handler or a GUI stop button).
+#[derive(Clone)]
+pub struct CancellationToken {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Request cancellation; the child process is killed
+    pub fn cancel(&self) {
+        self.sender.send_replace(true);
+    }
+
+    /// Check whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        *self.sender.borrow()
+    }
+
+    /// Wait until cancellation is requested
+    pub async fn cancelled(&self) {
+        let mut receiver = self.sender.subscribe();
+        let _ = receiver.wait_for(|cancelled| *cancelled).await;
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A line of output produced by a child process started with [`execute_async`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputLine {
+    Stdout(String),
+    Stderr(String),
+}
+
+/// Execute a command with the detected runner without blocking a thread.
+///
+/// When `output` is given, stdout and stderr are captured and streamed line by
+/// line through the channel; otherwise they are inherited from this process.
+///
+/// Like [`ExecutionBuilder::run`], the command runs in its own process group
+/// (on Unix), and cancelling `cancel` or running past `timeout` stops the
+/// whole group: SIGTERM, then SIGKILL after a grace period. A cancelled run's
+/// status reflects the signal; a timed out one ends in [`RunError::Timeout`].
+/// Unlike [`ExecutionBuilder::run`], the group is never handed the terminal
+/// and signals this process receives are not passed on to it, so callers
+/// stop a run through `cancel` (the JSON-RPC server does on `exit`).
+pub async fn execute_async(
+    runner: &DetectedRunner,
+    task: &str,
+    extra_args: &[String],
+    working_dir: &Path,
+    timeout: Option<Duration>,
+    cancel: CancellationToken,
+    output: Option<mpsc::UnboundedSender<OutputLine>>,
+) -> Result<RunResult, RunError> {
+    if runner.ecosystem != Ecosystem::Custom
+        && !runner.has_custom_command(task)
+        && !runner.is_installed()
+    {
+        return Err(RunError::ToolNotInstalled(format!(
+            "{} is not installed. Please install it to continue.",
+            runner.name
+        )));
+    }
+
+    let cmd_parts = tools::spawn_command(&runner.build_command(task, extra_args), working_dir);
+    let program = &cmd_parts[0];
+
+    let mut command = tokio::process::Command::new(program);
+    command
+        .args(&cmd_parts[1..])
+        .current_dir(working_dir)
+        .kill_on_drop(true);
+    #[cfg(unix)]
+    command.process_group(0);
+
+    if output.is_some() {
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+    } else {
+        command
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| RunError::CommandFailed(format!("Failed to execute {}: {}", program, e)))?;
+
+    let mut forwarders = Vec::new();
+    if let Some(sender) = output {
+        if let Some(stdout) = child.stdout.take() {
+            forwarders.push(tokio::spawn(forward_lines(
+                stdout,
+                sender.clone(),
+                OutputLine::Stdout,
+            )));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            forwarders.push(tokio::spawn(forward_lines(
+                stderr,
+                sender,
+                OutputLine::Stderr,
+            )));
+        }
+    }
+
+    let pid = child.id();
+    let expired = async {
+        match timeout {
+            Some(timeout) => tokio::time::sleep(timeout).await,
+            None => std::future::pending().await,
+        }
+    };
+    let wait_failed = |e: std::io::Error| {
+        RunError::CommandFailed(format!("Failed to wait for {}: {}", program, e))
+    };
+    let status = tokio::select! {
+        status = child.wait() => status.map_err(wait_failed),
+        _ = cancel.cancelled() => stop_group_async(&mut child, pid).await.map_err(wait_failed),
+        _ = expired => {
+            let _ = stop_group_async(&mut child, pid).await;
+            Err(RunError::Timeout(timeout.unwrap_or_default()))
+        }
+    };
+
+    // Drain remaining output before reporting the result
+    for forwarder in forwarders {
+        let _ = forwarder.await;
+    }
+    let status = status?;
+
+    Ok(RunResult {
+        exit_status: status,
+        runner: runner.clone(),
+        working_dir: working_dir.to_path_buf(),
+        attempts: 1,
+        stdout: Vec::new(),
+        stderr: Vec::new(),
+    })
+}
+
+async fn forward_lines<R: AsyncRead + Unpin>(
+    reader: R,
+    sender: mpsc::UnboundedSender<OutputLine>,
+    wrap: fn(String) -> OutputLine,
+) {
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if sender.send(wrap(line)).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RunError;
+    use std::fs::{self, File};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_search_runners_current_dir() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("package.json")).unwrap();
+
+        let (runners, found_dir) = search_runners(dir.path(), 3, &[], false).unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "npm");
+        assert_eq!(found_dir, dir.path());
+    }
+
+    #[test]
+    fn test_search_runners_parent_dir() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("package.json")).unwrap();
+
+        let subdir = dir.path().join("src");
+        std::fs::create_dir(&subdir).unwrap();
+
+        let (runners, found_dir) = search_runners(&subdir, 3, &[], false).unwrap();
+        assert_eq!(runners.len(), 1);
+        assert_eq!(runners[0].name, "npm");
+        assert_eq!(found_dir, dir.path());
+    }
+
+    #[test]
+    fn test_search_runners_not_found() {
+        let dir = tempdir().unwrap();
+        let result = search_runners(dir.path(), 3, &[], false);
+        assert!(matches!(result, Err(RunError::RunnerNotFound(3))));
+    }
+
+    #[test]
+    fn test_search_runners_with_ignore() {
+        let dir = tempdir().unwrap();
+        File::create(dir.path().join("package.json")).unwrap();
+
+        let result = search_runners(dir.path(), 3, &["npm".to_string()], false);
+        assert!(matches!(result, Err(RunError::RunnerNotFound(3))));
+    }
+
+    #[test]
+    fn test_check_conflicts_single_runner() {
+        let dir = tempdir().unwrap();
+        let runners = vec![DetectedRunner::new(
+            "npm",
+            "package.json",
+            Ecosystem::NodeJs,
+            4,
+        )];
+        let result = check_conflicts(&runners, dir.path(), false).unwrap();
+        assert_eq!(result.name, "npm");
+    }
+
+    #[test]
+    fn test_check_conflicts_different_ecosystems() {
+        let dir = tempdir().unwrap();
+        let runners = vec![
+            DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4),
+            DetectedRunner::new("cargo", "Cargo.toml", Ecosystem::Rust, 9),
+        ];
+        let result = check_conflicts(&runners, dir.path(), false).unwrap();
+        // Should return highest priority
+        assert_eq!(result.name, "npm");
+    }
+
+    #[test]
+    fn test_check_conflicts_corepack_resolves() {
+        use std::io::Write;
+
+        let dir = tempdir().unwrap();
+        // Create package.json with packageManager specifying pnpm
+        let mut file = File::create(dir.path().join("package.json")).unwrap();
+        writeln!(file, r#"{{"packageManager": "pnpm@9.0.0"}}"#).unwrap();
+
+        let runners = vec![
+            DetectedRunner::new("yarn", "yarn.lock", Ecosystem::NodeJs, 3),
+            DetectedRunner::new("pnpm", "pnpm-lock.yaml", Ecosystem::NodeJs, 2),
+            DetectedRunner::new("npm", "package-lock.json", Ecosystem::NodeJs, 4),
+        ];
+
+        // Corepack should resolve to pnpm
+        let result = check_conflicts(&runners, dir.path(), false).unwrap();
+        assert_eq!(result.name, "pnpm");
+    }
+
+    #[test]
+    fn test_resolve_runner_honors_package_manager_pin() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"packageManager": "yarn@4.1.0", "scripts": {"build": "tsc"}}"#,
+        )
+        .unwrap();
+
+        // bun has the higher priority, but package.json pins yarn
+        let runners = vec![
+            DetectedRunner::new("nx", "nx.json", Ecosystem::NodeJs, 0),
+            DetectedRunner::with_validator(
+                "bun",
+                "bun.lockb",
+                Ecosystem::NodeJs,
+                1,
+                Arc::new(node::NodeValidator),
+            ),
+            DetectedRunner::with_validator(
+                "yarn",
+                "package.json",
+                Ecosystem::NodeJs,
+                3,
+                Arc::new(node::NodeValidator),
+            ),
+        ];
+        let runner = resolve_runner(&runners, dir.path(), "build", false).unwrap();
+        assert_eq!(runner.name, "yarn");
+
+        // Orchestrators are not conflict losers
+        let runner = resolve_runner(&runners, dir.path(), "affected", false).unwrap();
+        assert_eq!(runner.name, "nx");
+    }
+
+    #[test]
+    fn test_corepack_prefix() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("package.json"),
+            r#"{"packageManager": "pnpm@9.1.0+sha256.abc"}"#,
+        )
+        .unwrap();
+        let runner = DetectedRunner::new("pnpm", "package.json", Ecosystem::NodeJs, 2);
+
+        let builder = ExecutionBuilder::new(&runner, "build").cwd(dir.path());
+        assert_eq!(builder.command().unwrap(), vec!["pnpm", "run", "build"]);
+
+        let builder = builder.corepack(true);
+        assert_eq!(
+            builder.command().unwrap(),
+            vec!["corepack", "pnpm", "run", "build"]
+        );
+
+        // Only the pinned manager goes through corepack
+        let npm = DetectedRunner::new("npm", "package-lock.json", Ecosystem::NodeJs, 4);
+        let builder = ExecutionBuilder::new(&npm, "build")
+            .cwd(dir.path())
+            .corepack(true);
+        assert_eq!(builder.command().unwrap(), vec!["npm", "run", "build"]);
+    }
+
+    #[cfg(unix)]
+    fn shell_runner(command: &str) -> DetectedRunner {
+        let mut commands = HashMap::new();
+        commands.insert("task".to_string(), command.to_string());
+        DetectedRunner::with_custom_commands(
+            "custom",
+            "run.toml",
+            Ecosystem::Custom,
+            0,
+            Arc::new(crate::detectors::UnknownValidator),
+            commands,
+        )
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_async_streams_output() {
+        let dir = tempdir().unwrap();
+        let runner = shell_runner("sh -c 'echo out; echo err >&2; exit 3'");
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let result = execute_async(
+            &runner,
+            "task",
+            &[],
+            dir.path(),
+            None,
+            CancellationToken::new(),
+            Some(tx),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(result.exit_status.code(), Some(3));
+
+        let mut lines = Vec::new();
+        while let Some(line) = rx.recv().await {
+            lines.push(line);
+        }
+        assert!(lines.contains(&OutputLine::Stdout("out".to_string())));
+        assert!(lines.contains(&OutputLine::Stderr("err".to_string())));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_async_cancellation() {
+        let dir = tempdir().unwrap();
+        let runner = shell_runner("sleep 30");
+        let cancel = CancellationToken::new();
+
+        let canceller = cancel.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            canceller.cancel();
+        });
+
+        let started = std::time::Instant::now();
+        let result = execute_async(&runner, "task", &[], dir.path(), None, cancel.clone(), None)
+            .await
+            .unwrap();
+
+        assert!(cancel.is_cancelled());
+        assert!(!result.exit_status.success());
+        assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_execute_async_timeout_stops_group() {
+        let dir = tempdir().unwrap();
+        // The background job would outlive its shell if only the shell died
+        let runner = shell_runner("sh -c '(sleep 1; touch late) & wait'");
+
+        let result = execute_async(
+            &runner,
+            "task",
+            &[],
+            dir.path(),
+            Some(Duration::from_millis(200)),
+            CancellationToken::new(),
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(RunError::Timeout(_))));
+
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        assert!(!dir.path().join("late").exists());
+    }
+
+    #[tokio::test]
+    async fn test_execute_async_tool_not_installed() {
+        let dir = tempdir().unwrap();
+        let runner =
+            DetectedRunner::new("definitely-not-a-real-tool", "file", Ecosystem::Generic, 1);
+        let result = execute_async(
+            &runner,
+            "build",
+            &[],
+            dir.path(),
+            None,
+            CancellationToken::new(),
+            None,
+        )
+        .await;
+        assert!(matches!(result, Err(RunError::ToolNotInstalled(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execution_builder_capture_and_env() {
+        let dir = tempdir().unwrap();
+        let runner = shell_runner("sh -c 'echo $GREETING; echo oops >&2'");
+
+        let result = ExecutionBuilder::new(&runner, "task")
+            .cwd(dir.path())
+            .env("GREETING", "hello")
+            .stdio(StdioPolicy::Capture)
+            .run()
+            .unwrap();
+
+        assert!(result.exit_status.success());
+        assert_eq!(result.attempts, 1);
+        assert_eq!(String::from_utf8_lossy(&result.stdout), "hello\n");
+        assert_eq!(String::from_utf8_lossy(&result.stderr), "oops\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_task_script_file() {
+        use std::hash::{Hash, Hasher};
+
+        let dir = tempdir().unwrap();
+        let runner = shell_runner("unused");
+        let script = "echo fresh";
+
+        // Where scripts used to be kept, named after their content: a file
+        // planted there must not be run in place of the script
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        script.hash(&mut hasher);
+        let old_dir = std::env::temp_dir().join("run-scripts");
+        let stale = old_dir.join(format!("{:016x}.sh", hasher.finish()));
+        std::fs::create_dir_all(&old_dir).unwrap();
+        std::fs::write(&stale, "echo stale").unwrap();
+
+        let builder = ExecutionBuilder::new(&runner, "task")
+            .cwd(dir.path())
+            .task_script(Some(script.to_string()))
+            .stdio(StdioPolicy::Capture);
+        let prepared = builder.prepare().unwrap();
+        let path = PathBuf::from(prepared.command.last().unwrap());
+        assert_ne!(path, stale);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), script);
+        drop(prepared);
+        assert!(!path.exists());
+
+        let result = builder.run().unwrap();
+        std::fs::remove_file(&stale).unwrap();
+        assert_eq!(String::from_utf8_lossy(&result.stdout), "fresh\n");
+    }
+
+    #[test]
+    fn test_stdin_source_from_str() {
+        assert_eq!("inherit".parse(), Ok(StdinSource::Inherit));
+        assert_eq!("null".parse(), Ok(StdinSource::Null));
+        assert_eq!(
+            "file:data/in.csv".parse(),
+            Ok(StdinSource::File(PathBuf::from("data/in.csv")))
+        );
+        assert!("file:".parse::<StdinSource>().is_err());
+        assert!("pipe".parse::<StdinSource>().is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execution_builder_stdin_file() {
+        let dir = tempdir().unwrap();
+        let input = dir.path().join("input.txt");
+        std::fs::write(&input, "from a file\n").unwrap();
+        let runner = shell_runner("cat");
+
+        let result = ExecutionBuilder::new(&runner, "task")
+            .cwd(dir.path())
+            .stdio(StdioPolicy::Capture)
+            .stdin(StdinSource::File(input))
+            .run()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&result.stdout), "from a file\n");
+
+        let missing = ExecutionBuilder::new(&runner, "task")
+            .cwd(dir.path())
+            .stdio(StdioPolicy::Capture)
+            .stdin(StdinSource::File(dir.path().join("missing.txt")))
+            .run();
+        assert!(matches!(missing, Err(RunError::InvalidArgument(_))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execution_builder_retries() {
+        let dir = tempdir().unwrap();
+        // Fails until the marker file exists, creating it on the first attempt
+        let runner = shell_runner("sh -c 'test -f marker || { touch marker; exit 1; }'");
+
+        let result = ExecutionBuilder::new(&runner, "task")
+            .cwd(dir.path())
+            .retries(2)
+            .stdio(StdioPolicy::Null)
+            .run()
+            .unwrap();
+
+        assert!(result.exit_status.success());
+        assert_eq!(result.attempts, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execution_builder_timeout() {
+        let dir = tempdir().unwrap();
+        let runner = shell_runner("sleep 30");
+
+        let started = std::time::Instant::now();
+        let result = ExecutionBuilder::new(&runner, "task")
+            .cwd(dir.path())
+            .timeout(Duration::from_millis(100))
+            .run();
+
+        assert!(matches!(result, Err(RunError::Timeout(_))));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_parallel() {
+        let dir = tempdir().unwrap();
+        let prepare = |command: &str| {
+            ExecutionBuilder::new(&shell_runner(command), "task")
+                .cwd(dir.path())
+                .prepare()
+                .unwrap()
+        };
+        let commands = [
+            prepare("sh -c 'echo one; echo oops >&2'"),
+            prepare("sh -c 'sleep 0.2; echo two; exit 3'"),
+            prepare("sleep 30"),
+        ];
+
+        let mut lines = Vec::new();
+        let started = std::time::Instant::now();
+        let results = execute_parallel(&commands, true, |index, line| lines.push((index, line)));
+        assert!(started.elapsed() < Duration::from_secs(10));
+
+        assert!(matches!(results[0].0, ParallelExit::Exited(s) if s.success()));
+        assert!(matches!(results[1].0, ParallelExit::Exited(s) if s.code() == Some(3)));
+        assert!(matches!(results[2].0, ParallelExit::Stopped));
+        assert!(lines.contains(&(0, OutputLine::Stdout("one".to_string()))));
+        assert!(lines.contains(&(0, OutputLine::Stderr("oops".to_string()))));
+        assert!(lines.contains(&(1, OutputLine::Stdout("two".to_string()))));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exit_code_of() {
+        let status = |script: &str| {
+            std::process::Command::new("sh")
+                .args(["-c", script])
+                .status()
+                .unwrap()
+        };
+        assert_eq!(exit_code_of(status("exit 3")), 3);
+        assert_eq!(exit_code_of(status("kill -TERM $$")), 143);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execute_parallel_groups() {
+        let dir = tempdir().unwrap();
+        let prepare = |command: &str, group: Option<&str>| {
+            ExecutionBuilder::new(&shell_runner(command), "task")
+                .cwd(dir.path())
+                .group(group.map(str::to_string))
+                .prepare()
+                .unwrap()
+        };
+        let commands = [
+            prepare(
+                "sh -c 'echo start >> db.log; sleep 0.2; echo end >> db.log'",
+                Some("db"),
+            ),
+            prepare(
+                "sh -c 'echo start >> db.log; sleep 0.2; echo end >> db.log'",
+                Some("db"),
+            ),
+            prepare("sh -c 'echo other >> other.log'", Some("web")),
+            prepare("sh -c 'exit 1'", Some("ci")),
+            prepare("sh -c 'echo late >> ci.log'", Some("ci")),
+        ];
+
+        let results = execute_parallel(&commands[..3], false, |_, _| {});
+        assert!(results
+            .iter()
+            .all(|(exit, _)| matches!(exit, ParallelExit::Exited(s) if s.success())));
+        assert_eq!(
+            fs::read_to_string(dir.path().join("db.log")).unwrap(),
+            "start\nend\nstart\nend\n"
+        );
+        assert!(dir.path().join("other.log").exists());
+
+        // Waiting commands are not started once one has failed
+        let results = execute_parallel(&commands[3..], true, |_, _| {});
+        assert!(matches!(results[1].0, ParallelExit::Stopped));
+        assert!(!dir.path().join("ci.log").exists());
+    }
+
+    #[test]
+    fn test_execution_builder_dry_run_skips_tool_check() {
+        let dir = tempdir().unwrap();
+        let runner =
+            DetectedRunner::new("definitely-not-a-real-tool", "file", Ecosystem::Generic, 1);
+
+        let builder = ExecutionBuilder::new(&runner, "build")
+            .args(["--release"])
+            .cwd(dir.path())
+            .dry_run(true);
+        assert_eq!(
+            builder.command().unwrap(),
+            vec!["definitely-not-a-real-tool", "build", "--release"]
+        );
+
+        let result = builder.run().unwrap();
+        assert!(result.exit_status.success());
+        assert_eq!(result.attempts, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_execution_builder_emits_events() {
+        use std::sync::Mutex;
+
+        let dir = tempdir().unwrap();
+        let runner = shell_runner("echo streamed");
+        let seen = Arc::new(Mutex::new(Vec::new()));
+
+        let sink = Arc::clone(&seen);
+        let events = EventBus::new().with(Arc::new(move |event: &RunEvent<'_>| {
+            let entry = match event {
+                RunEvent::CommandSelected { command, .. } => {
+                    format!("selected {}", command.join(" "))
+                }
+                RunEvent::Spawned { attempt, .. } => format!("spawned {}", attempt),
+                RunEvent::Output { stream, data } => {
+                    format!("{:?} {}", stream, String::from_utf8_lossy(data).trim())
+                }
+                RunEvent::Exit { attempts, .. } => format!("exit {}", attempts),
+                other => format!("{:?}", other),
+            };
+            sink.lock().unwrap().push(entry);
+        }));
+
+        ExecutionBuilder::new(&runner, "task")
+            .cwd(dir.path())
+            .stdio(StdioPolicy::Capture)
+            .events(events)
+            .run()
+            .unwrap();
+
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                "selected echo streamed",
+                "spawned 1",
+                "Stdout streamed",
+                "exit 1"
+            ]
+        );
     }
 }
@@ -0,0 +1,359 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Rhai scripting hooks.
+//!
+//! `script = "scripts/run.rhai"` in run.toml points to a Rhai script that may
+//! define any of these functions, each receiving a context map (`task`, `args`,
+//! `runner`, `ecosystem`, `detected_file`, `working_dir`, `command`, `os`):
+//!
+//! - `check(ctx)`: pre-run check, return `false` or an error message to abort
+//! - `env(ctx)`: return a map of extra environment variables for the child
+//! - `command(ctx)`: return an array of strings replacing the built command
+//!
+//! Scripts run in a sandboxed engine: no module imports, bounded operations
+//! and memory, and `print`/`debug` go to stderr.
+
+use crate::detectors::DetectedRunner;
+use crate::RunError;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "scripting")]
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+
+/// Detection context exposed to scripts
+#[derive(Debug, Clone)]
+pub struct ScriptContext {
+    pub task: String,
+    pub args: Vec<String>,
+    pub runner: String,
+    pub ecosystem: String,
+    pub detected_file: String,
+    pub working_dir: PathBuf,
+    pub command: Vec<String>,
+}
+
+impl ScriptContext {
+    pub fn new(
+        runner: &DetectedRunner,
+        task: &str,
+        args: &[String],
+        working_dir: &Path,
+        command: &[String],
+    ) -> Self {
+        Self {
+            task: task.to_string(),
+            args: args.to_vec(),
+            runner: runner.name.clone(),
+            ecosystem: runner.ecosystem.as_str().to_string(),
+            detected_file: runner.detected_file.clone(),
+            working_dir: working_dir.to_path_buf(),
+            command: command.to_vec(),
+        }
+    }
+
+    #[cfg(feature = "scripting")]
+    fn to_map(&self) -> Map {
+        let strings =
+            |items: &[String]| -> Array { items.iter().cloned().map(Dynamic::from).collect() };
+
+        let mut map = Map::new();
+        map.insert("task".into(), self.task.clone().into());
+        map.insert("args".into(), strings(&self.args).into());
+        map.insert("runner".into(), self.runner.clone().into());
+        map.insert("ecosystem".into(), self.ecosystem.clone().into());
+        map.insert("detected_file".into(), self.detected_file.clone().into());
+        map.insert(
+            "working_dir".into(),
+            self.working_dir.display().to_string().into(),
+        );
+        map.insert("command".into(), strings(&self.command).into());
+        map.insert("os".into(), std::env::consts::OS.into());
+        map
+    }
+}
+
+/// A compiled hooks script
+pub struct ScriptHooks {
+    path: PathBuf,
+    #[cfg(feature = "scripting")]
+    engine: Engine,
+    #[cfg(feature = "scripting")]
+    ast: AST,
+}
+
+impl ScriptHooks {
+    /// Path of the loaded script
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "scripting")]
+impl ScriptHooks {
+    /// Compile the script at `path`
+    pub fn load(path: &Path) -> Result<Self, RunError> {
+        let engine = sandboxed_engine();
+        let ast = engine
+            .compile_file(path.to_path_buf())
+            .map_err(|e| script_error(path, &e.to_string()))?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            engine,
+            ast,
+        })
+    }
+
+    fn has_hook(&self, name: &str) -> bool {
+        self.ast
+            .iter_functions()
+            .any(|f| f.name == name && f.params.len() == 1)
+    }
+
+    fn call(&self, name: &str, ctx: &ScriptContext) -> Result<Option<Dynamic>, RunError> {
+        if !self.has_hook(name) {
+            return Ok(None);
+        }
+
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, (ctx.to_map(),))
+            .map(Some)
+            .map_err(|e| script_error(&self.path, &format!("{}(): {}", name, e)))
+    }
+
+    /// Run the `check` hook, failing if it returns `false` or a message
+    pub fn check(&self, ctx: &ScriptContext) -> Result<(), RunError> {
+        let result = match self.call("check", ctx)? {
+            Some(r) => r,
+            None => return Ok(()),
+        };
+
+        if let Some(passed) = result.clone().try_cast::<bool>() {
+            if passed {
+                return Ok(());
+            }
+            return Err(RunError::CommandFailed(format!(
+                "Pre-run check in {} failed",
+                self.path.display()
+            )));
+        }
+
+        if result.is_string() {
+            return Err(RunError::CommandFailed(format!(
+                "Pre-run check failed: {}",
+                result
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Run the `env` hook, returning variables to set on the child process
+    pub fn env(&self, ctx: &ScriptContext) -> Result<HashMap<String, String>, RunError> {
+        let result = match self.call("env", ctx)? {
+            Some(r) if !r.is_unit() => r,
+            _ => return Ok(HashMap::new()),
+        };
+
+        let map = result
+            .try_cast::<Map>()
+            .ok_or_else(|| script_error(&self.path, "env() must return a map"))?;
+
+        Ok(map
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect())
+    }
+
+    /// Run the `command` hook, returning a replacement command if any
+    pub fn command(&self, ctx: &ScriptContext) -> Result<Option<Vec<String>>, RunError> {
+        let result = match self.call("command", ctx)? {
+            Some(r) if !r.is_unit() => r,
+            _ => return Ok(None),
+        };
+
+        let parts: Vec<String> = result
+            .try_cast::<Array>()
+            .ok_or_else(|| script_error(&self.path, "command() must return an array"))?
+            .into_iter()
+            .map(|v| v.to_string())
+            .collect();
+
+        if parts.is_empty() {
+            return Err(script_error(
+                &self.path,
+                "command() returned an empty array",
+            ));
+        }
+
+        Ok(Some(parts))
+    }
+}
+
+#[cfg(not(feature = "scripting"))]
+impl ScriptHooks {
+    /// Scripting support was disabled at build time
+    pub fn load(path: &Path) -> Result<Self, RunError> {
+        Err(script_error(
+            path,
+            "run was built without the `scripting` feature",
+        ))
+    }
+
+    pub fn check(&self, _ctx: &ScriptContext) -> Result<(), RunError> {
+        Ok(())
+    }
+
+    pub fn env(&self, _ctx: &ScriptContext) -> Result<HashMap<String, String>, RunError> {
+        Ok(HashMap::new())
+    }
+
+    pub fn command(&self, _ctx: &ScriptContext) -> Result<Option<Vec<String>>, RunError> {
+        Ok(None)
+    }
+}
+
+/// Build an engine with resource limits (rhai is compiled with `no_module`,
+/// so scripts cannot import anything)
+#[cfg(feature = "scripting")]
+fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_max_operations(1_000_000)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1024 * 1024)
+        .set_max_array_size(10_000)
+        .set_max_map_size(10_000);
+    engine.on_print(|s| eprintln!("{}", s));
+    engine.on_debug(|s, _, _| eprintln!("{}", s));
+    engine
+}
+
+fn script_error(path: &Path, message: &str) -> RunError {
+    RunError::ConfigError(format!("script {}: {}", path.display(), message))
+}
+
+#[cfg(all(test, feature = "scripting"))]
+mod tests {
+    use super::*;
+    use crate::detectors::Ecosystem;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn context() -> ScriptContext {
+        let runner = DetectedRunner::new("npm", "package.json", Ecosystem::NodeJs, 4);
+        ScriptContext::new(
+            &runner,
+            "test",
+            &["--ci".to_string()],
+            Path::new("/project"),
+            &["npm".to_string(), "test".to_string(), "--ci".to_string()],
+        )
+    }
+
+    fn hooks(source: &str) -> ScriptHooks {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("hooks.rhai");
+        fs::write(&path, source).unwrap();
+        ScriptHooks::load(&path).unwrap()
+    }
+
+    #[test]
+    fn test_missing_hooks_are_noops() {
+        let hooks = hooks("let x = 1;");
+        let ctx = context();
+        assert!(hooks.check(&ctx).is_ok());
+        assert!(hooks.env(&ctx).unwrap().is_empty());
+        assert_eq!(hooks.command(&ctx).unwrap(), None);
+    }
+
+    #[test]
+    fn test_check_hook() {
+        let ctx = context();
+        assert!(hooks("fn check(ctx) { ctx.task == \"test\" }")
+            .check(&ctx)
+            .is_ok());
+        assert!(hooks("fn check(ctx) { false }").check(&ctx).is_err());
+
+        let err = hooks("fn check(ctx) { \"database is not running\" }")
+            .check(&ctx)
+            .unwrap_err();
+        assert!(err.to_string().contains("database is not running"));
+    }
+
+    #[test]
+    fn test_env_hook() {
+        let hooks = hooks(
+            r#"
+fn env(ctx) {
+    #{ RUNNER: ctx.runner, ARGC: ctx.args.len() }
+}
+"#,
+        );
+        let env = hooks.env(&context()).unwrap();
+        assert_eq!(env.get("RUNNER").unwrap(), "npm");
+        assert_eq!(env.get("ARGC").unwrap(), "1");
+    }
+
+    #[test]
+    fn test_command_hook() {
+        let hooks = hooks(
+            r#"
+fn command(ctx) {
+    if ctx.runner != "npm" { return; }
+    ["npx", "vitest"] + ctx.args
+}
+"#,
+        );
+        assert_eq!(
+            hooks.command(&context()).unwrap(),
+            Some(vec![
+                "npx".to_string(),
+                "vitest".to_string(),
+                "--ci".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_command_hook_rejects_empty() {
+        assert!(hooks("fn command(ctx) { [] }").command(&context()).is_err());
+    }
+
+    #[test]
+    fn test_imports_are_disabled() {
+        // `import` is not even a keyword without rhai's module support
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("hooks.rhai");
+        fs::write(&path, "fn check(ctx) { import \"other\" as o; true }").unwrap();
+        assert!(ScriptHooks::load(&path).is_err());
+    }
+
+    #[test]
+    fn test_runaway_script_is_stopped() {
+        let hooks = hooks("fn check(ctx) { loop { } }");
+        assert!(hooks.check(&context()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_script() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("broken.rhai");
+        fs::write(&path, "fn check(ctx) {").unwrap();
+        assert!(matches!(
+            ScriptHooks::load(&path),
+            Err(RunError::ConfigError(_))
+        ));
+    }
+}
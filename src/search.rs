@@ -0,0 +1,139 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Fuzzy task search (`run search`).
+//!
+//! A keyword matches a task name exactly, as a prefix, as a substring or as
+//! a subsequence (`dpl` finds `deploy`), ranked in that order; a task whose
+//! body contains the keyword matches too, below any name match. Case is
+//! ignored throughout.
+
+use crate::detectors::{DetectedRunner, TaskDefinition};
+use crate::runner::task_definition;
+use serde::Serialize;
+use std::path::Path;
+
+/// A task matching the keyword
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchMatch {
+    pub task: String,
+    pub runner: String,
+    /// The file that triggered the runner's detection
+    pub file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub definition: Option<TaskDefinition>,
+    /// Higher is better
+    pub score: u32,
+}
+
+/// The tasks of `runners` matching `keyword`, best first
+pub fn search(runners: &[DetectedRunner], working_dir: &Path, keyword: &str) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    for runner in runners {
+        for task in runner.list_commands(working_dir) {
+            let definition = task_definition(runner, &task, working_dir);
+            let body = definition.as_ref().map(|d| d.body.as_str()).unwrap_or("");
+            if let Some(score) = score(keyword, &task, body) {
+                matches.push(SearchMatch {
+                    task,
+                    runner: runner.name.clone(),
+                    file: runner.detected_file.clone(),
+                    definition,
+                    score,
+                });
+            }
+        }
+    }
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.task.cmp(&b.task)));
+    matches
+}
+
+/// How well `keyword` matches a task named `name` that runs `body`
+fn score(keyword: &str, name: &str, body: &str) -> Option<u32> {
+    let keyword = keyword.to_lowercase();
+    let name = name.to_lowercase();
+    if keyword.is_empty() {
+        return None;
+    }
+    if name == keyword {
+        return Some(1000);
+    }
+    if name.starts_with(&keyword) {
+        return Some(900 - name.len().min(100) as u32);
+    }
+    if let Some(at) = name.find(&keyword) {
+        let boundary = if is_boundary(&name, at) { 100 } else { 0 };
+        return Some(700 + boundary - at.min(100) as u32);
+    }
+    if let Some(score) = subsequence(&keyword, &name) {
+        return Some(300 + score);
+    }
+    body.to_lowercase().contains(&keyword).then_some(100)
+}
+
+/// Score `keyword`'s characters found in order in `name`, favoring runs and
+/// word starts and penalizing gaps; `None` unless all of them are found
+fn subsequence(keyword: &str, name: &str) -> Option<u32> {
+    let mut score: i64 = 0;
+    let mut previous: Option<usize> = None;
+    let mut rest = name.char_indices();
+    for wanted in keyword.chars() {
+        let (at, _) = rest.find(|&(_, c)| c == wanted)?;
+        match previous {
+            Some(prev) if at == prev + 1 => score += 15,
+            Some(prev) => score -= (at - prev - 1) as i64,
+            None => score -= at as i64,
+        }
+        if is_boundary(name, at) {
+            score += 10;
+        }
+        previous = Some(at);
+    }
+    Some(score.clamp(0, 299) as u32)
+}
+
+/// Whether a word starts at byte `at` of `name` (`deploy` in `ci:deploy`)
+fn is_boundary(name: &str, at: usize) -> bool {
+    name[..at]
+        .chars()
+        .next_back()
+        .is_none_or(|c| !c.is_alphanumeric())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_ranks_name_matches() {
+        let exact = score("deploy", "deploy", "").unwrap();
+        let prefix = score("deploy", "deploy:prod", "").unwrap();
+        let word = score("deploy", "ci:deploy", "").unwrap();
+        let inner = score("deploy", "redeploy", "").unwrap();
+        let fuzzy = score("dpl", "deploy", "").unwrap();
+        let body = score("deploy", "release", "./scripts/deploy.sh").unwrap();
+        assert!(exact > prefix && prefix > word && word > inner);
+        assert!(inner > fuzzy && fuzzy > body);
+
+        assert!(score("Deploy", "DEPLOY", "").is_some());
+        assert!(score("xyz", "deploy", "echo").is_none());
+        assert!(score("", "deploy", "").is_none());
+    }
+
+    #[test]
+    fn test_subsequence_prefers_runs_and_word_starts() {
+        let run = subsequence("dep", "deploy").unwrap();
+        let spread = subsequence("dep", "dev-prepare").unwrap();
+        assert!(run > spread);
+        assert!(subsequence("bt", "build:test").unwrap() > subsequence("bt", "rebut").unwrap());
+        assert!(subsequence("yd", "deploy").is_none());
+    }
+}
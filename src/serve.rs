@@ -0,0 +1,639 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! JSON-RPC 2.0 server for editor integrations (`run serve --stdio`).
+//!
+//! Messages use LSP-style framing (`Content-Length: N\r\n\r\n` + JSON body).
+//!
+//! Requests:
+//! - `initialize` → server name, version and supported methods
+//! - `detect` `{dir?, levels?, ignore?}` → working dir and detected runners
+//! - `tasks` `{dir?, levels?, ignore?}` → known tasks per runner (plus
+//!   wireit-managed scripts and their dependencies for Node.js runners)
+//! - `resolve` `{task, dir?, levels?, ignore?, path?}` → `{working_dir, runner, command}`
+//!   without running anything; when `path` is given it must match the
+//!   server's `PATH`, since tool checks depend on it
+//! - `run` `{task, args?, dir?, levels?, ignore?}` → `{run_id, runner, command}`
+//! - `cancel` `{run_id}` → `{cancelled}`
+//! - `shutdown` → `null`
+//!
+//! Notifications sent by the server while a task runs:
+//! - `run/output` `{run_id, stream, line}` with stream `stdout` or `stderr`
+//! - `run/exit` `{run_id, exit_code, cancelled, error?}`
+//!
+//! The `exit` notification stops the server and cancels running tasks. On
+//! end of input the server waits for running tasks before exiting.
+
+use crate::config::{parse_duration, Config};
+use crate::detectors::{detect_all, node, DetectedRunner, Ecosystem};
+use crate::runner::{
+    detect_with_config, execute_async, resolve_runner, CancellationToken, OutputLine,
+};
+use crate::RunError;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+/// Application error; `data.exit_code` carries the matching CLI exit code
+const RUN_ERROR: i64 = -32000;
+
+const METHODS: &[&str] = &[
+    "initialize",
+    "detect",
+    "tasks",
+    "resolve",
+    "run",
+    "cancel",
+    "shutdown",
+];
+
+/// Per-directory detection used to answer requests (`detect_all` by default)
+pub type DetectFn = Arc<dyn Fn(&Path, &[String]) -> Vec<DetectedRunner> + Send + Sync>;
+
+/// Serve JSON-RPC over stdin/stdout until `exit` or end of input
+pub fn serve_stdio() -> Result<(), RunError> {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(serve(tokio::io::stdin(), tokio::io::stdout()))?;
+    Ok(())
+}
+
+/// Serve JSON-RPC on arbitrary streams
+pub async fn serve<R, W>(reader: R, writer: W) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    serve_with(reader, writer, Arc::new(detect_all)).await
+}
+
+/// [`serve`] with a custom detection function (the daemon passes its cache)
+pub async fn serve_with<R, W>(reader: R, writer: W, detect: DetectFn) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (out, out_rx) = mpsc::unbounded_channel();
+    let writer_task = tokio::spawn(write_messages(writer, out_rx));
+
+    let server = Server {
+        out,
+        runs: Arc::new(Mutex::new(HashMap::new())),
+        next_run_id: AtomicU64::new(1),
+        detect,
+    };
+    let mut reader = BufReader::new(reader);
+    let mut running = JoinSet::new();
+
+    let mut exiting = false;
+    while let Some(body) = read_message(&mut reader).await? {
+        if server.handle(&body, &mut running) == Flow::Exit {
+            exiting = true;
+            break;
+        }
+    }
+
+    if exiting {
+        for token in server.runs.lock().unwrap().values() {
+            token.cancel();
+        }
+    }
+    while running.join_next().await.is_some() {}
+
+    drop(server);
+    writer_task.await.map_err(io::Error::other)?
+}
+
+#[derive(Debug, PartialEq)]
+enum Flow {
+    Continue,
+    Exit,
+}
+
+struct Server {
+    out: mpsc::UnboundedSender<Value>,
+    runs: Arc<Mutex<HashMap<u64, CancellationToken>>>,
+    next_run_id: AtomicU64,
+    detect: DetectFn,
+}
+
+impl Server {
+    fn handle(&self, body: &[u8], running: &mut JoinSet<()>) -> Flow {
+        let message: Value = match serde_json::from_slice(body) {
+            Ok(v) => v,
+            Err(e) => {
+                self.send_error(Value::Null, PARSE_ERROR, &e.to_string(), None);
+                return Flow::Continue;
+            }
+        };
+
+        let method = message.get("method").and_then(Value::as_str);
+        let id = message.get("id").cloned();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        let (method, id) = match (method, id) {
+            (Some("exit"), _) => return Flow::Exit,
+            (Some(method), Some(id)) => (method, id),
+            // Notifications other than `exit` are ignored
+            (Some(_), None) => return Flow::Continue,
+            (None, id) => {
+                self.send_error(
+                    id.unwrap_or(Value::Null),
+                    INVALID_REQUEST,
+                    "missing method",
+                    None,
+                );
+                return Flow::Continue;
+            }
+        };
+
+        let result = match method {
+            "initialize" => Ok(json!({
+                "name": "run",
+                "version": env!("CARGO_PKG_VERSION"),
+                "methods": METHODS,
+            })),
+            "detect" => self.detect(&params),
+            "tasks" => self.tasks(&params),
+            "resolve" => self.resolve(&params),
+            "run" => self.run(&params, running),
+            "cancel" => self.cancel(&params),
+            "shutdown" => Ok(Value::Null),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method '{}'", method),
+            )),
+        };
+
+        match result {
+            Ok(result) => self.send(json!({"jsonrpc": "2.0", "id": id, "result": result})),
+            Err(e) => self.send_error(id, e.code, &e.message, e.data),
+        }
+
+        Flow::Continue
+    }
+
+    fn detect(&self, params: &Value) -> Result<Value, RpcError> {
+        let (runners, working_dir) = self.find_runners(params)?;
+        Ok(json!({
+            "working_dir": working_dir,
+            "runners": runners,
+        }))
+    }
+
+    fn tasks(&self, params: &Value) -> Result<Value, RpcError> {
+        let (runners, working_dir) = self.find_runners(params)?;
+        let runners: Vec<Value> = runners
+            .iter()
+            .map(|r| {
+                let mut tasks: Vec<&String> = r
+                    .custom_commands
+                    .as_ref()
+                    .map(|c| c.keys().filter(|task| !r.is_private(task)).collect())
+                    .unwrap_or_default();
+                tasks.sort();
+                let mut entry = json!({"runner": r.name, "tasks": tasks});
+                if r.ecosystem == Ecosystem::NodeJs {
+                    let wireit = node::wireit_scripts(&working_dir);
+                    if !wireit.is_empty() {
+                        entry["wireit"] = json!(wireit);
+                    }
+                }
+                entry
+            })
+            .collect();
+
+        Ok(json!({"working_dir": working_dir, "runners": runners}))
+    }
+
+    fn resolve(&self, params: &Value) -> Result<Value, RpcError> {
+        if let Some(path) = params.get("path").and_then(Value::as_str) {
+            if std::env::var_os("PATH").is_none_or(|ours| ours != path) {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    "'path' differs from the server's PATH",
+                ));
+            }
+        }
+
+        let task = required_str(params, "task")?;
+        let args = string_list(params, "args")?;
+        let (runner, working_dir) = self.resolve_runner(params, task)?;
+
+        Ok(json!({
+            "working_dir": working_dir,
+            "command": runner.build_command(task, &args),
+            "runner": runner,
+        }))
+    }
+
+    fn run(&self, params: &Value, running: &mut JoinSet<()>) -> Result<Value, RpcError> {
+        let task = required_str(params, "task")?.to_string();
+        let args = string_list(params, "args")?;
+
+        let (runner, working_dir) = self.resolve_runner(params, &task)?;
+        let command = runner.build_command(&task, &args);
+        // The task's `timeout`, as the CLI applies it without --timeout
+        let timeout = Config::load_from(&working_dir)
+            .task_settings(&task, &working_dir)
+            .timeout
+            .map(|timeout| parse_duration(&timeout))
+            .transpose()
+            .map_err(|e| RunError::ConfigError(format!("timeout of task '{}': {}", task, e)))?;
+
+        let run_id = self.next_run_id.fetch_add(1, Ordering::SeqCst);
+        let cancel = CancellationToken::new();
+        self.runs.lock().unwrap().insert(run_id, cancel.clone());
+
+        let out = self.out.clone();
+        let runs = Arc::clone(&self.runs);
+        let spawned_runner = runner.clone();
+        running.spawn(async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let forward_out = out.clone();
+            let forwarder = tokio::spawn(async move {
+                while let Some(line) = rx.recv().await {
+                    let (stream, line) = match line {
+                        OutputLine::Stdout(l) => ("stdout", l),
+                        OutputLine::Stderr(l) => ("stderr", l),
+                    };
+                    let _ = forward_out.send(notification(
+                        "run/output",
+                        json!({"run_id": run_id, "stream": stream, "line": line}),
+                    ));
+                }
+            });
+
+            let result = execute_async(
+                &spawned_runner,
+                &task,
+                &args,
+                &working_dir,
+                timeout,
+                cancel.clone(),
+                Some(tx),
+            )
+            .await;
+            let _ = forwarder.await;
+            runs.lock().unwrap().remove(&run_id);
+
+            let params = match result {
+                Ok(r) => json!({
+                    "run_id": run_id,
+                    "exit_code": r.exit_status.code(),
+                    "cancelled": cancel.is_cancelled(),
+                }),
+                Err(e) => json!({
+                    "run_id": run_id,
+                    "exit_code": e.exit_code(),
+                    "cancelled": false,
+                    "error": e.to_string(),
+                }),
+            };
+            let _ = out.send(notification("run/exit", params));
+        });
+
+        Ok(json!({
+            "run_id": run_id,
+            "runner": runner,
+            "command": command,
+        }))
+    }
+
+    fn cancel(&self, params: &Value) -> Result<Value, RpcError> {
+        let run_id = params
+            .get("run_id")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "missing 'run_id'"))?;
+
+        let cancelled = match self.runs.lock().unwrap().get(&run_id) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        };
+        Ok(json!({"cancelled": cancelled}))
+    }
+
+    /// Detect runners for the `dir`, `levels` and `ignore` params
+    fn find_runners(&self, params: &Value) -> Result<(Vec<DetectedRunner>, PathBuf), RpcError> {
+        let dir = match params.get("dir").and_then(Value::as_str) {
+            Some(dir) => PathBuf::from(dir),
+            None => std::env::current_dir().map_err(|e| {
+                RpcError::new(RUN_ERROR, format!("Failed to get current directory: {}", e))
+            })?,
+        };
+        let config = Config::load_from(&dir);
+
+        let levels = match params.get("levels").and_then(Value::as_u64) {
+            Some(levels) => levels.min(10) as u8,
+            None => config.max_levels.unwrap_or(3),
+        };
+        let mut ignore = config.ignore_tools.clone();
+        ignore.extend(string_list(params, "ignore")?);
+
+        let detect = |dir: &Path, ignore: &[String]| (self.detect)(dir, ignore);
+        Ok(detect_with_config(
+            &dir, levels, &ignore, &config, false, detect,
+        )?)
+    }
+
+    /// Pick the runner for `task`, as the CLI would
+    fn resolve_runner(
+        &self,
+        params: &Value,
+        task: &str,
+    ) -> Result<(DetectedRunner, PathBuf), RpcError> {
+        let (runners, working_dir) = self.find_runners(params)?;
+        let runner = resolve_runner(&runners, &working_dir, task, false)?;
+        Ok((runner, working_dir))
+    }
+
+    fn send(&self, message: Value) {
+        let _ = self.out.send(message);
+    }
+
+    fn send_error(&self, id: Value, code: i64, message: &str, data: Option<Value>) {
+        let mut error = json!({"code": code, "message": message});
+        if let Some(data) = data {
+            error["data"] = data;
+        }
+        self.send(json!({"jsonrpc": "2.0", "id": id, "error": error}));
+    }
+}
+
+struct RpcError {
+    code: i64,
+    message: String,
+    data: Option<Value>,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+}
+
+impl From<RunError> for RpcError {
+    fn from(e: RunError) -> Self {
+        Self {
+            code: RUN_ERROR,
+            message: e.to_string(),
+            data: Some(json!({"exit_code": e.exit_code()})),
+        }
+    }
+}
+
+fn notification(method: &str, params: Value) -> Value {
+    json!({"jsonrpc": "2.0", "method": method, "params": params})
+}
+
+fn string_list(params: &Value, key: &str) -> Result<Vec<String>, RpcError> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|v| {
+                v.as_str().map(str::to_string).ok_or_else(|| {
+                    RpcError::new(INVALID_PARAMS, format!("'{}' must be strings", key))
+                })
+            })
+            .collect(),
+        Some(_) => Err(RpcError::new(
+            INVALID_PARAMS,
+            format!("'{}' must be an array", key),
+        )),
+    }
+}
+
+fn required_str<'a>(params: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+    params
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::new(INVALID_PARAMS, format!("missing '{}'", key)))
+}
+
+/// Read one framed message, returning `None` at end of input
+async fn read_message<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+
+        let header = line.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            // Tolerate blank lines between messages
+            continue;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse::<usize>().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "invalid Content-Length")
+                })?);
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or(0)];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(body))
+}
+
+async fn write_messages<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    mut messages: mpsc::UnboundedReceiver<Value>,
+) -> io::Result<()> {
+    while let Some(message) = messages.recv().await {
+        let body = message.to_string();
+        writer
+            .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+            .await?;
+        writer.write_all(body.as_bytes()).await?;
+        writer.flush().await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn frame(message: Value) -> Vec<u8> {
+        let body = message.to_string();
+        format!("Content-Length: {}\r\n\r\n{}", body.len(), body).into_bytes()
+    }
+
+    async fn exchange(requests: Vec<Value>) -> Vec<Value> {
+        let input: Vec<u8> = requests.into_iter().flat_map(frame).collect();
+        let (client, server_out) = tokio::io::duplex(64 * 1024);
+
+        serve(&input[..], server_out).await.unwrap();
+
+        let mut reader = BufReader::new(client);
+        let mut messages = Vec::new();
+        while let Some(body) = read_message(&mut reader).await.unwrap() {
+            messages.push(serde_json::from_slice(&body).unwrap());
+        }
+        messages
+    }
+
+    fn response(messages: &[Value], id: u64) -> &Value {
+        messages
+            .iter()
+            .find(|m| m["id"] == json!(id))
+            .expect("missing response")
+    }
+
+    #[tokio::test]
+    async fn test_read_message() {
+        let input = b"Content-Length: 2\r\nContent-Type: application/json\r\n\r\n{}".to_vec();
+        let mut reader = BufReader::new(&input[..]);
+        assert_eq!(
+            read_message(&mut reader).await.unwrap(),
+            Some(b"{}".to_vec())
+        );
+        assert_eq!(read_message(&mut reader).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_unknown_method() {
+        let messages = exchange(vec![
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}),
+            json!({"jsonrpc": "2.0", "id": 2, "method": "bogus"}),
+            json!({"jsonrpc": "2.0", "method": "exit"}),
+        ])
+        .await;
+
+        assert_eq!(response(&messages, 1)["result"]["name"], "run");
+        assert_eq!(response(&messages, 2)["error"]["code"], METHOD_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_detect() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"").unwrap();
+
+        let messages = exchange(vec![json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "detect",
+            "params": {"dir": dir.path(), "levels": 0},
+        })])
+        .await;
+
+        let result = &response(&messages, 1)["result"];
+        assert_eq!(result["runners"][0]["name"], "cargo");
+        assert_eq!(result["runners"][0]["ecosystem"], "Rust");
+    }
+
+    #[tokio::test]
+    async fn test_detect_not_found() {
+        let dir = tempdir().unwrap();
+        let messages = exchange(vec![json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "detect",
+            "params": {"dir": dir.path(), "levels": 0},
+        })])
+        .await;
+
+        let error = &response(&messages, 1)["error"];
+        assert_eq!(error["code"], RUN_ERROR);
+        assert_eq!(error["data"]["exit_code"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_resolve() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("Cargo.toml"), "[package]\nname = \"x\"").unwrap();
+
+        let messages = exchange(vec![
+            json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "resolve",
+                "params": {"dir": dir.path(), "task": "build", "levels": 0},
+            }),
+            json!({
+                "jsonrpc": "2.0",
+                "id": 2,
+                "method": "resolve",
+                "params": {"dir": dir.path(), "task": "build", "path": "/elsewhere"},
+            }),
+        ])
+        .await;
+
+        let result = &response(&messages, 1)["result"];
+        assert_eq!(result["runner"]["name"], "cargo");
+        assert_eq!(result["command"], json!(["cargo", "build"]));
+        let runner: DetectedRunner = serde_json::from_value(result["runner"].clone()).unwrap();
+        assert_eq!(runner.name, "cargo");
+
+        assert_eq!(response(&messages, 2)["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_streams_output() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join("run.toml"),
+            "[commands]\nhello = \"echo hello from run\"\n",
+        )
+        .unwrap();
+
+        let messages = exchange(vec![json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "run",
+            "params": {"dir": dir.path(), "task": "hello", "levels": 0},
+        })])
+        .await;
+
+        let result = &response(&messages, 1)["result"];
+        assert_eq!(result["run_id"], 1);
+        assert_eq!(result["command"], json!(["echo", "hello", "from", "run"]));
+
+        assert!(messages.iter().any(|m| m["method"] == "run/output"
+            && m["params"]["line"] == "hello from run"
+            && m["params"]["stream"] == "stdout"));
+        assert!(messages
+            .iter()
+            .any(|m| m["method"] == "run/exit" && m["params"]["exit_code"] == 0));
+    }
+}
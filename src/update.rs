@@ -12,17 +12,22 @@
 use crate::config::Config;
 use crate::http;
 use crate::output;
-use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::env;
 use std::fs;
+use std::io::Read;
+use std::time::{Duration, SystemTime};
 
 const GITHUB_REPO: &str = "verseles/run";
+
+/// Argument that re-invokes the binary as the detached background checker
+pub const INTERNAL_CHECK_FLAG: &str = "--internal-update-check";
 const UPDATE_TIMEOUT_SECS: u64 = 5;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateInfo {
-    pub updated_at: DateTime<Utc>,
+    #[serde(with = "rfc3339")]
+    pub updated_at: SystemTime,
     pub from_version: String,
     pub to_version: String,
     pub changelog_url: String,
@@ -53,20 +58,54 @@ pub fn current_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
+/// Parse an RFC 3339 UTC timestamp.
+///
+/// Also accepts the `+00:00` offset written by releases that used chrono.
+fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let s = s.trim();
+    match s.strip_suffix("+00:00") {
+        Some(utc) => humantime::parse_rfc3339(&format!("{}Z", utc)).ok(),
+        None => humantime::parse_rfc3339(s).ok(),
+    }
+}
+
+/// Serde adapter storing a `SystemTime` as an RFC 3339 string
+mod rfc3339 {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+    use std::time::SystemTime;
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_rfc3339(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        super::parse_timestamp(&s).ok_or_else(|| D::Error::custom("invalid RFC 3339 timestamp"))
+    }
+}
+
+/// Whether more than `interval` has passed since `time` (false if `time` is in the future)
+fn elapsed_more_than(time: SystemTime, interval: Duration) -> bool {
+    SystemTime::now()
+        .duration_since(time)
+        .is_ok_and(|elapsed| elapsed > interval)
+}
+
 /// Read the last update check timestamp from disk
-pub fn read_last_check_timestamp() -> Option<DateTime<Utc>> {
+pub fn read_last_check_timestamp() -> Option<SystemTime> {
     let path = Config::last_update_check_path()?;
     let content = fs::read_to_string(&path).ok()?;
-    DateTime::parse_from_rfc3339(content.trim())
-        .ok()
-        .map(|dt| dt.with_timezone(&Utc))
+    parse_timestamp(&content)
 }
 
 /// Write the current timestamp as the last update check time
 pub fn write_last_check_timestamp() {
     if let Some(path) = Config::last_update_check_path() {
         let _ = Config::ensure_config_dir();
-        let _ = fs::write(&path, Utc::now().to_rfc3339());
+        let _ = fs::write(
+            &path,
+            humantime::format_rfc3339(SystemTime::now()).to_string(),
+        );
     }
 }
 
@@ -81,9 +120,7 @@ pub fn should_check_update(interval_hours: u64) -> bool {
         None => return true, // Never checked before
     };
 
-    let now = Utc::now();
-    let interval = Duration::hours(interval_hours as i64);
-    now - last_check > interval
+    elapsed_more_than(last_check, Duration::from_secs(interval_hours * 3600))
 }
 
 /// Check for and display any pending update notifications
@@ -117,8 +154,7 @@ pub fn check_update_notification(quiet: bool) {
     };
 
     // Check if update was recent (within 24 hours)
-    let now = Utc::now();
-    if now - info.updated_at > Duration::hours(24) {
+    if elapsed_more_than(info.updated_at, Duration::from_secs(24 * 3600)) {
         let _ = fs::remove_file(&update_path);
         return;
     }
@@ -189,7 +225,7 @@ pub fn spawn_background_update(config: &Config) {
 
         // Create a child process that will handle the update
         let _ = Command::new(&current_exe)
-            .arg("--internal-update-check")
+            .arg(INTERNAL_CHECK_FLAG)
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
@@ -209,7 +245,7 @@ pub fn spawn_background_update(config: &Config) {
 
         const DETACHED_PROCESS: u32 = 0x00000008;
         let _ = Command::new(&current_exe)
-            .arg("--internal-update-check")
+            .arg(INTERNAL_CHECK_FLAG)
             .creation_flags(DETACHED_PROCESS)
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::null())
@@ -219,25 +255,20 @@ pub fn spawn_background_update(config: &Config) {
 }
 
 /// Perform the actual update check (called from background process)
-pub async fn perform_update_check() -> Result<(), Box<dyn std::error::Error>> {
+pub fn perform_update_check() -> Result<(), Box<dyn std::error::Error>> {
     // Write the timestamp immediately to prevent multiple concurrent checks
     write_last_check_timestamp();
 
-    let client = http::create_client_builder()
-        .timeout(std::time::Duration::from_secs(UPDATE_TIMEOUT_SECS))
-        .build()?;
+    let agent = http::agent(Duration::from_secs(UPDATE_TIMEOUT_SECS));
 
     // Fetch latest release info
-    let release: GitHubRelease = client
-        .get(format!(
+    let release: GitHubRelease = agent
+        .get(&format!(
             "https://api.github.com/repos/{}/releases/latest",
             GITHUB_REPO
         ))
-        .header("User-Agent", format!("run-cli/{}", current_version()))
-        .send()
-        .await?
-        .json()
-        .await?;
+        .call()?
+        .into_json()?;
 
     // Parse versions
     let remote_version = release.tag_name.trim_start_matches('v');
@@ -259,8 +290,12 @@ pub async fn perform_update_check() -> Result<(), Box<dyn std::error::Error>> {
         .ok_or("Asset not found for this platform")?;
 
     // Download the new binary
-    let response = client.get(&asset.browser_download_url).send().await?;
-    let bytes = response.bytes().await?;
+    let mut bytes = Vec::new();
+    agent
+        .get(&asset.browser_download_url)
+        .call()?
+        .into_reader()
+        .read_to_end(&mut bytes)?;
 
     // Get current executable path
     let current_exe = env::current_exe()?;
@@ -296,7 +331,7 @@ pub async fn perform_update_check() -> Result<(), Box<dyn std::error::Error>> {
 
     // Save update info
     let update_info = UpdateInfo {
-        updated_at: Utc::now(),
+        updated_at: SystemTime::now(),
         from_version: local_version.to_string(),
         to_version: remote_version.to_string(),
         changelog_url: release.html_url,
@@ -314,26 +349,21 @@ pub async fn perform_update_check() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 /// Perform a synchronous (blocking) update check
-pub async fn perform_blocking_update(quiet: bool) -> Result<bool, Box<dyn std::error::Error>> {
+pub fn perform_blocking_update(quiet: bool) -> Result<bool, Box<dyn std::error::Error>> {
     if !quiet {
         output::info("Checking for updates...");
     }
 
-    let client = http::create_client_builder()
-        .timeout(std::time::Duration::from_secs(30))
-        .build()?;
+    let agent = http::agent(Duration::from_secs(30));
 
     // Fetch latest release info
-    let release: GitHubRelease = client
-        .get(format!(
+    let release: GitHubRelease = agent
+        .get(&format!(
             "https://api.github.com/repos/{}/releases/latest",
             GITHUB_REPO
         ))
-        .header("User-Agent", format!("run-cli/{}", current_version()))
-        .send()
-        .await?
-        .json()
-        .await?;
+        .call()?
+        .into_json()?;
 
     // Parse versions
     let remote_version = release.tag_name.trim_start_matches('v');
@@ -365,8 +395,12 @@ pub async fn perform_blocking_update(quiet: bool) -> Result<bool, Box<dyn std::e
         .ok_or("Asset not found for this platform")?;
 
     // Download the new binary
-    let response = client.get(&asset.browser_download_url).send().await?;
-    let bytes = response.bytes().await?;
+    let mut bytes = Vec::new();
+    agent
+        .get(&asset.browser_download_url)
+        .call()?
+        .into_reader()
+        .read_to_end(&mut bytes)?;
 
     // Get current executable path
     let current_exe = env::current_exe()?;
@@ -431,6 +465,35 @@ mod tests {
         assert!(asset.is_some());
     }
 
+    #[test]
+    fn test_parse_timestamp() {
+        let z = parse_timestamp("2025-01-02T03:04:05Z").unwrap();
+        // Format written by releases that used chrono
+        let offset = parse_timestamp("2025-01-02T03:04:05.000000000+00:00\n").unwrap();
+        assert_eq!(z, offset);
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_update_info_roundtrip() {
+        let info = UpdateInfo {
+            updated_at: parse_timestamp("2025-01-02T03:04:05Z").unwrap(),
+            from_version: "0.5.0".to_string(),
+            to_version: "0.6.0".to_string(),
+            changelog_url: "https://example.com".to_string(),
+            changelog: None,
+        };
+        let json = serde_json::to_string(&info).unwrap();
+        assert!(json.contains(r#""updated_at":"2025-01-02T03:04:05Z""#));
+
+        let parsed: UpdateInfo = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.updated_at, info.updated_at);
+        assert!(elapsed_more_than(
+            parsed.updated_at,
+            Duration::from_secs(3600)
+        ));
+    }
+
     #[test]
     fn test_should_check_update_no_previous_check() {
         // Use a temporary directory as HOME to ensure no previous check exists
@@ -0,0 +1,246 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+
+//! Re-running a task when project files change (`--watch`).
+//!
+//! The project root is watched recursively; changes below the skipped
+//! directories (dependencies, build output, `.git`) never trigger a run.
+//! A burst of changes is debounced into one restart, and a task that is
+//! still running (a dev server) is stopped before it starts again.
+
+use crate::error::RunError;
+use crate::output;
+use crate::runner::PreparedCommand;
+use crate::tools;
+use crate::walk;
+use notify::{RecursiveMode, Watcher};
+use run_core::registry::matches_pattern;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+/// Quiet period that ends a burst of changes
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// How long a stopped task gets to exit before it is killed
+#[cfg(unix)]
+const STOP_GRACE: Duration = Duration::from_secs(3);
+
+/// Which changed paths trigger a run
+#[derive(Debug, Clone)]
+pub struct WatchFilter {
+    root: PathBuf,
+    /// Absolute patterns (`*` matches any text, `/` included) or path
+    /// prefixes; empty means everything under `root`
+    patterns: Vec<String>,
+    skip_dirs: Vec<String>,
+}
+
+impl WatchFilter {
+    /// Watch `root`, limited to `patterns` (relative ones are resolved from
+    /// `base`), ignoring anything below `skip_dirs`
+    pub fn new(root: &Path, base: &Path, patterns: &[String], skip_dirs: Vec<String>) -> Self {
+        // Events report resolved paths (e.g. /private/tmp on macOS)
+        let resolve = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let base = resolve(base);
+        WatchFilter {
+            root: resolve(root),
+            patterns: patterns
+                .iter()
+                .map(|p| base.join(p).to_string_lossy().into_owned())
+                .collect(),
+            skip_dirs,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Whether a change to `path` should re-run the task
+    pub fn matches(&self, path: &Path) -> bool {
+        if !path.starts_with(&self.root) || walk::is_skipped(path, &self.root, &self.skip_dirs) {
+            return false;
+        }
+        self.patterns.is_empty()
+            || self.patterns.iter().any(|pattern| {
+                if pattern.contains('*') {
+                    matches_pattern(pattern, &path.to_string_lossy())
+                } else {
+                    path.starts_with(pattern)
+                }
+            })
+    }
+}
+
+/// Run `command`, then run it again whenever a file `filter` accepts
+/// changes. Only returns if the watcher fails.
+pub fn watch(command: &PreparedCommand, filter: &WatchFilter, quiet: bool) -> Result<(), RunError> {
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        // Reads (our own task's included) aren't changes
+        if let Ok(event) = event {
+            if !event.kind.is_access() {
+                let _ = sender.send(event.paths);
+            }
+        }
+    })
+    .map_err(watch_error)?;
+    watcher
+        .watch(filter.root(), RecursiveMode::Recursive)
+        .map_err(watch_error)?;
+
+    if !quiet {
+        output::info(&format!(
+            "Watching {} for changes (Ctrl+C to stop)",
+            filter.root().display()
+        ));
+    }
+
+    let mut child = Some(spawn(command)?);
+    loop {
+        let paths = match events.recv_timeout(Duration::from_millis(100)) {
+            Ok(paths) => paths,
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                if let Some(status) = child.as_mut().and_then(|c| c.try_wait().ok().flatten()) {
+                    child = None;
+                    if !quiet {
+                        let outcome = match status.code() {
+                            Some(0) => "finished".to_string(),
+                            Some(code) => format!("exited with {}", code),
+                            None => "was terminated".to_string(),
+                        };
+                        output::info(&format!(
+                            "{} {}; waiting for changes",
+                            command.task, outcome
+                        ));
+                    }
+                }
+                continue;
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err(RunError::CommandFailed(
+                    "File watcher stopped unexpectedly".to_string(),
+                ));
+            }
+        };
+
+        let Some(changed) = paths.into_iter().find(|p| filter.matches(p)) else {
+            continue;
+        };
+        settle(&events, filter);
+
+        if let Some(running) = child.take() {
+            stop(running);
+        }
+        if !quiet {
+            let shown = changed.strip_prefix(filter.root()).unwrap_or(&changed);
+            output::info(&format!(
+                "{} changed; running {}",
+                shown.display(),
+                command.task
+            ));
+        }
+        child = Some(spawn(command)?);
+    }
+}
+
+/// Wait until no matching change arrived for [`DEBOUNCE`], so a burst
+/// (an editor writing several files, or one file twice) runs the task once
+fn settle(events: &mpsc::Receiver<Vec<PathBuf>>, filter: &WatchFilter) {
+    let mut until = Instant::now() + DEBOUNCE;
+    while let Some(left) = until.checked_duration_since(Instant::now()) {
+        match events.recv_timeout(left) {
+            Ok(paths) if paths.iter().any(|p| filter.matches(p)) => {
+                until = Instant::now() + DEBOUNCE;
+            }
+            Ok(_) => {}
+            Err(_) => break,
+        }
+    }
+}
+
+fn watch_error(e: notify::Error) -> RunError {
+    RunError::CommandFailed(format!("Failed to watch for changes: {}", e))
+}
+
+/// Start `command` sharing the terminal, like a regular run
+fn spawn(command: &PreparedCommand) -> Result<Child, RunError> {
+    let parts = tools::spawn_command(&command.command, &command.cwd);
+    Command::new(&parts[0])
+        .args(&parts[1..])
+        .current_dir(&command.cwd)
+        .envs(&command.env)
+        .spawn()
+        .map_err(|e| RunError::CommandFailed(format!("Failed to execute {}: {}", parts[0], e)))
+}
+
+/// Ask the task to exit (SIGTERM on Unix), killing it if it doesn't in time
+fn stop(mut child: Child) {
+    #[cfg(unix)]
+    {
+        // SAFETY: kill has no memory-safety preconditions; the pid is our
+        // child's, which hasn't been reaped yet
+        unsafe {
+            libc::kill(child.id() as i32, libc::SIGTERM);
+        }
+        let deadline = Instant::now() + STOP_GRACE;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = child.try_wait() {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(patterns: &[&str]) -> WatchFilter {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        WatchFilter::new(
+            Path::new("/project"),
+            Path::new("/project/app"),
+            &patterns,
+            walk::default_skip_dirs(),
+        )
+    }
+
+    #[test]
+    fn test_watch_filter_skips_dependencies() {
+        let all = filter(&[]);
+        assert!(all.matches(Path::new("/project/src/main.rs")));
+        assert!(!all.matches(Path::new("/project/target/debug/run")));
+        assert!(!all.matches(Path::new("/project/web/node_modules/x/index.js")));
+        assert!(!all.matches(Path::new("/project/.git/index")));
+        assert!(!all.matches(Path::new("/elsewhere/main.rs")));
+    }
+
+    #[test]
+    fn test_watch_filter_patterns() {
+        // Relative to the directory `run` was started from
+        let only = filter(&["src/*.rs", "Cargo.toml"]);
+        assert!(only.matches(Path::new("/project/app/src/lib.rs")));
+        assert!(only.matches(Path::new("/project/app/src/nested/mod.rs")));
+        assert!(only.matches(Path::new("/project/app/Cargo.toml")));
+        assert!(!only.matches(Path::new("/project/app/src/notes.md")));
+        assert!(!only.matches(Path::new("/project/Cargo.toml")));
+
+        let dir = filter(&["/project/docs"]);
+        assert!(dir.matches(Path::new("/project/docs/index.md")));
+        assert!(!dir.matches(Path::new("/project/docs-old/index.md")));
+    }
+}
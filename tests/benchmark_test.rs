@@ -0,0 +1,97 @@
+// Copyright (C) 2025 Verseles
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published
+// by the Free Software Foundation, version 3 of the License.
+
+//! Size and cold-start budgets for the `run` binary.
+//!
+//! Numbers are printed on every run (`cargo test --release --test
+//! benchmark_test -- --nocapture`). The size budget only applies to release
+//! builds, with more room when `cloudflare-dns` links a DNS resolver in; debug
+//! builds get a looser startup budget.
+//!
+//! | Build (release)                       | Binary  | Dry run |
+//! |---------------------------------------|---------|---------|
+//! | reqwest + hickory + chrono            | 4.5 MiB | ~3 ms   |
+//! | ureq + humantime, slim rhai           | 3.7 MiB | ~3 ms   |
+//! | + notify (`daemon`)                   | 3.9 MiB | ~3 ms   |
+//! | `scripting`, `updater` (current)      | 4.8 MiB | ~3 ms   |
+//! | default features (current)            | 4.9 MiB | ~3 ms   |
+//! | all features (current)                | 5.3 MiB | ~3 ms   |
+
+#![allow(deprecated)]
+
+use assert_cmd::Command;
+use std::fs::File;
+use std::time::{Duration, Instant};
+use tempfile::tempdir;
+
+const BINARY_SIZE_BUDGET: u64 = 5 * 1024 * 1024 + 256 * 1024;
+/// hickory-resolver adds about 0.4 MiB
+const CLOUDFLARE_DNS_SIZE_BUDGET: u64 = BINARY_SIZE_BUDGET + 512 * 1024;
+const STARTUP_BUDGET: Duration = Duration::from_millis(50);
+/// Unoptimized test builds get more headroom for the startup check
+const DEBUG_STARTUP_BUDGET: Duration = Duration::from_millis(250);
+
+/// The size budget of this build, `None` for debug builds
+fn size_budget() -> Option<u64> {
+    if cfg!(debug_assertions) {
+        None
+    } else if cfg!(feature = "cloudflare-dns") {
+        Some(CLOUDFLARE_DNS_SIZE_BUDGET)
+    } else {
+        Some(BINARY_SIZE_BUDGET)
+    }
+}
+
+#[test]
+fn test_binary_size_budget() {
+    let size = std::fs::metadata(env!("CARGO_BIN_EXE_run")).unwrap().len();
+    eprintln!("binary size: {:.2} MiB", size as f64 / (1024.0 * 1024.0));
+
+    if let Some(budget) = size_budget() {
+        assert!(
+            size < budget,
+            "binary is {} bytes, budget is {}",
+            size,
+            budget
+        );
+    }
+}
+
+/// Best of several dry runs in a small project, to ignore scheduling noise
+#[test]
+fn test_startup_latency_budget() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("Cargo.toml")).unwrap();
+    File::create(dir.path().join("Cargo.lock")).unwrap();
+
+    let best = (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            Command::cargo_bin("run")
+                .unwrap()
+                .current_dir(dir.path())
+                .env("RUN_NO_UPDATE", "1")
+                .args(["--dry-run", "build"])
+                .assert()
+                .success();
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    eprintln!("dry run startup: {:?}", best);
+
+    let budget = if cfg!(debug_assertions) {
+        DEBUG_STARTUP_BUDGET
+    } else {
+        STARTUP_BUDGET
+    };
+    assert!(
+        best < budget,
+        "startup took {:?}, budget is {:?}",
+        best,
+        budget
+    );
+}
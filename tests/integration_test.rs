@@ -488,6 +488,28 @@ fn test_dry_run_maven() {
         .stdout(predicate::str::contains("mvn compile"));
 }
 
+#[test]
+#[cfg(unix)]
+fn test_gradle_wrapper_without_gradle() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("build.gradle")).unwrap();
+    let gradlew = dir.path().join("gradlew");
+    fs::write(&gradlew, "#!/bin/sh\necho \"wrapper $@\"\n").unwrap();
+    fs::set_permissions(&gradlew, fs::Permissions::from_mode(0o755)).unwrap();
+
+    // The wrapper runs even though gradle itself is not on PATH
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", dir.path().join("empty"))
+        .args(["build", "--quiet"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("wrapper build"));
+}
+
 // ============================================================================
 // .NET ecosystem detection
 // ============================================================================
@@ -570,423 +592,3383 @@ fn test_dry_run_zig() {
 }
 
 // ============================================================================
-// Make detection
+// Nix flakes detection
 // ============================================================================
 
 #[test]
-fn test_dry_run_makefile() {
+fn test_dry_run_nix_flake() {
     let dir = tempdir().unwrap();
-    let mut file = File::create(dir.path().join("Makefile")).unwrap();
-    writeln!(file, "build:\n\t@echo building").unwrap();
+    File::create(dir.path().join("flake.nix")).unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["build", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["check", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("make build"));
-}
-
-#[test]
-fn test_dry_run_makefile_lowercase() {
-    let dir = tempdir().unwrap();
-    let mut file = File::create(dir.path().join("makefile")).unwrap();
-    writeln!(file, "build:\n\t@echo building").unwrap();
+        .stdout(predicate::str::contains("nix flake check"));
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["build", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["docs", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("make build"));
+        .stdout(predicate::str::contains("nix run .#docs"));
 }
 
 // ============================================================================
-// CLI flag tests
+// Bazel detection
 // ============================================================================
 
 #[test]
-fn test_ignore_flag() {
+fn test_dry_run_bazel() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    File::create(dir.path().join("MODULE.bazel")).unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--ignore=npm", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["build", "--dry-run", "--", "//..."])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No runner found"));
-}
-
-#[test]
-fn test_ignore_comma_separated() {
-    let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
-    File::create(dir.path().join("Cargo.toml")).unwrap();
+        .success()
+        .stdout(predicate::str::contains("bazel build //..."));
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--ignore=npm,cargo", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["//app:server", "--dry-run"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No runner found"));
+        .success()
+        .stdout(predicate::str::contains("bazel run //app:server"));
 }
 
+// ============================================================================
+// CMake detection
+// ============================================================================
+
 #[test]
-fn test_ignore_multiple_flags() {
+fn test_dry_run_cmake_presets() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(dir.path().join("CMakeLists.txt"), "project(demo)\n").unwrap();
+    fs::write(
+        dir.path().join("CMakePresets.json"),
+        r#"{"version": 3, "configurePresets": [{"name": "default"}], "buildPresets": [{"name": "default", "configurePreset": "default"}]}"#,
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--ignore", "npm", "--ignore", "yarn", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["build", "--dry-run"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No runner found"));
-}
-
-#[test]
-fn test_ignore_case_insensitive() {
-    let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+        .success()
+        .stdout(predicate::str::contains("cmake --build --preset default"));
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--ignore=NPM", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["configure", "--dry-run"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No runner found"));
+        .success()
+        .stdout(predicate::str::contains("cmake --preset default"));
 }
 
 // ============================================================================
-// Recursive search tests
+// Scala (Mill, sbt) detection
 // ============================================================================
 
 #[test]
-fn test_recursive_search() {
+fn test_dry_run_mill() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
-
-    let subdir = dir.path().join("src").join("components");
-    fs::create_dir_all(&subdir).unwrap();
+    File::create(dir.path().join("build.mill")).unwrap();
 
     run_cmd()
-        .current_dir(&subdir)
-        .args(["test", "--dry-run"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["app.test", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("npm test"));
+        .stdout(predicate::str::contains("mill app.test"));
 }
 
 #[test]
-fn test_recursive_search_3_levels() {
+fn test_dry_run_sbt() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
-
-    let subdir = dir.path().join("a").join("b").join("c");
-    fs::create_dir_all(&subdir).unwrap();
+    File::create(dir.path().join("build.sbt")).unwrap();
 
     run_cmd()
-        .current_dir(&subdir)
-        .args(["test", "--dry-run"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["testOnly", "app.Spec", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("npm test"));
+        .stdout(predicate::str::contains("sbt testOnly app.Spec"));
 }
 
+// ============================================================================
+// Clojure (Leiningen) detection
+// ============================================================================
+
 #[test]
-fn test_levels_limit_zero() {
+fn test_dry_run_lein_alias() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
-
-    let subdir = dir.path().join("src");
-    fs::create_dir_all(&subdir).unwrap();
+    fs::write(
+        dir.path().join("project.clj"),
+        r#"(defproject demo "0.1.0" :aliases {"lint" ["run" "-m" "lint.main"]})"#,
+    )
+    .unwrap();
 
     run_cmd()
-        .current_dir(&subdir)
-        .args(["test", "--levels=0"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["lint", "--dry-run"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No runner found"));
+        .success()
+        .stdout(predicate::str::contains("lein lint"));
 }
 
+// ============================================================================
+// Docker detection
+// ============================================================================
+
 #[test]
-fn test_levels_limit_exceeded() {
+fn test_dry_run_docker_bake() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(
+        dir.path().join("docker-bake.hcl"),
+        "target \"app\" {\n  context = \".\"\n}\n",
+    )
+    .unwrap();
 
-    let deep_subdir = dir.path().join("a").join("b").join("c").join("d");
-    fs::create_dir_all(&deep_subdir).unwrap();
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["app", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("docker buildx bake app"));
 
+    // Unknown targets are rejected before docker runs
     run_cmd()
-        .current_dir(&deep_subdir)
-        .args(["test", "--levels=3"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["missing", "--dry-run"])
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No runner found"));
+        .failure();
 }
 
 #[test]
-fn test_levels_limit_sufficient() {
+fn test_dry_run_dockerfile_build() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(dir.path().join("Dockerfile"), "FROM alpine\n").unwrap();
+    let build = || {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .args(["build", "--dry-run"]);
+        cmd
+    };
 
-    let deep_subdir = dir.path().join("a").join("b").join("c").join("d");
-    fs::create_dir_all(&deep_subdir).unwrap();
+    build()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("docker build ."));
 
-    run_cmd()
-        .current_dir(&deep_subdir)
-        .args(["test", "--levels=5", "--dry-run"])
+    // A runner that declares `build` comes before the Dockerfile
+    fs::write(dir.path().join("pixi.toml"), "[tasks]\nbuild = \"make\"\n").unwrap();
+    build()
         .assert()
         .success()
-        .stdout(predicate::str::contains("npm test"));
+        .stdout(predicate::str::contains("pixi run build"));
 }
 
 // ============================================================================
-// Extra arguments tests
+// Earthly detection
 // ============================================================================
 
 #[test]
-fn test_extra_args() {
+fn test_dry_run_earthly() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(
+        dir.path().join("Earthfile"),
+        "VERSION 0.8\n\nbuild:\n    FROM alpine\n",
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--dry-run", "--", "--coverage", "--verbose"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["build", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("npm test --coverage --verbose"));
+        .stdout(predicate::str::contains("earthly +build"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["deploy", "--dry-run"])
+        .assert()
+        .failure();
 }
 
+// ============================================================================
+// GitHub Actions (act) detection
+// ============================================================================
+
 #[test]
-fn test_extra_args_with_equals() {
+#[cfg(unix)]
+fn test_dry_run_act_job() {
+    use std::os::unix::fs::PermissionsExt;
+
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    let project = dir.path().join("project");
+    let workflows = project.join(".github").join("workflows");
+    fs::create_dir_all(&workflows).unwrap();
+    fs::write(
+        workflows.join("ci.yml"),
+        "on: push\njobs:\n  lint:\n    runs-on: ubuntu-latest\n",
+    )
+    .unwrap();
 
+    // Without act the workflows are not a runner
     run_cmd()
-        .current_dir(dir.path())
-        .args(["test", "--dry-run", "--", "--reporter=json"])
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", dir.path().join("empty"))
+        .args(["lint", "--dry-run"])
+        .assert()
+        .failure();
+
+    let bin = dir.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let act = bin.join("act");
+    fs::write(&act, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&act, fs::Permissions::from_mode(0o755)).unwrap();
+
+    run_cmd()
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", &bin)
+        .args(["lint", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("npm test --reporter=json"));
+        .stdout(predicate::str::contains("act -j lint"));
 }
 
 // ============================================================================
-// Verbose and quiet mode tests
+// Devbox detection
 // ============================================================================
 
 #[test]
-fn test_verbose_mode() {
+fn test_dry_run_devbox_script() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(
+        dir.path().join("devbox.json"),
+        r#"{"shell": {"scripts": {"lint": "golangci-lint run"}}}"#,
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--dry-run", "--verbose"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["lint", "--dry-run"])
         .assert()
         .success()
-        .stderr(predicate::str::contains("Detected"));
+        .stdout(predicate::str::contains("devbox run lint"));
 }
 
+// ============================================================================
+// Pixi detection
+// ============================================================================
+
 #[test]
-fn test_quiet_mode_suppresses_executing() {
+fn test_dry_run_pixi_task() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(
+        dir.path().join("pixi.toml"),
+        "[workspace]\nname = \"demo\"\n\n[tasks]\nlint = \"ruff check\"\n",
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--dry-run", "--quiet"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["lint", "--dry-run"])
         .assert()
         .success()
-        .stderr(predicate::str::is_empty());
+        .stdout(predicate::str::contains("pixi run lint"));
 }
 
 // ============================================================================
-// Shell completions tests
+// Task listing
 // ============================================================================
 
 #[test]
-fn test_completions_bash() {
+fn test_list_tasks() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts": {"test": "jest", "build": "tsc"}}"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("Makefile"), "lint:\n\techo lint\n").unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\ndeploy = \"echo\"\n",
+    )
+    .unwrap();
+
+    // The runner `run` picks: run.toml's commands come first
     run_cmd()
-        .args(["completions", "bash"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("list")
         .assert()
         .success()
-        .stdout(predicate::str::contains("_run"));
-}
+        .stdout("custom (run.toml)\n  deploy\n");
 
-#[test]
-fn test_completions_zsh() {
+    fs::remove_file(dir.path().join("run.toml")).unwrap();
     run_cmd()
-        .args(["completions", "zsh"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("list")
         .assert()
         .success()
-        .stdout(predicate::str::contains("#compdef run"));
-}
+        .stdout("npm (package.json)\n  build\n  test\n");
 
-#[test]
-fn test_completions_fish() {
     run_cmd()
-        .args(["completions", "fish"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .arg("--list")
         .assert()
         .success()
-        .stdout(predicate::str::contains("complete"));
+        .stdout(predicate::str::contains("  build"));
 }
 
 #[test]
-fn test_completions_powershell() {
+fn test_tasks_all_lists_every_runner() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("Makefile"), "lint:\n\techo lint\n").unwrap();
+    fs::write(dir.path().join("justfile"), "fmt:\n    echo fmt\n").unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts": {"dev": "vite"}}"#,
+    )
+    .unwrap();
+    let web = dir.path().join("web");
+    fs::create_dir(&web).unwrap();
+    fs::write(
+        web.join("package.json"),
+        r#"{"scripts": {"build": "vite build"}}"#,
+    )
+    .unwrap();
+
     run_cmd()
-        .args(["completions", "powershell"])
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["tasks", "--all"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Register-ArgumentCompleter"));
-}
+        .stdout("npm (package.json)\n  dev\njust (justfile)\n  fmt\nmake (Makefile)\n  lint\n");
 
-// ============================================================================
-// Cross-ecosystem priority tests
-// ============================================================================
+    // Only the directory `run` uses, not its parents
+    run_cmd()
+        .current_dir(&web)
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["tasks", "--all"])
+        .assert()
+        .success()
+        .stdout("npm (package.json)\n  build\n");
+}
 
+// Paths in messages use the platform's separator
+#[cfg(unix)]
 #[test]
-fn test_priority_nodejs_over_make() {
+fn test_down_finds_project_below() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("package.json")).unwrap();
-    File::create(dir.path().join("Makefile")).unwrap();
+    let web = dir.path().join("apps/web");
+    let api = dir.path().join("apps/api");
+    fs::create_dir_all(&web).unwrap();
+    fs::create_dir_all(&api).unwrap();
+    fs::write(web.join("package.json"), r#"{"scripts": {"dev": "vite"}}"#).unwrap();
+    fs::write(api.join("Makefile"), "build:\n\tcc main.c\n").unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--levels=0", "dev", "--dry-run"])
+        .assert()
+        .code(2);
+
+    // Only apps/web knows `dev`
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["--levels=0", "--down", "dev", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("npm test"));
+        .stdout(predicate::str::contains("npm run dev"))
+        .stderr(predicate::str::contains("Using the project in apps/web"));
+
+    let admin = dir.path().join("apps/admin");
+    fs::create_dir_all(&admin).unwrap();
+    fs::write(
+        admin.join("package.json"),
+        r#"{"scripts": {"dev": "next"}}"#,
+    )
+    .unwrap();
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--levels=0", "--down=2", "dev", "--dry-run"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("apps/admin (npm), apps/web (npm)"));
+
+    // Too shallow to reach them
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--levels=0", "--down=1", "dev", "--dry-run"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("No runner found"));
 }
 
 #[test]
-fn test_priority_cargo_over_make() {
+fn test_list_without_listable_tasks() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("Cargo.toml")).unwrap();
-    File::create(dir.path().join("Makefile")).unwrap();
+    fs::write(dir.path().join("go.mod"), "module example.com/demo\n").unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["build", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .arg("list")
         .assert()
         .success()
-        .stdout(predicate::str::contains("cargo build"));
+        .stdout("")
+        .stderr(predicate::str::contains("No tasks to list"));
 }
 
 #[test]
-fn test_priority_python_over_make() {
+fn test_which_traces_resolution() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("requirements.txt")).unwrap();
-    File::create(dir.path().join("Makefile")).unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts": {"test": "jest"}}"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("Makefile"), "lint:\n\techo lint\n").unwrap();
+    let sub = dir.path().join("sub");
+    fs::create_dir(&sub).unwrap();
 
     run_cmd()
-        .current_dir(dir.path())
-        .args(["pytest", "--dry-run"])
+        .current_dir(&sub)
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["which", "lint", "--", "-j2"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("python -m pytest"));
+        .stdout(predicate::str::contains("(level 1)"))
+        .stdout(predicate::str::contains("npm (package.json)"))
+        .stdout(predicate::str::contains("not supported"))
+        .stdout(predicate::str::contains("Selected: make"))
+        .stdout(predicate::str::contains("Command:  make lint -j2"));
+
+    run_cmd()
+        .current_dir(&sub)
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["which", "deploy"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Error:"));
 }
 
 #[test]
-fn test_priority_mix_over_make() {
+fn test_explain_shows_definition() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("mix.exs")).unwrap();
-    File::create(dir.path().join("Makefile")).unwrap();
+    fs::write(
+        dir.path().join("Makefile"),
+        "CC := cc\n\nbuild: deps\n\t$(CC) main.c\n\t./check\n",
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["explain", "build", "--", "-j2"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("mix test"));
+        .stdout(predicate::str::contains("Makefile:3\n"))
+        .stdout(predicate::str::contains(
+            "Runs:     $(CC) main.c\n          ./check\n",
+        ))
+        .stdout(predicate::str::contains("Command:  make build -j2"));
 }
 
-// ============================================================================
-// Config file tests
-// ============================================================================
-
 #[test]
-fn test_local_config_ignore() {
+fn test_exec_passes_arguments_verbatim() {
     let dir = tempdir().unwrap();
     File::create(dir.path().join("package.json")).unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\ninstall = \"echo custom\"\n",
+    )
+    .unwrap();
 
-    let mut config = File::create(dir.path().join("run.toml")).unwrap();
-    writeln!(config, "ignore_tools = [\"npm\"]").unwrap();
+    // Custom commands are passed over, and nothing is mapped to `npm run`
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args([
+            "--dry-run",
+            "exec",
+            "--prefix",
+            "apps/web",
+            "install",
+            "lodash",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "npm --prefix apps/web install lodash",
+        ));
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["test", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .arg("exec")
         .assert()
-        .failure()
-        .stderr(predicate::str::contains("No runner found"));
+        .failure();
 }
 
-// ============================================================================
-// Command validation / fallback tests
-// ============================================================================
-
 #[test]
-fn test_fallback_cargo_to_make() {
+fn test_search_ranks_matches() {
     let dir = tempdir().unwrap();
-    File::create(dir.path().join("Cargo.toml")).unwrap();
-    let mut makefile = File::create(dir.path().join("Makefile")).unwrap();
-    writeln!(makefile, "precommit:\n\t@echo precommit").unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts": {"release": "npm run ship:prod", "ship:prod": "node deploy.js", "build": "tsc"}}"#,
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["precommit", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["search", "ship"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("make precommit"));
-}
+        .stdout(
+            predicate::str::is_match(
+                r"^ship:prod  \(npm, .*package.json:1\)\n    node deploy.js\nrelease ",
+            )
+            .unwrap(),
+        )
+        .stdout(predicate::str::contains("build").not());
 
-#[test]
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["search", "zzz"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No task matches 'zzz'"));
+}
+
+#[test]
+fn test_init_writes_starter_config() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("Makefile"), "build:\n\techo build\n").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .arg("init")
+        .assert()
+        .success();
+    let starter = fs::read_to_string(dir.path().join("run.toml")).unwrap();
+    assert!(starter.contains("# From make (Makefile)\n# build = \"make build\"\n"));
+
+    // Never clobbered without --force
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .arg("init")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--force"));
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["init", "--force"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_doctor_reports_invalid_config() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("Makefile"), "build:\n\techo build\n").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("doctor")
+        .assert()
+        .stdout(predicate::str::contains("Detected runners in"))
+        .stdout(predicate::str::contains("make (Makefile)"));
+
+    fs::write(dir.path().join("run.toml"), "max_levels = [\n").unwrap();
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("doctor")
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("run.toml is invalid"))
+        .stdout(predicate::str::contains("problem(s)"));
+}
+
+#[test]
+fn test_several_tasks_in_order() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Makefile"),
+        "build:\n\t@echo built\ntest:\n\t@exit 3\nlint:\n\t@echo linted\n",
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["build,test,lint"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("built"))
+        .stdout(predicate::str::contains("linted").not())
+        .stderr(predicate::str::contains("- lint"))
+        .stderr(predicate::str::contains("skipped"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["build,test,lint", "--keep-going"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("linted"))
+        .stderr(predicate::str::contains("✗ test"));
+
+    // Without a comma, the words after the task are its arguments, even
+    // when they name tasks too
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["build", "lint", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make build lint"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_parallel_tasks() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+server = "sh -c 'sleep 0.2; echo listening'"
+client = "echo bundled"
+broken = "sh -c 'exit 5'"
+slow = "sleep 30"
+"#,
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["--parallel", "server,client"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("server | listening"))
+        .stdout(predicate::str::contains("client | bundled"));
+
+    // A failure stops the others and sets the exit code
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["-p", "slow,broken"])
+        .timeout(std::time::Duration::from_secs(20))
+        .assert()
+        .code(5)
+        .stderr(predicate::str::contains("stopped"));
+}
+
+#[test]
+fn test_cwd_runs_from_other_directory() {
+    let dir = tempdir().unwrap();
+    let api = dir.path().join("api");
+    fs::create_dir(&api).unwrap();
+    fs::write(api.join("Makefile"), "build:\n\techo build\n").unwrap();
+    fs::write(api.join("run.toml"), "[commands]\nseed = \"echo seeded\"\n").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--cwd", "api", "build", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make build"));
+
+    // The target directory's run.toml is the local config
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["-C", "api", "seed"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("seeded"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--cwd", "missing", "build"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot change to missing"));
+}
+
+// ============================================================================
+// Mask detection
+// ============================================================================
+
+#[test]
+fn test_dry_run_mask() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("maskfile.md"),
+        "# Tasks\n\n## lint\n\n```sh\ncargo clippy\n```\n",
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["lint", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mask lint"));
+}
+
+// ============================================================================
+// Xcode detection
+// ============================================================================
+
+#[test]
+fn test_dry_run_xcodebuild_scheme() {
+    let dir = tempdir().unwrap();
+    let schemes = dir.path().join("Demo.xcodeproj/xcshareddata/xcschemes");
+    fs::create_dir_all(&schemes).unwrap();
+    fs::write(schemes.join("Demo.xcscheme"), "").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["test", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "xcodebuild -project Demo.xcodeproj -scheme Demo test",
+        ));
+}
+
+// ============================================================================
+// Data pipeline detection
+// ============================================================================
+
+#[test]
+fn test_dry_run_snakemake_and_dbt() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Snakefile"),
+        "rule all:\n    input: \"a\"\n",
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["all", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("snakemake all"));
+
+    let dbt = tempdir().unwrap();
+    fs::write(dbt.path().join("dbt_project.yml"), "name: demo\n").unwrap();
+
+    run_cmd()
+        .current_dir(dbt.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["seed", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("dbt seed"));
+}
+
+// ============================================================================
+// Make detection
+// ============================================================================
+
+#[test]
+fn test_dry_run_makefile() {
+    let dir = tempdir().unwrap();
+    let mut file = File::create(dir.path().join("Makefile")).unwrap();
+    writeln!(file, "build:\n\t@echo building").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["build", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make build"));
+}
+
+#[test]
+fn test_dry_run_makefile_lowercase() {
+    let dir = tempdir().unwrap();
+    let mut file = File::create(dir.path().join("makefile")).unwrap();
+    writeln!(file, "build:\n\t@echo building").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["build", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make build"));
+}
+
+// ============================================================================
+// CLI flag tests
+// ============================================================================
+
+#[test]
+fn test_ignore_flag() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--ignore=npm", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No runner found"));
+}
+
+#[test]
+fn test_ignore_comma_separated() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+    File::create(dir.path().join("Cargo.toml")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--ignore=npm,cargo", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No runner found"));
+}
+
+#[test]
+fn test_ignore_multiple_flags() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--ignore", "npm", "--ignore", "yarn", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No runner found"));
+}
+
+#[test]
+fn test_ignore_case_insensitive() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--ignore=NPM", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No runner found"));
+}
+
+// ============================================================================
+// Recursive search tests
+// ============================================================================
+
+#[test]
+fn test_recursive_search() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    let subdir = dir.path().join("src").join("components");
+    fs::create_dir_all(&subdir).unwrap();
+
+    run_cmd()
+        .current_dir(&subdir)
+        .args(["test", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm test"));
+}
+
+#[test]
+fn test_recursive_search_3_levels() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    let subdir = dir.path().join("a").join("b").join("c");
+    fs::create_dir_all(&subdir).unwrap();
+
+    run_cmd()
+        .current_dir(&subdir)
+        .args(["test", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm test"));
+}
+
+#[test]
+fn test_levels_limit_zero() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    let subdir = dir.path().join("src");
+    fs::create_dir_all(&subdir).unwrap();
+
+    run_cmd()
+        .current_dir(&subdir)
+        .args(["test", "--levels=0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No runner found"));
+}
+
+#[test]
+fn test_levels_limit_exceeded() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    let deep_subdir = dir.path().join("a").join("b").join("c").join("d");
+    fs::create_dir_all(&deep_subdir).unwrap();
+
+    run_cmd()
+        .current_dir(&deep_subdir)
+        .args(["test", "--levels=3"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No runner found"));
+}
+
+#[test]
+fn test_levels_limit_sufficient() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    let deep_subdir = dir.path().join("a").join("b").join("c").join("d");
+    fs::create_dir_all(&deep_subdir).unwrap();
+
+    run_cmd()
+        .current_dir(&deep_subdir)
+        .args(["test", "--levels=5", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm test"));
+}
+
+// ============================================================================
+// Extra arguments tests
+// ============================================================================
+
+#[test]
+fn test_extra_args() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run", "--", "--coverage", "--verbose"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm test --coverage --verbose"));
+}
+
+#[test]
+fn test_extra_args_with_equals() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run", "--", "--reporter=json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm test --reporter=json"));
+}
+
+// ============================================================================
+// Verbose and quiet mode tests
+// ============================================================================
+
+#[test]
+fn test_verbose_mode() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run", "--verbose"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Detected"));
+}
+
+#[test]
+fn test_quiet_mode_suppresses_executing() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run", "--quiet"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+}
+
+// ============================================================================
+// Shell completions tests
+// ============================================================================
+
+#[test]
+fn test_completions_bash() {
+    run_cmd()
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("_run"));
+}
+
+#[test]
+fn test_completions_zsh() {
+    run_cmd()
+        .args(["completions", "zsh"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("#compdef run"));
+}
+
+#[test]
+fn test_completions_fish() {
+    run_cmd()
+        .args(["completions", "fish"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("complete"));
+}
+
+#[test]
+fn test_completions_powershell() {
+    run_cmd()
+        .args(["completions", "powershell"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Register-ArgumentCompleter"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_completions_install() {
+    let home = tempdir().unwrap();
+
+    run_cmd()
+        .env("HOME", home.path())
+        .env("SHELL", "/usr/bin/zsh")
+        .env_remove("XDG_DATA_HOME")
+        .env_remove("XDG_CONFIG_HOME")
+        .args(["completions", "--install"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("fpath=("));
+    let script = fs::read_to_string(home.path().join(".zsh/completions/_run")).unwrap();
+    assert!(script.contains("#compdef run"));
+
+    // An explicit shell wins over $SHELL
+    run_cmd()
+        .env("HOME", home.path())
+        .env("SHELL", "/usr/bin/zsh")
+        .env("XDG_CONFIG_HOME", home.path().join("config"))
+        .args(["completions", "fish", "--install"])
+        .assert()
+        .success();
+    assert!(home
+        .path()
+        .join("config/fish/completions/run.fish")
+        .is_file());
+}
+
+// ============================================================================
+// Cross-ecosystem priority tests
+// ============================================================================
+
+#[test]
+fn test_priority_nodejs_over_make() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+    File::create(dir.path().join("Makefile")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm test"));
+}
+
+#[test]
+fn test_priority_cargo_over_make() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("Cargo.toml")).unwrap();
+    File::create(dir.path().join("Makefile")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["build", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cargo build"));
+}
+
+#[test]
+fn test_priority_python_over_make() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("requirements.txt")).unwrap();
+    File::create(dir.path().join("Makefile")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["pytest", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("python -m pytest"));
+}
+
+#[test]
+fn test_priority_mix_over_make() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("mix.exs")).unwrap();
+    File::create(dir.path().join("Makefile")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("mix test"));
+}
+
+// ============================================================================
+// Config file tests
+// ============================================================================
+
+#[test]
+fn test_local_config_ignore() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    let mut config = File::create(dir.path().join("run.toml")).unwrap();
+    writeln!(config, "ignore_tools = [\"npm\"]").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No runner found"));
+}
+
+#[cfg(all(target_os = "linux", feature = "updater"))]
+#[test]
+fn test_update_notification_shown_after_command() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nhello = \"echo hello\"\n",
+    )
+    .unwrap();
+
+    let config_home = tempdir().unwrap();
+    let write_info = || {
+        fs::create_dir_all(config_home.path().join("run")).unwrap();
+        // A timestamp in the future always counts as recent
+        fs::write(
+            config_home.path().join("run").join("update.json"),
+            r#"{"updated_at":"2099-01-01T00:00:00Z","from_version":"0.5.0","to_version":"0.6.0","changelog_url":"https://example.com","changelog":null}"#,
+        )
+        .unwrap();
+    };
+
+    // Skipped in CI, and the pending notice is kept for later
+    write_info();
+    run_cmd()
+        .current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("CI", "true")
+        .arg("hello")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("was updated").not());
+    assert!(config_home.path().join("run/update.json").exists());
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("XDG_CONFIG_HOME", config_home.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("CI", "false")
+        .env("NO_COLOR", "1")
+        .arg("hello")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello"))
+        .stderr(predicate::str::contains("was updated: 0.5.0 → 0.6.0"));
+    assert!(!config_home.path().join("run/update.json").exists());
+}
+
+// ============================================================================
+// Command validation / fallback tests
+// ============================================================================
+
+#[test]
+fn test_fallback_cargo_to_make() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("Cargo.toml")).unwrap();
+    let mut makefile = File::create(dir.path().join("Makefile")).unwrap();
+    writeln!(makefile, "precommit:\n\t@echo precommit").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["precommit", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make precommit"));
+}
+
+#[test]
 fn test_fallback_npm_to_make() {
     let dir = tempdir().unwrap();
-    let mut pkg = File::create(dir.path().join("package.json")).unwrap();
-    writeln!(pkg, r#"{{"name": "test"}}"#).unwrap();
-    let mut makefile = File::create(dir.path().join("Makefile")).unwrap();
-    writeln!(makefile, "deploy:\n\t@echo deploying").unwrap();
+    let mut pkg = File::create(dir.path().join("package.json")).unwrap();
+    writeln!(pkg, r#"{{"name": "test"}}"#).unwrap();
+    let mut makefile = File::create(dir.path().join("Makefile")).unwrap();
+    writeln!(makefile, "deploy:\n\t@echo deploying").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["deploy", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make deploy"));
+}
+
+#[test]
+fn test_npm_script_takes_priority() {
+    let dir = tempdir().unwrap();
+    let mut pkg = File::create(dir.path().join("package.json")).unwrap();
+    writeln!(pkg, r#"{{"scripts": {{"build": "tsc"}}}}"#).unwrap();
+    let mut makefile = File::create(dir.path().join("Makefile")).unwrap();
+    writeln!(makefile, "build:\n\t@echo make build").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["build", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm run build"));
+}
+
+#[test]
+fn test_command_not_supported_error() {
+    let dir = tempdir().unwrap();
+    // Use package.json with explicit scripts — NodeValidator returns NotSupported
+    // for commands not found in scripts (unlike Cargo which returns Unknown for
+    // extensible subcommands)
+    let mut file = File::create(dir.path().join("package.json")).unwrap();
+    writeln!(file, r#"{{"scripts": {{"test": "echo test"}}}}"#).unwrap();
+    File::create(dir.path().join("package-lock.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["nonexistent-command", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not supported"));
+}
+
+// ============================================================================
+// Scripting hooks tests
+// ============================================================================
+
+#[cfg(feature = "scripting")]
+#[test]
+fn test_script_command_hook() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(
+        dir.path().join("hooks.rhai"),
+        r#"fn command(ctx) { ["npx", "vitest"] + ctx.args }"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("run.toml"), "script = \"hooks.rhai\"\n").unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["test", "--dry-run", "--", "--run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npx vitest --run"));
+}
+
+// ============================================================================
+// JSON-RPC server tests
+// ============================================================================
+
+#[test]
+fn test_serve_stdio_detect() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+
+    let frame = |body: String| format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+    let input = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":2,"method":"detect","params":{"levels":0}}"#.to_string(),
+        r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#.to_string(),
+        r#"{"jsonrpc":"2.0","method":"exit"}"#.to_string(),
+    ]
+    .into_iter()
+    .map(frame)
+    .collect::<String>();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .args(["serve", "--stdio"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Content-Length:"))
+        .stdout(predicate::str::contains(r#""name":"npm""#))
+        .stdout(predicate::str::contains(r#""result":null"#));
+}
+
+#[cfg(all(unix, feature = "daemon"))]
+#[test]
+fn test_daemon_answers_cli() {
+    use std::time::{Duration, Instant};
+
+    struct KillOnDrop(std::process::Child);
+    impl Drop for KillOnDrop {
+        fn drop(&mut self) {
+            let _ = self.0.kill();
+            let _ = self.0.wait();
+        }
+    }
+
+    let project = tempdir().unwrap();
+    File::create(project.path().join("Makefile")).unwrap();
+    let socket_dir = tempdir().unwrap();
+    let socket = socket_dir.path().join("daemon.sock");
+
+    // Only the daemon's environment has a global config defining `hello`
+    let daemon_config = tempdir().unwrap();
+    fs::create_dir_all(daemon_config.path().join("run")).unwrap();
+    fs::write(
+        daemon_config.path().join("run").join("config.toml"),
+        "[commands]\nhello = \"echo from-daemon\"\n",
+    )
+    .unwrap();
+    let client_config = tempdir().unwrap();
+
+    let _daemon = KillOnDrop(
+        std::process::Command::new(assert_cmd::cargo::cargo_bin("run"))
+            .arg("daemon")
+            .arg("--socket")
+            .arg(&socket)
+            .arg(project.path())
+            .env("XDG_CONFIG_HOME", daemon_config.path())
+            .env("RUN_NO_UPDATE", "1")
+            .stderr(std::process::Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+    let start = Instant::now();
+    while !socket.exists() {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "daemon did not start"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    run_cmd()
+        .current_dir(project.path())
+        .env("RUN_DAEMON_SOCKET", &socket)
+        .env("XDG_CONFIG_HOME", client_config.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "hello"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("echo from-daemon"));
+
+    // Without the daemon the client only sees its own configuration
+    run_cmd()
+        .current_dir(project.path())
+        .env("RUN_DAEMON_SOCKET", &socket)
+        .env("RUN_NO_DAEMON", "1")
+        .env("XDG_CONFIG_HOME", client_config.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "hello"])
+        .assert()
+        .failure();
+
+    // A second daemon on the same socket refuses to start
+    run_cmd()
+        .args(["daemon", "--socket"])
+        .arg(&socket)
+        .arg(project.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already listening"));
+}
+
+#[test]
+fn test_wireit_warning_when_script_bypasses_wireit() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{
+            "scripts": {"build": "wireit", "bundle": "rollup -c"},
+            "wireit": {
+                "build": {"command": "tsc"},
+                "bundle": {"command": "rollup -c", "dependencies": ["build"]}
+            }
+        }"#,
+    )
+    .unwrap();
+    File::create(dir.path().join("package-lock.json")).unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "bundle"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("wireit dependencies (build)"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "build"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("wireit").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_vscode_task_runs_from_terminal() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join(".vscode")).unwrap();
+    fs::write(
+        dir.path().join(".vscode").join("tasks.json"),
+        r#"{
+            // Shared with the team's editor setup
+            "version": "2.0.0",
+            "tasks": [{"label": "greet", "type": "shell", "command": "echo hello from vscode"}]
+        }"#,
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["greet", "--", "and", "friends"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello from vscode and friends"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_missing_tool_runs_in_nix_dev_shell() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    let project = dir.path().join("project");
+    fs::create_dir(&project).unwrap();
+    fs::write(
+        project.join("package.json"),
+        r#"{"scripts": {"dev": "vite"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        project.join("flake.nix"),
+        "{ outputs = { nixpkgs, ... }: { devShells.x86_64-linux.default = null; }; }",
+    )
+    .unwrap();
+
+    // Only `nix` is on PATH; npm lives in the dev shell
+    let bin = dir.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+    let nix = bin.join("nix");
+    fs::write(&nix, "#!/bin/sh\n").unwrap();
+    fs::set_permissions(&nix, fs::Permissions::from_mode(0o755)).unwrap();
+
+    run_cmd()
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", &bin)
+        .arg("dev")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("npm is not installed"))
+        .stderr(predicate::str::contains("nix_develop = true"));
+
+    fs::write(project.join("run.toml"), "nix_develop = true\n").unwrap();
+    run_cmd()
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", &bin)
+        .args(["--dry-run", "dev"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("nix develop"))
+        .stdout(predicate::str::contains("-c npm run dev"));
+}
+
+#[test]
+fn test_in_devcontainer_dry_run() {
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join(".devcontainer")).unwrap();
+    fs::write(
+        dir.path().join(".devcontainer").join("devcontainer.json"),
+        r#"{"image": "mcr.microsoft.com/devcontainers/javascript-node"}"#,
+    )
+    .unwrap();
+    let web = dir.path().join("web");
+    fs::create_dir(&web).unwrap();
+    File::create(web.join("package.json")).unwrap();
+
+    run_cmd()
+        .current_dir(&web)
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "--in-devcontainer", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "devcontainer exec --workspace-folder",
+        ))
+        .stdout(predicate::str::contains("web npm test"));
+
+    // Without a configuration there is nothing to run in
+    run_cmd()
+        .current_dir(tempdir().unwrap().path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "--in-devcontainer", "test"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_env_overrides() {
+    let dir = tempdir().unwrap();
+    File::create(dir.path().join("package.json")).unwrap();
+    fs::write(dir.path().join("Makefile"), "test:\n\techo ok\n").unwrap();
+    let sub = dir.path().join("a").join("b").join("c").join("d");
+    fs::create_dir_all(&sub).unwrap();
+
+    // RUN_RUNNER picks the runner without resolution
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("RUN_RUNNER", "make")
+        .args(["--dry-run", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make test"));
+
+    // RUN_IGNORE skips runners like --ignore
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("RUN_IGNORE", "npm")
+        .args(["--dry-run", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make test"));
+
+    // Four levels up is out of reach by default; RUN_LEVELS extends the
+    // search, and --levels still wins over it
+    run_cmd()
+        .current_dir(&sub)
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "test"])
+        .assert()
+        .failure();
+    run_cmd()
+        .current_dir(&sub)
+        .env("RUN_NO_UPDATE", "1")
+        .env("RUN_LEVELS", "4")
+        .args(["--dry-run", "test"])
+        .assert()
+        .success();
+    run_cmd()
+        .current_dir(&sub)
+        .env("RUN_NO_UPDATE", "1")
+        .env("RUN_LEVELS", "4")
+        .args(["--dry-run", "--levels=3", "test"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_config_from_package_json() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{
+            "scripts": {"test": "jest"},
+            "run": {
+                "aliases": {"t": "test -- --coverage"},
+                "commands": {"hello": "echo hello"}
+            }
+        }"#,
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "t", "--", "--ci"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm test --coverage --ci"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "hello"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("echo hello"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_ci_defaults() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nhello = \"echo hi\"\n",
+    )
+    .unwrap();
+
+    // Plain, timestamped output on CI
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env_remove("NO_COLOR")
+        .env_remove("FORCE_COLOR")
+        .env_remove("CLICOLOR_FORCE")
+        .env("GITHUB_ACTIONS", "true")
+        .env_remove("CI")
+        .arg("hello")
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(r"^\[\d\d:\d\d:\d\d\] ✓ Executing: echo hi").unwrap())
+        .stderr(predicate::str::contains("Finished (exit status: 0)"))
+        .stderr(predicate::str::contains("\x1b[").not());
+
+    // Both can be turned back on
+    fs::write(
+        dir.path().join("run.toml"),
+        "timestamps = false\n[commands]\nhello = \"echo hi\"\n",
+    )
+    .unwrap();
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env_remove("NO_COLOR")
+        .env("CI", "true")
+        .env("FORCE_COLOR", "1")
+        .arg("hello")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Finished").not())
+        .stderr(predicate::str::contains("\x1b["));
+}
+
+#[test]
+fn test_color_flag() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nhello = \"echo hi\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        run_cmd()
+            .current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env_remove("NO_COLOR")
+            .env_remove("FORCE_COLOR")
+            .env_remove("CLICOLOR_FORCE")
+            .env("CI", "false")
+            .args(args)
+            .assert()
+            .success()
+    };
+
+    // Piped output is plain unless colors are asked for
+    run(&["hello"]).stderr(predicate::str::contains("\x1b[").not());
+    run(&["hello", "--color", "always"]).stderr(predicate::str::contains("\x1b["));
+    run(&["--color=always", "list"]).stdout(predicate::str::contains("\x1b["));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("FORCE_COLOR", "1")
+        .args(["hello", "--color", "never"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("\x1b[").not());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pty() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+check = """sh -c 'test -t 1 && echo terminal || echo pipe; test -t 2 && echo terminal >&2 || true'"""
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["check"]).assert().success().stdout("pipe\n");
+    // Both streams come out of the pseudo-terminal, on stdout
+    run(&["check", "--pty"])
+        .assert()
+        .success()
+        .stdout("terminal\nterminal\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_signal_forwarding() {
+    use std::time::{Duration, Instant};
+
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+server = """sh -c 'trap "echo stopped > signal.log; exit 0" TERM; touch ready; sleep 30 & wait'"""
+"#,
+    )
+    .unwrap();
+
+    let mut server = std::process::Command::new(env!("CARGO_BIN_EXE_run"))
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("server")
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let start = Instant::now();
+    while !dir.path().join("ready").exists() {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "task did not start"
+        );
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    // The task's whole group gets the signal, and run exits with 128 + 15
+    std::process::Command::new("kill")
+        .args(["-TERM", &server.id().to_string()])
+        .status()
+        .unwrap();
+    let status = server.wait().unwrap();
+    assert_eq!(status.code(), Some(143));
+    assert_eq!(
+        fs::read_to_string(dir.path().join("signal.log")).unwrap(),
+        "stopped\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_group() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+migrate = { cmd = "sh -c 'echo start >> db.log; sleep 0.2; echo end >> db.log'", group = "db" }
+seed = { cmd = "sh -c 'echo start >> db.log; sleep 0.2; echo end >> db.log'", group = "db" }
+"#,
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["--parallel", "migrate,seed"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("db.log")).unwrap(),
+        "start\nend\nstart\nend\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_confirm() {
+    let dir = tempdir().unwrap();
+    let config = |interactive: bool| {
+        fs::write(
+            dir.path().join("run.toml"),
+            format!(
+                "interactive = {}\n[commands]\nwipe = {{ cmd = \"echo wiped\", confirm = \"Wipe the database?\" }}\n",
+                interactive
+            ),
+        )
+        .unwrap();
+    };
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    config(true);
+    run(&["wipe"])
+        .write_stdin("n\n")
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("Wipe the database? [y/N]"))
+        .stderr(predicate::str::contains("Task 'wipe' was not run"));
+    run(&["wipe"])
+        .write_stdin("y\n")
+        .assert()
+        .success()
+        .stdout("wiped\n");
+
+    // Without prompts (CI), only --yes runs it
+    config(false);
+    run(&["wipe"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::contains("pass --yes"));
+    run(&["wipe", "--yes"]).assert().success().stdout("wiped\n");
+}
+
+#[test]
+fn test_schema() {
+    let output = run_cmd()
+        .env("RUN_NO_UPDATE", "1")
+        .arg("schema")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(schema["$schema"]
+        .as_str()
+        .unwrap()
+        .contains("json-schema.org"));
+    assert!(schema["properties"]["commands"].is_object());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_yaml_config() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.yaml"),
+        "env:\n  GREETING: hello\ncommands:\n  greet: sh -c 'echo $GREETING from yaml'\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["greet"])
+        .assert()
+        .success()
+        .stdout("hello from yaml\n");
+    run(&["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("custom (run.yaml)"));
+    // A run.toml would take the place of run.yaml
+    run(&["init"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run.yaml already exists"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_retries() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+broken = { cmd = "sh -c 'echo try; exit 3'", retries = 2 }
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["broken", "--retry-delay", "10ms"])
+        .assert()
+        .code(3)
+        .stdout("try\ntry\ntry\n")
+        .stderr(predicate::str::contains("Attempt 2 of 3 exited with 3"));
+    // --retry wins over the task's retries
+    run(&["broken", "--retry", "0"])
+        .assert()
+        .code(3)
+        .stdout("try\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_matrix() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands.build]
+cmd = "echo build {{matrix.target}} {{matrix.profile}}"
+matrix = { target = ["x86_64", "aarch64"], profile = ["release"] }
+
+[commands.fail]
+cmd = "sh -c 'echo {{matrix.n}}; exit {{matrix.n}}'"
+matrix.n = ["0", "3", "0"]
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["build"])
+        .assert()
+        .success()
+        .stdout("build x86_64 release\nbuild aarch64 release\n");
+    // The first failing combination stops the rest
+    run(&["fail"]).assert().code(3).stdout("0\n3\n");
+
+    run(&["--parallel", "build"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "build[release,x86_64]  | build x86_64 release",
+        ))
+        .stdout(predicate::str::contains(
+            "build[release,aarch64] | build aarch64 release",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_cache() {
+    let dir = tempdir().unwrap();
+    let config_home = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands.build]
+cmd = "sh -c 'mkdir -p dist && cat src/*.txt > dist/out.txt && echo built'"
+inputs = ["src/**"]
+outputs = ["dist/**"]
+"#,
+    )
+    .unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(dir.path().join("src/a.txt"), "a").unwrap();
+    let run = || {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .arg("build");
+        cmd
+    };
+
+    run().assert().success().stdout("built\n");
+    run()
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("'build' is cached"));
+
+    // A changed input runs it again
+    fs::write(dir.path().join("src/a.txt"), "b").unwrap();
+    run().assert().success().stdout("built\n");
+    run().assert().success().stdout("");
+
+    // So does a missing output
+    fs::remove_dir_all(dir.path().join("dist")).unwrap();
+    run().assert().success().stdout("built\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_secrets_masked() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[env]
+TOKEN = { value = "s3cr3t-token", secret = true }
+
+[vars]
+password = { value = "hunter2", secret = true }
+
+[commands]
+login = "echo login bob:{{password}}"
+token = "sh -c 'echo token=$TOKEN'"
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["--dry-run", "login"])
+        .assert()
+        .success()
+        .stdout("echo login bob:****\n");
+    run(&["login"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Executing: echo login bob:****"))
+        .stderr(predicate::str::contains("hunter2").not());
+    run(&["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("s3cr3t-token").not());
+
+    run(&["--log-file", "run.log", "token"])
+        .assert()
+        .success()
+        .stdout("token=s3cr3t-token\n");
+    let log = fs::read_to_string(dir.path().join("run.log")).unwrap();
+    assert!(log.contains("out token=****"), "{log}");
+    assert!(!log.contains("s3cr3t-token"), "{log}");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_vars() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[vars]
+registry = "ghcr.io/acme"
+tag = "dev"
+
+[commands]
+push = "echo push {{registry}}/app:{{tag}}"
+ps = "echo {{.Names}}"
+"#,
+    )
+    .unwrap();
+    let run = |task: &str| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .arg(task);
+        cmd
+    };
+
+    run("push")
+        .assert()
+        .success()
+        .stdout("push ghcr.io/acme/app:dev\n");
+    run("push")
+        .env("RUN_VAR_tag", "v2")
+        .assert()
+        .success()
+        .stdout("push ghcr.io/acme/app:v2\n");
+    // Not a variable: left for the command's own templates
+    run("ps").assert().success().stdout("{{.Names}}\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_runner_override() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("Makefile"), "greet:\n\t@echo make $(WHO)\n").unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts": {"greet": "echo npm"}}"#,
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+hi = { use = "make", task = "greet WHO=you" }
+greet = { use = "make" }
+deploy = { use = "cargo" }
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("RUN_NO_DAEMON", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["hi"]).assert().success().stdout("make you\n");
+    run(&["hi", "WHO=them"])
+        .assert()
+        .success()
+        .stdout("make them\n");
+    // Without `use`, the lockfile-free npm and make would conflict
+    run(&["greet"]).assert().success().stdout("make\n");
+    run(&["deploy"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "'cargo' was requested but not detected",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_env_files() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"env_files = [".env"]
+
+[env]
+C = "config"
+
+[commands]
+test = { cmd = "sh -c 'echo $A $B $C $D'", env_file = ".env.test" }
+plain = "sh -c 'echo $A $B'"
+"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join(".env"), "A=shared\nB=shared\nC=shared\n").unwrap();
+    fs::write(dir.path().join(".env.test"), "B=test\nC=test\nD=test\n").unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .env("A", "inherited")
+            .args(args);
+        cmd
+    };
+
+    run(&["test"])
+        .assert()
+        .success()
+        .stdout("shared test config test\n");
+    run(&["-e", "D=cli", "test"])
+        .assert()
+        .success()
+        .stdout("shared test config cli\n");
+    run(&["plain"]).assert().success().stdout("shared shared\n");
+
+    fs::remove_file(dir.path().join(".env.test")).unwrap();
+    run(&["test"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(".env.test"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_conditions() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+up = { cmd = "echo up", condition.file_exists = "docker-compose.yml" }
+release = { cmd = "echo release", condition.env_set = "RELEASE_TOKEN", depends_on = ["up"] }
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .env_remove("RELEASE_TOKEN")
+            .args(args);
+        cmd
+    };
+
+    run(&["up"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains(
+            "Skipping 'up': docker-compose.yml does not exist",
+        ));
+    run(&["list"])
+        .assert()
+        .stdout(predicate::str::contains("up").not());
+
+    fs::write(dir.path().join("docker-compose.yml"), "").unwrap();
+    run(&["up"]).assert().success().stdout("up\n");
+    run(&["release"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr(predicate::str::contains("RELEASE_TOKEN is not set"));
+    run(&["release"])
+        .env("RELEASE_TOKEN", "x")
+        .assert()
+        .success()
+        .stdout("up\nrelease\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_namespaces() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+build = "echo build"
+
+[commands.db]
+migrate = "echo migrate"
+seed = { cmd = "echo seed", depends_on = ["db:migrate"] }
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["list"])
+        .assert()
+        .success()
+        .stdout("custom (run.toml)\n  build\n  db\n    db:migrate\n    db:seed\n");
+    run(&["db:seed"])
+        .assert()
+        .success()
+        .stdout("migrate\nseed\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_private_tasks() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+build = { cmd = "echo build", depends_on = ["_codegen", "setup"] }
+_codegen = "echo codegen"
+setup = { cmd = "echo setup", private = true }
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("build"))
+        .stdout(predicate::str::contains("codegen").not())
+        .stdout(predicate::str::contains("setup").not());
+    run(&["build"])
+        .assert()
+        .success()
+        .stdout("codegen\nsetup\nbuild\n");
+    run(&["setup"]).assert().success().stdout("setup\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_global_commands() {
+    let config_home = tempdir().unwrap();
+    fs::create_dir(config_home.path().join("run")).unwrap();
+    let global = config_home.path().join("run").join("config.toml");
+    fs::write(
+        &global,
+        "[commands]\nhttp = \"echo global http\"\nfmt = \"echo global fmt\"\n",
+    )
+    .unwrap();
+    let project = tempdir().unwrap();
+    fs::write(
+        project.path().join("run.toml"),
+        "[commands]\nfmt = \"echo project fmt\"\n",
+    )
+    .unwrap();
+    let empty = tempdir().unwrap();
+    let run = |dir: &std::path::Path, args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir)
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("RUN_NO_DAEMON", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    // Available everywhere, even where nothing is detected
+    run(empty.path(), &["http"])
+        .assert()
+        .success()
+        .stdout("global http\n");
+    // The project's run.toml wins
+    run(project.path(), &["fmt"])
+        .assert()
+        .success()
+        .stdout("project fmt\n");
+    run(project.path(), &["explain", "http"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("{}:2", global.display())));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_config_includes() {
+    let dir = tempdir().unwrap();
+    let package = dir.path().join("packages/app");
+    fs::create_dir_all(&package).unwrap();
+    fs::write(
+        dir.path().join("shared-tasks.toml"),
+        "[commands]\nlint = \"echo shared lint\"\nfmt = \"echo shared fmt\"\n",
+    )
+    .unwrap();
+    fs::write(
+        package.join("run.toml"),
+        "include = [\"../../shared-tasks.toml\"]\n\n[commands]\nfmt = \"echo app fmt\"\n",
+    )
+    .unwrap();
+    let run = |task: &str| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(&package)
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .arg(task);
+        cmd
+    };
+
+    run("lint").assert().success().stdout("shared lint\n");
+    run("fmt").assert().success().stdout("app fmt\n");
+
+    fs::write(
+        dir.path().join("shared-tasks.toml"),
+        "include = [\"packages/app/run.toml\"]\n",
+    )
+    .unwrap();
+    run_cmd()
+        .current_dir(&package)
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("doctor")
+        .assert()
+        .stdout(predicate::str::contains("Include cycle"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_script() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+ci = { script = ["set -e", "echo building $1", "false", "echo unreachable"] }
+
+[commands.greet]
+script = """
+for name in "$@"; do
+  echo "hello $name"
+done
+"""
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["ci", "fast"])
+        .assert()
+        .code(1)
+        .stdout("building fast\n");
+    run(&["greet", "a", "b c"])
+        .assert()
+        .success()
+        .stdout("hello a\nhello b c\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_task_shell() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"shell = "sh"
+
+[commands]
+both = "echo a && echo {1}"
+direct = { cmd = "echo a && echo b", shell = false }
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["both", "it's b"])
+        .assert()
+        .success()
+        .stdout("a\nit's b\n");
+    run(&["direct"]).assert().success().stdout("a && echo b\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pre_and_post_hooks() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[hooks]
+pre = ["check"]
+post = ["say done"]
+
+[commands]
+check = "echo check"
+say = "echo"
+fail = "false"
+test = { cmd = "echo test", pre = ["say lint"], post = ["say cleanup"] }
+bad = { cmd = "false", post = ["say cleanup"] }
+blocked = { cmd = "echo never", pre = ["fail"] }
+"#,
+    )
+    .unwrap();
+    let run = |task: &str| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .arg(task);
+        cmd
+    };
+
+    run("test")
+        .assert()
+        .success()
+        .stdout("check\nlint\ntest\ncleanup\ndone\n");
+    // Post hooks run after a failure, which still sets the exit code
+    run("bad").assert().code(1).stdout("check\ncleanup\ndone\n");
+    run("blocked").assert().code(1).stdout("check\n");
+    // The global pre hook runs once when it is the task
+    run("check").assert().success().stdout("check\ndone\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_command_placeholders() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\ndeploy = \"echo deploying {1} to {2}\"\n",
+    )
+    .unwrap();
+    let deploy = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .arg("deploy")
+            .args(args);
+        cmd
+    };
+
+    deploy(&["web", "eu west"])
+        .assert()
+        .success()
+        .stdout("deploying web to eu west\n");
+    deploy(&["web"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Task 'deploy' needs 2 arguments, got 1",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_platform_command_variants() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands.hello]\ncmd.windows = \"echo windows\"\ncmd.unix = \"echo unix\"\n",
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["deploy", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("hello")
         .assert()
         .success()
-        .stdout(predicate::str::contains("make deploy"));
+        .stdout("unix\n");
 }
 
+#[cfg(unix)]
 #[test]
-fn test_npm_script_takes_priority() {
+fn test_task_dependencies() {
     let dir = tempdir().unwrap();
-    let mut pkg = File::create(dir.path().join("package.json")).unwrap();
-    writeln!(pkg, r#"{{"scripts": {{"build": "tsc"}}}}"#).unwrap();
-    let mut makefile = File::create(dir.path().join("Makefile")).unwrap();
-    writeln!(makefile, "build:\n\t@echo make build").unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+clean = "echo clean"
+codegen = { cmd = "echo codegen", depends_on = ["clean"] }
+broken = { cmd = "echo never", depends_on = ["fail"] }
+fail = "false"
+ping = { cmd = "true", depends_on = ["pong"] }
+pong = { cmd = "true", depends_on = ["ping"] }
+
+[commands.build]
+cmd = "echo build"
+depends_on = ["clean", "codegen"]
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["build"])
+        .assert()
+        .success()
+        .stdout("clean\ncodegen\nbuild\n");
+    // Already run as the first task, codegen isn't run again for build
+    run(&["codegen", "+then", "build"])
+        .assert()
+        .success()
+        .stdout("clean\ncodegen\nbuild\n");
+    run(&["broken"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("never").not());
+    run(&["ping"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Dependency cycle: ping -> pong -> ping",
+        ));
+}
+
+#[test]
+fn test_upgrade_tool_dry_run() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[package]\nname = \"demo\"\n",
+    )
+    .unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["build", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["upgrade-tool", "--dry-run"])
         .assert()
         .success()
-        .stdout(predicate::str::contains("npm run build"));
+        .stdout("rustup update\n");
+
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("Makefile"), "build:\n\techo b\n").unwrap();
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["upgrade-tool", "--dry-run"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("doesn't know how to upgrade make"));
 }
 
+#[cfg(unix)]
 #[test]
-fn test_command_not_supported_error() {
+fn test_stdin_sources() {
     let dir = tempdir().unwrap();
-    // Use package.json with explicit scripts — NodeValidator returns NotSupported
-    // for commands not found in scripts (unlike Cargo which returns Unknown for
-    // extensible subcommands)
-    let mut file = File::create(dir.path().join("package.json")).unwrap();
-    writeln!(file, r#"{{"scripts": {{"test": "echo test"}}}}"#).unwrap();
-    File::create(dir.path().join("package-lock.json")).unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nimport = \"cat\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("data.txt"), "from the file\n").unwrap();
+    let import = |stdin: &str| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(["import", stdin])
+            .write_stdin("from the pipe\n");
+        cmd
+    };
+
+    import("--stdin=inherit")
+        .assert()
+        .success()
+        .stdout("from the pipe\n");
+    import("--stdin=null").assert().success().stdout("");
+    import("--stdin=file:data.txt")
+        .assert()
+        .success()
+        .stdout("from the file\n");
+    import("--stdin=file:missing.txt")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Cannot read stdin"));
+    import("--stdin=pipe").assert().code(2);
+}
+
+#[test]
+fn test_no_validate_forwards_unknown_task() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts": {"build": "echo b"}}"#,
+    )
+    .unwrap();
+    fs::write(dir.path().join("package-lock.json"), "{}").unwrap();
 
     run_cmd()
         .current_dir(dir.path())
-        .args(["nonexistent-command", "--dry-run"])
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["deploy", "--dry-run"])
         .assert()
         .failure()
         .stderr(predicate::str::contains("not supported"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["deploy", "--dry-run", "--no-validate"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("npm run deploy"));
+}
+
+#[test]
+fn test_verbosity_levels() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nhello = \"echo hi\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("Makefile"), "build:\n\techo b\n").unwrap();
+
+    let verbose = |level: &str| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(["hello", level, "--ignore", "make"]);
+        cmd
+    };
+
+    verbose("-v")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Searching in"))
+        .stderr(predicate::str::contains("Detector custom").not());
+
+    verbose("-vv")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Detector custom: found custom (run.toml)",
+        ))
+        .stderr(predicate::str::contains(
+            "Detector make: nothing, ignored make (Makefile)",
+        ))
+        .stderr(predicate::str::contains("custom supports command 'hello'"))
+        .stderr(predicate::str::contains("Merged config").not());
+
+    verbose("-vvv")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Merged config:"))
+        .stderr(predicate::str::contains("hello = \"echo hi\""))
+        .stderr(predicate::str::contains("Environment for 'hello'"));
+}
+
+#[test]
+fn test_profile() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nhello = \"echo hi\"\n",
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["hello", "--profile"])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(r"(?m)^config +\d+\.\dms$").unwrap())
+        .stderr(predicate::str::is_match(r"(?m)^detection +\d+\.\dms$").unwrap())
+        .stderr(predicate::str::is_match(r"(?m)^validation +\d+\.\dms$").unwrap())
+        .stderr(predicate::str::is_match(r"(?m)^execution +\d+\.\dms$").unwrap())
+        .stderr(predicate::str::is_match(r"(?m)^total +\d+\.\dms$").unwrap());
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["hello", "--profile", "--json"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(r#""event":"profile""#));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symlinked_project_search() {
+    use std::os::unix::fs::symlink;
+
+    let dir = tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    let monorepo = root.join("monorepo");
+    let project = root.join("store").join("project");
+    fs::create_dir_all(&monorepo).unwrap();
+    fs::create_dir_all(&project).unwrap();
+    fs::write(monorepo.join("Makefile"), "test:\n\techo ok\n").unwrap();
+    let linked = monorepo.join("project");
+    symlink(&project, &linked).unwrap();
+
+    // By default the real location is searched, which has no runner
+    run_cmd()
+        .current_dir(&linked)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PWD", &linked)
+        .args(["--dry-run", "test"])
+        .assert()
+        .failure();
+
+    // Without following symlinks, the parents of the link are searched
+    fs::write(project.join("run.toml"), "follow_symlinks = false\n").unwrap();
+    run_cmd()
+        .current_dir(&linked)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PWD", &linked)
+        .args(["--dry-run", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("make test"));
+}
+
+#[test]
+fn test_workspace_member_targeting() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        "[workspace]\nmembers = [\"crates/*\"]\n",
+    )
+    .unwrap();
+    let member = dir.path().join("crates").join("foo");
+    fs::create_dir_all(member.join("src")).unwrap();
+    fs::write(member.join("Cargo.toml"), "[package]\nname = \"foo\"\n").unwrap();
+
+    run_cmd()
+        .current_dir(member.join("src"))
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cargo test -p foo"));
+
+    run_cmd()
+        .current_dir(&member)
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "--workspace", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cargo test --workspace"));
+
+    // The workspace root keeps cargo's own default
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["--dry-run", "test"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("cargo test").and(predicate::str::contains("-p").not()));
+}
+
+#[test]
+#[cfg(all(unix, feature = "watch"))]
+fn test_watch_reruns_on_change() {
+    use std::time::{Duration, Instant};
+
+    let dir = tempdir().unwrap();
+    fs::create_dir(dir.path().join("src")).unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nbuild = \"sh -c 'echo built >> runs.log'\"\n",
+    )
+    .unwrap();
+
+    let mut watcher = std::process::Command::new(env!("CARGO_BIN_EXE_run"))
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["build", "--watch-path", "src/*"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+
+    let runs = |count: usize| {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while Instant::now() < deadline {
+            let log = fs::read_to_string(dir.path().join("runs.log")).unwrap_or_default();
+            if log.lines().count() >= count {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        false
+    };
+
+    let first = runs(1);
+    // Give the watcher a moment before the change
+    std::thread::sleep(Duration::from_millis(300));
+    fs::write(dir.path().join("src/main.c"), "int main;").unwrap();
+    let second = runs(2);
+
+    watcher.kill().unwrap();
+    watcher.wait().unwrap();
+    assert!(first, "task didn't run at start");
+    assert!(second, "task didn't re-run after a change");
+}
+
+#[test]
+#[cfg(unix)]
+fn test_env_and_env_file() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\ngreet = \"sh -c 'echo \\\"$GREETING, $NAME\\\"'\"\n",
+    )
+    .unwrap();
+    fs::write(
+        dir.path().join(".env"),
+        "# local settings\nGREETING=\"Hello\"\nNAME=file # overridden\n",
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["greet", "--env-file", ".env", "--env", "NAME=flag"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Hello, flag"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["greet", "--env-file", "missing.env"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing.env"));
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_config_subcommand() {
+    let config_home = tempdir().unwrap();
+    let dir = tempdir().unwrap();
+    let config = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .arg("config")
+            .args(args);
+        cmd
+    };
+
+    config(&["path"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("run/config.toml"));
+    config(&["get", "max_levels"]).assert().failure();
+
+    config(&["set", "max_levels", "5"]).assert().success();
+    config(&["get", "max_levels"])
+        .assert()
+        .success()
+        .stdout("5\n");
+    config(&["set", "max_levels", "lots"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid value"));
+
+    // The effective config includes the local run.toml
+    fs::write(dir.path().join("run.toml"), "runner = \"make\"\n").unwrap();
+    config(&["show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("max_levels = 5"))
+        .stdout(predicate::str::contains("runner = \"make\""));
+
+    config(&["unset", "max_levels"]).assert().success();
+    config(&["get", "max_levels"]).assert().failure();
+}
+
+#[test]
+fn test_alias_subcommand() {
+    let config_home = tempdir().unwrap();
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{"scripts": {"test": "jest", "build": "tsc"}}"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let mut cmd = run_cmd();
+        cmd.current_dir(dir.path())
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .env("RUN_NO_UPDATE", "1")
+            .env("NO_COLOR", "1")
+            .args(args);
+        cmd
+    };
+
+    run(&["alias", "add", "t", "test", "--", "--coverage"])
+        .assert()
+        .success();
+    run(&["alias", "add", "--local", "b", "build", "--watch"])
+        .assert()
+        .success();
+    assert!(fs::read_to_string(dir.path().join("run.toml"))
+        .unwrap()
+        .contains(r#"b = "build --watch""#));
+
+    run(&["alias", "list"])
+        .assert()
+        .success()
+        .stdout("b  build --watch\nt  test -- --coverage\n");
+    // As `run test -- --coverage`, whose `--` has nothing to separate
+    run(&["--dry-run", "t"])
+        .assert()
+        .success()
+        .stdout("npm test --coverage\n");
+    run(&["--dry-run", "t", "api", "--", "--watch"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "npm test api -- --coverage --watch",
+        ));
+
+    run(&["alias", "rm", "t"]).assert().success();
+    run(&["alias", "rm", "t"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No alias 't'"));
+    run(&["alias", "add", "x,y", "test"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid alias name"));
+}
+
+#[test]
+fn test_json_output() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        "[commands]\nhello = \"echo hello\"\n",
+    )
+    .unwrap();
+
+    let output = run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["hello", "--json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "hello");
+
+    // Every diagnostic line is a JSON object
+    let events: Vec<serde_json::Value> = String::from_utf8_lossy(&output.stderr)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let names: Vec<&str> = events.iter().filter_map(|e| e["event"].as_str()).collect();
+    assert_eq!(names, vec!["detection", "command", "spawned", "exit"]);
+    assert_eq!(events[1]["runner"], "custom");
+    assert_eq!(events[1]["command"], serde_json::json!(["echo", "hello"]));
+    assert_eq!(events[3]["code"], 0);
+    assert!(events[3]["duration_ms"].is_u64());
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["which", "hello", "--json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""selected":"custom""#));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_timeout() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+slow = "sh -c 'sleep 30 & sleep 30'"
+limited = { cmd = "sleep 30", timeout = "200ms" }
+"#,
+    )
+    .unwrap();
+
+    let started = std::time::Instant::now();
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["slow", "--timeout", "300ms"])
+        .timeout(std::time::Duration::from_secs(20))
+        .assert()
+        .code(124)
+        .stderr(predicate::str::contains("timed out after 300ms"));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .arg("limited")
+        .timeout(std::time::Duration::from_secs(20))
+        .assert()
+        .code(124);
+    assert!(started.elapsed() < std::time::Duration::from_secs(15));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["slow", "--timeout", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid duration"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_retry() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+flaky = "sh -c 'test -f marker || { touch marker; exit 3; }'"
+broken = "sh -c 'exit 3'"
+"#,
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["flaky", "--retry", "2", "--retry-delay", "10ms"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Attempt 1 of 3 exited with 3, retrying in 10ms",
+        ));
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["broken", "--retry", "2", "--retry-delay", "10ms"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains(
+            "Attempt 2 of 3 exited with 3, retrying in 20ms",
+        ))
+        .stderr(predicate::str::contains("Attempt 3 of 3").not());
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["flaky,broken", "--parallel", "--retry", "1"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_then_chains_tasks() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+build = "echo building"
+deploy = "echo deploying"
+broken = "sh -c 'exit 4'"
+"#,
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["build", "fast", "+then", "deploy", "prod"])
+        .assert()
+        .success()
+        .stdout("building fast\ndeploying prod\n");
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["broken", "+then", "deploy"])
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains("deploying").not());
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["build", "--then", "deploy --now"])
+        .assert()
+        .success()
+        .stdout("building\ndeploying --now\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_log_file() {
+    let dir = tempdir().unwrap();
+    fs::write(
+        dir.path().join("run.toml"),
+        r#"[commands]
+build = "sh -c 'echo compiling; echo warning >&2; printf done'"
+"#,
+    )
+    .unwrap();
+
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .env("NO_COLOR", "1")
+        .args(["build", "--log-file", "logs/build.log"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("compiling\ndone"))
+        .stderr(predicate::str::contains("warning"));
+
+    let log = fs::read_to_string(dir.path().join("logs/build.log")).unwrap();
+    let lines: Vec<&str> = log
+        .lines()
+        .map(|line| line.split_once(' ').unwrap().1)
+        .collect();
+    assert_eq!(lines.len(), 5, "{}", log);
+    assert!(lines[0].starts_with("=== build: sh -c"), "{}", log);
+    assert!(lines.contains(&"out compiling"), "{}", log);
+    assert!(lines.contains(&"out done"), "{}", log);
+    assert!(lines.contains(&"err warning"), "{}", log);
+    assert_eq!(lines[4], "=== build: exit status: 0");
+
+    // Runs are appended
+    run_cmd()
+        .current_dir(dir.path())
+        .env("RUN_NO_UPDATE", "1")
+        .args(["build", "--log-file", "logs/build.log"])
+        .assert()
+        .success();
+    let log = fs::read_to_string(dir.path().join("logs/build.log")).unwrap();
+    assert_eq!(log.lines().count(), 10);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_choose_resolves_lockfile_conflict() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().unwrap();
+    let project = dir.path().join("project");
+    fs::create_dir(&project).unwrap();
+    fs::write(
+        project.join("package.json"),
+        r#"{"scripts": {"build": "tsc"}}"#,
+    )
+    .unwrap();
+    fs::write(project.join("package-lock.json"), "{}").unwrap();
+    fs::write(project.join("pnpm-lock.yaml"), "").unwrap();
+
+    let bin = dir.path().join("bin");
+    fs::create_dir(&bin).unwrap();
+    for tool in ["npm", "pnpm"] {
+        let path = bin.join(tool);
+        fs::write(&path, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    // Without a terminal there is no menu
+    run_cmd()
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", &bin)
+        .args(["build", "--dry-run"])
+        .assert()
+        .code(3);
+
+    // No answer keeps the conflict
+    run_cmd()
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", &bin)
+        .args(["build", "--dry-run", "--choose"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("pnpm (pnpm-lock.yaml)"));
+
+    run_cmd()
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", &bin)
+        .args(["build", "--dry-run", "--choose"])
+        .write_stdin("1\ny\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pnpm run build"));
+    let local = fs::read_to_string(project.join("run.toml")).unwrap();
+    assert!(local.contains(r#"ignore_tools = ["npm"]"#), "{}", local);
+
+    // Remembered: no question asked
+    run_cmd()
+        .current_dir(&project)
+        .env("RUN_NO_UPDATE", "1")
+        .env("PATH", &bin)
+        .args(["build", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pnpm run build"));
 }